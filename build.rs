@@ -4,7 +4,10 @@ use walkdir::WalkDir;
 
 fn main() {
     let dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
-    for e in WalkDir::new(dir.join("i18n")) {
+    for e in WalkDir::new(dir.join("i18n"))
+        .into_iter()
+        .chain(WalkDir::new(dir.join("static")))
+    {
         println!(
             "cargo:rerun-if-changed={}",
             e.unwrap()
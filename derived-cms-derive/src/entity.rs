@@ -1,22 +1,55 @@
-use convert_case::Case;
-use darling::{FromAttributes, FromField};
+use convert_case::{Case, Casing};
+use darling::{FromAttributes, FromField, FromMeta};
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::quote;
-use syn::{DataStruct, DeriveInput, Field, Path, Type};
+use quote::{format_ident, quote};
+use syn::{Attribute, DataStruct, DeriveInput, Field, Path, Type};
 
-use crate::util::{found_crate, renamed_name, RenameAll};
+use crate::util::{
+    field_label, found_crate, help_tokens, input_constraints, is_flattened, is_option, is_optional,
+    option_tokens, ormlite_column, references_files, renamed_name, serde_rename, validate_fn,
+    visible_fn, ByteSize, LabelCase, RenameAll,
+};
 
 #[derive(Debug, FromAttributes)]
 #[darling(attributes(cms, serde))]
 struct EntityStructOptions {
     create: Option<Path>,
     update: Option<Path>,
+    /// generate a struct with this name as the `Update` type, whose `Option<T>` fields are
+    /// `Option<Option<T>>` to distinguish a missing key from `null`
+    update_struct: Option<Ident>,
     rename: Option<String>,
     rename_all: Option<RenameAll>,
+    /// casing of the labels of inputs and columns, the names in forms always match serde
+    label_case: Option<LabelCase>,
+    /// generate a `ListRow` projection with the id and the columns of the list page
+    #[darling(default)]
+    list_rows: bool,
+    /// position in the sidebar, higher first
+    nav_priority: Option<i32>,
+    /// `fn(&Id) -> String` formatting the id in URLs
+    url_id: Option<Path>,
+    /// require a one-time token to delete using the JSON API
+    #[darling(default)]
+    confirm_api_delete: bool,
+    /// `fn(&Self) -> DeleteConfirm` customizing the confirmation of deletes in the admin interface
+    delete_confirmation: Option<Path>,
+    /// columns that are not fields, `#[cms(column(label = "...", render = "..."))]`
+    #[darling(multiple, rename = "column")]
+    computed_columns: Vec<ComputedColumnOptions>,
+}
+
+#[derive(Debug, FromMeta)]
+struct ComputedColumnOptions {
+    label: String,
+    /// `fn(&Self, &FluentLanguageLoader) -> Markup`
+    render: Path,
+    #[darling(default)]
+    hidden: bool,
 }
 
 #[derive(Debug, FromField)]
-#[darling(attributes(cms, serde))]
+#[darling(attributes(cms))]
 struct EntityFieldOptions {
     ident: Option<Ident>,
     ty: Type,
@@ -27,39 +60,86 @@ struct EntityFieldOptions {
     skip_column: bool,
     #[darling(default)]
     skip_input: bool,
+    /// the label in forms and columns, overriding the name in serde
     rename: Option<String>,
     #[darling(default)]
     column_hidden: bool,
+    /// Render this column using `Column::render_thumbnail`
+    #[darling(default)]
+    column_thumbnail: bool,
+    /// Search this field on the list page
+    #[darling(default)]
+    searchable: bool,
+    /// Allow filtering the JSON API by this field
+    #[darling(default)]
+    filter: bool,
+    max_length: Option<usize>,
+    min_length: Option<usize>,
+    /// accepted types of uploaded files, e.g. `"image/*"`
+    accept: Option<String>,
+    /// maximum size of each uploaded file, e.g. `"5MB"`
+    max_size: Option<ByteSize>,
+    /// Render this field in a tab with this name
+    tab: Option<String>,
+    /// Only show the column and input if `<T as Visibility<S>>::visible` returns `true`
+    visible_if: Option<Path>,
+    /// `fn(&T) -> Result<(), impl Into<String>>` checking the submitted value
+    validate: Option<Path>,
+    /// Description rendered beneath the input
+    help: Option<String>,
+    /// id of the localized description rendered beneath the input
+    help_key: Option<String>,
+    /// Label the entity with this field in the options of a `Relation`
+    #[darling(default)]
+    display_label: bool,
+    /// Reject saves if this field changed since the form was loaded, see `EntityBase::version_token`
+    #[darling(default)]
+    version: bool,
+    /// Whether the field references uploads, see `EntityBase::referenced_files`.
+    /// Default: whether the type contains `File`, `Image` or `Markdown`
+    files: Option<bool>,
+    #[darling(skip)]
+    optional: bool,
+    /// `#[serde(rename = "...")]`
+    #[darling(skip)]
+    serde_rename: Option<String>,
+    /// `#[ormlite(column = "...")]`
+    #[darling(skip)]
+    ormlite_column: Option<String>,
+    /// `#[serde(...)]`, copied to the generated `update_struct`
+    #[darling(skip)]
+    serde_attrs: Vec<Attribute>,
+    #[darling(skip)]
+    flatten: bool,
 }
 
 impl EntityFieldOptions {
     fn parse(f: &Field) -> Result<Self, darling::Error> {
-        // TODO: allow overwriting options from serde with #[cms(...)]
-        let attrs = f
-            .attrs
-            .iter()
-            // filter serde fields
-            .filter(|a| {
-                let path = a.path();
-                if !path.is_ident(&Ident::new("serde", Span::call_site())) {
-                    return true;
-                }
-                if let syn::Meta::NameValue(v) = &a.meta {
-                    return v.path.is_ident(&Ident::new("rename", Span::call_site()));
-                }
-                false
-            })
-            .cloned()
-            .collect();
-        let f = Field {
-            attrs,
-            vis: f.vis.clone(),
-            mutability: f.mutability.clone(),
-            ident: f.ident.clone(),
-            colon_token: f.colon_token,
-            ty: f.ty.clone(),
-        };
-        Self::from_field(&f)
+        Ok(Self {
+            optional: is_optional(f),
+            flatten: is_flattened(f),
+            serde_rename: serde_rename(&f.attrs),
+            ormlite_column: ormlite_column(&f.attrs),
+            serde_attrs: f
+                .attrs
+                .iter()
+                .filter(|a| a.path().is_ident("serde"))
+                .cloned()
+                .collect(),
+            ..Self::from_field(f)?
+        })
+    }
+
+    /// the name in serde and forms
+    fn name(&self, ident: &Ident, rename_all: Option<RenameAll>) -> String {
+        renamed_name(ident.to_string(), self.serde_rename.as_ref(), rename_all).into_owned()
+    }
+
+    /// the name of the column in the database
+    fn column_name(&self, ident: &Ident) -> String {
+        self.ormlite_column
+            .clone()
+            .unwrap_or_else(|| ident.to_string())
     }
 }
 
@@ -86,6 +166,12 @@ pub fn derive_struct(input: &DeriveInput, data: &DataStruct) -> syn::Result<Toke
         .iter()
         .filter(|attr| !attr.skip_input)
         .map(|EntityFieldOptions { ty, .. }| quote! (#ty: #found_crate::Input<S>,))
+        .chain(
+            fields
+                .iter()
+                .filter_map(|f| f.visible_if.as_ref())
+                .map(|path| quote!(#path: #found_crate::context::Visibility<S>,)),
+        )
         .collect::<TokenStream>();
 
     let mut id_iter = fields
@@ -113,21 +199,138 @@ pub fn derive_struct(input: &DeriveInput, data: &DataStruct) -> syn::Result<Toke
         .as_ref()
         .map(|v| quote!(#v))
         .unwrap_or(quote!(Self));
-    let update = struct_attr
-        .update
-        .as_ref()
-        .map(|v| quote!(#v))
-        .unwrap_or(quote!(Self));
+    let update = match (&struct_attr.update, &struct_attr.update_struct) {
+        (Some(_), Some(_)) => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "an Entity can only have one of `update` and `update_struct`",
+            ))
+        }
+        (Some(update), None) => Some(quote!(#update)),
+        (None, Some(update)) => Some(quote!(#update)),
+        (None, None) => None,
+    };
+    let (to_update, update_bounds) = match &update {
+        Some(update) => (
+            quote!(<#update as ::std::convert::From<&Self>>::from(self)),
+            quote!(#update: for<'a> ::std::convert::From<&'a Self>,),
+        ),
+        None => (quote!(self), quote!()),
+    };
+    let update = update.unwrap_or(quote!(Self));
 
     let cols = fields
         .iter()
         .filter(|attr| !attr.skip_column)
         .collect::<Vec<_>>();
-    let number_of_columns = Ident::new(&format!("U{}", cols.len()), Span::call_site());
+    let computed = &struct_attr.computed_columns;
+    let number_of_columns = Ident::new(
+        &format!("U{}", cols.len() + computed.len()),
+        Span::call_site(),
+    );
 
     let inputs = inputs_fn(&fields, &struct_attr);
+    let validate = validate_fn(fields.iter().filter_map(|f| {
+        let ident = f.ident.as_ref()?;
+        let name = f.name(ident, struct_attr.rename_all);
+        Some((ident, name, f.validate.as_ref()?))
+    }));
     let columns = colums_fn(&fields, &struct_attr);
-    let column_values = column_values_fn(&fields);
+    let column_values = column_values_fn(&fields, computed.len());
+    let visible_columns = visible_columns_fn(&fields, computed.len());
+    let computed_columns = computed.iter().enumerate().map(|(i, c)| {
+        let render = &c.render;
+        quote! {
+            #[automatically_derived]
+            impl #found_crate::column::ComputedColumn<#i> for #ident {
+                fn render_column(
+                    &self,
+                    i18n: &#found_crate::derive::i18n_embed::fluent::FluentLanguageLoader,
+                ) -> #found_crate::derive::maud::Markup {
+                    #render(self, i18n)
+                }
+            }
+        }
+    });
+    let search = search_fns(&fields);
+    let filter = filter_fns(&fields, &struct_attr);
+    let nav_priority = struct_attr.nav_priority.map(|priority| {
+        quote! {
+            fn nav_priority() -> ::std::primitive::i32 {
+                #priority
+            }
+        }
+    });
+    let url_id = struct_attr.url_id.as_ref().map(|url_id| {
+        quote! {
+            fn url_id(id: &#id_type) -> ::std::string::String {
+                #url_id(id)
+            }
+        }
+    });
+    let list_path = name_plural.to_case(Case::Kebab);
+    let url_id_string = match &struct_attr.url_id {
+        Some(url_id) => quote!(#url_id(id)),
+        None => quote!(::std::string::ToString::to_string(id)),
+    };
+    let display_label = fields
+        .iter()
+        .find(|f| f.display_label)
+        .and_then(|f| f.ident.as_ref())
+        .map(|ident| {
+            quote! {
+                fn display_label(&self) -> ::std::string::String {
+                    ::std::string::ToString::to_string(&self.#ident)
+                }
+            }
+        });
+    let version_token = fields
+        .iter()
+        .find(|f| f.version)
+        .and_then(|f| f.ident.as_ref())
+        .map(|ident| {
+            quote! {
+                fn version_token(&self) -> ::std::option::Option<::std::string::String> {
+                    ::std::option::Option::Some(::std::string::ToString::to_string(&self.#ident))
+                }
+            }
+        });
+    let referenced_files = referenced_files_fn(&fields);
+    let confirm_api_delete = struct_attr.confirm_api_delete.then(|| {
+        quote! {
+            fn confirm_api_delete() -> ::std::primitive::bool {
+                true
+            }
+        }
+    });
+    let delete_confirmation = struct_attr
+        .delete_confirmation
+        .as_ref()
+        .map(|delete_confirmation| {
+            quote! {
+                fn delete_confirmation(&self) -> #found_crate::entity::DeleteConfirm {
+                    #delete_confirmation(self)
+                }
+            }
+        });
+    let list_rows = match struct_attr.list_rows {
+        true if !computed.is_empty() => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "computed columns require the whole entity and can't be used with `list_rows`",
+            ))
+        }
+        true if struct_attr.delete_confirmation.is_some() => return Err(syn::Error::new_spanned(
+            input,
+            "`delete_confirmation` requires the whole entity and can't be used with `list_rows`",
+        )),
+        true => list_rows_impl(input, &fields, id_ident, &bounds, &update_bounds),
+        false => quote!(),
+    };
+    let update_struct = struct_attr
+        .update_struct
+        .as_ref()
+        .map(|update| update_struct_impl(input, &fields, &struct_attr, update, &bounds));
 
     Ok(quote! {
         #[automatically_derived]
@@ -135,6 +338,7 @@ pub fn derive_struct(input: &DeriveInput, data: &DataStruct) -> syn::Result<Toke
         where
             Self: #found_crate::derive::ormlite::Model<#found_crate::DB>,
             #bounds
+            #update_bounds
         {
             type Id = #id_type;
 
@@ -154,9 +358,41 @@ pub fn derive_struct(input: &DeriveInput, data: &DataStruct) -> syn::Result<Toke
                 &self.#id_ident
             }
 
+            fn to_update(&self) -> impl ::std::borrow::Borrow<Self::Update> + '_ {
+                #to_update
+            }
+
+            #url_id
+            #display_label
+            #version_token
+            #referenced_files
+            #nav_priority
+            #confirm_api_delete
+            #delete_confirmation
             #columns
             #column_values
+            #visible_columns
+            #search
+            #filter
+        }
+
+        #[automatically_derived]
+        impl #found_crate::entity::EntityPath<#id_type> for #ident {
+            fn list_path() -> ::std::string::String {
+                ::std::string::ToString::to_string(#list_path)
+            }
+            fn edit_path(id: &#id_type) -> ::std::string::String {
+                #found_crate::entity::edit_path(#name, &#url_id_string)
+            }
+        }
+
+        #[automatically_derived]
+        impl<S: #found_crate::context::ContextTrait> #found_crate::input::Inputs<S> for #ident
+        where
+            #bounds
+        {
             #inputs
+            #validate
         }
 
         #[automatically_derived]
@@ -169,11 +405,221 @@ pub fn derive_struct(input: &DeriveInput, data: &DataStruct) -> syn::Result<Toke
             Self: #found_crate::entity::Update<S>,
             Self: #found_crate::entity::Delete<S>,
             #bounds
+            #update_bounds
         {
         }
+
+        #(#computed_columns)*
+
+        #list_rows
+
+        #update_struct
     })
 }
 
+/// the struct `update` with the fields of the entity, implementing `Inputs` and `From<&Entity>`.
+/// `Option<T>` fields are `Option<Option<T>>` (de)serialized using `double_option`.
+fn update_struct_impl(
+    input: &DeriveInput,
+    fields: &[EntityFieldOptions],
+    struct_attr: &EntityStructOptions,
+    update: &Ident,
+    bounds: &TokenStream,
+) -> TokenStream {
+    let found_crate = found_crate();
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let doc = format!(
+        "The data used to update [`{ident}`]. Its optional fields are `None` if the key is missing \
+         and `Some(None)` if it is `null`, see `double_option`."
+    );
+    let serde_crate = format!("{found_crate}::derive::serde");
+    let double_option = format!("{found_crate}::double_option");
+
+    let update_fields = fields.iter().filter_map(|f| {
+        let ident = f.ident.as_ref()?;
+        let ty = &f.ty;
+        Some(match is_option(ty) {
+            true => {
+                let name = f.name(ident, struct_attr.rename_all);
+                quote! {
+                    #[serde(
+                        rename = #name,
+                        default,
+                        skip_serializing_if = "::std::option::Option::is_none",
+                        with = #double_option
+                    )]
+                    #vis #ident: ::std::option::Option<#ty>,
+                }
+            }
+            false => {
+                let serde_attrs = &f.serde_attrs;
+                // the other fields are renamed by `rename_all` of the entity
+                let rename = (f.serde_rename.is_none() && !f.flatten).then(|| {
+                    let name = f.name(ident, struct_attr.rename_all);
+                    quote!(#[serde(rename = #name)])
+                });
+                quote! {
+                    #(#serde_attrs)*
+                    #rename
+                    #vis #ident: #ty,
+                }
+            }
+        })
+    });
+    let from_fields = fields.iter().filter_map(|f| {
+        let ident = f.ident.as_ref()?;
+        Some(match is_option(&f.ty) {
+            true => quote!(#ident: ::std::option::Option::Some(::std::clone::Clone::clone(&value.#ident)),),
+            false => quote!(#ident: ::std::clone::Clone::clone(&value.#ident),),
+        })
+    });
+
+    let inputs = inputs_fn(fields, struct_attr);
+    // a missing optional field is not checked, since it is not changed
+    let checks = fields
+        .iter()
+        .filter_map(|f| {
+            let ident = f.ident.as_ref()?;
+            let name = f.name(ident, struct_attr.rename_all);
+            let path = f.validate.as_ref()?;
+            Some(match is_option(&f.ty) {
+                true => quote! {
+                    if let ::std::option::Option::Some(v) = &self.#ident {
+                        errors.check(#name, #path(v));
+                    }
+                },
+                false => quote!(errors.check(#name, #path(&self.#ident));),
+            })
+        })
+        .collect::<Vec<_>>();
+    let validate = (!checks.is_empty()).then(|| {
+        quote! {
+            fn validate(&self) -> ::std::result::Result<(), #found_crate::validate::ValidationErrors> {
+                let mut errors = #found_crate::validate::ValidationErrors::new();
+                #(#checks)*
+                errors.into_result()
+            }
+        }
+    });
+
+    quote! {
+        #[doc = #doc]
+        #[derive(::std::fmt::Debug, #found_crate::derive::serde::Deserialize, #found_crate::derive::serde::Serialize)]
+        #[serde(crate = #serde_crate)]
+        #vis struct #update {
+            #(#update_fields)*
+        }
+
+        #[automatically_derived]
+        impl ::std::convert::From<&#ident> for #update {
+            fn from(value: &#ident) -> Self {
+                Self {
+                    #(#from_fields)*
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl<S: #found_crate::context::ContextTrait> #found_crate::input::Inputs<S> for #update
+        where
+            #bounds
+        {
+            #inputs
+            #validate
+        }
+    }
+}
+
+/// a struct `{Entity}ListRow` with the id and all columns, implementing `ListRow` and `FromRow`
+fn list_rows_impl(
+    input: &DeriveInput,
+    fields: &[EntityFieldOptions],
+    id_ident: &Ident,
+    bounds: &TokenStream,
+    update_bounds: &TokenStream,
+) -> TokenStream {
+    let found_crate = found_crate();
+    let ormlite = quote!(#found_crate::derive::ormlite);
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let row_ident = format_ident!("{ident}ListRow");
+    let doc = format!("The id and the columns of [`{ident}`] rendered on the list page.");
+
+    let row_fields = fields
+        .iter()
+        .filter(|f| f.id || !f.skip_column)
+        .filter_map(|f| Some((f.ident.as_ref()?, f)))
+        .collect::<Vec<_>>();
+    let idents = row_fields
+        .iter()
+        .map(|(ident, _)| ident)
+        .collect::<Vec<_>>();
+    let types = row_fields.iter().map(|(_, f)| &f.ty).collect::<Vec<_>>();
+    let names = row_fields
+        .iter()
+        .map(|(ident, f)| f.column_name(ident))
+        .collect::<Vec<_>>();
+    let columns = fields
+        .iter()
+        .filter(|f| !f.skip_column)
+        .filter_map(|f| f.ident.as_ref());
+
+    quote! {
+        #[doc = #doc]
+        #[derive(::std::fmt::Debug)]
+        #vis struct #row_ident {
+            #(#vis #idents: #types,)*
+        }
+
+        #[automatically_derived]
+        impl<'r> #ormlite::FromRow<'r, <#found_crate::DB as #ormlite::Database>::Row> for #row_ident
+        where
+            #(#types: #ormlite::Decode<'r, #found_crate::DB> + #ormlite::types::Type<#found_crate::DB>,)*
+        {
+            fn from_row(
+                row: &'r <#found_crate::DB as #ormlite::Database>::Row,
+            ) -> ::std::result::Result<Self, #ormlite::SqlxError> {
+                ::std::result::Result::Ok(Self {
+                    #(#idents: #ormlite::Row::try_get(row, #names)?,)*
+                })
+            }
+        }
+
+        #[automatically_derived]
+        impl<S: #found_crate::context::ContextTrait> #found_crate::entity::ListRow<#ident, S> for #row_ident
+        where
+            #ident: #ormlite::Model<#found_crate::DB>,
+            #bounds
+            #update_bounds
+        {
+            fn id(&self) -> &<#ident as #found_crate::EntityBase<S>>::Id {
+                &self.#id_ident
+            }
+            fn column_values<'a>(&'a self) -> #found_crate::derive::generic_array::GenericArray<&'a dyn #found_crate::Column, <#ident as #found_crate::EntityBase<S>>::NumberOfColumns> {
+                #found_crate::derive::generic_array::arr![#(&self.#columns,)*]
+            }
+            fn delete_confirmation(&self) -> #found_crate::entity::DeleteConfirm {
+                ::std::default::Default::default()
+            }
+        }
+
+        #[automatically_derived]
+        impl<S: #found_crate::context::ContextTrait> #found_crate::entity::ListRows<S> for #ident
+        where
+            Self: #ormlite::Model<#found_crate::DB>,
+            #bounds
+            #update_bounds
+        {
+            type Row = #row_ident;
+
+            fn row_columns() -> &'static [&'static ::std::primitive::str] {
+                &[#(#names,)*]
+            }
+        }
+    }
+}
+
 fn colums_fn(fields: &[EntityFieldOptions], struct_attr: &EntityStructOptions) -> TokenStream {
     let found_crate = found_crate();
     let columns = fields.iter().filter(|f| !f.skip_column).map(|f| {
@@ -182,15 +628,30 @@ fn colums_fn(fields: &[EntityFieldOptions], struct_attr: &EntityStructOptions) -
                 "`Entity` can only be derived for `struct`s with named fields"
             ));
         };
-        let name = renamed_name(ident.to_string(), f.rename.as_ref(), struct_attr.rename_all);
+        let name = f.name(ident, struct_attr.rename_all);
+        let name = field_label(f.rename.as_ref(), &name, struct_attr.label_case);
         let hidden = f.column_hidden;
+        let thumbnail = f.column_thumbnail;
+        quote! {
+            #found_crate::column::ColumnInfo {
+                name: #name,
+                hidden: #hidden,
+                thumbnail: #thumbnail,
+            }
+        }
+    });
+    let computed = struct_attr.computed_columns.iter().map(|c| {
+        let name = &c.label;
+        let hidden = c.hidden;
         quote! {
             #found_crate::column::ColumnInfo {
                 name: #name,
-                hidden: #hidden
+                hidden: #hidden,
+                thumbnail: false,
             }
         }
     });
+    let columns = columns.chain(computed);
     quote! {
         fn columns() -> #found_crate::derive::generic_array::GenericArray<#found_crate::column::ColumnInfo, Self::NumberOfColumns> {
             #found_crate::derive::generic_array::arr![#(#columns,)*]
@@ -198,7 +659,7 @@ fn colums_fn(fields: &[EntityFieldOptions], struct_attr: &EntityStructOptions) -
     }
 }
 
-fn column_values_fn(fields: &[EntityFieldOptions]) -> TokenStream {
+fn column_values_fn(fields: &[EntityFieldOptions], computed: usize) -> TokenStream {
     let found_crate = found_crate();
     let columns = fields
         .iter()
@@ -213,6 +674,11 @@ fn column_values_fn(fields: &[EntityFieldOptions]) -> TokenStream {
                 &self.#ident,
             }
         })
+        .chain((0..computed).map(|i| {
+            quote! {
+                #found_crate::column::Computed::<Self, #i>::of(self),
+            }
+        }))
         .collect::<TokenStream>();
     quote! {
         fn column_values<'a>(&'a self) -> #found_crate::derive::generic_array::GenericArray<&'a dyn #found_crate::Column, Self::NumberOfColumns> {
@@ -221,6 +687,111 @@ fn column_values_fn(fields: &[EntityFieldOptions]) -> TokenStream {
     }
 }
 
+fn visible_columns_fn(fields: &[EntityFieldOptions], computed: usize) -> TokenStream {
+    let found_crate = found_crate();
+    let cols = fields.iter().filter(|f| !f.skip_column).collect::<Vec<_>>();
+    if cols.iter().all(|f| f.visible_if.is_none()) {
+        return quote!();
+    }
+    let visible = cols
+        .iter()
+        .map(|f| {
+            let visible = visible_fn(f.visible_if.as_ref());
+            quote!((#visible)(ctx))
+        })
+        .chain((0..computed).map(|_| quote!(true)));
+    quote! {
+        fn visible_columns(ctx: &S) -> #found_crate::derive::generic_array::GenericArray<::std::primitive::bool, Self::NumberOfColumns> {
+            #found_crate::derive::generic_array::arr![#(#visible,)*]
+        }
+    }
+}
+
+fn search_fns(fields: &[EntityFieldOptions]) -> TokenStream {
+    let found_crate = found_crate();
+    let idents = fields
+        .iter()
+        .filter(|f| f.searchable)
+        .filter_map(|f| f.ident.as_ref())
+        .collect::<Vec<_>>();
+    if idents.is_empty() {
+        return quote!();
+    }
+    let names = fields
+        .iter()
+        .filter(|f| f.searchable)
+        .filter_map(|f| Some(f.column_name(f.ident.as_ref()?)));
+    quote! {
+        fn searchable_columns() -> &'static [&'static ::std::primitive::str] {
+            &[#(#names,)*]
+        }
+        fn matches_search(&self, query: &::std::primitive::str) -> ::std::primitive::bool {
+            #(#found_crate::entity::search_matches(&self.#idents, query))||*
+        }
+    }
+}
+
+/// `EntityBase::referenced_files` collecting the uploads of the fields referencing files
+fn referenced_files_fn(fields: &[EntityFieldOptions]) -> TokenStream {
+    let found_crate = found_crate();
+    let idents = fields
+        .iter()
+        .filter(|f| f.files.unwrap_or_else(|| references_files(&f.ty)))
+        .filter_map(|f| f.ident.as_ref())
+        .collect::<Vec<_>>();
+    if idents.is_empty() {
+        return quote!();
+    }
+    quote! {
+        fn referenced_files(&self) -> ::std::vec::Vec<#found_crate::derive::uuid::Uuid> {
+            let mut ids = ::std::vec::Vec::new();
+            #(#found_crate::property::ReferencedFiles::referenced_files(&self.#idents, &mut ids);)*
+            ids
+        }
+    }
+}
+
+fn filter_fns(fields: &[EntityFieldOptions], struct_attr: &EntityStructOptions) -> TokenStream {
+    let found_crate = found_crate();
+    let fields = fields
+        .iter()
+        .filter(|f| f.filter)
+        .filter_map(|f| {
+            let ident = f.ident.as_ref()?;
+            let name = f.name(ident, struct_attr.rename_all);
+            Some((ident, name, f.column_name(ident), &f.ty))
+        })
+        .collect::<Vec<_>>();
+    if fields.is_empty() {
+        return quote!();
+    }
+    let filter_fields = fields.iter().map(|(_, name, column, ty)| {
+        quote! {
+            #name => ::std::option::Option::Some(#found_crate::filter::FilterField {
+                column: #column,
+                parse: <#ty as #found_crate::filter::Filterable>::parse_filter,
+            })
+        }
+    });
+    let matches = fields
+        .iter()
+        .map(|(ident, name, _, _)| quote!(#name => condition.matches(&self.#ident)));
+    quote! {
+        fn filter_field(name: &::std::primitive::str) -> ::std::option::Option<#found_crate::filter::FilterField> {
+            match name {
+                #(#filter_fields,)*
+                _ => ::std::option::Option::None,
+            }
+        }
+        fn matches_filter(&self, condition: &#found_crate::filter::Condition) -> ::std::primitive::bool {
+            match condition.field.as_str() {
+                #(#matches,)*
+                _ => true,
+            }
+        }
+    }
+}
+
 fn inputs_fn(fields: &[EntityFieldOptions], struct_attr: &EntityStructOptions) -> TokenStream {
     let found_crate = found_crate();
     let inputs = fields.iter().filter(|f| !f.skip_input).map(|f| {
@@ -229,11 +800,23 @@ fn inputs_fn(fields: &[EntityFieldOptions], struct_attr: &EntityStructOptions) -
                 "`Entity` can only be derived for `struct`s with named fields"
             ));
         };
-        let name = renamed_name(ident.to_string(), f.rename.as_ref(), struct_attr.rename_all);
+        let name = f.name(ident, struct_attr.rename_all);
+        let name_human = field_label(f.rename.as_ref(), &name, struct_attr.label_case);
+        let required = !f.optional;
+        let constraints =
+            input_constraints(f.min_length, f.max_length, f.accept.as_ref(), f.max_size);
+        let tab = option_tokens(f.tab.as_deref());
+        let visible = visible_fn(f.visible_if.as_ref());
+        let help = help_tokens(f.help.as_ref(), f.help_key.as_ref());
         quote! {
             #found_crate::input::InputInfo::<'a, S> {
                 name: #name,
-                name_human: #name,
+                name_human: #name_human,
+                required: #required,
+                constraints: #constraints,
+                tab: #tab,
+                visible: #visible,
+                help: #help,
                 value: ::std::boxed::Box::new(::std::option::Option::map(value, |v| &v.#ident)),
             }
         }
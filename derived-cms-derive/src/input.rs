@@ -1,9 +1,14 @@
+use convert_case::{Case, Casing};
 use darling::{FromDeriveInput, FromField, FromVariant};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
-use syn::{DataEnum, DataStruct, DeriveInput, Field, Type};
+use syn::{DataEnum, DataStruct, DeriveInput, Field, Path, Type};
 
-use crate::util::{found_crate, renamed_name, RenameAll};
+use crate::util::{
+    field_label, found_crate, has_cms_option, help_tokens, input_constraints, is_flattened,
+    is_optional, option_tokens, renamed_name, serde_rename, validate_fn, visible_fn, ByteSize,
+    LabelCase, RenameAll,
+};
 
 /**********
  * struct *
@@ -13,47 +18,58 @@ use crate::util::{found_crate, renamed_name, RenameAll};
 #[darling(attributes(cms, serde))]
 struct InputStructOptions {
     rename_all: Option<RenameAll>,
+    /// casing of the labels of inputs, the names in forms always match serde
+    label_case: Option<LabelCase>,
 }
 
 #[derive(Debug, FromField)]
-#[darling(attributes(cms, serde))]
+#[darling(attributes(cms))]
 struct InputFieldOptions {
     ty: Type,
     ident: Option<Ident>,
     /// Do not display this field in list columns
     #[darling(default)]
     skip_input: bool,
+    /// the label in forms, overriding the name in serde
     rename: Option<String>,
+    #[darling(skip)]
+    flatten: bool,
+    max_length: Option<usize>,
+    min_length: Option<usize>,
+    /// accepted types of uploaded files, e.g. `"image/*"`
+    accept: Option<String>,
+    /// maximum size of each uploaded file, e.g. `"5MB"`
+    max_size: Option<ByteSize>,
+    /// Render this field in a tab with this name if the struct is the top level of a form
+    tab: Option<String>,
+    /// Only show the input if `<T as Visibility<S>>::visible` returns `true`
+    visible_if: Option<Path>,
+    /// `fn(&T) -> Result<(), impl Into<String>>` checking the submitted value
+    validate: Option<Path>,
+    /// Description rendered beneath the input
+    help: Option<String>,
+    /// id of the localized description rendered beneath the input
+    help_key: Option<String>,
+    #[darling(skip)]
+    optional: bool,
+    /// `#[serde(rename = "...")]`
+    #[darling(skip)]
+    serde_rename: Option<String>,
 }
 
 impl InputFieldOptions {
     fn parse(f: &Field) -> Result<Self, darling::Error> {
-        // TODO: allow overwriting options from serde with #[cms(...)]
-        let attrs = f
-            .attrs
-            .iter()
-            // filter serde fields
-            .filter(|a| {
-                let path = a.path();
-                if !path.is_ident(&Ident::new("serde", Span::call_site())) {
-                    return true;
-                }
-                if let syn::Meta::NameValue(v) = &a.meta {
-                    return v.path.is_ident(&Ident::new("rename", Span::call_site()));
-                }
-                false
-            })
-            .cloned()
-            .collect();
-        let f = Field {
-            attrs,
-            vis: f.vis.clone(),
-            mutability: f.mutability.clone(),
-            ident: f.ident.clone(),
-            colon_token: f.colon_token,
-            ty: f.ty.clone(),
-        };
-        Self::from_field(&f)
+        Ok(Self {
+            flatten: is_flattened(f),
+            optional: is_optional(f),
+            serde_rename: serde_rename(&f.attrs),
+            ..Self::from_field(f)?
+        })
+    }
+
+    /// the name in serde and forms
+    fn name(&self, ident: &Ident, rename_all: Option<RenameAll>) -> String {
+        renamed_name(ident.to_string(), self.serde_rename.as_ref(), rename_all).into_owned()
     }
 }
 
@@ -73,23 +89,80 @@ pub fn derive_struct(input: &DeriveInput, data: &DataStruct) -> syn::Result<Toke
         .iter()
         .filter(|attr| !attr.skip_input)
         .map(|InputFieldOptions { ty, .. }| quote! (#ty: #found_crate::Input<S>,))
+        .chain(
+            fields
+                .iter()
+                .filter_map(|f| f.visible_if.as_ref())
+                .map(|path| quote!(#path: #found_crate::context::Visibility<S>,)),
+        )
         .collect::<TokenStream>();
 
+    let inputs_top_level = fields.iter().filter(|f| !f.skip_input).map(|f| {
+        let Some(ident) = &f.ident else {
+            return quote!(compile_error!(
+                "`Input` can only be derived for `struct`s with named fields"
+            ));
+        };
+        let name = f.name(ident, struct_attr.rename_all);
+        let name_human = field_label(f.rename.as_ref(), &name, struct_attr.label_case);
+        let required = !f.optional;
+        let constraints =
+            input_constraints(f.min_length, f.max_length, f.accept.as_ref(), f.max_size);
+        let tab = option_tokens(f.tab.as_deref());
+        let visible = visible_fn(f.visible_if.as_ref());
+        let help = help_tokens(f.help.as_ref(), f.help_key.as_ref());
+        quote! {
+            #found_crate::input::InputInfo::<'a, S> {
+                name: #name,
+                name_human: #name_human,
+                required: #required,
+                constraints: #constraints,
+                tab: #tab,
+                visible: #visible,
+                help: #help,
+                value: ::std::boxed::Box::new(::std::option::Option::map(value, |v| &v.#ident)),
+            }
+        }
+    });
+
+    let validate = validate_fn(fields.iter().filter_map(|f| {
+        let ident = f.ident.as_ref()?;
+        let name = f.name(ident, struct_attr.rename_all);
+        Some((ident, name, f.validate.as_ref()?))
+    }));
+
     let inputs = fields.iter().filter(|f| !f.skip_input).map(|f| {
         let Some(ident) = &f.ident else {
             return quote!(compile_error!(
-                "`Entity` can only be derived for `struct`s with named fields"
+                "`Input` can only be derived for `struct`s with named fields"
             ));
         };
-        let name = renamed_name(ident.to_string(), f.rename.as_ref(), struct_attr.rename_all);
+        let name = f.name(ident, struct_attr.rename_all);
+        let name_human = field_label(f.rename.as_ref(), &name, struct_attr.label_case);
+        // the fields of a flattened struct are on the same level as the other fields
+        let name_input = match f.flatten {
+            true => quote!(name),
+            false => quote!(&::std::format!("{}[{}]", name, #name)),
+        };
+        let required = !f.optional;
+        let constraints =
+            input_constraints(f.min_length, f.max_length, f.accept.as_ref(), f.max_size);
+        let visible = visible_fn(f.visible_if.as_ref());
+        let help = help_tokens(f.help.as_ref(), f.help_key.as_ref());
         quote! {
             #found_crate::input::InputInfo {
-                name: &::std::format!("{}[{}]", name, #name),
-                name_human: #name,
+                name: #name_input,
+                name_human: #name_human,
+                required: #required,
+                constraints: #constraints,
+                tab: ::std::option::Option::None,
+                visible: #visible,
+                help: #help,
                 value: ::std::boxed::Box::new(::std::option::Option::map(value, |v| &v.#ident)),
             }
         }
     });
+    let inputs = inputs.collect::<Vec<_>>();
 
     Ok(quote! {
         #[automatically_derived]
@@ -107,6 +180,26 @@ pub fn derive_struct(input: &DeriveInput, data: &DataStruct) -> syn::Result<Toke
             ) -> #found_crate::derive::maud::Markup {
                 #found_crate::render::struct_input(ctx, i18n, [#(#inputs, )*])
             }
+
+            fn check_constraints(
+                &self,
+                name: &::std::primitive::str,
+                _constraints: &#found_crate::input::InputConstraints,
+            ) -> ::std::result::Result<(), #found_crate::input::ConstraintError> {
+                let value = ::std::option::Option::Some(self);
+                #found_crate::input::check_inputs::<S>([#(#inputs, )*])
+            }
+        }
+
+        #[automatically_derived]
+        impl<S: #found_crate::context::ContextTrait> #found_crate::input::Inputs<S> for #ident
+        where
+            #bounds
+        {
+            fn inputs<'a>(value: ::std::option::Option<&'a Self>) -> impl ::std::iter::IntoIterator<Item = #found_crate::input::InputInfo<'a, S>> {
+                [#(#inputs_top_level, )*]
+            }
+            #validate
         }
     })
 }
@@ -119,8 +212,13 @@ pub fn derive_struct(input: &DeriveInput, data: &DataStruct) -> syn::Result<Toke
 #[darling(attributes(cms, serde))]
 struct InputEnumOptions {
     rename_all: Option<RenameAll>,
+    /// casing of the fields of struct variants, the same as `#[serde(rename_all_fields)]`
+    rename_all_fields: Option<RenameAll>,
+    /// casing of the labels of the fields of variants
+    label_case: Option<LabelCase>,
     tag: String,
-    content: String,
+    /// not required if all variants are unit variants
+    content: Option<String>,
 }
 
 #[derive(Debug, FromVariant)]
@@ -129,16 +227,113 @@ struct InputVariantOptions {
 }
 
 pub fn derive_enum(input: &DeriveInput, data: &DataEnum) -> syn::Result<TokenStream> {
+    if has_cms_option(&input.attrs, "strum") {
+        #[cfg(feature = "strum")]
+        return Ok(derive_strum_enum(input));
+        #[cfg(not(feature = "strum"))]
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`#[cms(strum)]` requires the `strum` feature of derived-cms",
+        ));
+    }
+
     let found_crate = found_crate();
 
     let ident = &input.ident;
     let attr = InputEnumOptions::from_derive_input(input)?;
 
-    let bounds = data
+    if !data.variants.is_empty()
+        && data
+            .variants
+            .iter()
+            .all(|v| matches!(v.fields, syn::Fields::Unit))
+    {
+        return derive_unit_enum(input, data, &attr);
+    }
+    let Some(content) = attr.content.clone() else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "enums with fields require `#[serde(tag = \"...\", content = \"...\")]`",
+        ));
+    };
+
+    let fields = data
         .variants
         .iter()
         .flat_map(|v| &v.fields)
-        .map(|Field { ty, .. }| quote! (#ty: #found_crate::Input<S>));
+        .map(InputFieldOptions::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+    let bounds = fields
+        .iter()
+        .filter(|f| !f.skip_input)
+        .map(|InputFieldOptions { ty, .. }| quote! (#ty: #found_crate::Input<S>))
+        .chain(
+            fields
+                .iter()
+                .filter_map(|f| f.visible_if.as_ref())
+                .map(|path| quote!(#path: #found_crate::context::Visibility<S>)),
+        );
+
+    // fields marked with `#[serde(flatten)]` have to be present in every variant
+    // and are rendered once independent of the selected variant
+    let shared = fields
+        .iter()
+        .filter(|f| f.flatten)
+        .filter_map(|f| Some((f.ident.as_ref()?, f)))
+        .fold(Vec::new(), |mut acc, (ident, f)| {
+            if !acc.iter().any(|(i, _)| *i == ident) {
+                acc.push((ident, f));
+            }
+            acc
+        });
+    if !shared.is_empty() {
+        for v in &data.variants {
+            let flattened = v
+                .fields
+                .iter()
+                .filter(|f| is_flattened(f))
+                .filter_map(|f| f.ident.as_ref())
+                .collect::<Vec<_>>();
+            if !matches!(v.fields, syn::Fields::Named(_))
+                || shared.iter().any(|(ident, _)| !flattened.contains(ident))
+            {
+                return Err(syn::Error::new_spanned(
+                    v,
+                    "if a variant has fields marked with `#[serde(flatten)]`, every variant must \
+                     have the same flattened fields",
+                ));
+            }
+        }
+    }
+    let shared_inputs = shared.iter().map(|(field, f)| {
+        let content = &content;
+        let name_human = field_label(
+            f.rename.as_ref(),
+            &f.name(field, attr.rename_all_fields),
+            attr.label_case,
+        );
+        let variants = data.variants.iter().map(|v| {
+            let ident = &v.ident;
+            quote!(Self::#ident { #field, .. })
+        });
+        quote! {
+            #found_crate::input::InputInfo {
+                name: &::std::format!("{}[{}]", name, #content),
+                name_human: #name_human,
+                required: true,
+                constraints: ::std::default::Default::default(),
+                tab: ::std::option::Option::None,
+                visible: |_| true,
+                help: ::std::option::Option::None,
+                value: ::std::boxed::Box::new(match value {
+                    ::std::option::Option::Some(#(#variants)|*) => ::std::option::Option::Some(#field),
+                    _ => ::std::option::Option::None,
+                }),
+            },
+        }
+    });
+    let shared_inputs = quote!(#(#shared_inputs)*);
+
     let x = data
         .variants
         .iter()
@@ -147,14 +342,60 @@ pub fn derive_enum(input: &DeriveInput, data: &DataEnum) -> syn::Result<TokenStr
 
             let ident = &v.ident;
             let tag = &attr.tag;
-            let content = &attr.content;
+            let content = &content;
 
             let name_tag = quote!(&::std::format!("{}[{}]", name, #tag));
             let name_content = quote!(&::std::format!("{}[{}]", name, #content));
             let value = renamed_name(ident.to_string(), variant_attr.rename, attr.rename_all);
 
+            let fields = match v.fields {
+                syn::Fields::Named(ref fields) => fields
+                    .named
+                    .iter()
+                    .map(InputFieldOptions::parse)
+                    .collect::<Result<Vec<_>, _>>()?
+                    .into_iter()
+                    .filter(|f| !f.flatten && !f.skip_input)
+                    .filter_map(|f| {
+                        let field = f.ident.as_ref()?;
+                        // the fields of struct variants are renamed by `rename_all_fields`,
+                        // not `rename_all`
+                        let field_name = f.name(field, attr.rename_all_fields);
+                        let field_label =
+                            field_label(f.rename.as_ref(), &field_name, attr.label_case);
+                        let required = !f.optional;
+                        let constraints = input_constraints(
+                            f.min_length,
+                            f.max_length,
+                            f.accept.as_ref(),
+                            f.max_size,
+                        );
+                        let visible = visible_fn(f.visible_if.as_ref());
+                        let help = help_tokens(f.help.as_ref(), f.help_key.as_ref());
+                        Some(quote! {
+                            #found_crate::input::InputInfo {
+                                name: &::std::format!("{}[{}][{}]", name, #content, #field_name),
+                                name_human: #field_label,
+                                required: #required,
+                                constraints: #constraints,
+                                tab: ::std::option::Option::None,
+                                visible: #visible,
+                                help: #help,
+                                value: ::std::boxed::Box::new(match value {
+                                    ::std::option::Option::Some(Self::#ident { #field, .. }) => {
+                                        ::std::option::Option::Some(#field)
+                                    }
+                                    _ => ::std::option::Option::None,
+                                }),
+                            },
+                        })
+                    })
+                    .collect::<TokenStream>(),
+                _ => TokenStream::new(),
+            };
+
             let content_val = match v.fields {
-                syn::Fields::Named(_) => todo!(),
+                syn::Fields::Named(_) => None,
                 syn::Fields::Unnamed(ref fields) => {
                     let fields = &fields.unnamed;
                     let fields = fields
@@ -180,6 +421,11 @@ pub fn derive_enum(input: &DeriveInput, data: &DataEnum) -> syn::Result<TokenStr
                         ::std::option::Option::Some(#found_crate::input::InputInfo {
                             name: #name_content,
                             name_human: #content,
+                            required: true,
+                            constraints: ::std::default::Default::default(),
+                            tab: ::std::option::Option::None,
+                            visible: |_| true,
+                            help: ::std::option::Option::None,
                             value: ::std::boxed::Box::new(#content_val),
                         })
                     }
@@ -191,28 +437,35 @@ pub fn derive_enum(input: &DeriveInput, data: &DataEnum) -> syn::Result<TokenStr
                     name: #name_tag,
                     value: #value,
                     content: #content_val,
+                    fields: ::std::vec![#fields],
                 },
             })
         })
         .collect::<syn::Result<TokenStream>>()?;
 
-    let selected_idx = data.variants.iter().enumerate().map(|(i, v)| {
-        let ident = &v.ident;
-        let fields = match &v.fields {
-            syn::Fields::Named(_) => quote!({ .. }),
-            syn::Fields::Unnamed(f) => f
-                .unnamed
-                .iter()
-                .enumerate()
-                .map(|(i, _)| {
-                    let ident = Ident::new(&format!("V{i}"), Span::call_site());
-                    quote!((#ident,))
-                })
-                .collect(),
-            syn::Fields::Unit => quote!(),
-        };
-        quote!(Self::#ident #fields => #i)
-    });
+    let selected_idx = data
+        .variants
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let ident = &v.ident;
+            let fields = match &v.fields {
+                syn::Fields::Named(_) => quote!({ .. }),
+                syn::Fields::Unnamed(f) => f
+                    .unnamed
+                    .iter()
+                    .enumerate()
+                    .map(|(i, _)| {
+                        let ident = Ident::new(&format!("V{i}"), Span::call_site());
+                        quote!((#ident,))
+                    })
+                    .collect(),
+                syn::Fields::Unit => quote!(),
+            };
+            quote!(Self::#ident #fields => #i)
+        })
+        .collect::<Vec<_>>();
+    let tag = &attr.tag;
 
     Ok(quote! {
         #[automatically_derived]
@@ -228,14 +481,133 @@ pub fn derive_enum(input: &DeriveInput, data: &DataEnum) -> syn::Result<TokenStr
                 ctx: &#found_crate::render::FormRenderContext<'_, S>,
                 i18n: &#found_crate::derive::i18n_embed::fluent::FluentLanguageLoader,
             ) -> #found_crate::derive::maud::Markup {
-                let selected_idx = match value {
-                    Some(v) => match v {
-                        #(#selected_idx,)*
-                    },
-                    None => 0,
-                };
-                #found_crate::render::input_enum(ctx, i18n, &[#x], selected_idx, required)
+                let selected_idx = value.map(|v| match v {
+                    #(#selected_idx,)*
+                });
+                #found_crate::render::input_enum(ctx, i18n, &[#x], &[#shared_inputs], selected_idx, required, false)
+            }
+
+            fn render_input_optional(
+                value: ::std::option::Option<&Self>,
+                name: &::std::primitive::str,
+                _name_human: &::std::primitive::str,
+                ctx: &#found_crate::render::FormRenderContext<'_, S>,
+                i18n: &#found_crate::derive::i18n_embed::fluent::FluentLanguageLoader,
+            ) -> #found_crate::derive::maud::Markup {
+                let selected_idx = value.map(|v| match v {
+                    #(#selected_idx,)*
+                });
+                #found_crate::render::input_enum(ctx, i18n, &[#x], &[#shared_inputs], selected_idx, false, true)
+            }
+
+            fn optional_key() -> ::std::option::Option<&'static ::std::primitive::str> {
+                ::std::option::Option::Some(#tag)
+            }
+
+            fn check_constraints(
+                &self,
+                name: &::std::primitive::str,
+                _constraints: &#found_crate::input::InputConstraints,
+            ) -> ::std::result::Result<(), #found_crate::input::ConstraintError> {
+                let value = ::std::option::Option::Some(self);
+                for variant in [#x] {
+                    #found_crate::input::check_inputs::<S>(variant.content.into_iter().chain(variant.fields))?;
+                }
+                #found_crate::input::check_inputs::<S>([#shared_inputs])
+            }
+        }
+    })
+}
+
+/// An enum with only unit variants: a select with the tags of the variants, labeled like the radio
+/// buttons of other enums. Submitted as `{name}[{tag}]`, the same as the radio buttons.
+fn derive_unit_enum(
+    input: &DeriveInput,
+    data: &DataEnum,
+    attr: &InputEnumOptions,
+) -> syn::Result<TokenStream> {
+    let found_crate = found_crate();
+    let ident = &input.ident;
+    let tag = &attr.tag;
+    let values = data
+        .variants
+        .iter()
+        .map(|v| {
+            let variant_attr = InputVariantOptions::from_variant(v)?;
+            let value = renamed_name(v.ident.to_string(), variant_attr.rename, attr.rename_all);
+            Ok((&v.ident, value.into_owned()))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    let selected = values
+        .iter()
+        .map(|(variant, value)| quote!(Self::#variant => #value));
+    let options = values.iter().map(|(_, value)| {
+        let label = value.to_case(Case::Title);
+        quote! {
+            (
+                ::std::string::ToString::to_string(#value),
+                ::std::string::ToString::to_string(#label),
+            )
+        }
+    });
+    Ok(quote! {
+        #[automatically_derived]
+        impl<S: #found_crate::context::ContextTrait> #found_crate::Input<S> for #ident {
+            fn render_input(
+                value: ::std::option::Option<&Self>,
+                name: &::std::primitive::str,
+                _name_human: &::std::primitive::str,
+                required: ::std::primitive::bool,
+                _ctx: &#found_crate::render::FormRenderContext<'_, S>,
+                _i18n: &#found_crate::derive::i18n_embed::fluent::FluentLanguageLoader,
+            ) -> #found_crate::derive::maud::Markup {
+                let selected = value.map(|v| match v {
+                    #(#selected,)*
+                });
+                #found_crate::render::select_input(
+                    &::std::format!("{}[{}]", name, #tag),
+                    required,
+                    [#(#options,)*],
+                    selected,
+                )
+            }
+
+            fn optional_key() -> ::std::option::Option<&'static ::std::primitive::str> {
+                ::std::option::Option::Some(#tag)
             }
         }
     })
 }
+
+/// `#[cms(strum)]`: a select with the variants returned by `strum::IntoEnumIterator::iter`,
+/// using their `Display` representation as both value and label.
+/// The serde representation of the enum has to match `Display` to parse the submitted form.
+#[cfg(feature = "strum")]
+fn derive_strum_enum(input: &DeriveInput) -> TokenStream {
+    let found_crate = found_crate();
+    let ident = &input.ident;
+    quote! {
+        #[automatically_derived]
+        impl<S: #found_crate::context::ContextTrait> #found_crate::Input<S> for #ident {
+            fn render_input(
+                value: ::std::option::Option<&Self>,
+                name: &::std::primitive::str,
+                _name_human: &::std::primitive::str,
+                required: ::std::primitive::bool,
+                _ctx: &#found_crate::render::FormRenderContext<'_, S>,
+                _i18n: &#found_crate::derive::i18n_embed::fluent::FluentLanguageLoader,
+            ) -> #found_crate::derive::maud::Markup {
+                let selected = value.map(::std::string::ToString::to_string);
+                #found_crate::render::select_input(
+                    name,
+                    required,
+                    <Self as ::strum::IntoEnumIterator>::iter().map(|v| {
+                        let v = ::std::string::ToString::to_string(&v);
+                        (::std::clone::Clone::clone(&v), v)
+                    }),
+                    selected.as_deref(),
+                )
+            }
+        }
+    }
+}
@@ -4,7 +4,8 @@ use convert_case::{Case, Casing};
 use darling::FromMeta;
 use proc_macro2::{Ident, Span, TokenStream};
 use proc_macro_crate::{crate_name, FoundCrate};
-use quote::quote;
+use quote::{quote, ToTokens};
+use syn::{Attribute, Field, GenericArgument, Lit, LitStr, Path, PathArguments, Type};
 
 #[derive(Clone, Copy, Debug, FromMeta)]
 pub enum RenameAll {
@@ -40,6 +41,62 @@ impl From<RenameAll> for Case {
     }
 }
 
+/// Casing of the labels of inputs and columns set using `#[cms(label_case = "Title")]`,
+/// independent of the names used in forms, which have to match serde.
+#[derive(Clone, Copy, Debug, FromMeta)]
+pub enum LabelCase {
+    #[darling(rename = "Title")]
+    Title,
+    #[darling(rename = "Sentence")]
+    Sentence,
+    #[darling(rename = "lowercase")]
+    Lower,
+    #[darling(rename = "UPPERCASE")]
+    Upper,
+}
+
+/// Size in bytes set using e.g. `#[cms(max_size = "5MB")]` or `#[cms(max_size = 1024)]`,
+/// units are powers of 1024.
+#[derive(Clone, Copy, Debug)]
+pub struct ByteSize(u64);
+
+impl FromMeta for ByteSize {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        let value = value.trim();
+        let (number, unit) = value.split_at(
+            value
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(value.len()),
+        );
+        let factor: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "KB" | "KIB" => 1 << 10,
+            "MB" | "MIB" => 1 << 20,
+            "GB" | "GIB" => 1 << 30,
+            _ => {
+                return Err(darling::Error::custom(format!(
+                    "unknown unit `{unit}`, expected one of B, KB, MB or GB"
+                )))
+            }
+        };
+        number
+            .parse::<u64>()
+            .ok()
+            .and_then(|number| number.checked_mul(factor))
+            .map(Self)
+            .ok_or_else(|| darling::Error::custom(format!("invalid size `{value}`")))
+    }
+
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        match value {
+            Lit::Int(int) => Ok(Self(int.base10_parse()?)),
+            Lit::Str(s) => Self::from_string(&s.value()),
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+        .map_err(|e| e.with_span(value))
+    }
+}
+
 pub fn found_crate() -> TokenStream {
     let found_crate = crate_name("derived-cms").expect("derived-cms is present in `Cargo.toml`");
     match found_crate {
@@ -51,6 +108,120 @@ pub fn found_crate() -> TokenStream {
     }
 }
 
+/// whether one of `attrs` is `#[serde(option)]` or `#[serde(option = ...)]`
+fn has_serde_option(attrs: &[Attribute], option: &str) -> bool {
+    has_option(attrs, "serde", option)
+}
+
+/// whether one of `attrs` is `#[cms(option)]` or `#[cms(option = ...)]`
+pub fn has_cms_option(attrs: &[Attribute], option: &str) -> bool {
+    has_option(attrs, "cms", option)
+}
+
+fn has_option(attrs: &[Attribute], attr: &str, option: &str) -> bool {
+    attrs.iter().filter(|a| a.path().is_ident(attr)).any(|a| {
+        let mut found = false;
+        // other options are irrelevant here and may have arguments, so errors are ignored
+        let _ = a.parse_nested_meta(|meta| {
+            if meta.path.is_ident(option) {
+                found = true;
+            }
+            if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<syn::Expr>()?;
+            }
+            Ok(())
+        });
+        found
+    })
+}
+
+/// whether the field is marked with `#[serde(flatten)]`
+pub fn is_flattened(field: &Field) -> bool {
+    has_serde_option(&field.attrs, "flatten")
+}
+
+/// Whether the field may be omitted when deserializing, using the same rules as `ts_rs`:
+/// the field is an `Option<T>` or marked with `#[serde(default)]`.
+pub fn is_optional(field: &Field) -> bool {
+    is_option(&field.ty) || has_serde_option(&field.attrs, "default")
+}
+
+/// Whether `ty` is a type named `Option`.
+pub fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) => p.path.segments.last().is_some_and(|s| s.ident == "Option"),
+        _ => false,
+    }
+}
+
+/// Whether `ty` or one of its generic arguments is a type named `File`, `Image` or `Markdown`,
+/// which reference uploads.
+pub fn references_files(ty: &Type) -> bool {
+    let Type::Path(p) = ty else {
+        return false;
+    };
+    p.path.segments.iter().any(|s| {
+        ["File", "Image", "Markdown"]
+            .iter()
+            .any(|name| s.ident == name)
+            || match &s.arguments {
+                PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| match arg {
+                    GenericArgument::Type(ty) => references_files(ty),
+                    _ => false,
+                }),
+                _ => false,
+            }
+    })
+}
+
+/// The name of a field in serde, set using `#[serde(rename = "...")]` or
+/// `#[serde(rename(deserialize = "..."))]`. It is the name of the input in forms.
+pub fn serde_rename(attrs: &[Attribute]) -> Option<String> {
+    let mut rename = None;
+    for a in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        // other options are irrelevant here and may have arguments, so errors are ignored
+        let _ = a.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                if meta.input.peek(syn::Token![=]) {
+                    rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    return Ok(());
+                }
+                return meta.parse_nested_meta(|meta| {
+                    let name = meta.value()?.parse::<LitStr>()?;
+                    if meta.path.is_ident("deserialize") {
+                        rename = Some(name.value());
+                    }
+                    Ok(())
+                });
+            }
+            if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<syn::Expr>()?;
+            }
+            Ok(())
+        });
+    }
+    rename
+}
+
+/// `#[ormlite(column = "...")]`, the name of the field in the database
+pub fn ormlite_column(attrs: &[Attribute]) -> Option<String> {
+    let mut column = None;
+    for a in attrs.iter().filter(|a| a.path().is_ident("ormlite")) {
+        // other options are irrelevant here and may have arguments, so errors are ignored
+        let _ = a.parse_nested_meta(|meta| {
+            if meta.path.is_ident("column") {
+                column = Some(meta.value()?.parse::<LitStr>()?.value());
+                return Ok(());
+            }
+            if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<syn::Expr>()?;
+            }
+            Ok(())
+        });
+    }
+    column
+}
+
 pub fn renamed_name<'a>(
     s: String,
     rename: Option<impl Into<Cow<'a, str>>>,
@@ -61,3 +232,106 @@ pub fn renamed_name<'a>(
         None => s.into(),
     })
 }
+
+/// the label of a field or column named `name`, converted to `label_case` if set
+pub fn label(name: &str, label_case: Option<LabelCase>) -> String {
+    match label_case {
+        None => name.to_string(),
+        Some(LabelCase::Title) => name.to_case(Case::Title),
+        Some(LabelCase::Sentence) => {
+            let lower = name.to_case(Case::Lower);
+            let mut chars = lower.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => lower,
+            }
+        }
+        Some(LabelCase::Lower) => name.to_case(Case::Lower),
+        Some(LabelCase::Upper) => name.to_case(Case::Upper),
+    }
+}
+
+/// The label of the field named `name` in forms and columns: `#[cms(rename = "...")]` if set,
+/// otherwise `name` converted to `label_case`.
+pub fn field_label(rename: Option<&String>, name: &str, label_case: Option<LabelCase>) -> String {
+    match rename {
+        Some(rename) => rename.clone(),
+        None => label(name, label_case),
+    }
+}
+
+/// `InputConstraints` from `#[cms(min_length = ..., max_length = ..., accept = "...", max_size = "...")]`
+pub fn input_constraints(
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    accept: Option<&String>,
+    max_size: Option<ByteSize>,
+) -> TokenStream {
+    let found_crate = found_crate();
+    let min_length = option_tokens(min_length);
+    let max_length = option_tokens(max_length);
+    let accept = option_tokens(accept);
+    let max_size = option_tokens(max_size.map(|size| size.0));
+    quote! {
+        #found_crate::input::InputConstraints {
+            min_length: #min_length,
+            max_length: #max_length,
+            accept: #accept,
+            max_size: #max_size,
+        }
+    }
+}
+
+/// `Inputs::validate` calling the `#[cms(validate = "...")]` of each field with its name in the form
+pub fn validate_fn<'a>(
+    fields: impl IntoIterator<Item = (&'a Ident, String, &'a Path)>,
+) -> TokenStream {
+    let found_crate = found_crate();
+    let checks = fields
+        .into_iter()
+        .map(|(ident, name, path)| quote!(errors.check(#name, #path(&self.#ident));))
+        .collect::<Vec<_>>();
+    if checks.is_empty() {
+        return quote!();
+    }
+    quote! {
+        fn validate(&self) -> ::std::result::Result<(), #found_crate::validate::ValidationErrors> {
+            let mut errors = #found_crate::validate::ValidationErrors::new();
+            #(#checks)*
+            errors.into_result()
+        }
+    }
+}
+
+/// `Option<Help>` of `#[cms(help = "...")]` or `#[cms(help_key = "...")]`
+pub fn help_tokens(help: Option<&String>, help_key: Option<&String>) -> TokenStream {
+    let found_crate = found_crate();
+    match (help, help_key) {
+        (Some(_), Some(_)) => quote!(compile_error!(
+            "a field can only have one of `help` and `help_key`"
+        )),
+        (Some(text), None) => {
+            quote!(::std::option::Option::Some(#found_crate::input::Help::Text(#text)))
+        }
+        (None, Some(key)) => {
+            quote!(::std::option::Option::Some(#found_crate::input::Help::Key(#key)))
+        }
+        (None, None) => quote!(::std::option::Option::None),
+    }
+}
+
+/// `fn(&S) -> bool` of `#[cms(visible_if = "...")]`
+pub fn visible_fn(path: Option<&Path>) -> TokenStream {
+    let found_crate = found_crate();
+    match path {
+        Some(path) => quote!(<#path as #found_crate::context::Visibility<S>>::visible),
+        None => quote!(|_| true),
+    }
+}
+
+pub fn option_tokens<T: ToTokens>(v: Option<T>) -> TokenStream {
+    match v {
+        Some(v) => quote!(::std::option::Option::Some(#v)),
+        None => quote!(::std::option::Option::None),
+    }
+}
@@ -1,17 +1,124 @@
 -cancel = Abbrechen
 
 enitity-list-add = Neu erstellen
+entity-list-search =
+    .placeholder = Suchen
+    .submit = Suchen
+
+dashboard =
+    .title = Übersicht
+    .count = { $count ->
+        [one] 1 Eintrag
+       *[other] { $count } Einträge
+    }
+    .view = Alle anzeigen
 
 create-entity-title = Erstelle {$name}
 edit-entity-title = {$name} bearbeiten
+edit-entity-versions = Versionen
+entity-versions =
+    .title = Versionen von { $name }
+    .back = Zurück
+    .empty = Es gibt noch keine Versionen.
+    .created = Erstellt
+    .version = Version
+    .published = Veröffentlicht
+    .publish = Diese Version veröffentlichen
 entity-inputs-submit = Speichern
+entity-detail-edit = Bearbeiten
 
 error-create-entity =
     .title = Fehler beim Erstellen von {$name}
+error-update-conflict =
+    .title = Dieser Eintrag wurde von jemand anderem geändert
+    .description = Deine Änderungen wurden nicht gespeichert. Die zwischenzeitlich geänderten Felder sind unten markiert, speichere erneut, um sie zu überschreiben.
+    .field = Von jemand anderem geändert zu: { $value }
+
+error-form-field = Feld "{$field}" ist ungültig: {$error}
+error-upload-too-large = Datei überschreitet das Limit von {$limit} MB
+
+maintenance-action =
+    .running = Läuft…
+    .progress = Läuft: {$done} von {$total}
+    .finished = Abgeschlossen
+    .failed = Fehlgeschlagen: {$error}
+error-maintenance-action =
+    .title = {$action} konnte nicht ausgeführt werden
+    .not-found = {$name} haben keine solche Aktion
 
 confirm-delete-modal =
     .title = Löschen bestätigen
     .cancel = {-cancel}
     .confirm = Löschen
+    .type-to-confirm = Gib "{$text}" zur Bestätigung ein
+    .trash-title = In den Papierkorb verschieben?
+    .trash-confirm = In den Papierkorb
+
+entity-list-trash = Papierkorb
+entity-trash =
+    .title = Gelöschte {$name}
+    .back = Zurück
+    .empty = Der Papierkorb ist leer
+    .restore = Wiederherstellen
+
+input-required = Pflichtfeld
+list-input-add-files = Dateien hinzufügen:
+tags-input-remove = Entfernen
+
+enum-input-none = Keine Auswahl
+
+bool-input =
+    .yes = Ja
+    .no = Nein
+    .unset = Nicht gesetzt
+
+weekday =
+    .monday = Montag
+    .tuesday = Dienstag
+    .wednesday = Mittwoch
+    .thursday = Donnerstag
+    .friday = Freitag
+    .saturday = Samstag
+    .sunday = Sonntag
+
+month =
+    .january = Januar
+    .february = Februar
+    .march = März
+    .april = April
+    .may = Mai
+    .june = Juni
+    .july = Juli
+    .august = August
+    .september = September
+    .october = Oktober
+    .november = November
+    .december = Dezember
+
+inline-relation-delete = Löschen
+error-inline-relation =
+    .title = Fehler beim Speichern von {$name}
+    .not-a-child = {$id} gehört nicht dazu
+    .forbidden = Keine Berechtigung für diese Aktion ({$action})
+
+image-alt-text = Bildunterschrift
+
+error-page-back = Zurück
+error-page-request-id = Anfrage-ID:
 
-image-alt-text = Bildunterschrift
\ No newline at end of file
+language-switcher-label = Sprache
+theme-switcher-label = Design
+theme-switcher =
+    .system = System
+    .light = Hell
+    .dark = Dunkel
+sidebar-download-uploads = Alle Uploads herunterladen
+sidebar-group-general = Allgemein
+sidebar-logout = Abmelden
+login-title = Anmelden
+login-username = Benutzername
+login-password = Passwort
+login-submit = Anmelden
+login-failed = Benutzername oder Passwort ist falsch.
+pagination-previous = Zurück
+pagination-next = Weiter
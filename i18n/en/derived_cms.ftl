@@ -1,10 +1,31 @@
 -cancel = Cancel
 
 enitity-list-add = Create new
+entity-list-search =
+    .placeholder = Search
+    .submit = Search
+
+dashboard =
+    .title = Dashboard
+    .count = { $count ->
+        [one] 1 entry
+       *[other] { $count } entries
+    }
+    .view = Show all
 
 create-entity-title = Create new {$name}
 edit-entity-title = Edit {$name}
+edit-entity-versions = Versions
+entity-versions =
+    .title = Versions of { $name }
+    .back = Back
+    .empty = There are no versions yet.
+    .created = Created
+    .version = Version
+    .published = Published
+    .publish = Publish this version
 entity-inputs-submit = Save
+entity-detail-edit = Edit
 
 -db-error = Database error:
 error-list-entities =
@@ -17,17 +38,104 @@ error-create-entity =
     .title = Failed to create {$name}
     .parse-form = Failed to parse form: {$error}
     .db = {-db-error} {$error}
+error-update-conflict =
+    .title = This record was modified by someone else
+    .description = Your changes were not saved. The fields that were changed in the meantime are marked below, save again to overwrite them.
+    .field = Changed by someone else to: { $value }
 error-update-entity =
     .title = Failed to update {$name}
     .parse-form = Failed to parse form: {$error}
     .db = {-db-error} {$error}
+error-form-field = Field "{$field}" is invalid: {$error}
+error-upload-too-large = File exceeds {$limit}MB limit
 error-delete-entity =
     .title = Failed to delete {$name}
     .db = {-db-error} {$error}
 
+maintenance-action =
+    .running = Running…
+    .progress = Running: {$done} of {$total}
+    .finished = Finished
+    .failed = Failed: {$error}
+error-maintenance-action =
+    .title = Failed to run {$action}
+    .not-found = {$name} have no such action
+
 confirm-delete-modal =
     .title = Confirm delete {$name}
     .cancel = {-cancel}
     .confirm = Delete
+    .type-to-confirm = Type "{$text}" to confirm
+    .trash-title = Move {$name} to the trash?
+    .trash-confirm = Move to trash
+
+entity-list-trash = Trash
+entity-trash =
+    .title = Deleted {$name}
+    .back = Back
+    .empty = The trash is empty
+    .restore = Restore
+
+input-required = Required
+list-input-add-files = Add files:
+tags-input-remove = Remove
+
+enum-input-none = None
+
+bool-input =
+    .yes = Yes
+    .no = No
+    .unset = Unset
+
+weekday =
+    .monday = Monday
+    .tuesday = Tuesday
+    .wednesday = Wednesday
+    .thursday = Thursday
+    .friday = Friday
+    .saturday = Saturday
+    .sunday = Sunday
+
+month =
+    .january = January
+    .february = February
+    .march = March
+    .april = April
+    .may = May
+    .june = June
+    .july = July
+    .august = August
+    .september = September
+    .october = October
+    .november = November
+    .december = December
+
+inline-relation-delete = Delete
+error-inline-relation =
+    .title = Failed to save {$name}
+    .parse-form = Failed to parse form: {$error}
+    .extract = Failed to extract {$error} from the request
+    .not-a-child = {$id} is not one of its children
+    .forbidden = You are not allowed to {$action} them
+
+image-alt-text = Alt text
+
+error-page-back = Go back
+error-page-request-id = Request ID:
 
-image-alt-text = Alt text
\ No newline at end of file
+language-switcher-label = Language
+theme-switcher-label = Theme
+theme-switcher =
+    .system = System
+    .light = Light
+    .dark = Dark
+sidebar-download-uploads = Download all uploads
+sidebar-group-general = General
+sidebar-logout = Log out
+login-title = Log in
+login-username = Username
+login-password = Password
+login-submit = Log in
+login-failed = Invalid username or password.
+pagination-previous = Previous
+pagination-next = Next
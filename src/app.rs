@@ -1,37 +1,85 @@
-use std::{convert::Infallible, path::PathBuf, sync::Arc};
+use std::{
+    convert::Infallible,
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use axum::{
+    body::Bytes,
     extract::{DefaultBodyLimit, Request, State},
-    http::{header::CONTENT_TYPE, HeaderMap, HeaderValue, StatusCode},
+    http::{
+        header::{
+            ACCEPT, ACCEPT_LANGUAGE, CACHE_CONTROL, CONTENT_TYPE, COOKIE, ETAG, IF_MODIFIED_SINCE,
+            IF_NONE_MATCH, LAST_MODIFIED, REFERRER_POLICY, X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+        },
+        HeaderMap, HeaderName, HeaderValue, StatusCode,
+    },
     middleware::{self, Next},
     response::{IntoResponse, Response},
-    routing::{get, post},
-    Extension, Router,
+    routing::{get, post, Route},
+    Extension, Json, Router,
 };
+use chrono::{DateTime, Timelike, Utc};
 use derive_more::Debug;
 use i18n_embed::{
     fluent::{fluent_language_loader, FluentLanguageLoader},
-    AssetsMultiplexor, I18nAssets,
+    AssetsMultiplexor, I18nAssets, LanguageLoader,
 };
 use include_dir::{include_dir, Dir, DirEntry};
+use maud::Markup;
+use ormlite::{Database, Executor, FromRow, Model};
 use rust_embed::RustEmbed;
+use sha2::{Digest, Sha256};
+use tokio::net::TcpListener;
 use tower_http::services::ServeDir;
-use tracing::error;
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::{debug, error, info, warn};
+use ts_rs::{ExportError, TS};
 use unic_langid::LanguageIdentifier;
 
 use crate::{
-    context::{Context, ContextExt},
+    auth::{require_user, AuthProvider, Guard, Permissions, User},
+    bindings,
+    branding::BrandingConfig,
+    cache::{ApiCache, ApiCacheConfig},
+    context::{Context, ContextExt, ContextTrait},
+    csp::{csp, DEFAULT_POLICY},
+    csrf::{verify_csrf, CsrfKey},
+    delete_token::DeleteTokens,
     easymde::EditorConfig,
+    encryption::{provide_key, EncryptionKey},
     endpoints::{
-        entity_routes,
-        ui::{parse_mde_upload, UploadDir},
+        api, auth_routes, count_entities, entity_routes, entity_routes_with, form_group_routes,
+        trash_routes,
+        ui::{self, parse_mde_upload, BasePath, UploadDir},
+        versions_routes, CountEntities,
     },
-    entity::Entity,
-    render,
+    entity::{Entity, EntityBase, EntityScope, SoftDelete, Versioned},
+    form_group::FormGroup,
+    maintenance::MaintenanceRuns,
+    openapi::OpenApi,
+    pagination::{DefaultPaginator, Paginator},
+    relation::{InlineRelation, InlineRelations, Relation},
+    render::{self, Slot},
+    request_id::request_id,
+    task::{run_seeds, BackgroundTasks, CancellationToken, Seed, Task},
+    theme::{theme, Theme, ThemeConfig},
+    uploads::{
+        export as export_uploads, gc_uploads, load_referenced_files, provide_signer,
+        verify_signature, ExportPermission, GcMode, LoadReferencedFiles, UploadSigner,
+    },
+    DB,
 };
 
 static STATIC_ASSETS: Dir = include_dir!("$CARGO_MANIFEST_DIR/static");
 
+/// the default limit of axum, see [`App::max_upload_size`]
+const DEFAULT_MAX_UPLOAD_SIZE: usize = 2 * 1024 * 1024;
+
 #[derive(RustEmbed)]
 #[folder = "i18n/"]
 struct Localizations;
@@ -43,9 +91,51 @@ where
     S: ContextExt<Context<S>>,
 {
     router: Router<Context<S>>,
-    names_plural: Vec<&'static str>,
+    entities: Vec<EntityScope>,
+    form_groups: Vec<&'static str>,
     editor_config: Option<EditorConfig>,
+    paginator: Arc<dyn Paginator>,
+    page_size: u64,
+    list_collapse_threshold: Option<usize>,
+    time_zone: Option<String>,
+    namespace_uploads: bool,
+    thumbnail_size: Option<u32>,
+    uploads_export: Option<fn(&User) -> bool>,
+    max_upload_size: usize,
+    #[debug(skip)]
+    api_doc: OpenApi,
+    serve_openapi: bool,
+    base_path: String,
+    upload_signer: Option<UploadSigner>,
+    headers: HeaderMap,
+    csp: Option<Arc<str>>,
+    csrf_key: Option<CsrfKey>,
+    #[debug(skip)]
+    auth: Vec<Guard>,
+    #[debug(skip)]
+    auth_routes: Router<Context<S>>,
+    request_ids: bool,
+    theme: Option<Theme>,
+    theme_switcher: bool,
+    branding: BrandingConfig,
+    stylesheets: Vec<String>,
+    #[debug(skip)]
+    banner: Option<Slot<Context<S>>>,
+    #[debug(skip)]
+    footer: Option<Slot<Context<S>>>,
+    api_cache: Option<ApiCacheConfig>,
+    delete_tokens: DeleteTokens,
+    #[debug(skip)]
+    referenced_files: Vec<LoadReferencedFiles<Context<S>>>,
+    #[debug(skip)]
+    entity_counts: Vec<(String, CountEntities<Context<S>>)>,
     state_ext: E,
+    inline_relations: InlineRelations,
+    #[debug(skip)]
+    tasks: Vec<Task<Context<S>>>,
+    #[debug(skip)]
+    seeds: Vec<Seed<Context<S>>>,
+    background_tasks: BackgroundTasks,
     #[debug(skip)]
     localizations: Vec<Box<dyn I18nAssets + Send + Sync + 'static>>,
 }
@@ -57,9 +147,42 @@ where
     fn default() -> Self {
         Self {
             router: Default::default(),
-            names_plural: Default::default(),
+            entities: Default::default(),
+            form_groups: Vec::new(),
             editor_config: None,
+            paginator: Arc::new(DefaultPaginator::default()),
+            page_size: 50,
+            list_collapse_threshold: None,
+            time_zone: None,
+            namespace_uploads: false,
+            thumbnail_size: None,
+            uploads_export: None,
+            max_upload_size: DEFAULT_MAX_UPLOAD_SIZE,
+            api_doc: Default::default(),
+            serve_openapi: false,
+            base_path: String::new(),
+            upload_signer: None,
+            headers: HeaderMap::new(),
+            csp: None,
+            csrf_key: None,
+            auth: Vec::new(),
+            auth_routes: Router::new(),
+            request_ids: false,
+            theme: None,
+            theme_switcher: false,
+            branding: BrandingConfig::default(),
+            stylesheets: Vec::new(),
+            banner: None,
+            footer: None,
+            api_cache: None,
+            delete_tokens: Default::default(),
+            referenced_files: Vec::new(),
+            entity_counts: Vec::new(),
             state_ext: Default::default(),
+            inline_relations: Default::default(),
+            tasks: Vec::new(),
+            seeds: Vec::new(),
+            background_tasks: Default::default(),
             localizations: Vec::new(),
         }
     }
@@ -78,9 +201,196 @@ impl<S, SE> App<S, SE>
 where
     S: ContextExt<Context<S>> + 'static,
 {
-    pub fn entity<E: Entity<Context<S>> + Send + Sync>(mut self) -> Self {
-        self.names_plural.push(E::name_plural());
-        self.router = self.router.merge(entity_routes::<E, Context<S>>());
+    pub fn entity<E: Entity<Context<S>> + Send + Sync>(self) -> Self {
+        self.entity_scoped::<E, _>(EntityScope::of::<E, Context<S>>(), ())
+    }
+
+    /// Register `E` with a [`nav_priority`](crate::EntityBase::nav_priority) that overrides the
+    /// one set using `#[cms(nav_priority = ...)]`.
+    pub fn entity_with_priority<E: Entity<Context<S>> + Send + Sync>(self, priority: i32) -> Self {
+        self.entity_scoped::<E, _>(
+            EntityScope::of::<E, Context<S>>().with_priority(priority),
+            (),
+        )
+    }
+
+    /// Register `E` and show it under the heading `group` in the sidebar, e.g. `"Blog"`.
+    /// Entities registered without a group are shown under "General".
+    pub fn entity_in_group<E: Entity<Context<S>> + Send + Sync>(self, group: &'static str) -> Self {
+        self.entity_scoped::<E, _>(EntityScope::of::<E, Context<S>>().with_group(group), ())
+    }
+
+    /// Register `E` and only allow the [`Action`](crate::auth::Action)s `perms` returns `true` for with the
+    /// [`User`](crate::auth::User) of the request, see [`auth`](crate::auth).
+    /// Denied requests are rejected with HTTP 403, requests without a user are always denied.
+    ///
+    /// ```rust,ignore
+    /// App::new().entity_with_perms::<Post>(|user, action| {
+    ///     user.has_role("editor") || matches!(action, Action::List | Action::Get)
+    /// })
+    /// ```
+    pub fn entity_with_perms<E: Entity<Context<S>> + Send + Sync>(
+        self,
+        perms: Permissions,
+    ) -> Self {
+        self.entity_scoped::<E, _>(
+            EntityScope::of::<E, Context<S>>().with_permissions(perms),
+            (),
+        )
+    }
+
+    /// Serve the [JSON Schema](crate::schema) of `E` and its [`Create`](crate::EntityBase::Create)
+    /// and [`Update`](crate::EntityBase::Update) types at `/api/v1/:name/schema`,
+    /// `/api/v1/:name/schema/create` and `/api/v1/:name/schema/update`.
+    /// The schemas are also used in the [OpenAPI document](App::openapi) and the
+    /// [TypeScript client](App::export_bindings).
+    pub fn with_json_schema<E>(mut self) -> Self
+    where
+        E: EntityBase<Context<S>> + TS,
+        E::Create: TS,
+        E::Update: TS,
+    {
+        self.api_doc.schemas::<E, Context<S>>();
+        let name = EntityScope::of::<E, Context<S>>().path();
+        self.router = self
+            .router
+            .route(&format!("/api/v1/{name}/schema"), get(api::get_schema::<E>))
+            .route(
+                &format!("/api/v1/{name}/schema/create"),
+                get(api::get_schema::<E::Create>),
+            )
+            .route(
+                &format!("/api/v1/{name}/schema/update"),
+                get(api::get_schema::<E::Update>),
+            );
+        self
+    }
+
+    /// Keep the uploads referenced by the entities of `E` when removing unreferenced uploads
+    /// using [`gc_uploads`] or [`App::gc_uploads_every`], see [`EntityBase::referenced_files`].
+    /// All entities with [`File`](crate::property::File)s, [`Image`](crate::property::Image)s
+    /// or uploads in [`Markdown`](crate::property::Markdown) have to be tracked, otherwise their
+    /// uploads are removed.
+    pub fn track_uploads<E>(mut self) -> Self
+    where
+        E: EntityBase<Context<S>> + Model<DB> + for<'r> FromRow<'r, <DB as Database>::Row>,
+        for<'c> &'c S: Executor<'c, Database = DB>,
+    {
+        self.referenced_files.push(load_referenced_files::<E, S>);
+        self
+    }
+
+    /// Serve the [OpenAPI document](App::openapi) at `/api/v1/openapi.json`.
+    pub fn with_openapi(mut self) -> Self {
+        self.serve_openapi = true;
+        self
+    }
+
+    /// The [OpenAPI](crate::openapi) document of the JSON API of all entities registered so far.
+    pub fn openapi(&self) -> serde_json::Value {
+        self.api_doc.document()
+    }
+
+    /// Write a [TypeScript client](crate::bindings) of the JSON API of all entities registered so
+    /// far to `dir/client.ts`, together with the [`TS`] types of the entities registered using
+    /// [`App::with_json_schema`].
+    pub fn export_bindings(&self, dir: impl AsRef<Path>) -> Result<(), ExportError> {
+        bindings::export(&self.api_doc, dir.as_ref(), &self.base_path)
+    }
+
+    /// Register `E` under the names and prefix of `scope`. The same type can be registered
+    /// multiple times, e.g. as `/tenant-a/documents` and `/tenant-b/documents`.
+    ///
+    /// `ext` is added as an [`Extension`] to all requests of this scope,
+    /// so it can be extracted in the `RequestExt` of `E`, e.g. to filter by tenant.
+    pub fn entity_scoped<E: Entity<Context<S>> + Send + Sync, X: Clone + Send + Sync + 'static>(
+        mut self,
+        scope: EntityScope,
+        ext: X,
+    ) -> Self {
+        self.entities.push(scope.clone());
+        self.api_doc.entity::<E>(&scope);
+        self.entity_counts.push((
+            scope.path_plural(),
+            count_entities::<E, Context<S>, X>(scope.clone(), ext.clone()),
+        ));
+        self.router = self
+            .router
+            .merge(entity_routes::<E, Context<S>>(scope).layer(Extension(ext)));
+        self
+    }
+
+    /// Register `E` with a trash page at `/:name-plural/trash` listing the soft-deleted entities,
+    /// see [`SoftDelete`].
+    pub fn entity_soft_delete<E: Entity<Context<S>> + SoftDelete<Context<S>> + Send + Sync>(
+        self,
+    ) -> Self {
+        self.entity_scoped_soft_delete::<E, _>(EntityScope::of::<E, Context<S>>(), ())
+    }
+
+    /// Register `E` like [`App::entity_scoped`] with a trash page, see [`SoftDelete`].
+    pub fn entity_scoped_soft_delete<
+        E: Entity<Context<S>> + SoftDelete<Context<S>> + Send + Sync,
+        X: Clone + Send + Sync + 'static,
+    >(
+        mut self,
+        mut scope: EntityScope,
+        ext: X,
+    ) -> Self {
+        scope.trash = true;
+        self.router = self
+            .router
+            .merge(trash_routes::<E, Context<S>>(scope.clone()).layer(Extension(ext.clone())));
+        self.entity_scoped::<E, X>(scope, ext)
+    }
+
+    /// Register `E` with a versions page at `/:name/:id/versions`, see [`Versioned`].
+    pub fn entity_versioned<E: Entity<Context<S>> + Versioned<Context<S>> + Send + Sync>(
+        self,
+    ) -> Self {
+        self.entity_scoped_versioned::<E, _>(EntityScope::of::<E, Context<S>>(), ())
+    }
+
+    /// Register `E` like [`App::entity_scoped`] with a versions page, see [`Versioned`].
+    /// The edit page shows the version of the query parameter `?version=` if it is set.
+    pub fn entity_scoped_versioned<
+        E: Entity<Context<S>> + Versioned<Context<S>> + Send + Sync,
+        X: Clone + Send + Sync + 'static,
+    >(
+        mut self,
+        mut scope: EntityScope,
+        ext: X,
+    ) -> Self {
+        scope.versions = true;
+        self.entities.push(scope.clone());
+        self.api_doc.entity::<E>(&scope);
+        self.entity_counts.push((
+            scope.path_plural(),
+            count_entities::<E, Context<S>, X>(scope.clone(), ext.clone()),
+        ));
+        let edit = get(ui::get_entity_versioned::<E, Context<S>>);
+        self.router = self
+            .router
+            .merge(versions_routes::<E, Context<S>>(scope.clone()).layer(Extension(ext.clone())))
+            .merge(entity_routes_with::<E, Context<S>>(scope, edit).layer(Extension(ext)));
+        self
+    }
+
+    /// Serve the form of `G` at `/forms/:name` and link it in the sidebar, see [`FormGroup`].
+    pub fn form_group<G: FormGroup<Context<S>>>(mut self) -> Self {
+        self.form_groups.push(G::name());
+        self.router = self.router.merge(form_group_routes::<G, Context<S>>());
+        self
+    }
+
+    /// Edit the children `C` of `P` inline on the edit page of `P`, see [`InlineRelation`].
+    pub fn inline_relation<P, C>(mut self) -> Self
+    where
+        P: InlineRelation<Context<S>, C>,
+        C: Entity<Context<S>>,
+    {
+        self.inline_relations
+            .insert::<Context<S>, P>(Arc::new(Relation::<C>::new()));
         self
     }
 }
@@ -89,10 +399,392 @@ impl<S, SE> App<S, SE>
 where
     S: ContextExt<Context<S>> + 'static,
 {
+    /// Render [`Markdown`](crate::property::Markdown) inputs using the
+    /// [editor backend](crate::easymde::EditorBackend) of `config` instead of a plain `<textarea>`.
     pub fn with_mdeditor(mut self, config: EditorConfig) -> Self {
         self.editor_config = Some(config);
         self
     }
+
+    /// Render the pager of the list page using `paginator` instead of [`DefaultPaginator`].
+    pub fn with_paginator(mut self, paginator: impl Paginator) -> Self {
+        self.paginator = Arc::new(paginator);
+        self
+    }
+
+    /// Show `page_size` entities per page on the list page unless the query string sets a `limit`.
+    /// Default: 50
+    pub fn with_page_size(mut self, page_size: u64) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Render the elements of list inputs (e.g. [`Vec<T>`]) with more than `threshold` elements
+    /// collapsed into summary rows. Only the first `threshold` elements are rendered with the page;
+    /// the inputs of the others are loaded when they are expanded or the form is submitted.
+    pub fn collapse_lists_longer_than(mut self, threshold: usize) -> Self {
+        self.list_collapse_threshold = Some(threshold);
+        self
+    }
+
+    /// Show and enter [`DateTime`](chrono::DateTime)s in the IANA time zone `time_zone`,
+    /// e.g. `"Europe/Berlin"`, instead of the time zone of the browser of each editor.
+    /// Values are still stored as UTC.
+    pub fn with_time_zone(mut self, time_zone: impl Into<String>) -> Self {
+        self.time_zone = Some(time_zone.into());
+        self
+    }
+
+    /// Store files uploaded in the forms of entities in `uploads_dir/<entity>/<field>/<uuid>/<name>`
+    /// instead of `uploads_dir/<uuid>/<name>`. Existing files are not moved.
+    pub fn namespace_uploads(mut self) -> Self {
+        self.namespace_uploads = true;
+        self
+    }
+
+    /// Store a copy of images uploaded in the forms of entities scaled down to fit into
+    /// `max_size`×`max_size` pixels next to the original, e.g. `max_size = 256`.
+    /// [`Image`](crate::property::Image) columns show the thumbnail instead of the file name,
+    /// see [`Image::thumbnail_url`](crate::property::Image::thumbnail_url).
+    /// PNG, JPEG, GIF and WebP images are supported, other files and images which are already
+    /// small enough are stored without a thumbnail.
+    pub fn with_thumbnails(mut self, max_size: u32) -> Self {
+        self.thumbnail_size = Some(max_size);
+        self
+    }
+
+    /// Set the path the [`Router`] returned by [`App::build`] is nested under using [`Router::nest`].
+    /// It is used to generate absolute URLs, e.g. for redirects.
+    pub fn with_base_path(mut self, path: impl Into<String>) -> Self {
+        let path = path.into();
+        let path = path.trim_end_matches('/');
+        self.base_path = match path.starts_with('/') || path.is_empty() {
+            true => path.to_string(),
+            false => format!("/{path}"),
+        };
+        self
+    }
+
+    /// Only serve uploaded files with a valid signature, otherwise respond with HTTP 403.
+    /// [`File::url`](crate::property::File::url) generates signed URLs that are valid for `valid_for`.
+    /// Use [`UploadSigner::current`] to sign URLs in custom handlers.
+    ///
+    /// Note that URLs of images uploaded using the markdown editor expire as well.
+    pub fn with_private_uploads(mut self, secret: impl AsRef<[u8]>, valid_for: Duration) -> Self {
+        self.upload_signer = Some(UploadSigner::new(secret, valid_for));
+        self
+    }
+
+    /// Serve a zip archive of all files in the uploads directory at `/export/uploads.zip`
+    /// and link it in the sidebar, e.g. for backups or to migrate to another server.
+    /// The archive is streamed while it is written, so it is never held in memory or on disk.
+    ///
+    /// Only [`User`]s for which `allow` returns `true` may download the archive, so it requires
+    /// [`App::with_auth_provider`] or an [`App::with_auth`] layer that adds the [`User`] to the
+    /// extensions of the request. Without either, the archive is not served.
+    /// It is not subject to [`App::with_private_uploads`].
+    ///
+    /// ```rust,ignore
+    /// App::new().with_uploads_export(|user| user.has_role("admin"))
+    /// ```
+    pub fn with_uploads_export(mut self, allow: fn(&User) -> bool) -> Self {
+        self.uploads_export = Some(allow);
+        self
+    }
+
+    /// Find uploads that are not referenced by any entity registered using
+    /// [`App::track_uploads`] every `interval` and remove them if `mode` is [`GcMode::Delete`],
+    /// see [`gc_uploads`]. With [`GcMode::DryRun`], they are only logged, so the tracked
+    /// entities can be checked before any upload is removed.
+    /// The first run is right after the app is built.
+    ///
+    /// ```rust,ignore
+    /// App::new().gc_uploads_every(Duration::from_secs(24 * 60 * 60), GcMode::Delete)
+    /// ```
+    pub fn gc_uploads_every(self, interval: Duration, mode: GcMode) -> Self {
+        self.spawn_task(move |ctx, token| async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = interval.tick() => match gc_uploads(&ctx, mode).await {
+                        Ok(found) if found.is_empty() => {}
+                        Ok(found) if mode == GcMode::DryRun => {
+                            info!("Found {} unreferenced uploads (dry run)", found.len());
+                            for dir in &found {
+                                debug!("Unreferenced upload: {}", dir.display());
+                            }
+                        }
+                        Ok(removed) => info!("Removed {} unreferenced uploads", removed.len()),
+                        Err(e) => error!("Failed to remove unreferenced uploads: {e:#}"),
+                    },
+                }
+            }
+        })
+    }
+
+    /// Accept bodies of up to `bytes` on the routes of entities instead of 2 MB, e.g. to upload
+    /// larger [`File`](crate::property::File)s and [`Image`](crate::property::Image)s using
+    /// the add and edit forms. Larger forms are rejected with HTTP 413.
+    ///
+    /// The size of images uploaded using the markdown editor is limited by
+    /// [`EditorConfig::upload_max_size`] instead.
+    pub fn max_upload_size(mut self, bytes: usize) -> Self {
+        self.max_upload_size = bytes;
+        self
+    }
+
+    /// Encrypt [`Encrypted`](crate::property::Encrypted) properties in the database using `key`.
+    /// The key is available in all requests, background tasks and seeds, use
+    /// [`EncryptionKey::scope`] to access encrypted properties elsewhere.
+    pub fn with_encryption_key(mut self, key: EncryptionKey) -> Self {
+        self.background_tasks.encryption_key = Some(key);
+        self
+    }
+
+    /// Cache the responses of `GET /api/v1/:name/:id` and `GET /api/v1/:name-plural` in memory,
+    /// see [`cache`](crate::cache).
+    ///
+    /// Responses are cached per [`User`](crate::auth::User) if authenticated using
+    /// [`App::with_auth_provider`]. Users authenticated by other means, e.g. [`App::with_auth`],
+    /// share the cache, so only enable it if the responses do not depend on them.
+    pub fn with_api_cache(mut self, config: ApiCacheConfig) -> Self {
+        self.api_cache = Some(config);
+        self
+    }
+
+    /// Require a second request with a one-time token to delete any entity using the JSON API,
+    /// see [`delete_token`](crate::delete_token). Tokens expire after `valid_for`.
+    ///
+    /// Use [`EntityBase::confirm_api_delete`](crate::EntityBase::confirm_api_delete) to only
+    /// enable this for some entities.
+    pub fn confirm_api_deletes(mut self, valid_for: Duration) -> Self {
+        self.delete_tokens = DeleteTokens::new(true, valid_for);
+        self
+    }
+
+    /// Handle every request with a [`RequestId`](crate::request_id::RequestId), taken from the
+    /// `X-Request-Id` header or generated. It is returned in the `X-Request-Id` header, shown on
+    /// error pages and in errors of the JSON API, and added to the tracing span of the request,
+    /// so editors can quote it when reporting an error.
+    pub fn with_request_id(mut self) -> Self {
+        self.request_ids = true;
+        self
+    }
+
+    /// Add a light and a dark theme, following `prefers-color-scheme` of the browser by default.
+    /// Editors can select a theme in the sidebar, which is stored in the
+    /// [`THEME_COOKIE`](crate::theme::THEME_COOKIE) and applied when rendering the page,
+    /// so it is shown without flashing the other theme first. The colors are CSS variables in
+    /// `css/theme.css`, e.g. `--cms-background`, that can be overridden by custom stylesheets.
+    pub fn with_theme_switcher(mut self) -> Self {
+        self.theme_switcher = true;
+        self
+    }
+
+    /// Add the light and dark theme of [`App::with_theme_switcher`] and show `theme` by default,
+    /// which is set as `data-theme` on `<html>`. [`Theme::System`] follows `prefers-color-scheme`
+    /// of the browser. Without the theme switcher, editors can't select another theme.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Set the title, favicon and logo of the admin interface.
+    ///
+    /// ```rust,ignore
+    /// App::new().with_branding(BrandingConfig::default().title("MyCMS").logo("https://example.com/logo.svg"))
+    /// ```
+    pub fn with_branding(mut self, config: BrandingConfig) -> Self {
+        self.branding = config;
+        self
+    }
+
+    /// Link the stylesheet at `url` on every page of the admin interface, after the bundled
+    /// stylesheets, e.g. to override the colors of the [theme](App::with_theme).
+    /// Relative URLs are resolved against the [base path](App::with_base_path).
+    pub fn with_stylesheet(mut self, url: impl Into<String>) -> Self {
+        self.stylesheets.push(url.into());
+        self
+    }
+
+    /// Show `banner` above every page of the admin interface, including error pages, e.g. to mark
+    /// a staging environment. It is rendered for every request with the context and the
+    /// localization of the editor.
+    ///
+    /// ```rust,ignore
+    /// App::new().with_banner(|_ctx, i18n| html! { (fl!(i18n, "staging-banner")) })
+    /// ```
+    pub fn with_banner(
+        mut self,
+        banner: impl Fn(&Context<S>, &FluentLanguageLoader) -> Markup + Send + Sync + 'static,
+    ) -> Self {
+        self.banner = Some(Arc::new(banner));
+        self
+    }
+
+    /// Show `footer` below every page of the admin interface, e.g. with the version of the
+    /// application, see [`App::with_banner`].
+    pub fn with_footer(
+        mut self,
+        footer: impl Fn(&Context<S>, &FluentLanguageLoader) -> Markup + Send + Sync + 'static,
+    ) -> Self {
+        self.footer = Some(Arc::new(footer));
+        self
+    }
+
+    /// Set the header `name` on all responses, including static assets, uploads and the JSON API.
+    /// Headers set by a handler take precedence.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Set security headers on all responses, unless they were already set using [`App::header`]:
+    ///
+    /// - `X-Content-Type-Options: nosniff`
+    /// - `X-Frame-Options: DENY`, so the admin interface can not be embedded in other sites
+    /// - `Referrer-Policy: same-origin`
+    /// - `Permissions-Policy: camera=(), microphone=(), geolocation=()`
+    /// - `Content-Security-Policy`: [`csp::DEFAULT_POLICY`](crate::csp::DEFAULT_POLICY),
+    ///   unless a policy was set using [`App::with_csp`]
+    pub fn with_security_headers(mut self) -> Self {
+        self.csp.get_or_insert_with(|| DEFAULT_POLICY.into());
+        for (name, value) in [
+            (X_CONTENT_TYPE_OPTIONS, "nosniff"),
+            (X_FRAME_OPTIONS, "DENY"),
+            (REFERRER_POLICY, "same-origin"),
+            (
+                HeaderName::from_static("permissions-policy"),
+                "camera=(), microphone=(), geolocation=()",
+            ),
+        ] {
+            self.headers
+                .entry(name)
+                .or_insert(HeaderValue::from_static(value));
+        }
+        self
+    }
+
+    /// Set the `Content-Security-Policy` header of all responses to `policy`, with
+    /// [`{nonce}`](crate::csp::NONCE_PLACEHOLDER) replaced by a random nonce per request that is
+    /// added to all `<script>` elements, see [`csp`](crate::csp).
+    ///
+    /// ```rust,ignore
+    /// let app = App::new().with_csp(
+    ///     "default-src 'self'; script-src 'self' 'nonce-{nonce}' https://cdn.jsdelivr.net",
+    /// );
+    /// ```
+    pub fn with_csp(mut self, policy: impl Into<String>) -> Self {
+        self.csp = Some(policy.into().into());
+        self
+    }
+
+    /// Protect the admin interface against cross-site request forgery using tokens signed with
+    /// `secret`. Requests that change data without a valid token are rejected with HTTP 403,
+    /// see [`csrf`](crate::csrf).
+    pub fn with_csrf_key(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.csrf_key = Some(CsrfKey::new(secret));
+        self
+    }
+
+    /// Wrap all routes of the admin interface and the API in `layer`, e.g. to authenticate requests
+    /// using a session store or a reverse proxy. Static files and uploads are not wrapped.
+    ///
+    /// ```rust,ignore
+    /// let app = App::new().with_auth(middleware::from_fn(|req: Request, next: Next| async move {
+    ///     match req.headers().contains_key("x-forwarded-user") {
+    ///         true => next.run(req).await,
+    ///         false => StatusCode::UNAUTHORIZED.into_response(),
+    ///     }
+    /// }));
+    /// ```
+    pub fn with_auth<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.auth
+            .push(Box::new(move |router, _| router.layer(layer.clone())));
+        self
+    }
+
+    /// Require a session of `provider` for all routes of the admin interface and the API, see
+    /// [`auth`](crate::auth). Serves the login page at `/login` and logs out on `POST /logout`.
+    ///
+    /// The authenticated [`User`](crate::auth::User) is added to the extensions of the request.
+    pub fn with_auth_provider<P: AuthProvider>(mut self, provider: P) -> Self {
+        let provider = Arc::new(provider);
+        self.auth_routes = self.auth_routes.merge(auth_routes(provider.clone()));
+        self.auth.push(Box::new(move |router, base_path| {
+            router.layer(middleware::from_fn_with_state(
+                (provider.clone(), Arc::from(base_path)),
+                require_user::<P>,
+            ))
+        }));
+        self
+    }
+
+    /// Run `task` in the background once the app is built using [`App::build`].
+    /// It receives the [`Context`] and a [`CancellationToken`] that is cancelled on shutdown,
+    /// see [`App::background_tasks`].
+    ///
+    /// ```rust,ignore
+    /// App::new().spawn_task(|ctx, token| async move {
+    ///     let mut interval = tokio::time::interval(Duration::from_secs(3600));
+    ///     loop {
+    ///         tokio::select! {
+    ///             _ = token.cancelled() => break,
+    ///             _ = interval.tick() => warm_cache(ctx.ext()).await,
+    ///         }
+    ///     }
+    /// })
+    /// ```
+    pub fn spawn_task<F, Fut>(mut self, task: F) -> Self
+    where
+        F: FnOnce(Context<S>, CancellationToken) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.tasks
+            .push(Box::new(move |ctx, token| Box::pin(task(ctx, token))));
+        self
+    }
+
+    /// Insert initial data, e.g. demo content, before the app is served.
+    /// Seeds run once, in order of registration, and receive the [`Context`] to access the database.
+    /// Errors are logged and do not prevent the app from starting.
+    ///
+    /// [`App::serve`] runs all seeds before accepting connections. [`App::build`] runs them as a
+    /// background task, so the first requests may be handled before they are finished.
+    ///
+    /// Seeds run on every start, so check whether the data already exists,
+    /// e.g. using [`table_is_empty`](crate::entity::table_is_empty):
+    ///
+    /// ```rust,ignore
+    /// App::new().seed(|ctx| async move {
+    ///     if entity::table_is_empty::<Post>(ctx.ext()).await.map_err(db_error)? {
+    ///         Post::new("Hello World").insert(ctx.ext()).await.map_err(db_error)?;
+    ///     }
+    ///     Ok(())
+    /// })
+    /// ```
+    pub fn seed<F, Fut>(mut self, seed: F) -> Self
+    where
+        F: FnOnce(Context<S>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), AppError>> + Send + 'static,
+    {
+        self.seeds.push(Box::new(move |ctx| Box::pin(seed(ctx))));
+        self
+    }
+
+    /// Handle to stop the tasks started using [`App::spawn_task`] and wait for them to finish,
+    /// e.g. after [`axum::serve`] returned.
+    pub fn background_tasks(&self) -> BackgroundTasks {
+        self.background_tasks.clone()
+    }
 }
 
 impl<S, E> App<S, E>
@@ -102,9 +794,42 @@ where
     pub fn with_state(self, data: S) -> App<S, S> {
         App {
             router: self.router,
-            names_plural: self.names_plural,
+            entities: self.entities,
+            form_groups: self.form_groups,
             editor_config: self.editor_config,
+            paginator: self.paginator,
+            page_size: self.page_size,
+            list_collapse_threshold: self.list_collapse_threshold,
+            time_zone: self.time_zone,
+            namespace_uploads: self.namespace_uploads,
+            thumbnail_size: self.thumbnail_size,
+            uploads_export: self.uploads_export,
+            max_upload_size: self.max_upload_size,
+            api_doc: self.api_doc,
+            serve_openapi: self.serve_openapi,
+            base_path: self.base_path,
+            upload_signer: self.upload_signer,
+            headers: self.headers,
+            csp: self.csp,
+            csrf_key: self.csrf_key,
+            auth: self.auth,
+            auth_routes: self.auth_routes,
+            request_ids: self.request_ids,
+            theme: self.theme,
+            theme_switcher: self.theme_switcher,
+            branding: self.branding,
+            stylesheets: self.stylesheets,
+            banner: self.banner,
+            footer: self.footer,
+            api_cache: self.api_cache,
+            delete_tokens: self.delete_tokens,
+            referenced_files: self.referenced_files,
+            entity_counts: self.entity_counts,
             state_ext: data,
+            inline_relations: self.inline_relations,
+            tasks: self.tasks,
+            seeds: self.seeds,
+            background_tasks: self.background_tasks,
             localizations: self.localizations,
         }
     }
@@ -139,22 +864,136 @@ impl<S> App<S, S>
 where
     S: ContextExt<Context<S>> + 'static,
 {
+    /// Build the [`Router`] and start the background tasks registered using [`App::spawn_task`]
+    /// and the seeds registered using [`App::seed`].
+    /// Must be called within a tokio runtime if there are background tasks or seeds.
     pub fn build(self, uploads_dir: impl Into<PathBuf>) -> Router {
+        let tasks = self.background_tasks();
+        let (router, ctx, seeds) = self.build_parts(uploads_dir);
+        if !seeds.is_empty() {
+            tasks.spawn(ctx, Box::new(|ctx, _| Box::pin(run_seeds(ctx, seeds))));
+        }
+        router
+    }
+
+    /// the [`Router`], the [`Context`] and the seeds, which have not been run yet
+    pub(crate) fn build_parts(
+        mut self,
+        uploads_dir: impl Into<PathBuf>,
+    ) -> (Router, Context<S>, Vec<Seed<Context<S>>>) {
         let uploads_dir = uploads_dir.into();
+        let seeds = std::mem::take(&mut self.seeds);
 
         let mut localizations = self.localizations;
         localizations.push(Box::new(Localizations));
         let localizations = Arc::new(AssetsMultiplexor::new(localizations));
+        let languages = fluent_language_loader!()
+            .available_languages(&*localizations)
+            .unwrap_or_else(|e| {
+                error!("Failed to list available languages: {e:#}");
+                Vec::new()
+            });
 
+        let mut entities = self.entities;
+        entities.sort_by_cached_key(|e| {
+            (
+                std::cmp::Reverse(e.priority()),
+                e.prefix().map(str::to_lowercase),
+                e.name_plural().to_lowercase(),
+            )
+        });
+
+        let uploads_export = match (self.uploads_export, self.auth.is_empty()) {
+            (Some(_), true) => {
+                error!("Not serving the uploads export, it requires App::with_auth or App::with_auth_provider");
+                None
+            }
+            (allow, _) => allow,
+        };
+
+        let ctx = Context {
+            entities,
+            form_groups: self.form_groups.clone(),
+            languages,
+            editor_config: self.editor_config.clone(),
+            paginator: self.paginator.clone(),
+            page_size: self.page_size,
+            list_collapse_threshold: self.list_collapse_threshold,
+            time_zone: self.time_zone.clone(),
+            namespace_uploads: self.namespace_uploads,
+            thumbnail_size: self.thumbnail_size,
+            uploads_export,
+            max_upload_size: self.max_upload_size,
+            branding: self.branding.clone(),
+            stylesheets: self.stylesheets.clone(),
+            banner: self.banner.clone(),
+            footer: self.footer.clone(),
+            uploads_dir: uploads_dir.clone(),
+            base_path: self.base_path.clone(),
+            inline_relations: Arc::new(self.inline_relations),
+            api_cache: self.api_cache.map(ApiCache::new),
+            maintenance_runs: MaintenanceRuns::new(self.background_tasks.clone()),
+            delete_tokens: self.delete_tokens,
+            referenced_files: self.referenced_files.into(),
+            ext: self.state_ext,
+        };
+
+        for task in self.tasks {
+            self.background_tasks.spawn(ctx.clone(), task);
+        }
+
+        let mut uploads = Router::new().nest_service("/uploads", ServeDir::new(&uploads_dir));
+        if let Some(signer) = &self.upload_signer {
+            uploads = uploads.layer(middleware::from_fn_with_state(
+                signer.clone(),
+                verify_signature,
+            ));
+        }
+
+        if self.serve_openapi {
+            let document = Bytes::from(self.api_doc.document().to_string());
+            self.router = self.router.route(
+                "/api/v1/openapi.json",
+                get(move || async move { ([(CONTENT_TYPE, "application/json")], document) }),
+            );
+        }
+
+        let entity_counts: Arc<[_]> = self.entity_counts.into();
+        self.router = self.router.route(
+            "/",
+            get(ui::get_dashboard::<Context<S>>).layer(Extension(entity_counts)),
+        );
+
+        if let Some(allow) = uploads_export {
+            self.router = self.router.route(
+                "/export/uploads.zip",
+                get(export_uploads::<Context<S>>).layer(Extension(ExportPermission(allow))),
+            );
+        }
+
+        let csrf_key = self.csrf_key.map(|mut key| {
+            key.path = match self.base_path.is_empty() {
+                true => "/".to_string(),
+                false => self.base_path.clone(),
+            };
+            key
+        });
         let mut router = self
             .router
-            .nest_service("/uploads", ServeDir::new(&uploads_dir))
-            .with_state(Context {
-                names_plural: self.names_plural,
-                editor_config: self.editor_config.clone(),
-                uploads_dir: uploads_dir.clone(),
-                ext: self.state_ext,
-            })
+            .layer(DefaultBodyLimit::max(self.max_upload_size))
+            .with_state(ctx.clone());
+        for guard in &self.auth {
+            router = guard(router, &self.base_path);
+        }
+        let mut router = router.merge(self.auth_routes.merge(uploads).with_state(ctx.clone()));
+        if let Some(key) = &csrf_key {
+            router = router.layer(middleware::from_fn_with_state(key.clone(), verify_csrf));
+        }
+        let mut router = router
+            .layer(middleware::from_fn_with_state(
+                ctx.clone(),
+                render_error::<Context<S>>,
+            ))
             .layer(middleware::from_fn(|mut req: Request, next: Next| {
                 // add extension `()` to prevent HTTP 500 response when using default/derived impl of `EntityHooks`.
                 req.extensions_mut().insert(());
@@ -162,35 +1001,162 @@ where
             }))
             .layer(middleware::from_fn_with_state(localizations, localize))
             .merge(include_static_files(&STATIC_ASSETS));
+        if self.csp.as_deref() == Some(DEFAULT_POLICY)
+            && self
+                .editor_config
+                .as_ref()
+                .is_some_and(EditorConfig::easymde_from_cdn)
+        {
+            warn!("EasyMDE is loaded from jsDelivr, which is blocked by the default Content-Security-Policy");
+        }
         if let Some(editor_config) = self.editor_config.filter(|config| config.enable_uploads) {
-            router = router.route(
-                "/upload",
-                post(parse_mde_upload)
-                    .layer::<_, Infallible>(DefaultBodyLimit::max(
-                        editor_config.upload_max_size as usize,
-                    ))
-                    .layer::<_, Infallible>(Extension(editor_config))
-                    .layer(Extension(UploadDir(uploads_dir))),
-            );
+            let mut upload = post(parse_mde_upload)
+                .layer::<_, Infallible>(DefaultBodyLimit::max(
+                    editor_config.upload_max_size as usize,
+                ))
+                .layer::<_, Infallible>(Extension(editor_config))
+                .layer::<_, Infallible>(Extension(UploadDir(uploads_dir)))
+                .layer(Extension(BasePath(self.base_path.clone())));
+            if let Some(key) = csrf_key {
+                upload = upload.layer(middleware::from_fn_with_state(key, verify_csrf));
+            }
+            let mut upload = Router::new().route("/upload", upload);
+            for guard in &self.auth {
+                upload = guard(upload, &self.base_path);
+            }
+            router = router.merge(upload);
+        }
+        if let Some(signer) = self.upload_signer {
+            router = router.layer(middleware::from_fn_with_state(signer, provide_signer));
+        }
+        if self.theme_switcher || self.theme.is_some() {
+            let config = ThemeConfig {
+                default: self.theme.unwrap_or_default(),
+                switcher: self.theme_switcher,
+            };
+            router = router.layer(middleware::from_fn_with_state(config, theme));
+        }
+        if let Some(key) = self.background_tasks.encryption_key.clone() {
+            router = router.layer(middleware::from_fn_with_state(key, provide_key));
+        }
+        if let Some(policy) = self.csp {
+            router = router.layer(middleware::from_fn_with_state(policy, csp));
+        }
+        if self.request_ids {
+            router = router.layer(middleware::from_fn(request_id));
+        }
+        if !self.headers.is_empty() {
+            // outermost layer, so the headers are set on all responses, including error pages
+            router = router.layer(middleware::from_fn_with_state(
+                Arc::new(self.headers),
+                set_headers,
+            ));
         }
 
-        router
+        (router, ctx, seeds)
     }
 }
 
+impl<S> App<S, S>
+where
+    S: ContextExt<Context<S>> + 'static,
+{
+    /// Build the [`Router`] like [`App::build`], run the seeds registered using [`App::seed`] and
+    /// serve it on `listener` until a shutdown signal is received, see [`serve`].
+    pub async fn serve(
+        self,
+        listener: TcpListener,
+        uploads_dir: impl Into<PathBuf>,
+    ) -> io::Result<()> {
+        let tasks = self.background_tasks();
+        let (router, ctx, seeds) = self.build_parts(uploads_dir);
+        match tasks.encryption_key.clone() {
+            Some(key) => key.scope(run_seeds(ctx, seeds)).await,
+            None => run_seeds(ctx, seeds).await,
+        }
+        serve(listener, router, tasks).await
+    }
+}
+
+/// Serve `router` on `listener` until [`shutdown_signal`] completes.
+/// In-flight requests are completed and `tasks` are stopped before returning.
+///
+/// Use this instead of [`App::serve`] if the [`Router`] is nested into an existing application.
+pub async fn serve(
+    listener: TcpListener,
+    router: Router,
+    tasks: BackgroundTasks,
+) -> io::Result<()> {
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+    info!("Waiting for background tasks to finish");
+    tasks.shutdown().await;
+    Ok(())
+}
+
+/// Completes when Ctrl+C is pressed or, on unix, SIGTERM is received.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl+C: {e:#}");
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {e:#}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    info!("Shutting down");
+}
+
+/// name of the cookie used to override the language requested with the `Accept-Language` header
+pub const LANGUAGE_COOKIE: &str = "lang";
+
+/// value of the cookie `name` of the request
+pub(crate) fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|c| c.trim().split_once('='))
+        .find(|(k, _)| *k == name)
+        .map(|(_, v)| v)
+}
+
 async fn localize(
     State(localizations): State<Arc<AssetsMultiplexor>>,
     mut req: Request,
     next: Next,
 ) -> Response {
-    let langs = req
-        .headers()
-        .get(axum::http::header::ACCEPT_LANGUAGE)
-        .and_then(|v| v.to_str().ok())
-        .map(accept_language::parse)
-        .unwrap_or_default()
+    let cookie_lang =
+        cookie(req.headers(), LANGUAGE_COOKIE).and_then(|v| v.parse::<LanguageIdentifier>().ok());
+    let langs = cookie_lang
         .into_iter()
-        .filter_map(|lang| lang.parse::<LanguageIdentifier>().ok())
+        .chain(
+            req.headers()
+                .get(ACCEPT_LANGUAGE)
+                .and_then(|v| v.to_str().ok())
+                .map(accept_language::parse)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|lang| lang.parse::<LanguageIdentifier>().ok()),
+        )
         .collect::<Vec<_>>();
     let language_loader: FluentLanguageLoader = fluent_language_loader!();
     i18n_embed::select(&language_loader, &*localizations, &langs).unwrap();
@@ -198,23 +1164,65 @@ async fn localize(
     next.run(req).await
 }
 
+async fn set_headers(State(headers): State<Arc<HeaderMap>>, req: Request, next: Next) -> Response {
+    let mut res = next.run(req).await;
+    for (name, value) in headers.iter() {
+        res.headers_mut()
+            .entry(name)
+            .or_insert_with(|| value.clone());
+    }
+    res
+}
+
+/// whether `path`, e.g. `js/list.js`, is one of the static files served by [`App::build`]
+pub(crate) fn is_static_file(path: &str) -> bool {
+    STATIC_ASSETS.get_file(path).is_some()
+}
+
+/// Serve the files of `dir` at their path within `dir`, e.g. `js/list.js`.
+///
+/// Responses have an `ETag` (a hash of the content) and a `Last-Modified` header (the time this
+/// function has been called, as the files cannot change while the application is running).
+/// Conditional requests using `If-None-Match` or `If-Modified-Since` are answered with HTTP 304.
 pub fn include_static_files<S: Clone + Send + Sync + 'static>(dir: &'static Dir<'_>) -> Router<S> {
+    include_static_files_modified_at(dir, Utc::now())
+}
+
+fn include_static_files_modified_at<S: Clone + Send + Sync + 'static>(
+    dir: &'static Dir<'_>,
+    last_modified: DateTime<Utc>,
+) -> Router<S> {
+    // HTTP dates have a precision of seconds
+    let last_modified = last_modified.with_nanosecond(0).unwrap_or(last_modified);
     let mut app = Router::<S>::new();
     for v in dir.entries() {
         match v {
-            DirEntry::Dir(d) => app = app.merge(include_static_files(d)),
+            DirEntry::Dir(d) => app = app.merge(include_static_files_modified_at(d, last_modified)),
             DirEntry::File(f) => {
                 if let Some(path) = f.path().to_str() {
                     let mime = mime_guess::from_path(path)
                         .first_or_octet_stream()
                         .to_string();
-                    let headers = HeaderMap::from_iter([(
-                        CONTENT_TYPE,
-                        HeaderValue::from_str(&mime).unwrap(),
-                    )]);
+                    let etag = format!("\"{}\"", hex::encode(&Sha256::digest(f.contents())[..16]));
+                    let headers = HeaderMap::from_iter([
+                        (CONTENT_TYPE, HeaderValue::from_str(&mime).unwrap()),
+                        (ETAG, HeaderValue::from_str(&etag).unwrap()),
+                        (
+                            LAST_MODIFIED,
+                            HeaderValue::from_str(&http_date(last_modified)).unwrap(),
+                        ),
+                        // revalidate using the conditional request instead of guessing how long
+                        // the file is fresh, so new versions are picked up after an update
+                        (CACHE_CONTROL, HeaderValue::from_static("no-cache")),
+                    ]);
                     app = app.route(
                         &format!("/{path}"),
-                        get(move || async move { (headers, f.contents()) }),
+                        get(move |req_headers: HeaderMap| async move {
+                            match not_modified(&req_headers, &etag, last_modified) {
+                                true => (StatusCode::NOT_MODIFIED, headers).into_response(),
+                                false => (headers, f.contents()).into_response(),
+                            }
+                        }),
                     )
                 }
             }
@@ -223,33 +1231,112 @@ pub fn include_static_files<S: Clone + Send + Sync + 'static>(dir: &'static Dir<
     app
 }
 
+/// `date` in the format of HTTP headers, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`
+fn http_date(date: DateTime<Utc>) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Whether the client already has the current version according to the conditional request headers.
+/// `If-Modified-Since` is ignored if `If-None-Match` is present (RFC 9110, section 13.1.3).
+fn not_modified(headers: &HeaderMap, etag: &str, last_modified: DateTime<Utc>) -> bool {
+    if let Some(if_none_match) = headers.get(IF_NONE_MATCH) {
+        let Ok(if_none_match) = if_none_match.to_str() else {
+            return false;
+        };
+        return if_none_match.split(',').map(str::trim).any(|tag| {
+            // weak comparison
+            tag == "*" || tag.trim_start_matches("W/") == etag
+        });
+    }
+    headers
+        .get(IF_MODIFIED_SINCE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+        .is_some_and(|since| last_modified <= since)
+}
+
+/// An error in the admin interface, rendered as an error page, or as JSON
+/// `{ "title": ..., "description": ... }` if the `Accept` header prefers `application/json`.
+#[derive(Clone, Debug)]
 pub struct AppError {
     pub title: String,
     pub description: String,
+    /// status of the response, 400 by default
+    pub status: StatusCode,
 }
 
 impl From<()> for AppError {
     fn from(_value: ()) -> Self {
-        Self {
-            title: "Infallible".to_string(),
-            description: "Infallible".to_string(),
-        }
+        Self::new("Infallible".to_string(), "Infallible".to_string())
     }
 }
 
 impl AppError {
     pub fn new(title: String, description: String) -> Self {
-        Self { title, description }
+        Self {
+            title,
+            description,
+            status: StatusCode::BAD_REQUEST,
+        }
+    }
+
+    /// Respond with `status` instead of 400.
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
     }
 }
 
 impl IntoResponse for AppError {
+    /// The error page is rendered by a middleware with access to the [`Context`].
+    /// Outside of the [`Router`] built by [`App::build`], the error is returned as plain text.
     fn into_response(self) -> Response {
         error!("{}: {}", self.title, self.description);
-        (
-            StatusCode::BAD_REQUEST,
-            render::error_page(&self.title, &self.description),
+        let mut res =
+            (self.status, format!("{}: {}", self.title, self.description)).into_response();
+        res.extensions_mut().insert(self);
+        res
+    }
+}
+
+async fn render_error<S: ContextTrait>(State(ctx): State<S>, req: Request, next: Next) -> Response {
+    let i18n = req.extensions().get::<Arc<FluentLanguageLoader>>().cloned();
+    let json = prefers_json(req.headers());
+    let mut res = next.run(req).await;
+    match (res.extensions_mut().remove::<AppError>(), i18n) {
+        (Some(e), _) if json => {
+            let body = serde_json::json!({
+                "title": e.title,
+                "description": e.description,
+            });
+            (res.status(), Json(body)).into_response()
+        }
+        (Some(e), Some(i18n)) => (
+            res.status(),
+            render::error_page(&ctx, &i18n, &e.title, &e.description),
         )
-            .into_response()
+            .into_response(),
+        _ => res,
     }
 }
+
+/// whether the `Accept` header prefers `application/json` over `text/html`
+fn prefers_json(headers: &HeaderMap) -> bool {
+    let Some(accept) = headers.get(ACCEPT).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let quality = |media_type: &str| {
+        accept
+            .split(',')
+            .filter_map(|range| {
+                let mut params = range.split(';').map(str::trim);
+                (params.next()? == media_type).then(|| {
+                    params
+                        .find_map(|p| p.strip_prefix("q=")?.parse::<f32>().ok())
+                        .unwrap_or(1.0)
+                })
+            })
+            .fold(0.0, f32::max)
+    };
+    quality("application/json") > quality("text/html")
+}
@@ -0,0 +1,201 @@
+//! Authentication of editors, see [`App::with_auth_provider`](crate::App::with_auth_provider)
+//! and [`App::with_auth`](crate::App::with_auth).
+//!
+//! An [`AuthProvider`] checks the credentials entered on the login page at `/login` and returns the
+//! value of the [`SESSION_COOKIE`], which it recognizes in [`AuthProvider::authenticate`].
+//! Requests without a valid session are redirected to the login page, requests to the JSON API
+//! are rejected with HTTP 401.
+//!
+//! The [`User`] of the request can be extracted in the `RequestExt` of an entity,
+//! e.g. to record the author:
+//!
+//! ```rust,ignore
+//! impl entity::Create<Ctx> for Post {
+//!     type RequestExt = (State<Ctx>, User);
+//!     type Error = MyError;
+//!
+//!     async fn create(mut data: Post, (state, user): Self::RequestExt) -> Result<Self, Self::Error> {
+//!         data.author = user.id;
+//!         Ok(data.insert(state.ext()).await?)
+//!     }
+//! }
+//! ```
+
+use std::{future::Future, sync::Arc};
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::{request::Parts, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+    Extension, Json, Router,
+};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{
+    app::{cookie, AppError},
+    entity::EntityScope,
+};
+
+/// Wraps the routes of the admin interface and the API, see [`App::with_auth`](crate::App::with_auth).
+/// Receives the [base path](crate::App::with_base_path).
+pub(crate) type Guard = Box<dyn Fn(Router, &str) -> Router + Send>;
+
+/// name of the cookie storing the session returned by [`AuthProvider::login`]
+pub const SESSION_COOKIE: &str = "session";
+
+tokio::task_local! {
+    /// user of the request that is currently handled
+    static USER: User;
+}
+
+/// An authenticated editor, available in the extensions of the request and as an extractor.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct User {
+    /// unique id, e.g. the primary key of the user in the database
+    pub id: String,
+    /// displayed in the sidebar
+    pub name: String,
+    /// e.g. `"admin"`, see [`User::has_role`]
+    pub roles: Vec<String>,
+}
+
+impl User {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            roles: Vec::new(),
+        }
+    }
+
+    pub fn with_role(mut self, role: impl Into<String>) -> Self {
+        self.roles.push(role.into());
+        self
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+
+    /// The user of the current request, if it is authenticated by an [`AuthProvider`].
+    pub fn current() -> Option<Self> {
+        USER.try_with(Clone::clone).ok()
+    }
+}
+
+/// Rejects requests without a [`User`] with HTTP 401.
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for User {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<User>()
+            .cloned()
+            .ok_or(StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// An operation on an entity that can be restricted using [`Permissions`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// list page, relation options, API list and CSV export
+    List,
+    /// edit form, detail page and API get
+    Get,
+    Create,
+    Update,
+    /// deleting and restoring from the trash
+    Delete,
+}
+
+/// Whether the [`User`] may perform the [`Action`] on an entity,
+/// see [`App::entity_with_perms`](crate::App::entity_with_perms).
+pub type Permissions = fn(&User, Action) -> bool;
+
+/// Checks credentials and sessions, see [`auth`](crate::auth).
+pub trait AuthProvider: Send + Sync + 'static {
+    /// The user of the session in the [`SESSION_COOKIE`], see [`session`], or `None` if the
+    /// request is not authenticated.
+    fn authenticate(&self, headers: &HeaderMap) -> impl Future<Output = Option<User>> + Send;
+
+    /// Check the credentials entered on the login page and start a session.
+    /// Returns the value of the [`SESSION_COOKIE`], or `None` if the credentials are invalid.
+    fn login(&self, username: &str, password: &str) -> impl Future<Output = Option<String>> + Send;
+
+    /// End the `session`. Default: do nothing, the cookie is removed anyway
+    fn logout(&self, _session: &str) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+}
+
+/// the value of the [`SESSION_COOKIE`] of a request
+pub fn session(headers: &HeaderMap) -> Option<&str> {
+    cookie(headers, SESSION_COOKIE)
+}
+
+/// Reject the request with HTTP 403 unless the [`User`] may perform `action` on the entity of the
+/// [`EntityScope`], see [`EntityScope::allows_user`].
+pub(crate) async fn authorize(
+    State(action): State<Action>,
+    Extension(scope): Extension<EntityScope>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let user = req.extensions().get::<User>().cloned();
+    if !scope.allows_user(user.as_ref(), action) {
+        if req.uri().path().starts_with("/api/") {
+            return (StatusCode::FORBIDDEN, Json(json!({ "error": "forbidden" }))).into_response();
+        }
+        let error = AppError::new(
+            "Forbidden".to_string(),
+            format!(
+                "You are not allowed to {} {}.",
+                format!("{action:?}").to_lowercase(),
+                scope.name_plural()
+            ),
+        )
+        .with_status(StatusCode::FORBIDDEN);
+        return error.into_response();
+    }
+    match user {
+        // the user may have been added by a custom layer, see `App::with_auth`
+        Some(user) if User::current().is_none() => USER.scope(user, next.run(req)).await,
+        _ => next.run(req).await,
+    }
+}
+
+/// Handle the request with the [`User`] authenticated by `provider`, or redirect to the login page.
+pub(crate) async fn require_user<P: AuthProvider>(
+    State((provider, base_path)): State<(Arc<P>, Arc<str>)>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    match provider.authenticate(req.headers()).await {
+        Some(user) => {
+            req.extensions_mut().insert(user.clone());
+            USER.scope(user, next.run(req)).await
+        }
+        None if req.uri().path().starts_with("/api/") => (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "unauthorized" })),
+        )
+            .into_response(),
+        None => {
+            let next = req
+                .uri()
+                .path_and_query()
+                .map(|p| p.as_str())
+                .unwrap_or_default();
+            Redirect::to(&format!(
+                "{base_path}/login?next={}",
+                urlencoding::encode(&format!("{base_path}{next}"))
+            ))
+            .into_response()
+        }
+    }
+}
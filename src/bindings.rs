@@ -0,0 +1,199 @@
+//! TypeScript client of the JSON API, see [`App::export_bindings`](crate::App::export_bindings).
+//!
+//! The client module `client.ts` exports a function for every endpoint of every registered
+//! entity, e.g. for an entity `Post`:
+//!
+//! ```typescript
+//! import { listPosts, getPost, createPost, updatePost, patchPost, deletePost } from "./bindings/client";
+//!
+//! const drafts = await listPosts({ draft: true, limit: 10 });
+//! const post = await getPost(drafts[0].id);
+//! ```
+//!
+//! Request and response bodies of entities registered using
+//! [`App::with_json_schema`](crate::App::with_json_schema) are typed by their [`TS`] definitions,
+//! which are exported next to the client, the bodies of other entities are typed as any object.
+//! Failed requests throw an `ApiError` with the status and JSON body of the response.
+
+use std::{collections::BTreeMap, fmt::Write, path::Path};
+
+use convert_case::{Case, Casing};
+use ts_rs::{ExportError, TypeVisitor, TS};
+
+use crate::{entity::EntityScope, openapi::OpenApi};
+
+/// name of the client module
+const CLIENT: &str = "client.ts";
+
+/// the functions shared by the endpoints
+const RUNTIME: &str = r#"export type ListParams = {
+  limit?: number;
+  offset?: number;
+  /** the id of the last entity of the previous page, see `X-Next-Cursor` */
+  after?: string | number;
+  [filter: string]: string | number | boolean | undefined;
+};
+
+export class ApiError extends Error {
+  constructor(public status: number, public body: unknown) {
+    super(`request failed with status ${status}`);
+  }
+}
+
+let baseUrl = BASE_PATH;
+
+/** set the URL the API is served at, e.g. `https://example.com/admin` */
+export function setBaseUrl(url: string) {
+  baseUrl = url.replace(/\/$/, "");
+}
+
+async function request<T>(method: string, path: string, init: { query?: ListParams; body?: unknown; version?: string } = {}): Promise<T> {
+  const query = new URLSearchParams();
+  for (const [key, value] of Object.entries(init.query ?? {})) {
+    if (value !== undefined) query.append(key, String(value));
+  }
+  const headers: Record<string, string> = { Accept: "application/json" };
+  if (init.body !== undefined) headers["Content-Type"] = "application/json";
+  if (init.version !== undefined) headers["If-Match"] = init.version;
+  const search = query.toString();
+  const res = await fetch(`${baseUrl}/api/v1/${path}${search ? `?${search}` : ""}`, {
+    method,
+    headers,
+    body: init.body === undefined ? undefined : JSON.stringify(init.body),
+    credentials: "same-origin",
+  });
+  const text = await res.text();
+  const body = text ? JSON.parse(text) : undefined;
+  if (!res.ok) throw new ApiError(res.status, body);
+  return body as T;
+}
+
+function id(id: string | number): string {
+  return encodeURIComponent(String(id));
+}
+"#;
+
+/// Write the client module and the TypeScript types of all entities of `api` to `dir`.
+pub(crate) fn export(api: &OpenApi, dir: &Path, base_path: &str) -> Result<(), ExportError> {
+    let mut imports = BTreeMap::new();
+    let mut functions = String::new();
+    for (scope, schemas) in api.entities() {
+        let [entity, create, update] = match schemas {
+            Some(schemas) => [
+                (schemas.entity_ts)(dir, &mut imports)?,
+                (schemas.create_ts)(dir, &mut imports)?,
+                (schemas.update_ts)(dir, &mut imports)?,
+            ],
+            None => [(); 3].map(|_| "Record<string, unknown>".to_string()),
+        };
+        entity_functions(&mut functions, scope, &entity, &create, &update)?;
+    }
+
+    let mut client = String::from(
+        "// This file was generated by derived-cms. Do not edit this file manually.\n",
+    );
+    for (ident, path) in &imports {
+        writeln!(client, "import type {{ {ident} }} from \"{path}\";")?;
+    }
+    client.push('\n');
+    client.push_str(&RUNTIME.replace("BASE_PATH", &format!("{base_path:?}")));
+    client.push_str(&functions);
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(dir.join(CLIENT), client)?;
+    Ok(())
+}
+
+/// the functions calling the `/api/v1` endpoints of the entity of `scope`
+fn entity_functions(
+    out: &mut String,
+    scope: &EntityScope,
+    entity: &str,
+    create: &str,
+    update: &str,
+) -> std::fmt::Result {
+    let name = scope.path();
+    let name_plural = scope.path_plural();
+    let ident = |name: &str| name.replace(['/', '-'], " ").to_case(Case::Pascal);
+    let (singular, plural) = (ident(&name), ident(&name_plural));
+    write!(
+        out,
+        r#"
+export function list{plural}(params: ListParams = {{}}): Promise<Array<{entity}>> {{
+  return request("GET", "{name_plural}", {{ query: params }});
+}}
+
+export function get{singular}(key: string | number): Promise<{entity}> {{
+  return request("GET", `{name}/${{id(key)}}`);
+}}
+
+export function create{singular}(data: {create}): Promise<{entity}> {{
+  return request("POST", "{name_plural}", {{ body: data }});
+}}
+
+/** `version` is the `ETag` the update is based on, the update is rejected with 412 if it changed since */
+export function update{singular}(key: string | number, data: {update}, version?: string): Promise<{entity}> {{
+  return request("POST", `{name}/${{id(key)}}`, {{ body: data, version }});
+}}
+
+export function patch{singular}(key: string | number, data: Partial<{update}>, version?: string): Promise<{entity}> {{
+  return request("PATCH", `{name}/${{id(key)}}`, {{ body: data, version }});
+}}
+
+/** `confirm` is the token of a previous response with status 409, if deletes have to be confirmed */
+export function delete{singular}(key: string | number, confirm?: string): Promise<void> {{
+  return request("DELETE", `{name}/${{id(key)}}`, {{ query: {{ confirm }} }});
+}}
+"#
+    )
+}
+
+/// The TypeScript type of `T`, after exporting it and all types it depends on to `dir` and adding
+/// them to `imports`, by identifier.
+pub(crate) fn ts_type<T: TS + 'static + ?Sized>(
+    dir: &Path,
+    imports: &mut BTreeMap<String, String>,
+) -> Result<String, ExportError> {
+    let mut exporter = Exporter {
+        dir,
+        imports,
+        error: None,
+    };
+    exporter.visit::<T>();
+    if let Some(e) = exporter.error {
+        return Err(e);
+    }
+    Ok(match T::output_path() {
+        Some(_) => T::name(),
+        None => T::inline(),
+    })
+}
+
+/// exports all visited types which can be exported
+struct Exporter<'a> {
+    dir: &'a Path,
+    imports: &'a mut BTreeMap<String, String>,
+    error: Option<ExportError>,
+}
+
+impl TypeVisitor for Exporter<'_> {
+    fn visit<T: TS + 'static + ?Sized>(&mut self) {
+        if self.error.is_some() {
+            return;
+        }
+        let Some(path) = T::output_path() else {
+            T::visit_dependencies(self);
+            T::visit_generics(self);
+            return;
+        };
+        if self.imports.contains_key(&T::ident()) {
+            return;
+        }
+        if let Err(e) = T::export_all_to(self.dir) {
+            self.error = Some(e);
+            return;
+        }
+        let path = path.with_extension("").to_string_lossy().replace('\\', "/");
+        self.imports.insert(T::ident(), format!("./{path}"));
+        T::visit_generics(self);
+    }
+}
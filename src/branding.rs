@@ -0,0 +1,42 @@
+//! Title, favicon and logo of the admin interface, see [`App::with_branding`](crate::App::with_branding).
+
+#[derive(Debug, Clone, Default)]
+pub struct BrandingConfig {
+    /// name of the application, appended to the title of every page
+    pub(crate) title: Option<String>,
+    /// URL of the favicon, `favicon.png` if not set
+    pub(crate) favicon: Option<String>,
+    /// URL of the logo at the top of the sidebar
+    pub(crate) logo: Option<String>,
+}
+
+impl BrandingConfig {
+    /// Set the name of the application, e.g. `MyCMS`. It is appended to the title of every page,
+    /// e.g. `Posts — MyCMS`, and shown at the top of the sidebar.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the URL of the favicon. Relative URLs are resolved against the
+    /// [base path](crate::App::with_base_path).
+    pub fn favicon(mut self, url: impl Into<String>) -> Self {
+        self.favicon = Some(url.into());
+        self
+    }
+
+    /// Show the image at `url` at the top of the sidebar, linking to the dashboard.
+    /// Relative URLs are resolved against the [base path](crate::App::with_base_path).
+    pub fn logo(mut self, url: impl Into<String>) -> Self {
+        self.logo = Some(url.into());
+        self
+    }
+
+    /// The title of a page with the heading `page`, followed by the name of the application.
+    pub fn page_title(&self, page: &str) -> String {
+        match &self.title {
+            Some(title) => format!("{page} — {title}"),
+            None => page.to_string(),
+        }
+    }
+}
@@ -0,0 +1,303 @@
+//! In-process cache of the responses of the JSON API, see [`App::with_api_cache`](crate::App::with_api_cache).
+//!
+//! Responses of `GET /api/v1/:name/:id` and `GET /api/v1/:name-plural` are cached by their URI and
+//! the id of the [`User`] making the request, if it is authenticated by an
+//! [`AuthProvider`](crate::auth::AuthProvider).
+//! Creating, updating or deleting an entity using the generated endpoints evicts the cached lists of its
+//! type and the cached entity itself. Use [`ApiCache::invalidate`] after modifying entities elsewhere.
+
+use std::{
+    any::TypeId,
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Bytes,
+    http::{
+        header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+use crate::{auth::User, context::ContextTrait};
+
+#[derive(Debug, Clone)]
+pub struct ApiCacheConfig {
+    /// max number of cached responses
+    pub(crate) max_entries: usize,
+    /// how long responses are cached
+    pub(crate) ttl: Duration,
+    /// `max-age` of the `Cache-Control` header
+    pub(crate) max_age: Duration,
+}
+
+impl Default for ApiCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 1000,
+            ttl: Duration::from_secs(60),
+            max_age: Duration::ZERO,
+        }
+    }
+}
+
+impl ApiCacheConfig {
+    /// Set the max number of cached responses. The least recently used response is evicted first.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Set how long responses are cached, independent of invalidation.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set how long clients may cache responses without revalidating them using the `ETag`.
+    /// Default: `Cache-Control: no-cache`
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+}
+
+/// Bounded cache of API responses, available using [`ContextTrait::api_cache`].
+#[derive(Clone, Debug)]
+pub struct ApiCache {
+    config: ApiCacheConfig,
+    entries: Arc<Mutex<Entries>>,
+}
+
+/// id of the [`User`] and URI of a cached response
+type Key = (Option<String>, String);
+
+#[derive(Debug, Default)]
+struct Entries {
+    by_key: HashMap<Key, Entry>,
+    /// incremented on every invalidation of an entity type, see [`ApiCache::generation`]
+    generations: HashMap<TypeId, u64>,
+    clock: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    entity: TypeId,
+    /// `None` for lists
+    id: Option<String>,
+    body: Bytes,
+    etag: HeaderValue,
+    /// additional headers of the response, e.g. `X-Total-Count`
+    headers: HeaderMap,
+    created: Instant,
+    last_used: u64,
+}
+
+impl ApiCache {
+    pub(crate) fn new(config: ApiCacheConfig) -> Self {
+        Self {
+            config,
+            entries: Default::default(),
+        }
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Evict the cached lists of `E` and, if `id` is set, the cached entity with this id.
+    pub fn invalidate<E: 'static>(&self, id: Option<&str>) {
+        let entity = TypeId::of::<E>();
+        let mut entries = self.entries();
+        *entries.generations.entry(entity).or_default() += 1;
+        entries
+            .by_key
+            .retain(|_, e| e.entity != entity || (e.id.is_some() && e.id.as_deref() != id));
+    }
+
+    /// Evict all cached responses.
+    pub fn clear(&self) {
+        let mut entries = self.entries();
+        for generation in entries.generations.values_mut() {
+            *generation += 1;
+        }
+        entries.by_key.clear();
+    }
+
+    /// Responses loaded before an invalidation of `E` must not be cached,
+    /// so the generation is compared before and after loading.
+    fn generation<E: 'static>(&self) -> u64 {
+        self.entries()
+            .generations
+            .get(&TypeId::of::<E>())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn get(&self, key: &Key) -> Option<(Bytes, HeaderValue, HeaderMap)> {
+        let mut entries = self.entries();
+        entries.clock += 1;
+        let clock = entries.clock;
+        let entry = entries.by_key.get_mut(key)?;
+        if entry.created.elapsed() > self.config.ttl {
+            entries.by_key.remove(key);
+            return None;
+        }
+        entry.last_used = clock;
+        Some((
+            entry.body.clone(),
+            entry.etag.clone(),
+            entry.headers.clone(),
+        ))
+    }
+
+    fn insert<E: 'static>(
+        &self,
+        key: Key,
+        id: Option<String>,
+        body: Bytes,
+        etag: HeaderValue,
+        headers: HeaderMap,
+        generation: u64,
+    ) {
+        let entity = TypeId::of::<E>();
+        let mut entries = self.entries();
+        if entries
+            .generations
+            .get(&entity)
+            .copied()
+            .unwrap_or_default()
+            != generation
+        {
+            return;
+        }
+        while entries.by_key.len() >= self.config.max_entries.max(1) {
+            let Some(lru) = entries
+                .by_key
+                .iter()
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.by_key.remove(&lru);
+        }
+        entries.clock += 1;
+        let last_used = entries.clock;
+        entries.by_key.insert(
+            key,
+            Entry {
+                entity,
+                id,
+                body,
+                etag,
+                headers,
+                created: Instant::now(),
+                last_used,
+            },
+        );
+    }
+
+    fn respond(
+        &self,
+        request_headers: &HeaderMap,
+        body: Bytes,
+        etag: HeaderValue,
+        headers: HeaderMap,
+    ) -> Response {
+        let cache_control = match self.config.max_age.as_secs() {
+            0 => HeaderValue::from_static("no-cache"),
+            secs => {
+                HeaderValue::from_str(&format!("max-age={secs}")).expect("header value is valid")
+            }
+        };
+        let not_modified = request_headers
+            .get_all(IF_NONE_MATCH)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|v| v.trim() == etag || v.trim() == "*");
+        let cache_headers = [(ETAG, etag), (CACHE_CONTROL, cache_control)];
+        match not_modified {
+            true => (StatusCode::NOT_MODIFIED, headers, cache_headers).into_response(),
+            false => (
+                headers,
+                cache_headers,
+                [(CONTENT_TYPE, HeaderValue::from_static("application/json"))],
+                body,
+            )
+                .into_response(),
+        }
+    }
+}
+
+/// Respond with the cached response of `uri` for the current [`User`] or with the result of `load`,
+/// which is then cached.
+/// Without cache, `load` is returned as JSON. `None` is returned as HTTP 404 and not cached.
+/// The headers returned by `load` are added to the response and cached with it.
+pub(crate) async fn cached<E, S, T, Err>(
+    ctx: &S,
+    uri: String,
+    id: Option<String>,
+    request_headers: &HeaderMap,
+    load: impl Future<Output = Result<Option<(T, HeaderMap)>, Err>>,
+) -> Result<Response, Err>
+where
+    E: 'static,
+    S: ContextTrait,
+    T: Serialize,
+{
+    let Some(cache) = ctx.api_cache() else {
+        return Ok(match load.await? {
+            Some((v, headers)) => (headers, axum::Json(v)).into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        });
+    };
+    let key = (User::current().map(|user| user.id), uri);
+    if let Some((body, etag, headers)) = cache.get(&key) {
+        return Ok(cache.respond(request_headers, body, etag, headers));
+    }
+    let generation = cache.generation::<E>();
+    let Some((value, headers)) = load.await? else {
+        return Ok(StatusCode::NOT_FOUND.into_response());
+    };
+    let body = match serde_json::to_vec(&value) {
+        Ok(v) => Bytes::from(v),
+        Err(e) => {
+            error!("Failed to serialize response: {e:#}");
+            return Ok(StatusCode::INTERNAL_SERVER_ERROR.into_response());
+        }
+    };
+    // the `ETag` of `load`, e.g. the version token of an entity, is kept
+    let etag = match headers.get(ETAG) {
+        Some(etag) => etag.clone(),
+        None => HeaderValue::from_str(&format!(
+            "\"{}\"",
+            hex::encode(&Sha256::digest(&body)[..16])
+        ))
+        .expect("header value is valid"),
+    };
+    cache.insert::<E>(
+        key,
+        id,
+        body.clone(),
+        etag.clone(),
+        headers.clone(),
+        generation,
+    );
+    Ok(cache.respond(request_headers, body, etag, headers))
+}
+
+/// evict the cached responses affected by modifying `E`, see [`ApiCache::invalidate`]
+pub(crate) fn invalidate<E: 'static, S: ContextTrait>(ctx: &S, id: Option<&str>) {
+    if let Some(cache) = ctx.api_cache() {
+        cache.invalidate::<E>(id);
+    }
+}
@@ -1,12 +1,88 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, sync::LazyLock};
 
 pub use derived_cms_derive::Column;
 use i18n_embed::fluent::FluentLanguageLoader;
 use maud::Markup;
+use ref_cast::RefCast;
+use regex::Regex;
 
 /// A property of an entity that can be rendered as a column on the list page
 pub trait Column: Debug {
     fn render(&self, i18n: &FluentLanguageLoader) -> Markup;
+
+    /// compact preview rendered instead of [`Column::render`] in columns marked with
+    /// `#[cms(column_thumbnail)]`, e.g. a thumbnail of an [`Image`](crate::property::Image).
+    /// Default: [`Column::render`]
+    fn render_thumbnail(&self, i18n: &FluentLanguageLoader) -> Markup {
+        self.render(i18n)
+    }
+
+    /// plain text of this column in the CSV export `GET /api/v1/:name-plural.csv`.
+    /// Default: the [`text_content`] of [`Column::render`]
+    fn render_csv(&self, i18n: &FluentLanguageLoader) -> String {
+        text_content(&self.render(i18n))
+    }
+}
+
+/// The `I`th column of an entity that is not a field, set using
+/// `#[cms(column(label = "...", render = "path::to::fn"))]` when deriving [`Entity`](crate::Entity),
+/// where `fn(&Self, &FluentLanguageLoader) -> Markup` renders the column. The columns are numbered
+/// in the order of the attributes, after the columns of the fields.
+///
+/// ```rust,ignore
+/// #[derive(Debug, Deserialize, Serialize, Entity, Model, TS)]
+/// #[cms(column(label = "Full Name", render = "full_name"))]
+/// struct Author {
+///     first_name: Text,
+///     last_name: Text,
+///     // ...
+/// }
+///
+/// fn full_name(author: &Author, _i18n: &FluentLanguageLoader) -> Markup {
+///     html!((author.first_name) " " (author.last_name))
+/// }
+/// ```
+pub trait ComputedColumn<const I: usize> {
+    fn render_column(&self, i18n: &FluentLanguageLoader) -> Markup;
+}
+
+/// The [`Column`] of the `I`th [`ComputedColumn`] of `E`, which is a reference to the entity.
+#[derive(RefCast)]
+#[repr(transparent)]
+pub struct Computed<E, const I: usize>(E);
+
+impl<E, const I: usize> Computed<E, I> {
+    pub fn of(entity: &E) -> &Self {
+        Self::ref_cast(entity)
+    }
+}
+
+impl<E, const I: usize> Debug for Computed<E, I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Computed").field(&I).finish()
+    }
+}
+
+impl<E: ComputedColumn<I>, const I: usize> Column for Computed<E, I> {
+    fn render(&self, i18n: &FluentLanguageLoader) -> Markup {
+        self.0.render_column(i18n)
+    }
+}
+
+/// scripts and tags of HTML
+static MARKUP_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<script\b.*?</script>|<[^>]*>").unwrap());
+
+/// The text of `markup` without tags and scripts, with the entities escaped by [`maud`] decoded.
+pub fn text_content(markup: &Markup) -> String {
+    MARKUP_REGEX
+        .replace_all(&markup.0, "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
 }
 
 #[derive(Clone, Debug)]
@@ -14,4 +90,6 @@ pub struct ColumnInfo {
     pub name: &'static str,
     /// whether the column is hidden by default
     pub hidden: bool,
+    /// whether the values are rendered using [`Column::render_thumbnail`]
+    pub thumbnail: bool,
 }
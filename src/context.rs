@@ -1,32 +1,139 @@
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use axum::extract::FromRef;
+use derive_more::Debug;
+use i18n_embed::fluent::FluentLanguageLoader;
+use maud::Markup;
+use unic_langid::LanguageIdentifier;
 
-use crate::easymde::EditorConfig;
+use crate::{
+    auth::User,
+    branding::BrandingConfig,
+    cache::ApiCache,
+    delete_token::DeleteTokens,
+    easymde::EditorConfig,
+    entity::EntityScope,
+    maintenance::MaintenanceRuns,
+    pagination::Paginator,
+    relation::{DynInlineRelation, InlineRelations},
+    render::Slot,
+    uploads::LoadReferencedFiles,
+};
 
 /// Trait implemented by the context available in all endpoints using [`axum::extract::State`].
 pub trait ContextTrait: Clone + Send + Sync + 'static {
     type Ext: ContextExt<Self>;
 
-    fn names_plural(&self) -> impl Iterator<Item = impl AsRef<str>>;
+    /// all entities registered using [`App::entity`](crate::App::entity) or
+    /// [`App::entity_scoped`](crate::App::entity_scoped), sorted by
+    /// [priority](crate::EntityBase::nav_priority), prefix and name
+    fn entities(&self) -> &[EntityScope];
+    /// names of all [`FormGroup`](crate::form_group::FormGroup)s registered using
+    /// [`App::form_group`](crate::App::form_group)
+    fn form_groups(&self) -> &[&'static str];
+    /// all languages available in the loaded localizations
+    fn languages(&self) -> &[LanguageIdentifier];
     fn editor(&self) -> Option<&EditorConfig>;
+    /// renders the pager of the list page, see [`App::with_paginator`](crate::App::with_paginator)
+    fn paginator(&self) -> &dyn Paginator;
+    /// number of entities per list page, see [`App::with_page_size`](crate::App::with_page_size)
+    fn page_size(&self) -> u64;
+    /// lists with more elements are rendered collapsed, see [`App::collapse_lists_longer_than`](crate::App::collapse_lists_longer_than)
+    fn list_collapse_threshold(&self) -> Option<usize>;
+    /// time zone of date and time inputs, see [`App::with_time_zone`](crate::App::with_time_zone)
+    fn time_zone(&self) -> Option<&str>;
+    /// whether uploads are stored by entity and field, see [`App::namespace_uploads`](crate::App::namespace_uploads)
+    fn namespace_uploads(&self) -> bool;
+    /// max width and height of the thumbnails of uploaded images, see [`App::with_thumbnails`](crate::App::with_thumbnails)
+    fn thumbnail_size(&self) -> Option<u32>;
+    /// whether the current [`User`] may download all uploads as a zip, see [`App::with_uploads_export`](crate::App::with_uploads_export)
+    fn uploads_export(&self) -> bool;
+    /// max size of the body of entity forms in bytes, see [`App::max_upload_size`](crate::App::max_upload_size)
+    fn max_upload_size(&self) -> usize;
+    /// title, favicon and logo, see [`App::with_branding`](crate::App::with_branding)
+    fn branding(&self) -> &BrandingConfig;
+    /// URLs of additional stylesheets of every page, see [`App::with_stylesheet`](crate::App::with_stylesheet)
+    fn stylesheets(&self) -> &[String];
+    /// rendered above every page, see [`App::with_banner`](crate::App::with_banner)
+    fn banner(&self, i18n: &FluentLanguageLoader) -> Option<Markup>;
+    /// rendered below every page, see [`App::with_footer`](crate::App::with_footer)
+    fn footer(&self, i18n: &FluentLanguageLoader) -> Option<Markup>;
     fn uploads_dir(&self) -> &Path;
+    /// path the admin interface is served under without a trailing slash, e.g. `"/cms"`,
+    /// see [`App::with_base_path`](crate::App::with_base_path)
+    fn base_path(&self) -> &str;
+    /// the [`InlineRelation`](crate::relation::InlineRelation)s of the parent entity `P`,
+    /// see [`App::inline_relation`](crate::App::inline_relation)
+    fn inline_relations<P: 'static>(&self) -> &[Arc<dyn DynInlineRelation<Self, P>>];
+    /// cache of the JSON API, see [`App::with_api_cache`](crate::App::with_api_cache)
+    fn api_cache(&self) -> Option<&ApiCache>;
+    /// status of the [`MaintenanceAction`](crate::maintenance::MaintenanceAction)s of all entities
+    fn maintenance_runs(&self) -> &MaintenanceRuns;
+    /// tokens of pending API deletes, see [`delete_token`](crate::delete_token)
+    fn delete_tokens(&self) -> &DeleteTokens;
     fn ext(&self) -> &Self::Ext;
 }
 
 #[derive(Debug)]
 pub struct Context<T: ContextExt<Self>> {
-    pub(crate) names_plural: Vec<&'static str>,
+    pub(crate) entities: Vec<EntityScope>,
+    pub(crate) form_groups: Vec<&'static str>,
+    pub(crate) languages: Vec<LanguageIdentifier>,
     pub(crate) editor_config: Option<EditorConfig>,
+    pub(crate) paginator: Arc<dyn Paginator>,
+    pub(crate) page_size: u64,
+    pub(crate) list_collapse_threshold: Option<usize>,
+    pub(crate) time_zone: Option<String>,
+    pub(crate) namespace_uploads: bool,
+    pub(crate) thumbnail_size: Option<u32>,
+    pub(crate) uploads_export: Option<fn(&User) -> bool>,
+    pub(crate) max_upload_size: usize,
+    pub(crate) branding: BrandingConfig,
+    pub(crate) stylesheets: Vec<String>,
+    #[debug(skip)]
+    pub(crate) banner: Option<Slot<Self>>,
+    #[debug(skip)]
+    pub(crate) footer: Option<Slot<Self>>,
     pub(crate) uploads_dir: PathBuf,
+    pub(crate) base_path: String,
+    pub(crate) inline_relations: Arc<InlineRelations>,
+    pub(crate) api_cache: Option<ApiCache>,
+    pub(crate) maintenance_runs: MaintenanceRuns,
+    pub(crate) delete_tokens: DeleteTokens,
+    /// see [`App::track_uploads`](crate::App::track_uploads)
+    #[debug(skip)]
+    pub(crate) referenced_files: Arc<[LoadReferencedFiles<Self>]>,
     pub(crate) ext: T,
 }
 impl<E: ContextExt<Self>> Clone for Context<E> {
     fn clone(&self) -> Self {
         Self {
-            names_plural: self.names_plural.clone(),
+            entities: self.entities.clone(),
+            form_groups: self.form_groups.clone(),
+            languages: self.languages.clone(),
             uploads_dir: self.uploads_dir.clone(),
+            base_path: self.base_path.clone(),
+            inline_relations: self.inline_relations.clone(),
+            api_cache: self.api_cache.clone(),
+            maintenance_runs: self.maintenance_runs.clone(),
+            delete_tokens: self.delete_tokens.clone(),
+            referenced_files: self.referenced_files.clone(),
             editor_config: self.editor_config.clone(),
+            paginator: self.paginator.clone(),
+            page_size: self.page_size,
+            list_collapse_threshold: self.list_collapse_threshold,
+            time_zone: self.time_zone.clone(),
+            namespace_uploads: self.namespace_uploads,
+            thumbnail_size: self.thumbnail_size,
+            uploads_export: self.uploads_export,
+            max_upload_size: self.max_upload_size,
+            branding: self.branding.clone(),
+            stylesheets: self.stylesheets.clone(),
+            banner: self.banner.clone(),
+            footer: self.footer.clone(),
             ext: self.ext.clone(),
         }
     }
@@ -34,15 +141,73 @@ impl<E: ContextExt<Self>> Clone for Context<E> {
 impl<E: ContextExt<Self> + 'static> ContextTrait for Context<E> {
     type Ext = E;
 
-    fn names_plural(&self) -> impl Iterator<Item = impl AsRef<str>> {
-        self.names_plural.iter()
+    fn entities(&self) -> &[EntityScope] {
+        &self.entities
+    }
+    fn form_groups(&self) -> &[&'static str] {
+        &self.form_groups
+    }
+    fn languages(&self) -> &[LanguageIdentifier] {
+        &self.languages
     }
     fn editor(&self) -> Option<&EditorConfig> {
         self.editor_config.as_ref()
     }
+    fn paginator(&self) -> &dyn Paginator {
+        &*self.paginator
+    }
+    fn page_size(&self) -> u64 {
+        self.page_size
+    }
+    fn list_collapse_threshold(&self) -> Option<usize> {
+        self.list_collapse_threshold
+    }
+    fn time_zone(&self) -> Option<&str> {
+        self.time_zone.as_deref()
+    }
+    fn namespace_uploads(&self) -> bool {
+        self.namespace_uploads
+    }
+    fn thumbnail_size(&self) -> Option<u32> {
+        self.thumbnail_size
+    }
+    fn uploads_export(&self) -> bool {
+        self.uploads_export
+            .is_some_and(|allow| User::current().is_some_and(|user| allow(&user)))
+    }
+    fn max_upload_size(&self) -> usize {
+        self.max_upload_size
+    }
+    fn branding(&self) -> &BrandingConfig {
+        &self.branding
+    }
+    fn stylesheets(&self) -> &[String] {
+        &self.stylesheets
+    }
+    fn banner(&self, i18n: &FluentLanguageLoader) -> Option<Markup> {
+        self.banner.as_ref().map(|banner| banner(self, i18n))
+    }
+    fn footer(&self, i18n: &FluentLanguageLoader) -> Option<Markup> {
+        self.footer.as_ref().map(|footer| footer(self, i18n))
+    }
     fn uploads_dir(&self) -> &Path {
         &self.uploads_dir
     }
+    fn base_path(&self) -> &str {
+        &self.base_path
+    }
+    fn inline_relations<P: 'static>(&self) -> &[Arc<dyn DynInlineRelation<Self, P>>] {
+        self.inline_relations.get::<Self, P>()
+    }
+    fn api_cache(&self) -> Option<&ApiCache> {
+        self.api_cache.as_ref()
+    }
+    fn maintenance_runs(&self) -> &MaintenanceRuns {
+        &self.maintenance_runs
+    }
+    fn delete_tokens(&self) -> &DeleteTokens {
+        &self.delete_tokens
+    }
     fn ext(&self) -> &E {
         &self.ext
     }
@@ -54,4 +219,24 @@ impl FromRef<Context<()>> for () {
 
 pub trait ContextExt<Ctx>: Clone + Send + Sync {}
 
+/// Whether a field is shown for the current request, e.g. depending on the role of the user.
+/// Used with `#[cms(visible_if = "Type")]` on fields deriving [`Entity`](crate::Entity) or
+/// [`Input`](crate::Input).
+///
+/// ```rust,ignore
+/// struct IsAdmin;
+///
+/// impl Visibility<Context<Users>> for IsAdmin {
+///     fn visible(ctx: &Context<Users>) -> bool {
+///         ctx.ext().current_user_is_admin()
+///     }
+/// }
+/// ```
+///
+/// Hidden inputs are not submitted, so their fields have to be optional or have a `#[serde(default)]`.
+/// This only affects the admin interface, not the JSON API.
+pub trait Visibility<S: ContextTrait> {
+    fn visible(ctx: &S) -> bool;
+}
+
 impl<Ctx, T: Send + Sync + 'static> ContextExt<Ctx> for T where T: Clone {}
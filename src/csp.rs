@@ -0,0 +1,67 @@
+//! `Content-Security-Policy` of the admin interface with a nonce per request,
+//! see [`App::with_csp`](crate::App::with_csp).
+//!
+//! All `<script>` elements rendered by derived-cms have the [`nonce`] of the request, e.g. using
+//! [`FormRenderContext::nonce`](crate::render::FormRenderContext::nonce) in
+//! [`Input::render_input`](crate::Input::render_input).
+//! Instead of inline event handlers, which are blocked by [`DEFAULT_POLICY`], inputs name a global
+//! function in a `data-cms-mount` attribute, which is called with the element when the form is
+//! loaded, or in `data-cms-click`, `data-cms-change`, `data-cms-input`, `data-cms-submit` or
+//! `data-cms-toggle`, which is called with the element and the event.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header::CONTENT_SECURITY_POLICY, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+/// placeholder in policies that is replaced with the [`nonce`] of the request
+pub const NONCE_PLACEHOLDER: &str = "{nonce}";
+
+/// The policy set by [`App::with_security_headers`](crate::App::with_security_headers).
+///
+/// Scripts are allowed from the application itself and inline with the nonce of the request.
+/// Inputs are initialized and handle events using `data-cms-*` attributes, see `js/events.js`,
+/// so neither `'unsafe-eval'` nor inline event handlers are allowed.
+/// External origins:
+///
+/// - `https://maxcdn.bootstrapcdn.com` for the Font Awesome icons of EasyMDE
+/// - images from any `https:` origin, e.g. in markdown
+///
+/// EasyMDE has to be bundled in `static/` (see [`EditorConfig::cdn`](crate::easymde::EditorConfig::cdn)),
+/// or `https://cdn.jsdelivr.net` added to `script-src` and `style-src` using
+/// [`App::with_csp`](crate::App::with_csp).
+pub const DEFAULT_POLICY: &str = "default-src 'self'; \
+    script-src 'self' 'nonce-{nonce}'; \
+    style-src 'self' 'unsafe-inline' https://maxcdn.bootstrapcdn.com; \
+    font-src 'self' https://maxcdn.bootstrapcdn.com; \
+    img-src 'self' data: blob: https:; \
+    frame-ancestors 'none'";
+
+tokio::task_local! {
+    /// nonce of the request that is currently handled
+    static NONCE: String;
+}
+
+/// The nonce of the current request, if a policy is set using [`App::with_csp`](crate::App::with_csp).
+pub fn nonce() -> Option<String> {
+    NONCE.try_with(Clone::clone).ok()
+}
+
+/// Handle the request with a random nonce and set the `Content-Security-Policy` header
+/// with [`NONCE_PLACEHOLDER`] replaced, unless it was set by the handler.
+pub(crate) async fn csp(State(policy): State<Arc<str>>, req: Request, next: Next) -> Response {
+    let nonce = Uuid::new_v4().simple().to_string();
+    let value = HeaderValue::from_str(&policy.replace(NONCE_PLACEHOLDER, &nonce));
+    let mut res = NONCE.scope(nonce, next.run(req)).await;
+    if let Ok(value) = value {
+        res.headers_mut()
+            .entry(CONTENT_SECURITY_POLICY)
+            .or_insert(value);
+    }
+    res
+}
@@ -0,0 +1,275 @@
+//! Protection against cross-site request forgery, see [`App::with_csrf_key`](crate::App::with_csrf_key).
+//!
+//! Every browser gets a random token in the [`CSRF_COOKIE`], signed using a HMAC-SHA256 so it can
+//! not be set by other sites. Requests that change data (all methods except `GET`, `HEAD` and
+//! `OPTIONS`) have to send the same token in the [`X_CSRF_TOKEN`] header or the `_csrf` query
+//! parameter, otherwise they are rejected with HTTP 403. Pages of the admin interface contain the
+//! token in `<meta name="csrf-token">`, which `js/csrf.js` adds to all submitted forms.
+//!
+//! Requests to the JSON API without the cookie don't need a token if other sites can't send them
+//! without a CORS preflight, e.g. JSON or with an `Authorization` header, so API clients that do
+//! not run in a browser don't need a token. Forms and other simple requests are always checked.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE, SET_COOKIE},
+        HeaderName, HeaderValue, Method, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::app::{cookie, AppError};
+
+/// name of the cookie storing the signed CSRF token
+pub const CSRF_COOKIE: &str = "csrf";
+
+/// `X-CSRF-Token`
+pub static X_CSRF_TOKEN: HeaderName = HeaderName::from_static("x-csrf-token");
+
+/// `X-Requested-With`, sent by some API clients to mark requests that are not forms
+static X_REQUESTED_WITH: HeaderName = HeaderName::from_static("x-requested-with");
+
+/// query parameter of the token, used by forms
+const CSRF_PARAM: &str = "_csrf";
+
+tokio::task_local! {
+    /// token of the request that is currently handled
+    static TOKEN: String;
+}
+
+/// The CSRF token of the current request, if CSRF protection is enabled.
+pub fn token() -> Option<String> {
+    TOKEN.try_with(Clone::clone).ok()
+}
+
+/// Signs and verifies CSRF tokens.
+#[derive(Clone)]
+pub struct CsrfKey {
+    secret: Arc<[u8]>,
+    /// path of the cookie
+    pub(crate) path: String,
+}
+
+impl std::fmt::Debug for CsrfKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CsrfKey").finish_non_exhaustive()
+    }
+}
+
+impl CsrfKey {
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secret: secret.as_ref().into(),
+            path: "/".to_string(),
+        }
+    }
+
+    fn mac(&self, nonce: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any size");
+        mac.update(nonce.as_bytes());
+        mac
+    }
+
+    /// a new token `{nonce}.{signature}`
+    fn issue(&self) -> String {
+        let nonce = Uuid::new_v4().simple().to_string();
+        let signature = hex::encode(self.mac(&nonce).finalize().into_bytes());
+        format!("{nonce}.{signature}")
+    }
+
+    /// whether `token` has been issued using this key
+    fn verify(&self, token: &str) -> bool {
+        let Some((nonce, signature)) = token.split_once('.') else {
+            return false;
+        };
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        self.mac(nonce).verify_slice(&signature).is_ok()
+    }
+}
+
+/// Handle the request with the token of the [`CSRF_COOKIE`], or a new one that is set in the
+/// response, and reject requests that change data without the token with HTTP 403.
+pub(crate) async fn verify_csrf(State(key): State<CsrfKey>, req: Request, next: Next) -> Response {
+    let cookie_token = cookie(req.headers(), CSRF_COOKIE).filter(|t| key.verify(t));
+    let safe = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    let api = req.uri().path().starts_with("/api/");
+    // API clients outside of browsers have no cookie
+    let exempt = api && cookie_token.is_none() && requires_preflight(&req);
+    let checked = !safe && !exempt;
+    if checked {
+        let submitted = req
+            .headers()
+            .get(&X_CSRF_TOKEN)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+            .or_else(|| query_token(req.uri().query().unwrap_or_default()));
+        if cookie_token.is_none() || submitted.as_deref() != cookie_token {
+            let error = AppError::new(
+                "Forbidden".to_string(),
+                "The CSRF token of the request is missing or invalid. Please reload the page and try again."
+                    .to_string(),
+            )
+            .with_status(StatusCode::FORBIDDEN);
+            return error.into_response();
+        }
+    }
+    let (token, new) = match cookie_token {
+        Some(token) => (token.to_string(), false),
+        None => (key.issue(), true),
+    };
+    let set_cookie = new.then(|| {
+        format!(
+            "{CSRF_COOKIE}={token}; Path={}; HttpOnly; SameSite=Strict",
+            key.path
+        )
+    });
+    let mut res = TOKEN.scope(token, next.run(req)).await;
+    if let Some(Ok(value)) = set_cookie.map(HeaderValue::try_from) {
+        res.headers_mut().append(SET_COOKIE, value);
+    }
+    res
+}
+
+/// Whether browsers only send `req` to other sites after a CORS preflight, which the API does
+/// not allow. Only `POST` requests with the content type of a form or `text/plain` and no
+/// custom headers are sent without.
+fn requires_preflight(req: &Request) -> bool {
+    if *req.method() != Method::POST {
+        return true;
+    }
+    let headers = req.headers();
+    let simple = match headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok()) {
+        Some(content_type) => {
+            let essence = content_type.split(';').next().unwrap_or_default();
+            matches!(
+                essence.trim().to_ascii_lowercase().as_str(),
+                "application/x-www-form-urlencoded" | "multipart/form-data" | "text/plain"
+            )
+        }
+        None => true,
+    };
+    !simple
+        || [&AUTHORIZATION, &X_REQUESTED_WITH, &X_CSRF_TOKEN]
+            .into_iter()
+            .any(|h| headers.contains_key(h))
+}
+
+fn query_token(query: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|p| p.split_once('='))
+        .find(|(k, _)| *k == CSRF_PARAM)
+        .and_then(|(_, v)| urlencoding::decode(v).ok())
+        .map(|v| v.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, middleware, routing::post, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn status(req: axum::http::request::Builder) -> StatusCode {
+        let key = CsrfKey::new("secret");
+        let app = Router::new()
+            .route("/post/1", post(|| async { "ok" }))
+            .route(
+                "/api/v1/post/1",
+                post(|| async { "ok" }).put(|| async { "ok" }),
+            )
+            .layer(middleware::from_fn_with_state(key, verify_csrf));
+        let res = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        res.status()
+    }
+
+    fn with_cookie(path: &str) -> (axum::http::request::Builder, String) {
+        let token = CsrfKey::new("secret").issue();
+        let req = Request::post(path).header("cookie", format!("{CSRF_COOKIE}={token}"));
+        (req, token)
+    }
+
+    #[tokio::test]
+    async fn form_post_with_the_token_of_the_cookie() {
+        let (req, token) = with_cookie("/post/1");
+        let req = req
+            .uri(format!("/post/1?{CSRF_PARAM}={token}"))
+            .header(CONTENT_TYPE, "application/x-www-form-urlencoded");
+        assert_eq!(status(req).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn missing_token() {
+        let (req, _) = with_cookie("/post/1");
+        assert_eq!(status(req).await, StatusCode::FORBIDDEN);
+        assert_eq!(
+            status(Request::post("/post/1")).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_token() {
+        let (req, _) = with_cookie("/post/1");
+        let other = CsrfKey::new("secret").issue();
+        assert_eq!(
+            status(req.header(&X_CSRF_TOKEN, other)).await,
+            StatusCode::FORBIDDEN
+        );
+        // signed using another key
+        let forged = CsrfKey::new("other").issue();
+        let req = Request::post("/post/1")
+            .header("cookie", format!("{CSRF_COOKIE}={forged}"))
+            .header(&X_CSRF_TOKEN, &forged);
+        assert_eq!(status(req).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn api_form_post_without_cookie_is_rejected() {
+        for content_type in [
+            "application/x-www-form-urlencoded",
+            "multipart/form-data; boundary=x",
+            "Text/Plain; charset=utf-8",
+        ] {
+            let req = Request::post("/api/v1/post/1").header(CONTENT_TYPE, content_type);
+            assert_eq!(status(req).await, StatusCode::FORBIDDEN, "{content_type}");
+        }
+        assert_eq!(
+            status(Request::post("/api/v1/post/1")).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn api_json_post_without_cookie_is_allowed() {
+        let req = Request::post("/api/v1/post/1").header(CONTENT_TYPE, "application/json");
+        assert_eq!(status(req).await, StatusCode::OK);
+        let req = Request::post("/api/v1/post/1")
+            .header(CONTENT_TYPE, "text/plain")
+            .header(AUTHORIZATION, "Bearer token");
+        assert_eq!(status(req).await, StatusCode::OK);
+        assert_eq!(status(Request::put("/api/v1/post/1")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn api_json_post_with_cookie_requires_the_token() {
+        let (req, _) = with_cookie("/api/v1/post/1");
+        let req = req.header(CONTENT_TYPE, "application/json");
+        assert_eq!(status(req).await, StatusCode::FORBIDDEN);
+        let (req, token) = with_cookie("/api/v1/post/1");
+        let req = req
+            .header(CONTENT_TYPE, "application/json")
+            .header(&X_CSRF_TOKEN, token);
+        assert_eq!(status(req).await, StatusCode::OK);
+    }
+}
@@ -0,0 +1,72 @@
+//! Two-step deletes using the JSON API, see [`App::confirm_api_deletes`](crate::App::confirm_api_deletes)
+//! and [`EntityBase::confirm_api_delete`](crate::EntityBase::confirm_api_delete).
+//!
+//! A `DELETE /api/v1/:name/:id` without a valid `confirm` query parameter does not delete the entity,
+//! but responds with `409 Conflict` and a one-time token:
+//!
+//! ```json
+//! { "confirm": "c0ffee...", "expires_in": 60 }
+//! ```
+//!
+//! The entity is deleted once the client repeats the request as `DELETE /api/v1/:name/:id?confirm=c0ffee...`
+//! before the token expires. Tokens are only valid for the entity they were issued for.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+/// One-time tokens of pending API deletes, available using [`ContextTrait::delete_tokens`](crate::context::ContextTrait::delete_tokens).
+#[derive(Clone, Debug)]
+pub struct DeleteTokens {
+    /// whether deletes of all entities have to be confirmed
+    pub(crate) all: bool,
+    pub(crate) ttl: Duration,
+    tokens: Arc<Mutex<HashMap<String, (String, Instant)>>>,
+}
+
+impl Default for DeleteTokens {
+    fn default() -> Self {
+        Self::new(false, Duration::from_secs(60))
+    }
+}
+
+impl DeleteTokens {
+    pub(crate) fn new(all: bool, ttl: Duration) -> Self {
+        Self {
+            all,
+            ttl,
+            tokens: Default::default(),
+        }
+    }
+
+    fn tokens(&self) -> MutexGuard<'_, HashMap<String, (String, Instant)>> {
+        self.tokens.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// how long issued tokens are valid
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Issue a new token for deleting the entity identified by `key`.
+    pub(crate) fn issue(&self, key: &str) -> String {
+        let now = Instant::now();
+        let token = Uuid::new_v4().simple().to_string();
+        let mut tokens = self.tokens();
+        tokens.retain(|_, (_, expires)| *expires > now);
+        tokens.insert(token.clone(), (key.to_owned(), now + self.ttl));
+        token
+    }
+
+    /// Consume `token`. Returns `true` if it was issued for `key` and has not expired.
+    pub(crate) fn redeem(&self, key: &str, token: &str) -> bool {
+        match self.tokens().remove(token) {
+            Some((k, expires)) => k == key && expires > Instant::now(),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! (De)serialize an [`Option<Option<T>>`] such that an explicit `null` can be distinguished
+//! from a missing key.
+//!
+//! | JSON                  | deserialized value |
+//! |-----------------------|--------------------|
+//! | key missing           | `None`             |
+//! | `"key": null`         | `Some(None)`       |
+//! | `"key": value`        | `Some(Some(value))`|
+//!
+//! This is useful for [`Update`](crate::EntityBase::Update) types of entities with optional
+//! fields: `None` should leave the stored value unchanged, `Some(None)` should clear it.
+//! `#[cms(update_struct = "PostUpdate")]` generates such a type `PostUpdate` with the fields of the
+//! entity, where every `Option<T>` field is an `Option<Option<T>>` using this module.
+//! In custom update types (see `#[cms(update = ...)]`), the field must also be marked with
+//! `#[serde(default)]`, otherwise a missing key is a deserialization error.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, Serialize)]
+//! struct UpdatePost {
+//!     #[serde(
+//!         default,
+//!         skip_serializing_if = "Option::is_none",
+//!         with = "derived_cms::double_option"
+//!     )]
+//!     subtitle: Option<Option<String>>,
+//! }
+//!
+//! let v: UpdatePost = serde_json::from_str("{}").unwrap();
+//! assert_eq!(v.subtitle, None);
+//! let v: UpdatePost = serde_json::from_str(r#"{"subtitle": null}"#).unwrap();
+//! assert_eq!(v.subtitle, Some(None));
+//! let v: UpdatePost = serde_json::from_str(r#"{"subtitle": "abc"}"#).unwrap();
+//! assert_eq!(v.subtitle, Some(Some("abc".to_string())));
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn serialize<T, S>(value: &Option<Option<T>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    // a missing value should be skipped using `skip_serializing_if = "Option::is_none"`
+    match value {
+        Some(Some(v)) => serializer.serialize_some(v),
+        _ => serializer.serialize_none(),
+    }
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    // only called if the key is present, a missing key results in `Default::default()`
+    Option::<T>::deserialize(deserializer).map(Some)
+}
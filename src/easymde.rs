@@ -4,8 +4,32 @@ use axum::{extract::multipart::MultipartError, http::StatusCode, response::IntoR
 use serde::Serialize;
 use tracing::error;
 
+use crate::app::is_static_file;
+
+/// The editor of [`Markdown`](crate::property::Markdown) inputs.
+#[derive(Debug, Clone, Default)]
+pub enum EditorBackend {
+    /// [EasyMDE](https://github.com/Ionaru/easy-markdown-editor), served from `js/easymde.min.js`
+    /// and `css/easymde.min.css` or loaded from jsDelivr, see [`EditorConfig::cdn`]
+    #[default]
+    EasyMde,
+    /// a bare `<textarea>` without any external script, e.g. for air-gapped setups
+    Plain,
+    /// Load the script at `script_url` once and call the global function named `init_fn`, which
+    /// is defined by the script, with the `<textarea>` when it is mounted.
+    /// Uploads are accepted at `upload` relative to the base path.
+    Custom {
+        script_url: Cow<'static, str>,
+        init_fn: Cow<'static, str>,
+    },
+}
+
 #[derive(Debug, Clone)]
 pub struct EditorConfig {
+    /// the editor rendered by markdown inputs
+    pub(crate) backend: EditorBackend,
+    /// load EasyMDE from jsDelivr instead of the bundled copy
+    pub(crate) cdn: bool,
     /// enable drag-and-drop upload functionality in the default markdown editor
     pub(crate) enable_uploads: bool,
     /// max upload size in bytes
@@ -17,6 +41,8 @@ pub struct EditorConfig {
 impl Default for EditorConfig {
     fn default() -> Self {
         Self {
+            backend: EditorBackend::default(),
+            cdn: false,
             enable_uploads: true,
             upload_max_size: 1024 * 1024 * 2,
             allowed_file_types: vec!["image/png".into(), "image/jpeg".into()],
@@ -25,6 +51,21 @@ impl Default for EditorConfig {
 }
 
 impl EditorConfig {
+    /// Set the editor rendered by markdown inputs. Default: [`EditorBackend::EasyMde`]
+    pub fn backend(mut self, backend: EditorBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Load EasyMDE from jsDelivr instead of the copy served by the application at
+    /// `js/easymde.min.js` and `css/easymde.min.css`, which is used if these files exist in `static/`.
+    /// The bundled copy works in offline installations and with a strict `Content-Security-Policy`.
+    /// Default: `false`
+    pub fn cdn(mut self, cdn: bool) -> Self {
+        self.cdn = cdn;
+        self
+    }
+
     /// Enable uploads directly in the editor.
     pub fn enable_uploads(mut self, enable: bool) -> Self {
         self.enable_uploads = enable;
@@ -48,8 +89,27 @@ impl EditorConfig {
         self.allowed_file_types = file_types;
         self
     }
+
+    /// whether EasyMDE is loaded from jsDelivr, see [`EditorConfig::cdn`]
+    pub(crate) fn easymde_from_cdn(&self) -> bool {
+        matches!(self.backend, EditorBackend::EasyMde)
+            && (self.cdn || !is_static_file(EASYMDE_SCRIPT) || !is_static_file(EASYMDE_STYLESHEET))
+    }
+
+    /// URLs of the script and the stylesheet of EasyMDE
+    pub(crate) fn easymde_assets(&self) -> (&'static str, &'static str) {
+        match self.easymde_from_cdn() {
+            true => (EASYMDE_CDN_SCRIPT, EASYMDE_CDN_STYLESHEET),
+            false => (EASYMDE_SCRIPT, EASYMDE_STYLESHEET),
+        }
+    }
 }
 
+const EASYMDE_SCRIPT: &str = "js/easymde.min.js";
+const EASYMDE_STYLESHEET: &str = "css/easymde.min.css";
+const EASYMDE_CDN_SCRIPT: &str = "https://cdn.jsdelivr.net/npm/easymde/dist/easymde.min.js";
+const EASYMDE_CDN_STYLESHEET: &str = "https://cdn.jsdelivr.net/npm/easymde/dist/easymde.min.css";
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct UploadedFileInfo {
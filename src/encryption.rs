@@ -0,0 +1,210 @@
+//! Encryption at rest of [`Encrypted`](crate::property::Encrypted) properties,
+//! see [`App::with_encryption_key`](crate::App::with_encryption_key).
+//!
+//! Values are serialized as JSON and encrypted using AES-256-GCM with a random nonce.
+//! The stored bytes are a version byte, the nonce and the ciphertext with the authentication tag.
+
+use std::{future::Future, sync::Arc};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    rand::{SecureRandom, SystemRandom},
+};
+use thiserror::Error;
+
+tokio::task_local! {
+    /// key of the [`App`](crate::App) handling the current request or running the current task
+    static KEY: EncryptionKey;
+}
+
+/// format of the encrypted bytes
+const VERSION: u8 = 1;
+
+/// Key used to encrypt and decrypt [`Encrypted`](crate::property::Encrypted) properties.
+#[derive(Clone)]
+pub struct EncryptionKey {
+    key: Arc<LessSafeKey>,
+    rng: SystemRandom,
+}
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptionKey").finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum EncryptionError {
+    #[error("no encryption key is configured, see App::with_encryption_key")]
+    NoKey,
+    #[error("failed to encrypt value")]
+    Encrypt,
+    #[error(
+        "failed to decrypt value, it is corrupted or has been encrypted using a different key"
+    )]
+    Decrypt,
+}
+
+impl EncryptionKey {
+    /// `key` is a secret of 256 random bits. Store it outside of the database,
+    /// values encrypted with it cannot be decrypted once it is lost.
+    pub fn new(key: [u8; 32]) -> Self {
+        let key = UnboundKey::new(&AES_256_GCM, &key).expect("AES-256-GCM keys have 32 bytes");
+        Self {
+            key: Arc::new(LessSafeKey::new(key)),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    /// The key of the current request or background task, if encryption is enabled.
+    pub fn current() -> Option<Self> {
+        KEY.try_with(Clone::clone).ok()
+    }
+
+    /// Run `f` with `self` as the [current](EncryptionKey::current) key,
+    /// e.g. to access [`Encrypted`](crate::property::Encrypted) properties outside of the [`App`](crate::App).
+    pub async fn scope<F: Future>(self, f: F) -> F::Output {
+        KEY.scope(self, f).await
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        let mut nonce = [0; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| EncryptionError::Encrypt)?;
+        let mut data = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| EncryptionError::Encrypt)?;
+        let mut encrypted = Vec::with_capacity(1 + NONCE_LEN + data.len());
+        encrypted.push(VERSION);
+        encrypted.extend(nonce);
+        encrypted.extend(data);
+        Ok(encrypted)
+    }
+
+    pub fn decrypt(&self, encrypted: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        let Some((&VERSION, encrypted)) = encrypted.split_first() else {
+            return Err(EncryptionError::Decrypt);
+        };
+        if encrypted.len() < NONCE_LEN {
+            return Err(EncryptionError::Decrypt);
+        }
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_LEN);
+        let nonce =
+            Nonce::try_assume_unique_for_key(nonce).map_err(|_| EncryptionError::Decrypt)?;
+        let mut data = ciphertext.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut data)
+            .map_err(|_| EncryptionError::Decrypt)?;
+        Ok(plaintext.to_vec())
+    }
+}
+
+/// make the key available to [`Encrypted`](crate::property::Encrypted) while handling the request
+pub(crate) async fn provide_key(
+    State(key): State<EncryptionKey>,
+    req: Request,
+    next: Next,
+) -> Response {
+    KEY.scope(key, next.run(req)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn key(byte: u8) -> EncryptionKey {
+        EncryptionKey::new([byte; 32])
+    }
+
+    #[test]
+    fn round_trip() {
+        let key = key(1);
+        for plaintext in [&b""[..], b"secret", &[0xff; 1000]] {
+            let encrypted = key.encrypt(plaintext).unwrap();
+            assert_eq!(encrypted.len(), 1 + NONCE_LEN + plaintext.len() + 16);
+            assert_eq!(encrypted[0], VERSION);
+            if !plaintext.is_empty() {
+                assert_ne!(&encrypted[1 + NONCE_LEN..][..plaintext.len()], plaintext);
+            }
+            assert_eq!(key.decrypt(&encrypted).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn wrong_key() {
+        let encrypted = key(1).encrypt(b"secret").unwrap();
+        assert!(matches!(
+            key(2).decrypt(&encrypted),
+            Err(EncryptionError::Decrypt)
+        ));
+    }
+
+    #[test]
+    fn tampered_ciphertext() {
+        let key = key(1);
+        let encrypted = key.encrypt(b"secret").unwrap();
+        // the version, nonce, ciphertext and tag
+        for i in 0..encrypted.len() {
+            let mut tampered = encrypted.clone();
+            tampered[i] ^= 1;
+            assert!(key.decrypt(&tampered).is_err(), "byte {i}");
+        }
+        for len in [0, 1, NONCE_LEN, encrypted.len() - 1] {
+            assert!(key.decrypt(&encrypted[..len]).is_err(), "length {len}");
+        }
+        let mut extended = encrypted.clone();
+        extended.push(0);
+        assert!(key.decrypt(&extended).is_err());
+    }
+
+    #[test]
+    fn nonces_are_unique() {
+        let key = key(1);
+        let nonces = (0..1000)
+            .map(|_| key.encrypt(b"secret").unwrap()[1..1 + NONCE_LEN].to_vec())
+            .collect::<HashSet<_>>();
+        assert_eq!(nonces.len(), 1000);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn encrypted_column_round_trip() {
+        use ormlite::sqlite::SqlitePool;
+
+        use crate::property::Encrypted;
+
+        let db = &SqlitePool::connect("sqlite::memory:").await.unwrap();
+        let value = &Encrypted("secret".to_string());
+        // values are encrypted when they are bound and decrypted when they are fetched
+        let encrypt = || async move {
+            sqlx::query_scalar::<_, Vec<u8>>("SELECT ?")
+                .bind(value)
+                .fetch_one(db)
+                .await
+        };
+        let decrypt = |stored: Vec<u8>| async move {
+            sqlx::query_scalar::<_, Encrypted<String>>("SELECT ?")
+                .bind(stored)
+                .fetch_one(db)
+                .await
+        };
+        assert!(encrypt().await.is_err());
+
+        let stored = key(1).scope(encrypt()).await.unwrap();
+        assert!(!stored.windows(6).any(|w| w == b"secret"));
+        let decrypted = key(1).scope(decrypt(stored.clone())).await.unwrap();
+        assert_eq!(decrypted.0, value.0);
+        assert!(key(2).scope(decrypt(stored.clone())).await.is_err());
+        assert!(decrypt(stored).await.is_err());
+    }
+}
@@ -1,64 +1,534 @@
+use std::{convert::Infallible, sync::Arc};
+
 use axum::{
-    extract::Path,
-    http::StatusCode,
+    async_trait,
+    body::Body,
+    extract::{rejection::JsonRejection, FromRequest, OriginalUri, Query, Request, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE, ETAG, IF_MATCH, LINK},
+        HeaderMap, HeaderName, HeaderValue, StatusCode, Uri,
+    },
     response::{IntoResponse, Response},
-    Json,
+    Extension, Json,
 };
-use serde::Serialize;
+use convert_case::{Case, Casing};
+use i18n_embed::fluent::FluentLanguageLoader;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
 use thiserror::Error;
-use tracing::debug;
+use tracing::{debug, error, info};
+use ts_rs::TS;
+
+use crate::{
+    cache::{cached, invalidate},
+    context::ContextTrait,
+    entity::{self, EntityBase, EntityScope, IdPath, ListParams, PatchError},
+    filter::{Filter, FilterError},
+    input::Inputs,
+    request_id::RequestId,
+    schema,
+    uploads::{verify_files_during, verify_files_in},
+};
+
+/// number of entities of all pages of `GET /api/v1/:name-plural`
+const X_TOTAL_COUNT: HeaderName = HeaderName::from_static("x-total-count");
+
+/// cursor of the next page of `GET /api/v1/:name-plural?after=:id`, also in the `Link` header
+const X_NEXT_CURSOR: HeaderName = HeaderName::from_static("x-next-cursor");
 
-use crate::{context::ContextTrait, entity};
+/// number of entities of a page of `GET /api/v1/:name-plural?after=:id` without `limit`
+const DEFAULT_KEYSET_LIMIT: u64 = 100;
 
 #[derive(Error)]
 #[error(transparent)]
 pub struct ApiError<T: Serialize>(#[from] T);
 
 impl<T: Serialize> IntoResponse for ApiError<T> {
+    /// If [request ids](crate::App::with_request_id) are enabled, they are added to errors
+    /// serialized as objects as `request_id`.
     fn into_response(self) -> axum::response::Response {
-        (StatusCode::BAD_REQUEST, Json(self.0)).into_response()
+        let Some(id) = RequestId::current() else {
+            return (StatusCode::BAD_REQUEST, Json(self.0)).into_response();
+        };
+        let mut body = serde_json::to_value(&self.0).unwrap_or(Value::Null);
+        error!("API error: {body}");
+        if let Value::Object(map) = &mut body {
+            map.insert("request_id".to_string(), Value::String(id.to_string()));
+        }
+        (StatusCode::BAD_REQUEST, Json(body)).into_response()
     }
 }
 
+/// JSON Schema of `T`, see [`schema`](crate::schema)
+pub async fn get_schema<T: TS + 'static>() -> Json<Value> {
+    Json(schema::json_schema::<T>())
+}
+
+/// `GET /api/v1/:name-plural`: the page selected by `limit` and `offset`, or by `after` and
+/// `limit` using [`List::list_after`](entity::List::list_after)
 pub async fn get_entities<E: entity::List<S>, S: ContextTrait>(
+    State(ctx): State<S>,
+    uri: Uri,
+    OriginalUri(original_uri): OriginalUri,
+    headers: HeaderMap,
+    Query(mut query): Query<Vec<(String, String)>>,
+    ext: E::RequestExt,
+) -> Result<Response, ApiError<E::Error>> {
+    if let Some(i) = query.iter().position(|(key, _)| key == "after") {
+        let (_, after) = query.remove(i);
+        return get_entities_after::<E, S>(ctx, uri, original_uri, headers, query, after, ext)
+            .await;
+    }
+    let params = match list_params::<E, S>(query) {
+        Ok(params) => params,
+        Err(e) => return Ok(e.into_response()),
+    };
+    Ok(
+        cached::<E, S, _, _>(&ctx, uri.to_string(), None, &headers, async {
+            let (entities, total) = E::list_paginated(params, ext).await?;
+            let entities = entities
+                .into_iter()
+                .map(|e| e.api_serialize())
+                .collect::<Vec<_>>();
+            Ok::<_, E::Error>(Some((entities, total_count(total))))
+        })
+        .await?,
+    )
+}
+
+/// the page of [`List::list_after`](entity::List::list_after) after the entity `after`, or the
+/// first page if it is empty, with a `Link` header to the next page
+async fn get_entities_after<E: entity::List<S>, S: ContextTrait>(
+    ctx: S,
+    uri: Uri,
+    original_uri: Uri,
+    headers: HeaderMap,
+    query: Vec<(String, String)>,
+    after: String,
+    ext: E::RequestExt,
+) -> Result<Response, ApiError<E::Error>> {
+    let cursor = match after.as_str() {
+        "" => None,
+        _ => match Query::<AfterQuery<E::Id>>::try_from_uri(&original_uri) {
+            Ok(Query(AfterQuery { after })) => Some(after),
+            Err(_) => {
+                let key = "after".to_string();
+                return Ok(FilterError::InvalidValue { key, value: after }.into_response());
+            }
+        },
+    };
+    let params = match list_params::<E, S>(query.clone()) {
+        Ok(params) => params,
+        Err(e) => return Ok(e.into_response()),
+    };
+    let limit = params.limit.unwrap_or(DEFAULT_KEYSET_LIMIT);
+    Ok(
+        cached::<E, S, _, _>(&ctx, uri.to_string(), None, &headers, async move {
+            let (entities, next) = E::list_after(cursor, limit, params.filter, ext).await?;
+            let entities = entities
+                .into_iter()
+                .map(|e| e.api_serialize())
+                .collect::<Vec<_>>();
+            let mut headers = HeaderMap::new();
+            if let Some(next) = next.map(|next| next.to_string()) {
+                let query = url::form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(&query)
+                    .append_pair("after", &next)
+                    .finish();
+                let link = format!("<{}?{query}>; rel=\"next\"", original_uri.path());
+                if let (Ok(link), Ok(next)) =
+                    (HeaderValue::try_from(link), HeaderValue::try_from(next))
+                {
+                    headers.insert(LINK, link);
+                    headers.insert(X_NEXT_CURSOR, next);
+                }
+            }
+            Ok::<_, E::Error>(Some((entities, headers)))
+        })
+        .await?,
+    )
+}
+
+#[derive(Deserialize)]
+struct AfterQuery<T> {
+    after: T,
+}
+
+/// `GET /api/v1/:name-plural.csv`: the entities as CSV, filtered and paginated like
+/// [`get_entities`]. The first row are the names of the visible columns, the cells are rendered
+/// using [`Column::render_csv`](crate::Column::render_csv).
+pub async fn get_entities_csv<E: entity::List<S>, S: ContextTrait>(
+    State(ctx): State<S>,
+    Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    Query(query): Query<Vec<(String, String)>>,
     ext: E::RequestExt,
-) -> Result<Json<Vec<E>>, ApiError<E::Error>> {
-    Ok(Json(E::list(ext).await?.into_iter().collect()))
+) -> Result<Response, ApiError<E::Error>> {
+    let params = match list_params::<E, S>(query) {
+        Ok(params) => params,
+        Err(e) => return Ok(e.into_response()),
+    };
+    let (entities, total) = E::list_paginated(params, ext).await?;
+    let visible = E::visible_columns(&ctx);
+    let header = csv_row(
+        E::columns()
+            .iter()
+            .zip(visible.iter())
+            .filter(|(_, v)| **v)
+            .map(|(c, _)| c.name.to_string()),
+    );
+    // rows are rendered while the response is sent
+    let rows = entities.into_iter().map(move |e| {
+        csv_row(
+            e.column_values()
+                .iter()
+                .zip(visible.iter())
+                .filter(|(_, v)| **v)
+                .map(|(c, _)| c.render_csv(&i18n)),
+        )
+    });
+    let body = Body::from_stream(tokio_stream::iter(
+        std::iter::once(header).chain(rows).map(Ok::<_, Infallible>),
+    ));
+    let mut headers = total_count(total);
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("text/csv; charset=utf-8"),
+    );
+    headers.insert(
+        CONTENT_DISPOSITION,
+        HeaderValue::from_str(&format!(
+            "attachment; filename=\"{}.csv\"",
+            E::name_plural().to_case(Case::Kebab)
+        ))
+        .unwrap_or(HeaderValue::from_static("attachment")),
+    );
+    Ok((headers, body).into_response())
+}
+
+/// a line of CSV as in RFC 4180, terminated by CRLF
+fn csv_row(cells: impl IntoIterator<Item = String>) -> String {
+    let mut row = cells
+        .into_iter()
+        .map(|cell| csv_cell(&cell))
+        .collect::<Vec<_>>()
+        .join(",");
+    row.push_str("\r\n");
+    row
+}
+
+/// Quote `cell` if required. Cells that would be evaluated as a formula by spreadsheet
+/// applications are prefixed with `'`.
+fn csv_cell(cell: &str) -> String {
+    let formula = cell.starts_with(['=', '+', '@', '\t', '\r'])
+        || (cell.starts_with('-') && cell.parse::<f64>().is_err());
+    let cell = match formula {
+        true => format!("'{cell}"),
+        false => cell.to_string(),
+    };
+    match cell.contains([',', '"', '\r', '\n']) {
+        true => format!("\"{}\"", cell.replace('"', "\"\"")),
+        false => cell,
+    }
+}
+
+/// `limit`, `offset` and the [filter](crate::filter) of the query string
+fn list_params<E: EntityBase<S>, S: ContextTrait>(
+    query: Vec<(String, String)>,
+) -> Result<ListParams, FilterError> {
+    let mut params = ListParams::default();
+    let mut filters = Vec::new();
+    for (key, value) in query {
+        let target = match key.as_str() {
+            "limit" => &mut params.limit,
+            "offset" => &mut params.offset,
+            _ => {
+                filters.push((key, value));
+                continue;
+            }
+        };
+        match value.parse() {
+            Ok(v) => *target = Some(v),
+            Err(_) => return Err(FilterError::InvalidValue { key, value }),
+        }
+    }
+    params.filter = Filter::parse::<E, S>(filters)?;
+    Ok(params)
+}
+
+/// the `X-Total-Count` header with the number of entities of all pages
+fn total_count(total: u64) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(X_TOTAL_COUNT, HeaderValue::from(total));
+    headers
 }
 
 pub async fn get_entity<E: entity::Get<S>, S: ContextTrait>(
+    State(ctx): State<S>,
+    uri: Uri,
+    headers: HeaderMap,
     ext: E::RequestExt,
-    Path(id): Path<E::Id>,
+    IdPath(id): IdPath<E::Id>,
 ) -> Result<Response, ApiError<E::Error>> {
-    Ok(match E::get(&id, ext).await? {
-        Some(v) => Json(v).into_response(),
-        None => StatusCode::NOT_FOUND.into_response(),
-    })
+    let id_str = id.to_string();
+    Ok(
+        cached::<E, S, _, _>(&ctx, uri.to_string(), Some(id_str), &headers, async move {
+            Ok::<_, E::Error>(
+                E::get(&id, ext)
+                    .await?
+                    .map(|e| (e.api_serialize(), etag::<E, S>(&e))),
+            )
+        })
+        .await?,
+    )
+}
+
+/// [`Json`] body that rejects [`File`](crate::property::File)s missing from the uploads directory,
+/// see [`verify_files_in`]
+pub struct UploadsJson<T>(pub T);
+
+#[async_trait]
+impl<T: DeserializeOwned, S: ContextTrait> FromRequest<S> for UploadsJson<T> {
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<Value>::from_request(req, state)
+            .await
+            .map_err(JsonRejection::into_response)?;
+        verify_files_in(state.uploads_dir(), || {
+            serde_path_to_error::deserialize(value)
+        })
+        .map(Self)
+        .map_err(|e| {
+            let body = format!("Failed to deserialize the JSON body into the target type: {e}");
+            (StatusCode::UNPROCESSABLE_ENTITY, body).into_response()
+        })
+    }
 }
 
 /// create a new entity
 pub async fn post_entities<E: entity::Create<S>, S: ContextTrait>(
+    State(ctx): State<S>,
     ext: E::RequestExt,
-    Json(data): Json<E::Create>,
-) -> Result<Json<E>, ApiError<E::Error>> {
+    UploadsJson(data): UploadsJson<E::Create>,
+) -> Result<Response, ApiError<E::Error>> {
     debug!("creating entity {}", E::name());
-    Ok(Json(E::create(data, ext).await?))
+    if let Err(errors) = data.validate() {
+        return Ok(errors.into_response());
+    }
+    let e = E::create(data, ext).await;
+    invalidate::<E, S>(&ctx, None);
+    Ok(Json(e?.api_serialize()).into_response())
+}
+
+/// the `ETag` header with the [version token](EntityBase::version_token) of `e`
+fn etag<E: EntityBase<S>, S: ContextTrait>(e: &E) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(Ok(etag)) = e
+        .version_token()
+        .map(|version| HeaderValue::try_from(format!("\"{version}\"")))
+    {
+        headers.insert(ETAG, etag);
+    }
+    headers
+}
+
+/// the version tokens of the `If-Match` header, `None` if it is missing or `*`
+fn if_match(headers: &HeaderMap) -> Option<Vec<String>> {
+    let value = headers.get(IF_MATCH)?.to_str().ok()?;
+    let versions = value
+        .split(',')
+        .map(|v| v.trim().trim_start_matches("W/").trim_matches('"'))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    match versions.iter().any(|v| v == "*") {
+        true => None,
+        false => Some(versions),
+    }
+}
+
+/// HTTP 412 if the entity was changed since the version of the `If-Match` header
+fn precondition_failed() -> Response {
+    let body = json!({
+        "error": "precondition_failed",
+        "message": "This record was modified by someone else",
+    });
+    (StatusCode::PRECONDITION_FAILED, Json(body)).into_response()
 }
 
 /// update existing entity
-pub async fn post_entity<E: entity::Update<S>, S: ContextTrait>(
-    ext: E::RequestExt,
-    Path(id): Path<E::Id>,
-    Json(data): Json<E::Update>,
-) -> Result<Json<E>, ApiError<E::Error>> {
+pub async fn post_entity<E: entity::Get<S> + entity::Update<S>, S: ContextTrait>(
+    State(ctx): State<S>,
+    headers: HeaderMap,
+    get_ext: <E as entity::Get<S>>::RequestExt,
+    ext: <E as entity::Update<S>>::RequestExt,
+    IdPath(id): IdPath<E::Id>,
+    UploadsJson(data): UploadsJson<E::Update>,
+) -> Result<Response, ApiError<<E as entity::Update<S>>::Error>> {
     debug!("updating entity {}", E::name());
-    Ok(Json(E::update(&id, data, ext).await?))
+    if let Err(errors) = data.validate() {
+        return Ok(errors.into_response());
+    }
+    let e = match if_match(&headers) {
+        None => E::update(&id, data, ext).await.map(Some),
+        Some(versions) => {
+            // the only check of entities that don't implement `update_if_version`
+            match E::get(&id, get_ext).await {
+                Ok(Some(stored)) if !entity::version_matches(&stored, &versions) => {
+                    return Ok(precondition_failed())
+                }
+                Ok(_) => {}
+                Err(e) => return Ok(ApiError(e).into_response()),
+            }
+            E::update_if_version(&id, data, &versions, ext).await
+        }
+    };
+    invalidate::<E, S>(&ctx, Some(&id.to_string()));
+    match e? {
+        Some(e) => Ok((etag::<E, S>(&e), Json(e.api_serialize())).into_response()),
+        None => Ok(precondition_failed()),
+    }
 }
 
+/// update the fields of the entity in the request body, see [`entity::Patch`]
+pub async fn patch_entity<E: entity::Patch<S>, S: ContextTrait>(
+    State(ctx): State<S>,
+    headers: HeaderMap,
+    get_ext: <E as entity::Get<S>>::RequestExt,
+    update_ext: <E as entity::Update<S>>::RequestExt,
+    IdPath(id): IdPath<E::Id>,
+    Json(patch): Json<Value>,
+) -> Result<Response, PatchError<E, S>> {
+    debug!("patching entity {}", E::name());
+    let versions = if_match(&headers);
+    let e = verify_files_during(
+        ctx.uploads_dir(),
+        E::patch(&id, patch, versions, get_ext, update_ext),
+    )
+    .await;
+    invalidate::<E, S>(&ctx, Some(&id.to_string()));
+    match e? {
+        Some(e) => Ok((etag::<E, S>(&e), Json(e.api_serialize())).into_response()),
+        None => Ok(StatusCode::NOT_FOUND.into_response()),
+    }
+}
+
+impl<E: entity::Get<S> + entity::Update<S>, S: ContextTrait> IntoResponse for PatchError<E, S> {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Get(e) => ApiError(e).into_response(),
+            Self::Update(e) => ApiError(e).into_response(),
+            Self::Validation(errors) => errors.into_response(),
+            Self::VersionChanged => precondition_failed(),
+            Self::Invalid(e) => {
+                let body = json!({
+                    "error": "invalid_patch",
+                    "message": e.to_string(),
+                });
+                (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response()
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DeleteQuery {
+    confirm: Option<String>,
+}
+
+/// delete entity, if required only after confirming with a token, see [`delete_token`](crate::delete_token)
 pub async fn delete_entity<E: entity::Delete<S>, S: ContextTrait>(
+    State(ctx): State<S>,
+    Extension(scope): Extension<EntityScope>,
+    Query(query): Query<DeleteQuery>,
     ext: E::RequestExt,
-    Path(id): Path<E::Id>,
-) -> Result<(), ApiError<E::Error>> {
+    IdPath(id): IdPath<E::Id>,
+) -> Result<Response, ApiError<E::Error>> {
+    let id_str = id.to_string();
+    let tokens = ctx.delete_tokens();
+    if tokens.all || E::confirm_api_delete() {
+        let key = format!("{}/{id_str}", scope.path());
+        let confirmed = query
+            .confirm
+            .is_some_and(|token| tokens.redeem(&key, &token));
+        if !confirmed {
+            let token = tokens.issue(&key);
+            let body = json!({
+                "confirm": token,
+                "expires_in": tokens.ttl().as_secs(),
+            });
+            return Ok((StatusCode::CONFLICT, Json(body)).into_response());
+        }
+        info!(
+            "deleting entity {} {id_str} confirmed using token",
+            E::name()
+        );
+    }
     debug!("deleting entity {}", E::name());
-    Ok(E::delete(&id, ext).await?)
+    let res = E::delete(&id, ext).await;
+    invalidate::<E, S>(&ctx, Some(&id_str));
+    res?;
+    Ok(StatusCode::OK.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formulas_are_prefixed() {
+        for (cell, escaped) in [
+            ("=SUM(A1:A2)", "'=SUM(A1:A2)"),
+            ("+1+1", "'+1+1"),
+            ("-1+1", "'-1+1"),
+            ("-cmd", "'-cmd"),
+            ("@SUM(A1)", "'@SUM(A1)"),
+            ("\t=1", "'\t=1"),
+            ("\r=1", "\"'\r=1\""),
+            (
+                "=HYPERLINK(\"http://x\",\"a,b\")",
+                "\"'=HYPERLINK(\"\"http://x\"\",\"\"a,b\"\")\"",
+            ),
+        ] {
+            assert_eq!(csv_cell(cell), escaped, "{cell:?}");
+        }
+    }
+
+    #[test]
+    fn negative_numbers_are_not_prefixed() {
+        for cell in ["-1", "-1.5", "-0", "1", "a=b", "a-b", ""] {
+            assert_eq!(csv_cell(cell), cell);
+        }
+    }
+
+    #[test]
+    fn cells_are_quoted() {
+        assert_eq!(csv_cell("a,b"), "\"a,b\"");
+        assert_eq!(csv_cell("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_cell("a\nb"), "\"a\nb\"");
+        assert_eq!(csv_cell("a\r\nb"), "\"a\r\nb\"");
+        assert_eq!(
+            csv_row(["1".to_string(), "a,b".to_string(), "=1".to_string()]),
+            "1,\"a,b\",'=1\r\n"
+        );
+    }
+
+    #[test]
+    fn if_match_accepts_quoted_and_weak_versions() {
+        let if_match_of = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(IF_MATCH, HeaderValue::from_static(value));
+            if_match(&headers)
+        };
+        assert_eq!(if_match(&HeaderMap::new()), None);
+        assert_eq!(if_match_of("\"3\""), Some(vec!["3".to_string()]));
+        assert_eq!(
+            if_match_of("W/\"1\", 2"),
+            Some(vec!["1".to_string(), "2".to_string()])
+        );
+        assert_eq!(if_match_of("*"), None);
+        assert_eq!(
+            precondition_failed().status(),
+            StatusCode::PRECONDITION_FAILED
+        );
+    }
 }
@@ -1,51 +1,198 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
 use axum::{
-    routing::{delete, get, post},
-    Router,
+    extract::FromRequestParts,
+    http::request::Parts,
+    middleware,
+    routing::{delete, get, patch, post, MethodRouter},
+    Extension, Router,
 };
-use convert_case::{Case, Casing};
 
-use crate::{context::ContextTrait, Entity};
+use tracing::error;
+
+use crate::{
+    app::AppError,
+    auth::{authorize, Action, AuthProvider},
+    context::ContextTrait,
+    entity::{self, EntityScope, SoftDelete, Versioned},
+    form_group::FormGroup,
+    render::form_group_path,
+    Entity,
+};
 
 pub mod api;
 pub mod ui;
 
-/// returns a [Router] with all generated HTTP endponts
-pub fn entity_routes<E: Entity<S>, S: ContextTrait>() -> Router<S> {
-    let name = E::name().to_case(Case::Kebab);
-    let name = urlencoding::encode(&name);
-    let name_pl = E::name_plural().to_case(Case::Kebab);
-    let name_pl = urlencoding::encode(&name_pl);
+/// returns a [Router] with all generated HTTP endponts of `E` served under `scope`
+pub fn entity_routes<E: Entity<S>, S: ContextTrait>(scope: EntityScope) -> Router<S> {
+    entity_routes_with::<E, S>(scope, get(ui::get_entity::<E, S>))
+}
+
+/// like [`entity_routes`] with `edit` handling the edit page at `/:name/:id`
+pub fn entity_routes_with<E: Entity<S>, S: ContextTrait>(
+    scope: EntityScope,
+    edit: MethodRouter<S>,
+) -> Router<S> {
+    let name = scope.path();
+    let name_pl = scope.path_plural();
 
     Router::new()
         // API
         .route(
             &format!("/api/v1/{name_pl}"),
-            get(api::get_entities::<E, S>),
+            allow(Action::List, get(api::get_entities::<E, S>)),
+        )
+        .route(
+            &format!("/api/v1/{name_pl}.csv"),
+            allow(Action::List, get(api::get_entities_csv::<E, S>)),
+        )
+        .route(
+            &format!("/api/v1/{name}/:id"),
+            allow(Action::Get, get(api::get_entity::<E, S>)),
         )
-        .route(&format!("/api/v1/{name}/:id"), get(api::get_entity::<E, S>))
         .route(
             &format!("/api/v1/{name_pl}"),
-            post(api::post_entities::<E, S>),
+            allow(Action::Create, post(api::post_entities::<E, S>)),
         )
         .route(
             &format!("/api/v1/{name}/:id"),
-            post(api::post_entity::<E, S>),
+            allow(Action::Update, post(api::post_entity::<E, S>)),
         )
         .route(
             &format!("/api/v1/{name}/:id"),
-            delete(api::delete_entity::<E, S>),
+            allow(Action::Update, patch(api::patch_entity::<E, S>)),
+        )
+        .route(
+            &format!("/api/v1/{name}/:id"),
+            allow(Action::Delete, delete(api::delete_entity::<E, S>)),
         )
         // UI
-        .route(&format!("/{name_pl}"), get(ui::get_entities::<E, S>))
-        .route(&format!("/{name}/:id"), get(ui::get_entity::<E, S>))
-        .route(&format!("/{name}/:id"), post(ui::post_entity::<E, S>))
-        .route(&format!("/{name_pl}/add"), get(ui::get_add_entity::<E, S>))
+        .route(
+            &format!("/{name_pl}"),
+            allow(Action::List, get(ui::get_entities::<E, S>)),
+        )
+        .route(&format!("/{name}/:id"), allow(Action::Get, edit))
+        .route(
+            &format!("/{name}/:id"),
+            allow(Action::Update, post(ui::post_entity::<E, S>)),
+        )
+        .route(
+            &format!("/{name}/:id/view"),
+            allow(Action::Get, get(ui::get_entity_detail::<E, S>)),
+        )
+        .route(
+            &format!("/{name}/:id/list-elements"),
+            allow(Action::Get, get(ui::get_list_elements::<E, S>)),
+        )
         .route(
             &format!("/{name_pl}/add"),
-            post(ui::post_add_entity::<E, S>),
+            allow(Action::Create, get(ui::get_add_entity::<E, S>)),
+        )
+        .route(
+            &format!("/{name_pl}/options"),
+            allow(Action::List, get(ui::get_relation_options::<E, S>)),
+        )
+        .route(
+            &format!("/{name_pl}/add"),
+            allow(Action::Create, post(ui::post_add_entity::<E, S>)),
         )
         .route(
             &format!("/{name}/:id/delete"),
-            post(ui::delete_entity::<E, S>),
+            allow(Action::Delete, post(ui::delete_entity::<E, S>)),
+        )
+        .route(
+            &format!("/{name_pl}/actions/:action"),
+            allow(Action::Update, post(ui::post_maintenance_action::<E, S>)),
+        )
+        .layer(Extension(scope))
+}
+
+/// reject requests to `route` if the user may not perform `action`, see [`EntityScope::allows_user`]
+fn allow<S: ContextTrait>(action: Action, route: MethodRouter<S>) -> MethodRouter<S> {
+    route.route_layer(middleware::from_fn_with_state(action, authorize))
+}
+
+/// returns a [Router] with the trash page of `E` served under `scope`
+pub fn trash_routes<E: Entity<S> + SoftDelete<S>, S: ContextTrait>(
+    scope: EntityScope,
+) -> Router<S> {
+    let name = scope.path();
+    let name_pl = scope.path_plural();
+    Router::new()
+        .route(
+            &format!("/{name_pl}/trash"),
+            allow(Action::List, get(ui::get_trash::<E, S>)),
+        )
+        .route(
+            &format!("/{name}/:id/restore"),
+            allow(Action::Delete, post(ui::post_restore::<E, S>)),
         )
+        .layer(Extension(scope))
+}
+
+/// returns a [Router] with the versions page of `E` served under `scope`
+pub fn versions_routes<E: Entity<S> + Versioned<S>, S: ContextTrait>(
+    scope: EntityScope,
+) -> Router<S> {
+    let name = scope.path();
+    Router::new()
+        .route(
+            &format!("/{name}/:id/versions"),
+            allow(Action::Get, get(ui::get_versions::<E, S>)),
+        )
+        .route(
+            &format!("/{name}/:id/versions/:version_id/publish"),
+            allow(Action::Update, post(ui::post_publish_version::<E, S>)),
+        )
+        .layer(Extension(scope))
+}
+
+/// counts the entities of one [`EntityScope`] for the request of the dashboard, or returns `None`
+/// if they can't be counted, see [`List::count`](entity::List::count)
+pub(crate) type CountEntities<S> =
+    Arc<dyn Fn(Parts, S) -> Pin<Box<dyn Future<Output = Option<u64>> + Send>> + Send + Sync>;
+
+/// the [`CountEntities`] of all entities by [`EntityScope::path_plural`]
+pub(crate) type EntityCounts<S> = Arc<[(String, CountEntities<S>)]>;
+
+/// [`CountEntities`] of `E` with the extension `ext` of [`App::entity_scoped`](crate::App::entity_scoped)
+pub(crate) fn count_entities<E: Entity<S>, S: ContextTrait, X: Clone + Send + Sync + 'static>(
+    scope: EntityScope,
+    ext: X,
+) -> CountEntities<S> {
+    Arc::new(move |mut parts, ctx| {
+        parts.extensions.insert(scope.clone());
+        parts.extensions.insert(ext.clone());
+        Box::pin(async move {
+            let ext = <E as entity::List<S>>::RequestExt::from_request_parts(&mut parts, &ctx)
+                .await
+                .ok()?;
+            match E::count(ext).await {
+                Ok(count) => Some(count),
+                Err(e) => {
+                    let e: AppError = e.into();
+                    error!("Failed to count entities: {}", e.description);
+                    None
+                }
+            }
+        })
+    })
+}
+
+/// returns a [Router] with the form of the [`FormGroup`] `G`
+pub fn form_group_routes<G: FormGroup<S>, S: ContextTrait>() -> Router<S> {
+    let path = format!("/{}", form_group_path(G::name()));
+    Router::new()
+        .route(&path, get(ui::get_form_group::<G, S>))
+        .route(&path, post(ui::post_form_group::<G, S>))
+}
+
+/// returns a [Router] with the login page and logout endpoint of `provider`,
+/// which must not require authentication
+pub fn auth_routes<P: AuthProvider, S: ContextTrait>(provider: Arc<P>) -> Router<S> {
+    Router::new()
+        .route("/login", get(ui::get_login::<S>))
+        .route("/login", post(ui::post_login::<P, S>))
+        .route("/logout", post(ui::post_logout::<P, S>))
+        .layer(Extension(provider))
 }
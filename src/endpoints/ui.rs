@@ -1,17 +1,25 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+};
 
 use axum::{
     extract::{
         multipart::{Field, MultipartError},
-        Multipart, Path, State,
+        Multipart, Path, Query, RawQuery, State,
     },
-    response::{IntoResponse, Redirect},
-    Extension, Json,
+    http::{header::SET_COOKIE, request::Parts, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    Extension, Form, Json,
 };
 use convert_case::{Case, Casing};
-use i18n_embed::fluent::FluentLanguageLoader;
+use i18n_embed::{fluent::FluentLanguageLoader, LanguageLoader};
 use i18n_embed_fl::fl;
-use serde::Deserialize;
+use maud::Markup;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use thiserror::Error;
 use tokio::io::AsyncWriteExt;
 use tracing::{debug, error};
@@ -19,56 +27,443 @@ use uuid::Uuid;
 
 use crate::{
     app::AppError,
+    auth::{self, AuthProvider, User},
+    cache::invalidate,
     context::ContextTrait,
     easymde::{EditorConfig, UploadError, UploadSuccess},
-    entity,
-    property::File,
-    render, Entity,
+    endpoints::EntityCounts,
+    entity::{self, EntityScope, IdPath, ListParams},
+    filter::Filter,
+    form_group::{self, FormGroup},
+    input::{check_inputs, ConstraintViolation, InputConstraints, Inputs},
+    locale::NumberFormat,
+    pagination::Pagination,
+    property::{File, MULTIPLE_FILES},
+    render::{self, FormSection, ListElements, Submission, VERSION_FIELD},
+    uploads::{create_thumbnail, verify_files_in},
+    validate::ValidationErrors,
+    Entity,
 };
 
+#[derive(Debug, Deserialize)]
+pub struct ListQuery {
+    /// search query
+    q: Option<String>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
 pub async fn get_entities<E: Entity<S>, S: ContextTrait>(
     ctx: State<S>,
     Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    Extension(scope): Extension<EntityScope>,
+    Query(query): Query<ListQuery>,
+    RawQuery(raw_query): RawQuery,
     ext: <E as entity::List<S>>::RequestExt,
 ) -> Result<impl IntoResponse, AppError> {
-    let r = E::list(ext).await.map_err(Into::into)?;
-    Ok(render::entity_list_page(ctx, &i18n, r))
+    let search = query.q.filter(|q| !q.trim().is_empty());
+    let limit = query.limit.unwrap_or(ctx.page_size());
+    let offset = query.offset.unwrap_or_default();
+    let params = ListParams {
+        limit: Some(limit),
+        offset: Some(offset),
+        ..Default::default()
+    };
+    let pagination = |total| {
+        Pagination::new(
+            &scope.path_plural(),
+            offset,
+            limit,
+            total,
+            raw_query.as_deref().unwrap_or_default(),
+        )
+    };
+    Ok(match &search {
+        Some(q) => {
+            let params = ListParams {
+                filter: Filter::search::<E, S>(q.trim()),
+                ..params
+            };
+            let (rows, total) = E::list_paginated(params, ext).await.map_err(Into::into)?;
+            render::entity_list_page(
+                ctx,
+                &i18n,
+                &scope,
+                search.as_deref(),
+                &pagination(total),
+                rows,
+            )
+        }
+        None => {
+            let (rows, total) = E::list_rows_paginated(params, ext)
+                .await
+                .map_err(Into::into)?;
+            render::entity_list_page(ctx, &i18n, &scope, None, &pagination(total), rows)
+        }
+    })
+}
+
+/// the dashboard at `/` with the entities the [`User`](auth::User) of the request may list
+pub async fn get_dashboard<S: ContextTrait>(
+    State(ctx): State<S>,
+    Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    Extension(counts): Extension<EntityCounts<S>>,
+    parts: Parts,
+) -> impl IntoResponse {
+    let mut entities = Vec::new();
+    for scope in ctx.entities() {
+        if !scope.allows(auth::Action::List) {
+            continue;
+        }
+        let path = scope.path_plural();
+        let count = match counts.iter().find(|(p, _)| *p == path) {
+            Some((_, count)) => count(parts.clone(), ctx.clone()).await,
+            None => None,
+        };
+        entities.push((scope, count));
+    }
+    render::dashboard_page(&ctx, &i18n, &entities)
 }
 
 pub async fn get_entity<E: Entity<S>, S: ContextTrait>(
     ctx: State<S>,
     Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    Extension(scope): Extension<EntityScope>,
+    parts: Parts,
+    ext: <E as entity::Get<S>>::RequestExt,
+    IdPath(id): IdPath<E::Id>,
+) -> Result<impl IntoResponse, AppError> {
+    let e = E::get(&id, ext)
+        .await
+        .map_err(Into::into)?
+        .ok_or_else(|| not_found(&scope, &id))?;
+    edit_page::<E, S>(ctx, &i18n, &scope, &parts, e, true).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VersionQuery<V> {
+    version: Option<V>,
+}
+
+/// edit page of the entity `id` like [`get_entity`], showing the version of the query parameter
+/// `?version=` instead of the published version if it is set, see [`entity::Versioned`]
+pub async fn get_entity_versioned<E: Entity<S> + entity::Versioned<S>, S: ContextTrait>(
+    ctx: State<S>,
+    Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    Extension(scope): Extension<EntityScope>,
+    parts: Parts,
     ext: <E as entity::Get<S>>::RequestExt,
-    Path(id): Path<E::Id>,
+    version_ext: <E as entity::Versioned<S>>::RequestExt,
+    IdPath(id): IdPath<E::Id>,
 ) -> Result<impl IntoResponse, AppError> {
-    let e = E::get(&id, ext).await.map_err(Into::into)?.ok_or_else(|| {
+    let Query(query) = Query::<VersionQuery<E::VersionId>>::try_from_uri(&parts.uri)
+        .map_err(|e| AppError::new("Invalid version".to_string(), e.body_text()))?;
+    let e = match &query.version {
+        Some(version) => E::get_version(&id, version, version_ext)
+            .await
+            .map_err(Into::into)?,
+        None => E::get(&id, ext).await.map_err(Into::into)?,
+    };
+    let e = e.ok_or_else(|| not_found(&scope, &id))?;
+    // the elements of collapsed lists are loaded from the published version
+    let stored = query.version.is_none();
+    edit_page::<E, S>(ctx, &i18n, &scope, &parts, e, stored).await
+}
+
+/// the edit page of `e`, which loads the elements of collapsed lists from the stored entity if
+/// `stored` is `true`, see [`get_list_elements`]
+async fn edit_page<E: Entity<S>, S: ContextTrait>(
+    ctx: State<S>,
+    i18n: &FluentLanguageLoader,
+    scope: &EntityScope,
+    parts: &Parts,
+    e: E,
+    stored: bool,
+) -> Result<Markup, AppError> {
+    let sections = load_inline_relations(&e, parts, &ctx, i18n).await?;
+    let update = e.to_update();
+    let submission = Submission {
+        version: e.version_token(),
+        list_elements: stored.then(|| list_elements_path(scope, &E::url_id(e.id()))),
+        ..Default::default()
+    };
+    Ok(render::entity_page::<E, S>(
+        ctx,
+        i18n,
+        scope,
+        e.id(),
+        Some(update.borrow()),
+        &submission,
+        sections,
+    ))
+}
+
+/// path of [`get_list_elements`] relative to the base path, `url_id` is [`EntityBase::url_id`](crate::EntityBase::url_id)
+fn list_elements_path(scope: &EntityScope, url_id: &str) -> String {
+    format!(
+        "{}/{}/list-elements",
+        scope.path(),
+        urlencoding::encode(url_id)
+    )
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListElementsQuery {
+    /// id of the form on the edit page
+    form: Uuid,
+    /// name of the list input
+    field: String,
+    start: usize,
+    end: usize,
+    /// nonce of the `Content-Security-Policy` of the edit page
+    nonce: Option<Uuid>,
+}
+
+/// The inputs of the elements `start..end` of the collapsed list `field` in the edit form of the
+/// entity `id`, loaded by `js/list.js` when they are expanded or the form is submitted,
+/// see [`App::collapse_lists_longer_than`](crate::App::collapse_lists_longer_than).
+pub async fn get_list_elements<E: Entity<S>, S: ContextTrait>(
+    ctx: State<S>,
+    Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    Extension(scope): Extension<EntityScope>,
+    ext: <E as entity::Get<S>>::RequestExt,
+    IdPath(id): IdPath<E::Id>,
+    Query(query): Query<ListElementsQuery>,
+) -> Result<Markup, AppError> {
+    let e = E::get(&id, ext)
+        .await
+        .map_err(Into::into)?
+        .ok_or_else(|| not_found(&scope, &id))?;
+    let update = e.to_update();
+    render::list_elements::<E::Update, S>(
+        ctx.0,
+        &i18n,
+        update.borrow(),
+        &list_elements_path(&scope, &E::url_id(&id)),
+        &query.form.to_string(),
+        query.nonce.map(|nonce| nonce.simple().to_string()),
+        ListElements::new(&query.field, query.start..query.end),
+    )
+    .ok_or_else(|| {
         AppError::new(
             "Not Found".to_string(),
-            format!(
-                "The {} with id {} does not exist",
-                E::name().to_case(Case::Title),
-                id
-            ),
+            format!("The {} has no list {:?}", scope.name(), query.field),
         )
-    })?;
-    Ok(render::entity_page(ctx, &i18n, Some(&e)))
+        .with_status(StatusCode::NOT_FOUND)
+    })
+}
+
+/// versions of the entity `id` with a button to publish each of them
+pub async fn get_versions<E: Entity<S> + entity::Versioned<S>, S: ContextTrait>(
+    ctx: State<S>,
+    Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    Extension(scope): Extension<EntityScope>,
+    ext: <E as entity::Versioned<S>>::RequestExt,
+    IdPath(id): IdPath<E::Id>,
+) -> Result<impl IntoResponse, AppError> {
+    let versions = E::versions(&id, ext).await.map_err(Into::into)?;
+    Ok(render::versions_page::<E, S>(
+        ctx, &i18n, &scope, &id, versions,
+    ))
+}
+
+pub async fn post_publish_version<E: Entity<S> + entity::Versioned<S>, S: ContextTrait>(
+    ctx: State<S>,
+    Extension(scope): Extension<EntityScope>,
+    ext: <E as entity::Versioned<S>>::RequestExt,
+    IdPath((id, version)): IdPath<(E::Id, E::VersionId)>,
+) -> Result<impl IntoResponse, AppError> {
+    debug!("publishing version {version} of entity {}", E::name());
+    let res = E::publish(&id, &version, ext).await;
+    invalidate::<E, S>(&*ctx, Some(&id.to_string()));
+    res.map_err(Into::into)?;
+    Ok(Redirect::to(&format!(
+        "{}/{}/{}/versions",
+        ctx.base_path(),
+        scope.path(),
+        urlencoding::encode(&E::url_id(&id))
+    )))
+}
+
+/// read-only page of the entity `id`, see [`render::entity_detail_page`]
+pub async fn get_entity_detail<E: Entity<S>, S: ContextTrait>(
+    ctx: State<S>,
+    Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    Extension(scope): Extension<EntityScope>,
+    ext: <E as entity::Get<S>>::RequestExt,
+    IdPath(id): IdPath<E::Id>,
+) -> Result<impl IntoResponse, AppError> {
+    let e = E::get(&id, ext)
+        .await
+        .map_err(Into::into)?
+        .ok_or_else(|| not_found(&scope, &id))?;
+    Ok(render::entity_detail_page::<E, S>(ctx, &i18n, &scope, &e))
+}
+
+fn not_found(scope: &EntityScope, id: &impl std::fmt::Display) -> AppError {
+    AppError::new(
+        "Not Found".to_string(),
+        format!(
+            "The {} with id {} does not exist",
+            scope.name().to_case(Case::Title),
+            id
+        ),
+    )
+    .with_status(StatusCode::NOT_FOUND)
+}
+
+/// load the children of all inline relations of the entity `id` to render its form again,
+/// without any if it does not exist anymore
+async fn load_inline_relations_of<E: Entity<S>, S: ContextTrait>(
+    id: E::Id,
+    ext: <E as entity::Get<S>>::RequestExt,
+    parts: &Parts,
+    ctx: &S,
+    i18n: &FluentLanguageLoader,
+) -> Result<Vec<FormSection<'static, S>>, AppError> {
+    match E::get(&id, ext).await.map_err(Into::into)? {
+        Some(e) => load_inline_relations(&e, parts, ctx, i18n).await,
+        None => Ok(Vec::new()),
+    }
+}
+
+/// [check](crate::relation::DynInlineRelation::check) the children of `e` submitted in `query_string`
+async fn check_inline_relations<E: Entity<S>, S: ContextTrait>(
+    e: &E,
+    parts: &Parts,
+    ctx: &S,
+    i18n: &FluentLanguageLoader,
+    query_string: &str,
+) -> Result<(), AppError> {
+    let number_format = NumberFormat::of(&i18n.current_language());
+    for relation in ctx.inline_relations::<E>() {
+        number_format
+            .scope(relation.check(e, parts.clone(), ctx, i18n, query_string))
+            .await?;
+    }
+    Ok(())
+}
+
+/// the [`Submission`] of a form that failed with `error`
+fn submission_failed(error: AppError, values: Vec<(String, String)>) -> Submission {
+    error!("{}: {}", error.title, error.description);
+    Submission {
+        error: Some(error),
+        values,
+        ..Default::default()
+    }
+}
+
+/// The form of `update` noting the fields of `stored` that differ, since it was modified after the
+/// form was loaded. Submitting it again overwrites `stored`.
+fn conflict_submission<E: Entity<S>, S: ContextTrait>(
+    i18n: &FluentLanguageLoader,
+    update: &E::Update,
+    stored: &E,
+) -> Submission {
+    let mut errors = ValidationErrors::new();
+    let current = stored.to_update();
+    if let (Ok(Value::Object(submitted)), Ok(Value::Object(current))) = (
+        serde_json::to_value(update),
+        serde_json::to_value(current.borrow()),
+    ) {
+        for (field, value) in current {
+            if submitted.get(&field) != Some(&value) {
+                let value = match value {
+                    Value::String(s) => s,
+                    value => value.to_string(),
+                };
+                errors.add(
+                    field,
+                    fl!(i18n, "error-update-conflict", "field", value = value),
+                );
+            }
+        }
+    }
+    Submission {
+        error: Some(AppError::new(
+            fl!(i18n, "error-update-conflict", "title"),
+            fl!(i18n, "error-update-conflict", "description"),
+        )),
+        errors,
+        version: stored.version_token(),
+        ..Default::default()
+    }
+}
+
+/// deserialize the submitted data again after it was moved into a failed create or update,
+/// to render the form with it
+fn roundtrip<T: for<'de> Deserialize<'de>>(
+    data: serde_json::Result<serde_json::Value>,
+) -> Option<T> {
+    serde_json::from_value(data.ok()?).ok()
+}
+
+/// load the children of all [`InlineRelation`](crate::relation::InlineRelation)s of `e`
+async fn load_inline_relations<E: Entity<S>, S: ContextTrait>(
+    e: &E,
+    parts: &Parts,
+    ctx: &S,
+    i18n: &FluentLanguageLoader,
+) -> Result<Vec<FormSection<'static, S>>, AppError> {
+    let mut sections = Vec::new();
+    for relation in ctx.inline_relations::<E>() {
+        sections.push(relation.load(e, parts.clone(), ctx, i18n).await?);
+    }
+    Ok(sections)
+}
+
+#[derive(Serialize)]
+pub struct RelationOption {
+    id: String,
+    label: String,
+}
+
+/// the options of a [`Relation`](crate::property::Relation) to `E`: the id and
+/// [`display_label`](crate::EntityBase::display_label) of all entities
+pub async fn get_relation_options<E: Entity<S>, S: ContextTrait>(
+    ext: <E as entity::List<S>>::RequestExt,
+) -> Result<Json<Vec<RelationOption>>, AppError> {
+    let entities = E::list(ext).await.map_err(Into::into)?;
+    let options = entities
+        .into_iter()
+        .map(|e| RelationOption {
+            id: e.id().to_string(),
+            label: e.display_label(),
+        })
+        .collect();
+    Ok(Json(options))
 }
 
 pub async fn get_add_entity<E: Entity<S>, S: ContextTrait>(
     ctx: State<S>,
     Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    Extension(scope): Extension<EntityScope>,
 ) -> impl IntoResponse {
-    render::add_entity_page::<E, S>(ctx, &i18n, None)
+    render::add_entity_page::<E, S>(ctx, &i18n, &scope, None, &Submission::default())
 }
 
-pub async fn post_add_entity<E: entity::Create<S>, S: ContextTrait>(
+pub async fn post_add_entity<E: Entity<S>, S: ContextTrait>(
     ctx: State<S>,
     Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
-    ext: E::RequestExt,
+    Extension(scope): Extension<EntityScope>,
+    ext: <E as entity::Create<S>>::RequestExt,
     form: Multipart,
-) -> Result<impl IntoResponse, AppError> {
+) -> Result<Response, AppError> {
     debug!("creating entity {}", E::name());
-    let e = parse_form::<E::Create>(form, ctx.uploads_dir())
+    let namespace = upload_namespace(&*ctx, &scope);
+    let number_format = NumberFormat::of(&i18n.current_language());
+    let mut values = Vec::new();
+    let mut uploads = NewUploads::default();
+    let parsed = number_format
+        .scope(parse_form::<E::Create, S>(
+            form,
+            ctx.uploads_dir(),
+            namespace.as_deref(),
+            ctx.thumbnail_size(),
+            &mut values,
+            &mut uploads,
+        ))
         .await
         .map_err(|e| {
             AppError::new(
@@ -76,34 +471,191 @@ pub async fn post_add_entity<E: entity::Create<S>, S: ContextTrait>(
                     i18n,
                     "error-create-entity",
                     "title",
-                    name = E::name().to_case(Case::Title)
+                    name = scope.name().to_case(Case::Title)
                 ),
                 fl!(
                     i18n,
                     "error-create-entity",
                     "parse-form",
-                    error = format!("{e:#}")
+                    error = e.localize(&i18n, ctx.max_upload_size())
                 ),
             )
-        })?;
-    let e = E::create(e, ext).await.map_err(Into::into)?;
+            .with_status(e.status())
+        });
+    let e = match parsed {
+        Ok((e, _)) => e,
+        Err(error) => {
+            uploads.remove().await;
+            let status = error.status;
+            let submission = submission_failed(error, values);
+            let page = render::add_entity_page::<E, S>(ctx, &i18n, &scope, None, &submission);
+            return Ok((status, page).into_response());
+        }
+    };
+    if let Err(errors) = e.validate() {
+        let value = (!uploads.discard().await).then_some(&e);
+        let submission = Submission {
+            errors,
+            values,
+            ..Default::default()
+        };
+        let page = render::add_entity_page::<E, S>(ctx, &i18n, &scope, value, &submission);
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, page).into_response());
+    }
+    let data = serde_json::to_value(&e);
+    let res = E::create(e, ext).await;
+    invalidate::<E, S>(&*ctx, None);
+    let e = match res {
+        Ok(e) => e,
+        Err(error) => {
+            let value = match uploads.discard().await {
+                true => None,
+                false => roundtrip::<E::Create>(data),
+            };
+            let submission = submission_failed(error.into(), values);
+            let page =
+                render::add_entity_page::<E, S>(ctx, &i18n, &scope, value.as_ref(), &submission);
+            return Ok((StatusCode::BAD_REQUEST, page).into_response());
+        }
+    };
     let uri = &format!(
-        "/{}/{}",
-        E::name().to_case(Case::Kebab),
-        urlencoding::encode(&e.id().to_string())
+        "{}/{}/{}",
+        ctx.base_path(),
+        scope.path(),
+        urlencoding::encode(&E::url_id(e.id()))
     );
-    Ok(Redirect::to(uri))
+    Ok(Redirect::to(uri).into_response())
+}
+
+/// reject the request with HTTP 403 unless `user` may perform all [`FormGroup::actions`] of `G`
+fn authorize_form_group<G: FormGroup<S>, S: ContextTrait>(
+    ctx: &S,
+    user: Option<User>,
+) -> Result<(), AppError> {
+    let user = user.or_else(User::current);
+    match form_group::allows::<G, S>(ctx.entities(), user.as_ref()) {
+        true => Ok(()),
+        false => Err(AppError::new(
+            "Forbidden".to_string(),
+            format!("You are not allowed to submit {}.", G::name()),
+        )
+        .with_status(StatusCode::FORBIDDEN)),
+    }
+}
+
+pub async fn get_form_group<G: FormGroup<S>, S: ContextTrait>(
+    ctx: State<S>,
+    Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    user: Option<User>,
+) -> Result<Markup, AppError> {
+    authorize_form_group::<G, S>(&ctx, user)?;
+    Ok(render::form_group_page::<G, S>(
+        ctx,
+        &i18n,
+        None,
+        &Submission::default(),
+    ))
+}
+
+pub async fn post_form_group<G: FormGroup<S>, S: ContextTrait>(
+    ctx: State<S>,
+    Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    user: Option<User>,
+    ext: G::RequestExt,
+    form: Multipart,
+) -> Result<Response, AppError> {
+    authorize_form_group::<G, S>(&ctx, user)?;
+    debug!("creating form group {}", G::name());
+    let namespace = ctx
+        .namespace_uploads()
+        .then(|| upload_dir_component(&render::form_group_path(G::name()).replace('/', "-")));
+    let number_format = NumberFormat::of(&i18n.current_language());
+    let mut values = Vec::new();
+    let mut uploads = NewUploads::default();
+    let parsed = number_format
+        .scope(parse_form::<G, S>(
+            form,
+            ctx.uploads_dir(),
+            namespace.as_deref(),
+            ctx.thumbnail_size(),
+            &mut values,
+            &mut uploads,
+        ))
+        .await
+        .map_err(|e| {
+            AppError::new(
+                fl!(i18n, "error-create-entity", "title", name = G::name()),
+                fl!(
+                    i18n,
+                    "error-create-entity",
+                    "parse-form",
+                    error = e.localize(&i18n, ctx.max_upload_size())
+                ),
+            )
+            .with_status(e.status())
+        });
+    let g = match parsed {
+        Ok((g, _)) => g,
+        Err(error) => {
+            uploads.remove().await;
+            let status = error.status;
+            let submission = submission_failed(error, values);
+            let page = render::form_group_page::<G, S>(ctx, &i18n, None, &submission);
+            return Ok((status, page).into_response());
+        }
+    };
+    if let Err(errors) = g.validate() {
+        let value = (!uploads.discard().await).then_some(&g);
+        let submission = Submission {
+            errors,
+            values,
+            ..Default::default()
+        };
+        let page = render::form_group_page::<G, S>(ctx, &i18n, value, &submission);
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, page).into_response());
+    }
+    let res = g.create(ext).await;
+    if let Some(cache) = ctx.api_cache() {
+        cache.clear();
+    }
+    let path = match res {
+        Ok(path) => path,
+        Err(error) => {
+            uploads.remove().await;
+            let submission = submission_failed(error.into(), values);
+            let page = render::form_group_page::<G, S>(ctx, &i18n, None, &submission);
+            return Ok((StatusCode::BAD_REQUEST, page).into_response());
+        }
+    };
+    Ok(Redirect::to(&format!("{}/{path}", ctx.base_path())).into_response())
 }
 
 pub async fn post_entity<E: Entity<S>, S: ContextTrait>(
     ctx: State<S>,
     Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
-    ext: <E as entity::Update<S>>::RequestExt,
-    Path(id): Path<E::Id>,
+    Extension(scope): Extension<EntityScope>,
+    parts: Parts,
+    (get_ext, ext): (
+        <E as entity::Get<S>>::RequestExt,
+        <E as entity::Update<S>>::RequestExt,
+    ),
+    IdPath(id): IdPath<E::Id>,
     form: Multipart,
-) -> Result<impl IntoResponse, AppError> {
+) -> Result<Response, AppError> {
     debug!("updating entity {}", E::name());
-    let e = parse_form::<E::Update>(form, ctx.uploads_dir())
+    let namespace = upload_namespace(&*ctx, &scope);
+    let number_format = NumberFormat::of(&i18n.current_language());
+    let mut values = Vec::new();
+    let mut uploads = NewUploads::default();
+    let parsed = number_format
+        .scope(parse_form::<E::Update, S>(
+            form,
+            ctx.uploads_dir(),
+            namespace.as_deref(),
+            ctx.thumbnail_size(),
+            &mut values,
+            &mut uploads,
+        ))
         .await
         .map_err(|e| {
             AppError::new(
@@ -111,29 +663,293 @@ pub async fn post_entity<E: Entity<S>, S: ContextTrait>(
                     i18n,
                     "error-update-entity",
                     "title",
-                    name = E::name().to_case(Case::Title)
+                    name = scope.name().to_case(Case::Title)
                 ),
                 fl!(
                     i18n,
                     "error-update-entity",
                     "parse-form",
-                    error = format!("{e:#}")
+                    error = e.localize(&i18n, ctx.max_upload_size())
                 ),
             )
-        })?;
-    let e = E::update(&id, e, ext).await.map_err(Into::into)?;
-    Ok(render::entity_page(ctx, &i18n, Some(&e)))
+            .with_status(e.status())
+        });
+    let version = values
+        .iter()
+        .find(|(name, _)| name == VERSION_FIELD)
+        .map(|(_, version)| version.clone());
+    let (e, query_string) = match parsed {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            uploads.remove().await;
+            let status = error.status;
+            let submission = Submission {
+                version,
+                ..submission_failed(error, values)
+            };
+            let sections =
+                load_inline_relations_of::<E, S>(id.clone(), get_ext, &parts, &ctx, &i18n).await?;
+            let page =
+                render::entity_page::<E, S>(ctx, &i18n, &scope, &id, None, &submission, sections);
+            return Ok((status, page).into_response());
+        }
+    };
+    if let Err(errors) = e.validate() {
+        let value = (!uploads.discard().await).then_some(&e);
+        let submission = Submission {
+            errors,
+            values,
+            version,
+            ..Default::default()
+        };
+        let sections =
+            load_inline_relations_of::<E, S>(id.clone(), get_ext, &parts, &ctx, &i18n).await?;
+        let page =
+            render::entity_page::<E, S>(ctx, &i18n, &scope, &id, value, &submission, sections);
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, page).into_response());
+    }
+    if let Some(version) = &version {
+        let stored = E::get(&id, get_ext.clone()).await.map_err(Into::into)?;
+        let changed = stored.filter(|stored| {
+            stored
+                .version_token()
+                .is_some_and(|current| current != *version)
+        });
+        if let Some(stored) = changed {
+            let submission = conflict_submission::<E, S>(&i18n, &e, &stored);
+            let sections = load_inline_relations(&stored, &parts, &ctx, &i18n).await?;
+            let value = (!uploads.discard().await).then_some(&e);
+            let page =
+                render::entity_page::<E, S>(ctx, &i18n, &scope, &id, value, &submission, sections);
+            return Ok((StatusCode::CONFLICT, page).into_response());
+        }
+    }
+    // the children are checked before the entity or any of them is changed
+    if !ctx.inline_relations::<E>().is_empty() {
+        let checked = match E::get(&id, get_ext.clone()).await.map_err(Into::into) {
+            Ok(Some(stored)) => {
+                check_inline_relations(&stored, &parts, &ctx, &i18n, &query_string).await
+            }
+            Ok(None) => Err(not_found(&scope, &id)),
+            Err(error) => Err(error),
+        };
+        if let Err(error) = checked {
+            uploads.remove().await;
+            return Err(error);
+        }
+    }
+    let data = serde_json::to_value(&e);
+    let res = E::update(&id, e, ext).await;
+    invalidate::<E, S>(&*ctx, Some(&id.to_string()));
+    let e = match res {
+        Ok(e) => e,
+        Err(error) => {
+            let value = match uploads.discard().await {
+                true => None,
+                false => roundtrip::<E::Update>(data),
+            };
+            let submission = Submission {
+                version,
+                ..submission_failed(error.into(), values)
+            };
+            let sections =
+                load_inline_relations_of::<E, S>(id.clone(), get_ext, &parts, &ctx, &i18n).await?;
+            let page = render::entity_page::<E, S>(
+                ctx,
+                &i18n,
+                &scope,
+                &id,
+                value.as_ref(),
+                &submission,
+                sections,
+            );
+            return Ok((StatusCode::BAD_REQUEST, page).into_response());
+        }
+    };
+    for relation in ctx.inline_relations::<E>() {
+        number_format
+            .scope(relation.save(&e, parts.clone(), &ctx, &i18n, &query_string))
+            .await?;
+    }
+    let sections = load_inline_relations(&e, &parts, &ctx, &i18n).await?;
+    let update = e.to_update();
+    let submission = Submission {
+        version: e.version_token(),
+        ..Default::default()
+    };
+    let page = render::entity_page::<E, S>(
+        ctx,
+        &i18n,
+        &scope,
+        &id,
+        Some(update.borrow()),
+        &submission,
+        sections,
+    );
+    Ok(page.into_response())
 }
 
 pub async fn delete_entity<E: entity::Delete<S>, S: ContextTrait>(
+    ctx: State<S>,
+    Extension(scope): Extension<EntityScope>,
     ext: E::RequestExt,
-    Path(id): Path<E::Id>,
+    IdPath(id): IdPath<E::Id>,
 ) -> Result<impl IntoResponse, AppError> {
     debug!("deleting entity {}", E::name());
-    E::delete(&id, ext).await.map_err(Into::into)?;
+    let res = E::delete(&id, ext).await;
+    invalidate::<E, S>(&*ctx, Some(&id.to_string()));
+    res.map_err(Into::into)?;
+    Ok(Redirect::to(&format!(
+        "{}/{}",
+        ctx.base_path(),
+        scope.path_plural()
+    )))
+}
+
+/// the soft-deleted entities of `E`, see [`SoftDelete`](entity::SoftDelete)
+pub async fn get_trash<E: Entity<S> + entity::SoftDelete<S>, S: ContextTrait>(
+    ctx: State<S>,
+    Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    Extension(scope): Extension<EntityScope>,
+    ext: <E as entity::SoftDelete<S>>::RequestExt,
+) -> Result<impl IntoResponse, AppError> {
+    let entities = E::list_deleted(ext).await.map_err(Into::into)?;
+    Ok(render::trash_page::<E, S>(ctx, &i18n, &scope, entities))
+}
+
+pub async fn post_restore<E: Entity<S> + entity::SoftDelete<S>, S: ContextTrait>(
+    ctx: State<S>,
+    Extension(scope): Extension<EntityScope>,
+    ext: <E as entity::SoftDelete<S>>::RequestExt,
+    IdPath(id): IdPath<E::Id>,
+) -> Result<impl IntoResponse, AppError> {
+    debug!("restoring entity {}", E::name());
+    let res = E::restore(&id, ext).await;
+    invalidate::<E, S>(&*ctx, Some(&id.to_string()));
+    res.map_err(Into::into)?;
+    Ok(Redirect::to(&format!(
+        "{}/{}/trash",
+        ctx.base_path(),
+        scope.path_plural()
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginQuery {
+    /// page to redirect to after logging in
+    next: Option<String>,
+    /// the credentials of the previous attempt were invalid
+    #[serde(default)]
+    failed: bool,
+}
+
+#[derive(Deserialize)]
+pub struct LoginForm {
+    username: String,
+    password: String,
+    next: Option<String>,
+}
+
+pub async fn get_login<S: ContextTrait>(
+    ctx: State<S>,
+    Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    Query(query): Query<LoginQuery>,
+) -> impl IntoResponse {
+    let next = query.next.filter(|next| is_local_path(next));
+    render::login_page(&*ctx, &i18n, query.failed, next.as_deref())
+}
+
+pub async fn post_login<P: AuthProvider, S: ContextTrait>(
+    ctx: State<S>,
+    Extension(provider): Extension<Arc<P>>,
+    Form(form): Form<LoginForm>,
+) -> Response {
+    let base_path = ctx.base_path();
+    let next = form.next.filter(|next| is_local_path(next));
+    let Some(session) = provider.login(&form.username, &form.password).await else {
+        debug!("login of {:?} failed", form.username);
+        let next = next
+            .map(|next| format!("&next={}", urlencoding::encode(&next)))
+            .unwrap_or_default();
+        return Redirect::to(&format!("{base_path}/login?failed=true{next}")).into_response();
+    };
+    let cookie = session_cookie(base_path, &session, None);
+    let next = next.unwrap_or_else(|| format!("{base_path}/"));
+    ([(SET_COOKIE, cookie)], Redirect::to(&next)).into_response()
+}
+
+pub async fn post_logout<P: AuthProvider, S: ContextTrait>(
+    ctx: State<S>,
+    Extension(provider): Extension<Arc<P>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Some(session) = auth::session(&headers) {
+        provider.logout(session).await;
+    }
+    let base_path = ctx.base_path();
+    (
+        [(SET_COOKIE, session_cookie(base_path, "", Some(0)))],
+        Redirect::to(&format!("{base_path}/login")),
+    )
+}
+
+/// value of the `Set-Cookie` header storing the [`SESSION_COOKIE`](auth::SESSION_COOKIE)
+fn session_cookie(base_path: &str, session: &str, max_age: Option<u64>) -> String {
+    let path = match base_path.is_empty() {
+        true => "/",
+        false => base_path,
+    };
+    let max_age = max_age
+        .map(|max_age| format!("; Max-Age={max_age}"))
+        .unwrap_or_default();
+    format!(
+        "{}={session}; Path={path}; HttpOnly; SameSite=Lax{max_age}",
+        auth::SESSION_COOKIE
+    )
+}
+
+/// whether `path` is an absolute path on this host, so redirecting to it is safe
+fn is_local_path(path: &str) -> bool {
+    path.starts_with('/') && !path.starts_with("//") && !path.contains('\\')
+}
+
+/// start a [`MaintenanceAction`](crate::maintenance::MaintenanceAction) of `E` unless it is already running
+pub async fn post_maintenance_action<E: Entity<S>, S: ContextTrait>(
+    ctx: State<S>,
+    Extension(i18n): Extension<Arc<FluentLanguageLoader>>,
+    Extension(scope): Extension<EntityScope>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let action = E::maintenance_actions()
+        .into_iter()
+        .find(|a| a.name() == name)
+        .ok_or_else(|| {
+            AppError::new(
+                fl!(
+                    i18n,
+                    "error-maintenance-action",
+                    "title",
+                    action = name.clone()
+                ),
+                fl!(
+                    i18n,
+                    "error-maintenance-action",
+                    "not-found",
+                    name = scope.name_plural().to_case(Case::Title)
+                ),
+            )
+            .with_status(StatusCode::NOT_FOUND)
+        })?;
+    if !ctx.maintenance_runs().start(ctx.0.clone(), &scope, &action) {
+        debug!(
+            "maintenance action {name} of {} is already running",
+            E::name()
+        );
+    }
     Ok(Redirect::to(&format!(
-        "/{}",
-        E::name().to_case(Case::Kebab)
+        "{}/{}",
+        ctx.base_path(),
+        scope.path_plural()
     )))
 }
 
@@ -162,11 +978,127 @@ enum ParseFormError {
         serde: serde_qs::Error,
         query_string: String,
     },
+    #[error("Invalid value for field '{field}': {serde:#}")]
+    InvalidField {
+        field: String,
+        serde: serde_qs::Error,
+    },
+    #[error("Invalid value for field '{field}': {violation}")]
+    Constraint {
+        field: String,
+        violation: ConstraintViolation,
+    },
+}
+
+impl ParseFormError {
+    /// HTTP 413 if the form is larger than [`ContextTrait::max_upload_size`] or a file is larger
+    /// than its `max_size`, 415 if a file is not accepted by its input, 400 otherwise
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Multipart(e) if e.status() == StatusCode::PAYLOAD_TOO_LARGE => e.status(),
+            Self::Constraint {
+                violation: ConstraintViolation::FileTooLarge { .. },
+                ..
+            } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Constraint {
+                violation: ConstraintViolation::FileType { .. },
+                ..
+            } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    /// localized description pointing at the offending field if possible
+    fn localize(&self, i18n: &FluentLanguageLoader, max_upload_size: usize) -> String {
+        match self {
+            Self::Multipart(e) if e.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+                let limit = (max_upload_size as f64 / (1024.0 * 1024.0) * 10.0).round() / 10.0;
+                fl!(i18n, "error-upload-too-large", limit = limit)
+            }
+            Self::InvalidField { field, serde } => fl!(
+                i18n,
+                "error-form-field",
+                field = field.as_str(),
+                error = format!("{serde:#}")
+            ),
+            Self::Constraint { field, violation } => fl!(
+                i18n,
+                "error-form-field",
+                field = field.as_str(),
+                error = violation.to_string()
+            ),
+            _ => format!("{self:#}"),
+        }
+    }
+}
+
+/// directory of the uploads of the entities of `scope` relative to the uploads directory,
+/// if [namespaced uploads](crate::App::namespace_uploads) are enabled
+fn upload_namespace<S: ContextTrait>(ctx: &S, scope: &EntityScope) -> Option<String> {
+    if !ctx.namespace_uploads() {
+        return None;
+    }
+    let name = scope.name().to_case(Case::Kebab);
+    Some(
+        scope
+            .prefix()
+            .into_iter()
+            .flat_map(|prefix| prefix.split('/'))
+            .chain([name.as_str()])
+            .map(upload_dir_component)
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// replace all characters that are not allowed in the directory of a [`File`]
+fn upload_dir_component(name: &str) -> String {
+    match name.is_empty() {
+        true => "_".to_string(),
+        false => name
+            .chars()
+            .map(
+                |c| match c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    true => c,
+                    false => '_',
+                },
+            )
+            .collect(),
+    }
 }
 
+/// Files written while parsing a form, which are removed if the submission is rejected.
+#[derive(Debug, Default)]
+struct NewUploads(Vec<PathBuf>);
+
+impl NewUploads {
+    /// remove the directories of all files
+    async fn remove(self) {
+        for dir in self.0 {
+            debug!("removing rejected upload {}", dir.display());
+            if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+                error!("failed to remove rejected upload {}: {e:#}", dir.display());
+            }
+        }
+    }
+
+    /// Remove all files and return whether there were any, in which case the rejected submission
+    /// must not be rendered again, since it refers to the removed files.
+    async fn discard(self) -> bool {
+        let any = !self.0.is_empty();
+        self.remove().await;
+        any
+    }
+}
+
+/// `dir` is relative to `output_dir`
 async fn stream_field_to_file<'a>(
     mut field: Field<'a>,
     output_dir: &'a std::path::Path,
+    dir: Option<String>,
+    thumbnail_size: Option<u32>,
+    constraints: Option<(&String, &InputConstraints)>,
+    uploads: &mut NewUploads,
 ) -> Result<File, ParseFormError> {
     let id = Uuid::new_v4();
     let Some(filename) = field.file_name().filter(|name| !name.is_empty()) else {
@@ -175,22 +1107,55 @@ async fn stream_field_to_file<'a>(
     if filename.contains('/') {
         return Err(ParseFormError::FilenameSlash(filename.to_string()));
     }
+    let is_image = field
+        .content_type()
+        .is_some_and(|content_type| content_type.starts_with("image/"));
+    let violation = |field: &String, violation| ParseFormError::Constraint {
+        field: field.clone(),
+        violation,
+    };
+    if let Some((name, c)) = constraints {
+        c.check_file(filename, field.content_type())
+            .map_err(|v| violation(name, v))?;
+    }
+    let max_size = constraints.and_then(|(name, c)| Some((name, c.max_size?)));
 
-    let folder_path = output_dir.join(id.to_string());
+    let folder_path = match &dir {
+        Some(dir) => output_dir.join(dir),
+        None => output_dir.to_path_buf(),
+    }
+    .join(id.to_string());
     tokio::fs::create_dir_all(&folder_path).await?;
+    uploads.0.push(folder_path.clone());
 
     let file_path = folder_path.join(filename);
 
     // clone such that we don't keep a reference to filename for too long
     let filename = filename.to_string();
 
-    let mut file = tokio::fs::File::create_new(file_path).await?;
+    let mut file = tokio::fs::File::create_new(&file_path).await?;
 
+    let mut size = 0;
     while let Some(v) = field.chunk().await? {
+        size += v.len() as u64;
+        if let Some((name, max)) = max_size.filter(|(_, max)| size > *max) {
+            return Err(violation(name, ConstraintViolation::FileTooLarge { max }));
+        }
         file.write_all(&v).await?;
     }
+    file.flush().await?;
+
+    let thumbnail = match thumbnail_size.filter(|_| is_image) {
+        Some(max_size) => create_thumbnail(file_path, max_size).await,
+        None => None,
+    };
 
-    Ok(File::new_with_id(id, filename))
+    Ok(File {
+        id,
+        name: filename,
+        dir,
+        thumbnail,
+    })
 }
 
 /// Parse multipart/form-data with nested fields like in [serde_qs].
@@ -201,33 +1166,105 @@ async fn stream_field_to_file<'a>(
 ///     id: String,
 ///     /// original filename
 ///     name: String,
+///     /// `{namespace}/{field}` relative to `files_dir` if `namespace` is set
+///     dir: Option<String>,
 /// }
 /// ```
-async fn parse_form<T: for<'de> Deserialize<'de>>(
+/// Empty values of fields of `T` that are not [required](crate::input::InputInfo::required)
+/// and all empty values nested within fields are omitted, such that they are deserialized as
+/// `None` or their default value. This is also required within flattened structs and enum
+/// variants, where serde buffers the values and e.g. can't deserialize `""` as `Option<bool>`.
+/// The parsed value is checked against the [constraints](Input::check_constraints) of its
+/// inputs, including nested ones.
+///
+/// The name and value of each field that is not a file are added to `values`, such that the form
+/// can be rendered again with them if it is invalid. The files are added to `uploads`, also if
+/// parsing fails, such that they can be removed if the form is rejected.
+async fn parse_form<T: for<'de> Deserialize<'de> + Inputs<S>, S: ContextTrait>(
     mut form: Multipart,
     files_dir: &std::path::Path,
-) -> Result<T, ParseFormError> {
+    namespace: Option<&str>,
+    thumbnail_size: Option<u32>,
+    values: &mut Vec<(String, String)>,
+    uploads: &mut NewUploads,
+) -> Result<(T, String), ParseFormError> {
+    let mut optional = HashSet::new();
+    let mut secret = HashSet::new();
+    let mut constraints = HashMap::new();
+    for i in T::inputs(None) {
+        if i.value.secret() {
+            secret.insert(i.name.to_string());
+        }
+        if !i.required {
+            optional.insert(i.name.to_string());
+            if let Some(key) = i.value.optional_key() {
+                optional.insert(format!("{}[{key}]", i.name));
+            }
+        }
+        if i.constraints != InputConstraints::default() {
+            constraints.insert(i.name.to_string(), i.constraints);
+        }
+    }
     let mut qs = String::new();
+    let mut list_lengths = ListLengths::default();
     while let Some(field) = form.next_field().await? {
         let name = field.name().ok_or(ParseFormError::NameMissing)?;
+        list_lengths.record(name);
+        let is_optional = optional.contains(name);
+        let is_nested = name.contains('[');
+        // files are stored by the top level field, e.g. `content` for `content[0][data]`
+        let dir = namespace.map(|namespace| {
+            let field = name.split('[').next().unwrap_or_default();
+            format!("{namespace}/{}", upload_dir_component(field))
+        });
+        let raw_name = name.to_string();
         let name = urlencoding::encode(name).to_string();
         match field.file_name() {
             Some(filename) if !filename.is_empty() => {
-                let file = stream_field_to_file(field, files_dir).await?;
+                let name = match list_lengths.append(&name) {
+                    Some(element) => urlencoding::encode(&element).to_string(),
+                    None => name,
+                };
+                let top_level = raw_name.split('[').next().unwrap_or_default();
+                let file = stream_field_to_file(
+                    field,
+                    files_dir,
+                    dir,
+                    thumbnail_size,
+                    constraints.get_key_value(top_level),
+                    uploads,
+                )
+                .await?;
                 let filename_escaped = urlencoding::encode(&file.name);
                 let id = file.id;
                 if !qs.is_empty() {
                     qs.push('&');
                 }
                 qs.push_str(&format!("{name}[name]={filename_escaped}&{name}[id]={id}"));
-                // TODO: delete newly created files on error
+                if let Some(dir) = &file.dir {
+                    qs.push_str(&format!("&{name}[dir]={}", urlencoding::encode(dir)));
+                }
+                if let Some(thumbnail) = &file.thumbnail {
+                    qs.push_str(&format!(
+                        "&{name}[thumbnail]={}",
+                        urlencoding::encode(thumbnail)
+                    ));
+                }
             }
             None => {
+                let bytes = field.bytes().await?;
+                let top_level = raw_name.split('[').next().unwrap_or_default();
+                if !secret.contains(top_level) {
+                    let value = String::from_utf8_lossy(&bytes);
+                    values.push((raw_name, value.to_string()));
+                }
+                if bytes.is_empty() && (is_optional || is_nested) {
+                    continue;
+                }
                 if !qs.is_empty() {
                     qs.push('&');
                 }
                 qs.push_str(&name);
-                let bytes = field.bytes().await?;
                 let value = urlencoding::encode_binary(&bytes);
                 qs.push('=');
                 qs.push_str(&value);
@@ -235,38 +1272,301 @@ async fn parse_form<T: for<'de> Deserialize<'de>>(
             _ => {}
         };
     }
-    serde_qs::Config::new(5, false)
-        .deserialize_str(&qs)
-        .map_err(|e| ParseFormError::Deserialize {
-            serde: e,
-            query_string: qs,
-        })
+    let config = serde_qs::Config::new(5, false);
+    let deserializer =
+        serde_qs::Deserializer::with_config(&config, qs.as_bytes()).map_err(|e| {
+            ParseFormError::Deserialize {
+                serde: e,
+                query_string: qs.clone(),
+            }
+        })?;
+    let value = verify_files_in(files_dir, || serde_path_to_error::deserialize(deserializer))
+        .map_err(|e| match form_field_name(e.path()) {
+            Some(field) => {
+                debug!("failed to deserialize field {field}: {qs}");
+                ParseFormError::InvalidField {
+                    field,
+                    serde: e.into_inner(),
+                }
+            }
+            None => ParseFormError::Deserialize {
+                serde: e.into_inner(),
+                query_string: qs.clone(),
+            },
+        })?;
+    check_inputs(T::inputs(Some(&value))).map_err(|e| ParseFormError::Constraint {
+        field: e.field,
+        violation: e.violation,
+    })?;
+    Ok((value, qs))
+}
+
+/// number of elements of the list inputs of a form, used to append files selected at once
+/// to the end of their list, see [`Input::multiple_files`](crate::Input::multiple_files)
+#[derive(Debug, Default)]
+struct ListLengths(HashMap<String, usize>);
+
+impl ListLengths {
+    /// record the indices in `name`, e.g. `images` has at least 3 elements if `name` is `images[2][alt_text]`
+    fn record(&mut self, name: &str) {
+        let mut rest = name;
+        while let Some(open) = rest.find('[') {
+            let after = &rest[open + 1..];
+            let Some(close) = after.find(']') else { break };
+            if let Ok(index) = after[..close].parse::<usize>() {
+                let list = &name[..name.len() - rest.len() + open];
+                let len = self.0.entry(list.to_string()).or_default();
+                *len = (*len).max(index + 1);
+            }
+            rest = &after[close + 1..];
+        }
+    }
+
+    /// the name of a new element at the end of the list if `name` is a [`MULTIPLE_FILES`] input
+    fn append(&mut self, name: &str) -> Option<String> {
+        let list = urlencoding::decode(name).ok()?;
+        let list = list.strip_suffix(MULTIPLE_FILES)?;
+        let len = self.0.entry(list.to_string()).or_default();
+        let element = format!("{list}[{len}]");
+        *len += 1;
+        Some(element)
+    }
+}
+
+/// name of the form input at `path`, e.g. `content[0][data]`
+fn form_field_name(path: &serde_path_to_error::Path) -> Option<String> {
+    use serde_path_to_error::Segment;
+
+    let mut segments = path.iter().filter_map(|s| match s {
+        Segment::Seq { index } => Some(index.to_string()),
+        Segment::Map { key } => Some(key.clone()),
+        Segment::Enum { .. } | Segment::Unknown => None,
+    });
+    let mut name = segments.next()?;
+    for s in segments {
+        name.push_str(&format!("[{s}]"));
+    }
+    Some(name)
 }
 
 #[derive(Clone, Debug)]
 pub(crate) struct UploadDir(pub(crate) PathBuf);
 
+#[derive(Clone, Debug)]
+pub(crate) struct BasePath(pub(crate) String);
+
 pub(crate) async fn parse_mde_upload(
     config: Extension<EditorConfig>,
     path: Extension<UploadDir>,
+    Extension(BasePath(base_path)): Extension<BasePath>,
     mut form: Multipart,
 ) -> Result<Json<UploadSuccess>, UploadError> {
     let upload_dir = path.0 .0;
-    while let Some(field) = form.next_field().await? {
-        let accepted = match field.content_type() {
-            Some(content_type) => config.allowed_file_types.contains(&content_type.into()),
-            None => false,
-        };
-        if !accepted {
-            return Err(UploadError::TypeNotAllowed);
+    let Some(field) = form.next_field().await? else {
+        return Err(UploadError::NoFileGiven);
+    };
+    let accepted = match field.content_type() {
+        Some(content_type) => config.allowed_file_types.contains(&content_type.into()),
+        None => false,
+    };
+    if !accepted {
+        return Err(UploadError::TypeNotAllowed);
+    }
+    let mut uploads = NewUploads::default();
+    match stream_field_to_file(field, &upload_dir, None, None, None, &mut uploads).await {
+        Ok(file) => Ok(Json::from(UploadSuccess::new(format!(
+            "{base_path}/{}",
+            file.admin_url()
+        )))),
+        Err(err) => {
+            error!("writing uploaded file failed: {err}");
+            uploads.remove().await;
+            Err(UploadError::ImportError)
         }
-        return match stream_field_to_file(field, &upload_dir).await {
-            Ok(file) => Ok(Json::from(UploadSuccess::new(file.url()))),
-            Err(err) => {
-                error!("writing uploaded file failed: {err}");
-                Err(UploadError::ImportError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, extract::FromRequest, http::Request};
+
+    use super::*;
+    use crate::{
+        context::Context,
+        input::InputInfo,
+        property::{Password, Text},
+    };
+
+    #[derive(Debug, Deserialize)]
+    struct Upload {
+        file: File,
+        count: u32,
+    }
+
+    impl<S: ContextTrait> Inputs<S> for Upload {
+        fn inputs(value: Option<&Self>) -> impl IntoIterator<Item = InputInfo<'_, S>> {
+            [
+                InputInfo::new("file", "File", Box::new(value.map(|v| &v.file))),
+                InputInfo::new("count", "Count", Box::new(value.map(|v| &v.count))),
+            ]
+        }
+    }
+
+    const BOUNDARY: &str = "derived-cms-boundary";
+
+    /// multipart form of `fields`, which are files if a filename is given
+    async fn multipart(fields: &[(&str, Option<&str>, &str)]) -> Multipart {
+        let mut body = String::new();
+        for (name, filename, value) in fields {
+            body.push_str(&format!("--{BOUNDARY}\r\n"));
+            match filename {
+                Some(filename) => body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{name}\"; filename=\"{filename}\"\r\n\
+                     Content-Type: text/plain\r\n\r\n"
+                )),
+                None => body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{name}\"\r\n\r\n"
+                )),
             }
-        };
+            body.push_str(value);
+            body.push_str("\r\n");
+        }
+        body.push_str(&format!("--{BOUNDARY}--\r\n"));
+        let req = Request::builder()
+            .method("POST")
+            .header(
+                "content-type",
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            )
+            .body(Body::from(body))
+            .unwrap();
+        Multipart::from_request(req, &()).await.unwrap()
+    }
+
+    /// empty directory for the uploads of one test
+    fn uploads_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("derived-cms-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn rejected_form_removes_uploads() {
+        let dir = uploads_dir();
+        let form = multipart(&[
+            ("file", Some("hello.txt"), "Hello, world!"),
+            ("count", None, "not a number"),
+        ])
+        .await;
+        let mut uploads = NewUploads::default();
+        let parsed = parse_form::<Upload, Context<()>>(
+            form,
+            &dir,
+            None,
+            None,
+            &mut Vec::new(),
+            &mut uploads,
+        )
+        .await;
+        assert!(matches!(
+            parsed,
+            Err(ParseFormError::InvalidField { ref field, .. }) if field == "count"
+        ));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        uploads.remove().await;
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn fabricated_file_is_rejected() {
+        let dir = uploads_dir();
+        let id = Uuid::new_v4().to_string();
+        let form = multipart(&[
+            ("file[name]", None, "passwd"),
+            ("file[id]", None, &id),
+            ("count", None, "1"),
+        ])
+        .await;
+        let parsed = parse_form::<Upload, Context<()>>(
+            form,
+            &dir,
+            None,
+            None,
+            &mut Vec::new(),
+            &mut NewUploads::default(),
+        )
+        .await;
+        assert!(matches!(
+            parsed,
+            Err(ParseFormError::InvalidField { ref field, .. }) if field == "file"
+        ));
+
+        std::fs::create_dir(dir.join(&id)).unwrap();
+        std::fs::write(dir.join(&id).join("passwd"), "").unwrap();
+        let form = multipart(&[
+            ("file[name]", None, "passwd"),
+            ("file[id]", None, &id),
+            ("count", None, "1"),
+        ])
+        .await;
+        let parsed = parse_form::<Upload, Context<()>>(
+            form,
+            &dir,
+            None,
+            None,
+            &mut Vec::new(),
+            &mut NewUploads::default(),
+        )
+        .await;
+        assert!(parsed.is_ok());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Login {
+        user: Text,
+        password: Password,
+        count: u32,
+    }
+
+    impl<S: ContextTrait> Inputs<S> for Login {
+        fn inputs(value: Option<&Self>) -> impl IntoIterator<Item = InputInfo<'_, S>> {
+            [
+                InputInfo::new("user", "User", Box::new(value.map(|v| &v.user))),
+                InputInfo::new("password", "Password", Box::new(value.map(|v| &v.password))),
+                InputInfo::new("count", "Count", Box::new(value.map(|v| &v.count))),
+            ]
+        }
+    }
+
+    #[tokio::test]
+    async fn rejected_form_does_not_restore_passwords() {
+        let dir = uploads_dir();
+        let form = multipart(&[
+            ("user", None, "admin"),
+            ("password", None, "hunter2"),
+            ("count", None, "not a number"),
+        ])
+        .await;
+        let mut values = Vec::new();
+        let parsed = parse_form::<Login, Context<()>>(
+            form,
+            &dir,
+            None,
+            None,
+            &mut values,
+            &mut NewUploads::default(),
+        )
+        .await;
+        assert!(parsed.is_err());
+        assert_eq!(
+            values,
+            [
+                ("user".to_string(), "admin".to_string()),
+                ("count".to_string(), "not a number".to_string()),
+            ]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
     }
-    Err(UploadError::NoFileGiven)
 }
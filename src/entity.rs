@@ -1,15 +1,34 @@
-use std::{fmt::Display, future::Future};
+use std::{borrow::Borrow, fmt::Display, future::Future};
 
-use axum::extract::FromRequestParts;
+use axum::{
+    async_trait,
+    extract::{
+        rejection::{PathRejection, RawPathParamsRejection},
+        FromRequestParts, Path, RawPathParams,
+    },
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use convert_case::{Case, Casing};
 pub use derived_cms_derive::Entity;
-use generic_array::{ArrayLength, GenericArray};
-use serde::{Deserialize, Serialize};
+use generic_array::{sequence::GenericSequence, ArrayLength, GenericArray};
+use ormlite::{query_builder::SelectQueryBuilder, Database, Executor, FromRow, Model};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+use tracing::error;
+use uuid::Uuid;
 
 use crate::{
     app::AppError,
+    auth::{Action, Permissions, User},
     column::{Column, ColumnInfo},
     context::ContextTrait,
-    input::InputInfo,
+    filter::{Condition, Filter, FilterField},
+    input::Inputs,
+    maintenance::MaintenanceAction,
+    validate::ValidationErrors,
+    DB,
 };
 
 pub trait EntityBase<S: ContextTrait>:
@@ -18,20 +37,396 @@ pub trait EntityBase<S: ContextTrait>:
     /// should usually be an UUID
     type Id: for<'de> Deserialize<'de> + Clone + Display + Serialize + Send;
 
-    type Create: for<'de> Deserialize<'de> + Serialize + Send + Sync + Unpin + 'static;
-    type Update: for<'de> Deserialize<'de> + Serialize + Send + Sync + Unpin + 'static;
+    /// data used to create the entity, rendered as the add form.
+    type Create: for<'de> Deserialize<'de> + Inputs<S> + Serialize + Send + Sync + Unpin + 'static;
+    /// data used to update the entity, rendered as the edit form.
+    type Update: for<'de> Deserialize<'de> + Inputs<S> + Serialize + Send + Sync + Unpin + 'static;
 
     type NumberOfColumns: ArrayLength;
 
     fn name() -> &'static str;
     fn name_plural() -> &'static str;
 
+    /// representation of `id` in the URLs of the admin interface and the JSON API, which is
+    /// percent-encoded in links and redirects. It has to deserialize to `id` again when it is
+    /// extracted from the path, and must not contain `/`, see [`IdPath`].
+    /// Set using `#[cms(url_id = "path::to::fn")]`.
+    /// Default: [`Display`] of `id`
+    fn url_id(id: &Self::Id) -> String {
+        id.to_string()
+    }
+
+    /// Token identifying the stored version of the entity, e.g. an `updated_at` timestamp or an
+    /// integer `version` that [`Update::update`] changes on every save.
+    /// The edit form submits the token it was loaded with and the JSON API accepts it in the
+    /// `If-Match` header. Saves are rejected with HTTP 409 in the admin interface and
+    /// 412 in the API if the token changed in the meantime, see [`Update::update_if_version`].
+    /// Set using `#[cms(version)]` on a field.
+    /// Default: `None`, saves are not checked
+    fn version_token(&self) -> Option<String> {
+        None
+    }
+
+    /// ids of the uploaded [`File`](crate::property::File)s this entity references, uploads
+    /// referenced by no entity are removed by [`gc_uploads`](crate::uploads::gc_uploads).
+    /// When deriving [`Entity`], this includes all fields whose type contains `File`, `Image` or
+    /// `Markdown`, and fields marked with `#[cms(files)]`, see
+    /// [`ReferencedFiles`](crate::property::ReferencedFiles). Use `#[cms(files = false)]` to
+    /// exclude a field.
+    /// Default: no files
+    fn referenced_files(&self) -> Vec<Uuid> {
+        Vec::new()
+    }
+
+    /// Entities with a higher priority are listed first in the sidebar, entities with the same
+    /// priority are sorted by name. Set using `#[cms(nav_priority = 10)]`.
+    /// Default: `0`
+    fn nav_priority() -> i32 {
+        0
+    }
+
     /// should return the value of the field used as primary key.
     fn id(&self) -> &Self::Id;
 
+    /// label of this entity in the options of a [`Relation`](crate::property::Relation).
+    /// Set using `#[cms(display_label)]` on a field implementing [`Display`].
+    /// Default: [`Display`] of `id`
+    fn display_label(&self) -> String {
+        self.id().to_string()
+    }
+
+    /// The [`Update`](EntityBase::Update) data of this entity, used to prefill the edit form.
+    ///
+    /// When deriving [`Entity`], this is `self` if `Update` is `Self`. For a custom type set with
+    /// `#[cms(update = UpdatePost)]`, `UpdatePost` has to implement `for<'a> From<&'a Post>`.
+    fn to_update(&self) -> impl Borrow<Self::Update> + '_;
+
     fn columns() -> GenericArray<ColumnInfo, Self::NumberOfColumns>;
     fn column_values(&self) -> GenericArray<&dyn Column, Self::NumberOfColumns>;
-    fn inputs(value: Option<&Self>) -> impl IntoIterator<Item = InputInfo<'_, S>>;
+    /// whether each of the [`columns`](EntityBase::columns) is shown for the current request,
+    /// see [`Visibility`](crate::context::Visibility).
+    /// Default: all columns
+    fn visible_columns(_ctx: &S) -> GenericArray<bool, Self::NumberOfColumns> {
+        GenericArray::generate(|_| true)
+    }
+
+    /// names in the database of the columns marked with `#[cms(searchable)]`, see
+    /// [`Filter::search`].
+    fn searchable_columns() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// whether one of the fields marked with `#[cms(searchable)]` contains the lowercase `query`,
+    /// used by [`Filter::matches`].
+    /// Default: `true`
+    fn matches_search(&self, _query: &str) -> bool {
+        true
+    }
+
+    /// the field with the serde name `name` if it is marked with `#[cms(filter)]`,
+    /// see [`filter`](crate::filter)
+    fn filter_field(_name: &str) -> Option<FilterField> {
+        None
+    }
+
+    /// whether the field of `condition` matches, used by the default implementation of
+    /// [`List::list_paginated`].
+    /// Default: `true`
+    fn matches_filter(&self, _condition: &Condition) -> bool {
+        true
+    }
+
+    /// The representation of this entity returned by the JSON API, e.g. to redact internal fields
+    /// that should still be editable in the admin interface.
+    /// Default: the entity serialized using [`Serialize`]
+    fn api_serialize(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_else(|e| {
+            error!("Failed to serialize {}: {e:#}", Self::name());
+            serde_json::Value::Null
+        })
+    }
+
+    /// how deleting this entity has to be confirmed in the admin interface.
+    /// Set using `#[cms(delete_confirmation = "path::to::fn")]`, which can't be combined with
+    /// `#[cms(list_rows)]`.
+    /// Default: a simple confirmation dialog
+    fn delete_confirmation(&self) -> DeleteConfirm {
+        DeleteConfirm::default()
+    }
+
+    /// Whether deleting this entity using the JSON API has to be confirmed with a one-time token,
+    /// see [`delete_token`](crate::delete_token). Set using `#[cms(confirm_api_delete)]`.
+    /// Default: `false`, unless enabled for all entities using
+    /// [`App::confirm_api_deletes`](crate::App::confirm_api_deletes)
+    fn confirm_api_delete() -> bool {
+        false
+    }
+
+    /// Buttons on the list page that run a task over all entities, e.g. rebuilding a search index.
+    /// Default: none
+    ///
+    /// ```rust,ignore
+    /// fn maintenance_actions() -> Vec<MaintenanceAction<Ctx>> {
+    ///     vec![MaintenanceAction::new("reindex", "Rebuild search index", |ctx: Ctx, progress| async move {
+    ///         let posts = Post::select().fetch_all(ctx.ext()).await.map_err(db_error)?;
+    ///         for (i, post) in posts.iter().enumerate() {
+    ///             index.add(post).await.map_err(index_error)?;
+    ///             progress.set(i as u64 + 1, posts.len() as u64);
+    ///         }
+    ///         Ok(())
+    ///     })]
+    /// }
+    /// ```
+    fn maintenance_actions() -> Vec<MaintenanceAction<S>> {
+        Vec::new()
+    }
+}
+
+/// Configuration of the dialog shown before deleting an entity in the admin interface.
+#[derive(Clone, Debug, Default)]
+pub struct DeleteConfirm {
+    pub(crate) message: Option<String>,
+    pub(crate) type_to_confirm: Option<String>,
+}
+
+impl DeleteConfirm {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the default message of the dialog.
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Only enable the delete button once `text` (e.g. the name of the entity) has been typed.
+    pub fn type_to_confirm(mut self, text: impl Into<String>) -> Self {
+        self.type_to_confirm = Some(text.into());
+        self
+    }
+}
+
+/// The names and URL prefix an [`Entity`] is served under, see [`App::entity_scoped`](crate::App::entity_scoped).
+///
+/// Routes are `/{prefix}/{name-plural}`, `/{prefix}/{name}/:id` and
+/// `/api/v1/{prefix}/{name-plural}`, `/api/v1/{prefix}/{name}/:id`
+/// with names converted to [kebab-case](convert_case::Case::Kebab).
+#[derive(Clone, Debug)]
+pub struct EntityScope {
+    pub(crate) prefix: Option<String>,
+    pub(crate) name: String,
+    pub(crate) name_plural: String,
+    pub(crate) priority: i32,
+    pub(crate) group: Option<String>,
+    pub(crate) trash: bool,
+    pub(crate) versions: bool,
+    pub(crate) permissions: Option<Permissions>,
+}
+
+impl EntityScope {
+    pub fn new(name: impl Into<String>, name_plural: impl Into<String>) -> Self {
+        Self {
+            prefix: None,
+            name: name.into(),
+            name_plural: name_plural.into(),
+            priority: 0,
+            group: None,
+            trash: false,
+            versions: false,
+            permissions: None,
+        }
+    }
+
+    /// the [`name`](EntityBase::name), [`name_plural`](EntityBase::name_plural) and
+    /// [`nav_priority`](EntityBase::nav_priority) of `E` without prefix
+    pub fn of<E: EntityBase<S>, S: ContextTrait>() -> Self {
+        Self::new(E::name(), E::name_plural()).with_priority(E::nav_priority())
+    }
+
+    /// Set the position in the sidebar, see [`EntityBase::nav_priority`].
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Serve the entity under `prefix`, e.g. `"tenant-a"`.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        let prefix = prefix.trim_matches('/');
+        self.prefix = (!prefix.is_empty()).then(|| prefix.to_string());
+        self
+    }
+
+    /// Show the entity under the heading `group` in the sidebar,
+    /// see [`App::entity_in_group`](crate::App::entity_in_group).
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
+    /// Restrict the actions of users, see [`App::entity_with_perms`](crate::App::entity_with_perms).
+    pub fn with_permissions(mut self, permissions: Permissions) -> Self {
+        self.permissions = Some(permissions);
+        self
+    }
+
+    pub fn prefix(&self) -> Option<&str> {
+        self.prefix.as_deref()
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn name_plural(&self) -> &str {
+        &self.name_plural
+    }
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+    /// whether the entity is [soft-deleted](SoftDelete) and has a trash page
+    pub fn trash(&self) -> bool {
+        self.trash
+    }
+    /// whether the entity is [versioned](Versioned) and has a versions page
+    pub fn versions(&self) -> bool {
+        self.versions
+    }
+
+    /// whether the [`User`] of the current request may perform `action`, see [`User::current`]
+    pub fn allows(&self, action: Action) -> bool {
+        self.allows_user(User::current().as_ref(), action)
+    }
+    /// whether `user` may perform `action`. Without [permissions](EntityScope::with_permissions),
+    /// all actions are allowed, otherwise all actions are denied if there is no user.
+    pub fn allows_user(&self, user: Option<&User>, action: Action) -> bool {
+        match self.permissions {
+            Some(permissions) => user.is_some_and(|user| permissions(user, action)),
+            None => true,
+        }
+    }
+
+    /// path of a single entity relative to the base path, e.g. `tenant-a/document`
+    pub fn path(&self) -> String {
+        self.path_of(&self.name)
+    }
+    /// path of the list of entities relative to the base path, e.g. `tenant-a/documents`
+    pub fn path_plural(&self) -> String {
+        self.path_of(&self.name_plural)
+    }
+
+    fn path_of(&self, name: &str) -> String {
+        let name = name.to_case(Case::Kebab);
+        let name = urlencoding::encode(&name);
+        match &self.prefix {
+            Some(prefix) => format!("{prefix}/{name}"),
+            None => name.into_owned(),
+        }
+    }
+}
+
+/// [permissions](EntityScope::with_permissions) are compared by address
+impl PartialEq for EntityScope {
+    fn eq(&self, other: &Self) -> bool {
+        self.prefix == other.prefix
+            && self.name == other.name
+            && self.name_plural == other.name_plural
+            && self.priority == other.priority
+            && self.group == other.group
+            && self.trash == other.trash
+            && self.versions == other.versions
+            && match (self.permissions, other.permissions) {
+                (Some(a), Some(b)) => std::ptr::fn_addr_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    }
+}
+
+impl Eq for EntityScope {}
+
+/// The pages of an entity that is not [scoped](crate::App::entity_scoped), independent of the
+/// context such that they can be linked by a [`Relation`](crate::property::Relation).
+/// Implemented by `#[derive(Entity)]`.
+pub trait EntityPath<Id> {
+    /// path of the list page relative to the base path, e.g. `posts`
+    fn list_path() -> String;
+    /// path of the edit page of the entity `id` relative to the base path, see [`edit_path`]
+    fn edit_path(id: &Id) -> String;
+}
+
+/// `{name}/{url_id}` with `name` converted to kebab-case and `url_id` percent-encoded
+pub fn edit_path(name: &str, url_id: &str) -> String {
+    format!(
+        "{}/{}",
+        name.to_case(Case::Kebab),
+        urlencoding::encode(url_id)
+    )
+}
+
+/// Extracts the percent-decoded `:id` of the path like [`Path`], used by all endpoints of an
+/// entity. Ids containing a `/` are rejected with HTTP 400, since `%2F` is not reliably passed
+/// through by proxies.
+///
+/// ```rust
+/// use axum::{body::Body, http::{Request, StatusCode}, routing::get, Router};
+/// use derived_cms::entity::IdPath;
+/// use tower::ServiceExt;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let app = Router::new().route("/page/:id", get(|IdPath(id): IdPath<String>| async move { id }));
+///
+/// let req = Request::get("/page/hello%20world").body(Body::empty()).unwrap();
+/// let res = app.clone().oneshot(req).await.unwrap();
+/// assert_eq!(res.status(), StatusCode::OK);
+/// let body = axum::body::to_bytes(res.into_body(), usize::MAX).await.unwrap();
+/// assert_eq!(body, "hello world");
+///
+/// let req = Request::get("/page/hello%2Fworld").body(Body::empty()).unwrap();
+/// let res = app.oneshot(req).await.unwrap();
+/// assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct IdPath<T>(pub T);
+
+#[derive(Debug, Error)]
+pub enum IdPathRejection {
+    #[error("invalid id {0:?}: ids must not contain slashes")]
+    Slash(String),
+    #[error(transparent)]
+    Raw(#[from] RawPathParamsRejection),
+    #[error(transparent)]
+    Path(#[from] PathRejection),
+}
+
+impl IntoResponse for IdPathRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Slash(_) => (StatusCode::BAD_REQUEST, self.to_string()).into_response(),
+            Self::Raw(e) => e.into_response(),
+            Self::Path(e) => e.into_response(),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: DeserializeOwned + Send, S: Send + Sync> FromRequestParts<S> for IdPath<T> {
+    type Rejection = IdPathRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let params = RawPathParams::from_request_parts(parts, state).await?;
+        if let Some((_, id)) = params
+            .iter()
+            .find(|(name, id)| *name == "id" && id.contains('/'))
+        {
+            return Err(IdPathRejection::Slash(id.to_string()));
+        }
+        let Path(id) = Path::from_request_parts(parts, state).await?;
+        Ok(Self(id))
+    }
 }
 
 pub trait Entity<S: ContextTrait>:
@@ -53,10 +448,268 @@ pub trait List<S: ContextTrait>: EntityBase<S> {
     type RequestExt: FromRequestParts<S> + Send + Sync + Clone;
     type Error: Into<AppError> + Serialize + Send;
 
-    // TODO: limit & offset
     fn list(
         ext: Self::RequestExt,
     ) -> impl Future<Output = Result<impl IntoIterator<Item = Self>, Self::Error>> + Send;
+
+    /// The entities matching the [filter](crate::filter) of `params` on the page selected by
+    /// `params` and the number of matching entities of all pages, returned by the JSON API and
+    /// rendered on the list page when [searching](Filter::search).
+    ///
+    /// The default implementation filters and selects the page from the result of [`List::list`].
+    /// Override it to filter and use `LIMIT` and `OFFSET` in the database instead, e.g. using
+    /// [`filter_query`](crate::filter::filter_query).
+    fn list_paginated(
+        params: ListParams,
+        ext: Self::RequestExt,
+    ) -> impl Future<Output = Result<(Vec<Self>, u64), Self::Error>> + Send {
+        async move {
+            let entities = Self::list(ext).await?;
+            Ok(params.apply(
+                entities
+                    .into_iter()
+                    .filter(|e| params.filter.matches::<Self, S>(e)),
+            ))
+        }
+    }
+
+    /// The first `limit` entities after the entity `cursor`, or of all entities if `None`, matching
+    /// `filter`, and the cursor of the next page if there may be more entities, returned by the
+    /// JSON API with `?after=:id`, see [`keyset_page`].
+    ///
+    /// The default implementation pages the result of [`List::list`] in its order, starting after
+    /// the entity `cursor`, or returns nothing if it does not exist anymore. Override it to select
+    /// the entities with a greater primary key in the database instead, e.g. using [`after_query`],
+    /// which is stable even if entities are inserted or deleted between pages.
+    fn list_after(
+        cursor: Option<Self::Id>,
+        limit: u64,
+        filter: Filter,
+        ext: Self::RequestExt,
+    ) -> impl Future<Output = Result<KeysetPage<Self, S>, Self::Error>> + Send {
+        async move {
+            let mut entities = Self::list(ext).await?.into_iter();
+            if let Some(cursor) = cursor.map(|c| c.to_string()) {
+                entities.find(|e| EntityBase::id(e).to_string() == cursor);
+            }
+            let page = entities
+                .filter(|e| filter.matches::<Self, S>(e))
+                .take(usize::try_from(limit.saturating_add(1)).unwrap_or(usize::MAX))
+                .collect();
+            Ok(keyset_page::<Self, S>(page, limit))
+        }
+    }
+
+    /// The number of entities, shown on the dashboard.
+    ///
+    /// The default implementation counts the result of [`List::list`]. Override it to count the
+    /// rows in the database instead, e.g. using [`count_rows`].
+    fn count(ext: Self::RequestExt) -> impl Future<Output = Result<u64, Self::Error>> + Send {
+        async move {
+            let count = Self::list(ext).await?.into_iter().count();
+            Ok(u64::try_from(count).unwrap_or(u64::MAX))
+        }
+    }
+
+    /// The rows rendered on the list page.
+    ///
+    /// The default implementation returns the entities of [`List::list`]. Override it to load only
+    /// the columns of the list page, e.g. using [`fetch_list_rows`].
+    fn list_rows(
+        ext: Self::RequestExt,
+    ) -> impl Future<Output = Result<impl IntoIterator<Item = impl ListRow<Self, S>>, Self::Error>> + Send
+    {
+        Self::list(ext)
+    }
+
+    /// The rows of the page of the list page selected by `params` and the number of rows of all
+    /// pages.
+    ///
+    /// The default implementation selects the page from the result of [`List::list_rows`].
+    /// Override it together with [`List::list_paginated`] to use `LIMIT` and `OFFSET` in the database.
+    fn list_rows_paginated(
+        params: ListParams,
+        ext: Self::RequestExt,
+    ) -> impl Future<Output = Result<(Vec<impl ListRow<Self, S>>, u64), Self::Error>> + Send {
+        async move { Ok(params.apply(Self::list_rows(ext).await?)) }
+    }
+}
+
+/// The page of a list, from the `limit` and `offset` in the query string of the list page and
+/// of `GET /api/v1/:name-plural`, see [`List::list_paginated`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ListParams {
+    /// max number of entities, all if `None`
+    pub limit: Option<u64>,
+    /// number of entities to skip
+    pub offset: Option<u64>,
+    /// conditions of the JSON API or the search of the list page
+    pub filter: Filter,
+}
+
+impl ListParams {
+    /// The page of `items` selected by `self` and the number of all items.
+    pub fn apply<T>(&self, items: impl IntoIterator<Item = T>) -> (Vec<T>, u64) {
+        let mut total = 0;
+        let offset = self.offset.unwrap_or_default();
+        let limit = self.limit.unwrap_or(u64::MAX);
+        let page = items
+            .into_iter()
+            .inspect(|_| total += 1)
+            .enumerate()
+            .filter(|(i, _)| (*i as u64) >= offset && (*i as u64) - offset < limit)
+            .map(|(_, item)| item)
+            .collect();
+        (page, total)
+    }
+}
+
+/// The entities of a page of [`List::list_after`] and the cursor of the next page.
+pub type KeysetPage<E, S> = (Vec<E>, Option<<E as EntityBase<S>>::Id>);
+
+/// Truncate `entities`, the first up to `limit + 1` entities of a page of
+/// [`List::list_after`], to `limit` entities and return the id of the last entity as the cursor
+/// of the next page if there are more entities.
+pub fn keyset_page<E: EntityBase<S>, S: ContextTrait>(
+    mut entities: Vec<E>,
+    limit: u64,
+) -> KeysetPage<E, S> {
+    let limit = usize::try_from(limit).unwrap_or(usize::MAX);
+    if entities.len() <= limit {
+        return (entities, None);
+    }
+    entities.truncate(limit);
+    let next = entities.last().map(|e| e.id().clone());
+    (entities, next)
+}
+
+/// Select the first `limit + 1` entities of `M` with a primary key greater than `cursor`, ordered
+/// by the primary key, for [`keyset_page`].
+///
+/// ```rust,ignore
+/// async fn list_after(cursor: Option<Self::Id>, limit: u64, filter: Filter, ext: Self::RequestExt) -> Result<KeysetPage<Self, Ctx>, Self::Error> {
+///     let select = filter::filter_query(entity::after_query(cursor, limit), &filter);
+///     Ok(entity::keyset_page::<Self, Ctx>(select.fetch_all(ext.ext()).await?, limit))
+/// }
+/// ```
+pub fn after_query<'a, M>(
+    cursor: Option<impl for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB> + Send + 'a>,
+    limit: u64,
+) -> SelectQueryBuilder<'a, DB, M>
+where
+    M: Model<DB> + for<'r> FromRow<'r, <DB as Database>::Row> + Send + Sync + Unpin + 'static,
+{
+    let key = M::primary_key().unwrap_or("id");
+    let mut select = M::select();
+    if let Some(cursor) = cursor {
+        select = select
+            .dangerous_where(&format!("\"{key}\" > ?"))
+            .bind(cursor);
+    }
+    // `LIMIT` is a signed 64 bit integer in SQLite and Postgres
+    let limit = limit.saturating_add(1).min(i64::MAX as u64);
+    select
+        .order_asc(&format!("\"{key}\""))
+        .limit(usize::try_from(limit).unwrap_or(usize::MAX))
+}
+
+/// The data of an entity rendered as a row of the list page.
+pub trait ListRow<E: EntityBase<S>, S: ContextTrait>: Send {
+    fn id(&self) -> &E::Id;
+    fn column_values(&self) -> GenericArray<&dyn Column, E::NumberOfColumns>;
+    /// has to match [`EntityBase::delete_confirmation`] of the entity
+    fn delete_confirmation(&self) -> DeleteConfirm;
+}
+
+impl<E: EntityBase<S>, S: ContextTrait> ListRow<E, S> for E {
+    fn id(&self) -> &E::Id {
+        EntityBase::id(self)
+    }
+    fn column_values(&self) -> GenericArray<&dyn Column, E::NumberOfColumns> {
+        EntityBase::column_values(self)
+    }
+    fn delete_confirmation(&self) -> DeleteConfirm {
+        EntityBase::delete_confirmation(self)
+    }
+}
+
+/// A projection of an entity to the fields rendered on the list page,
+/// implemented by `#[derive(Entity)]` with `#[cms(list_rows)]`.
+pub trait ListRows<S: ContextTrait>: EntityBase<S> {
+    /// contains the id and all fields that are not `#[cms(skip_column)]`
+    type Row: ListRow<Self, S> + for<'r> FromRow<'r, <DB as Database>::Row> + Unpin + 'static;
+
+    /// names of the database columns of [`ListRows::Row`]
+    fn row_columns() -> &'static [&'static str];
+}
+
+/// Select only the [columns of the list rows](ListRows::row_columns) of `E`,
+/// e.g. to add conditions before calling [`fetch_list_rows`].
+pub fn list_rows_query<'a, E, S>() -> SelectQueryBuilder<'a, DB, E>
+where
+    E: ListRows<S> + Model<DB> + for<'r> FromRow<'r, <DB as Database>::Row>,
+    S: ContextTrait,
+{
+    E::row_columns()
+        .iter()
+        .fold(SelectQueryBuilder::default(), |query, column| {
+            query.select(format!("{}.{column}", E::table_name()))
+        })
+}
+
+/// Fetch the [`ListRows::Row`]s selected by `query`, see [`list_rows_query`].
+///
+/// ```rust,ignore
+/// fn list_rows(ext: Self::RequestExt) -> impl Future<Output = Result<impl IntoIterator<Item = impl ListRow<Self, Ctx>>, Self::Error>> + Send {
+///     async move {
+///         let query = entity::list_rows_query::<Self, Ctx>();
+///         Ok(entity::fetch_list_rows::<Self, Ctx>(query, ext.ext()).await?)
+///     }
+/// }
+/// ```
+pub async fn fetch_list_rows<'a, 'c, E, S>(
+    query: SelectQueryBuilder<'a, DB, E>,
+    db: impl Executor<'c, Database = DB>,
+) -> Result<Vec<E::Row>, ormlite::Error>
+where
+    E: ListRows<S> + Model<DB> + for<'r> FromRow<'r, <DB as Database>::Row>,
+    S: ContextTrait,
+{
+    let (sql, args) = query.into_query_and_args()?;
+    Ok(ormlite::query_as_with::<DB, E::Row, _>(&sql, *args.0)
+        .fetch_all(db)
+        .await?)
+}
+
+/// whether the table of `M` has no rows, e.g. to only [seed](crate::App::seed) an empty database
+pub async fn table_is_empty<'c, M: Model<DB>>(
+    db: impl Executor<'c, Database = DB>,
+) -> Result<bool, ormlite::Error> {
+    let row = ormlite::query(&format!("SELECT 1 FROM \"{}\" LIMIT 1", M::table_name()))
+        .fetch_optional(db)
+        .await?;
+    Ok(row.is_none())
+}
+
+/// number of rows in the table of `M`, see [`List::count`]
+pub async fn count_rows<'c, M: Model<DB>>(
+    db: impl Executor<'c, Database = DB>,
+) -> Result<u64, ormlite::Error> {
+    let (count,): (i64,) =
+        ormlite::query_as(&format!("SELECT COUNT(*) FROM \"{}\"", M::table_name()))
+            .fetch_one(db)
+            .await?;
+    Ok(u64::try_from(count).unwrap_or_default())
+}
+
+/// whether the serialized `value` contains the lowercase `query`, used by `#[derive(Entity)]`
+#[doc(hidden)]
+pub fn search_matches(value: &impl Serialize, query: &str) -> bool {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(v)) => v.to_lowercase().contains(query),
+        Ok(serde_json::Value::Null) | Err(_) => false,
+        Ok(v) => v.to_string().to_lowercase().contains(query),
+    }
 }
 
 pub trait Create<S: ContextTrait>: EntityBase<S> {
@@ -78,6 +731,156 @@ pub trait Update<S: ContextTrait>: EntityBase<S> {
         data: <Self as EntityBase<S>>::Update,
         ext: Self::RequestExt,
     ) -> impl Future<Output = Result<Self, Self::Error>> + Send;
+
+    /// Update the entity only if its [version token](EntityBase::version_token) is one of
+    /// `versions`, used by the JSON API for requests with an `If-Match` header.
+    /// Returns `None` if it has another version, which is answered with `412 Precondition Failed`.
+    ///
+    /// The check has to be atomic with the update, e.g. using [`update_if_version`].
+    /// Default: [`Update::update`], the API only compares the version before calling it, which
+    /// does not prevent another update in between.
+    fn update_if_version(
+        id: &<Self as EntityBase<S>>::Id,
+        data: <Self as EntityBase<S>>::Update,
+        versions: &[String],
+        ext: Self::RequestExt,
+    ) -> impl Future<Output = Result<Option<Self>, Self::Error>> + Send {
+        let _ = versions;
+        let update = Self::update(id, data, ext);
+        async move { update.await.map(Some) }
+    }
+}
+
+/// Partial updates of `PATCH /api/v1/:name/:id`, implemented for all entities that implement
+/// [`Get`] and [`Update`].
+pub trait Patch<S: ContextTrait>: Get<S> + Update<S> {
+    /// Fetch the entity, replace the top-level fields of its [`Update`](EntityBase::Update) data
+    /// with the keys of `patch` and persist it using [`Update::update`], or
+    /// [`Update::update_if_version`] if `versions` are given.
+    ///
+    /// Fields are replaced as a whole, e.g. a [`Json`](crate::property::Json) field is not merged
+    /// with its previous value. Returns `None` if the entity does not exist.
+    fn patch(
+        id: &<Self as EntityBase<S>>::Id,
+        patch: serde_json::Value,
+        versions: Option<Vec<String>>,
+        get_ext: <Self as Get<S>>::RequestExt,
+        update_ext: <Self as Update<S>>::RequestExt,
+    ) -> impl Future<Output = Result<Option<Self>, PatchError<Self, S>>> + Send {
+        let id = id.clone();
+        async move {
+            let Some(entity) = Self::get(&id, get_ext).await.map_err(PatchError::Get)? else {
+                return Ok(None);
+            };
+            let data = merge_patch::<Self, S>(&entity, patch).map_err(PatchError::Invalid)?;
+            data.validate().map_err(PatchError::Validation)?;
+            let Some(versions) = versions else {
+                let entity = Self::update(&id, data, update_ext)
+                    .await
+                    .map_err(PatchError::Update)?;
+                return Ok(Some(entity));
+            };
+            // the patch is merged into this version
+            if !version_matches(&entity, &versions) {
+                return Err(PatchError::VersionChanged);
+            }
+            match Self::update_if_version(&id, data, &versions, update_ext).await {
+                Ok(Some(entity)) => Ok(Some(entity)),
+                Ok(None) => Err(PatchError::VersionChanged),
+                Err(e) => Err(PatchError::Update(e)),
+            }
+        }
+    }
+}
+
+impl<E: Get<S> + Update<S>, S: ContextTrait> Patch<S> for E {}
+
+/// Error of [`Patch::patch`]
+pub enum PatchError<E: Get<S> + Update<S>, S: ContextTrait> {
+    Get(<E as Get<S>>::Error),
+    Update(<E as Update<S>>::Error),
+    /// `patch` is not an object or the merged data is not a valid [`Update`](EntityBase::Update)
+    Invalid(serde_json::Error),
+    /// the merged data is rejected by [`Inputs::validate`]
+    Validation(ValidationErrors),
+    /// the entity does not have one of the expected versions, see [`Update::update_if_version`]
+    VersionChanged,
+}
+
+/// whether the [version token](EntityBase::version_token) of `entity` is one of `versions`,
+/// always `true` for entities without a version token
+pub fn version_matches<E: EntityBase<S>, S: ContextTrait>(entity: &E, versions: &[String]) -> bool {
+    match entity.version_token() {
+        Some(current) => versions.contains(&current),
+        None => true,
+    }
+}
+
+/// Update the row of `model` with the primary key `id` in a transaction if the stored entity has
+/// one of the [version tokens](EntityBase::version_token) `versions`, see
+/// [`Update::update_if_version`]. The row is locked before it is compared, so it can't be changed
+/// until it is updated. Returns `None` if the row does not exist or has another version.
+///
+/// ```rust,ignore
+/// impl Update<Ctx> for Post {
+///     // ...
+///     async fn update_if_version(
+///         id: &i64,
+///         mut data: Self,
+///         versions: &[String],
+///         ext: Self::RequestExt,
+///     ) -> Result<Option<Self>, Self::Error> {
+///         data.id = *id;
+///         data.version += 1;
+///         Ok(entity::update_if_version::<Self, Ctx>(*id, data, versions, ext.ext()).await?)
+///     }
+/// }
+/// ```
+pub async fn update_if_version<M, S>(
+    id: impl for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB> + Clone + Send,
+    model: M,
+    versions: &[String],
+    db: &sqlx::Pool<DB>,
+) -> Result<Option<M>, ormlite::Error>
+where
+    M: Model<DB> + EntityBase<S>,
+    S: ContextTrait,
+{
+    let mut tx = db.begin().await?;
+    let pk = M::primary_key().unwrap_or("id");
+    // an update that changes nothing locks the row until the end of the transaction
+    let locked = ormlite::query(&format!(
+        "UPDATE \"{}\" SET \"{pk}\" = \"{pk}\" WHERE \"{pk}\" = ?",
+        M::table_name()
+    ))
+    .bind(id.clone())
+    .execute(&mut *tx)
+    .await?;
+    if locked.rows_affected() == 0 {
+        return Ok(None);
+    }
+    let stored = M::fetch_one(id, &mut *tx).await?;
+    if !version_matches(&stored, versions) {
+        return Ok(None);
+    }
+    let model = model.update_all_fields(&mut *tx).await?;
+    tx.commit().await?;
+    Ok(Some(model))
+}
+
+/// the [`Update`](EntityBase::Update) data of `entity` with the top-level fields in `patch` replaced
+pub fn merge_patch<E: EntityBase<S>, S: ContextTrait>(
+    entity: &E,
+    patch: serde_json::Value,
+) -> Result<E::Update, serde_json::Error> {
+    let serde_json::Value::Object(patch) = patch else {
+        return Err(serde::de::Error::custom("expected an object"));
+    };
+    let mut data = serde_json::to_value(entity.to_update().borrow())?;
+    if let serde_json::Value::Object(fields) = &mut data {
+        fields.extend(patch);
+    }
+    serde_json::from_value(data)
 }
 
 pub trait Delete<S: ContextTrait>: EntityBase<S> {
@@ -89,3 +892,297 @@ pub trait Delete<S: ContextTrait>: EntityBase<S> {
         ext: Self::RequestExt,
     ) -> impl Future<Output = Result<(), Self::Error>> + Send;
 }
+
+/// Entities that are moved to a trash instead of being deleted, registered using
+/// [`App::entity_soft_delete`](crate::App::entity_soft_delete).
+///
+/// [`Delete::delete`] should set the `deleted_at` timestamp of the entity, e.g. using
+/// [`soft_delete_row`], and [`Get`] and [`List`] should exclude soft-deleted entities, e.g. using
+/// [`not_deleted_query`]. The trash page at `/:name-plural/trash` lists the entities of
+/// [`SoftDelete::list_deleted`] with a button to [restore](SoftDelete::restore) them.
+///
+/// ```rust,ignore
+/// impl Delete<Ctx> for Post {
+///     // ...
+///     async fn delete(id: &Uuid, ext: Self::RequestExt) -> Result<(), Self::Error> {
+///         Ok(entity::soft_delete_row::<Self>(*id, ext.ext()).await?)
+///     }
+/// }
+///
+/// impl SoftDelete<Ctx> for Post {
+///     type RequestExt = State<Ctx>;
+///     type Error = MyError;
+///
+///     async fn list_deleted(ext: Self::RequestExt) -> Result<impl IntoIterator<Item = Self>, Self::Error> {
+///         Ok(entity::deleted_query::<Self>().fetch_all(ext.ext()).await?)
+///     }
+///     async fn restore(id: &Uuid, ext: Self::RequestExt) -> Result<(), Self::Error> {
+///         Ok(entity::restore_row::<Self>(*id, ext.ext()).await?)
+///     }
+/// }
+/// ```
+pub trait SoftDelete<S: ContextTrait>: EntityBase<S> {
+    type RequestExt: FromRequestParts<S> + Send + Sync + Clone;
+    type Error: Into<AppError> + Serialize + Send;
+
+    /// the soft-deleted entities, listed on the trash page
+    fn list_deleted(
+        ext: Self::RequestExt,
+    ) -> impl Future<Output = Result<impl IntoIterator<Item = Self>, Self::Error>> + Send;
+
+    /// clear the `deleted_at` timestamp of the entity `id`
+    fn restore(
+        id: &<Self as EntityBase<S>>::Id,
+        ext: Self::RequestExt,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+/// Select the entities of `M` that are not [soft-deleted](SoftDelete), i.e. where `deleted_at` is `NULL`.
+pub fn not_deleted_query<'a, M>() -> SelectQueryBuilder<'a, DB, M>
+where
+    M: Model<DB> + for<'r> FromRow<'r, <DB as Database>::Row> + Send + Sync + Unpin + 'static,
+{
+    M::select().where_("deleted_at IS NULL")
+}
+
+/// Select the [soft-deleted](SoftDelete) entities of `M`, i.e. where `deleted_at` is not `NULL`.
+pub fn deleted_query<'a, M>() -> SelectQueryBuilder<'a, DB, M>
+where
+    M: Model<DB> + for<'r> FromRow<'r, <DB as Database>::Row> + Send + Sync + Unpin + 'static,
+{
+    M::select().where_("deleted_at IS NOT NULL")
+}
+
+/// Set `deleted_at` of the row with the primary key `id` to the current time.
+pub async fn soft_delete_row<'c, M: Model<DB>>(
+    id: impl for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB> + Send,
+    db: impl Executor<'c, Database = DB>,
+) -> Result<(), ormlite::Error> {
+    set_deleted_at::<M>(id, Some(Utc::now()), db).await
+}
+
+/// Set `deleted_at` of the row with the primary key `id` to `NULL`.
+pub async fn restore_row<'c, M: Model<DB>>(
+    id: impl for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB> + Send,
+    db: impl Executor<'c, Database = DB>,
+) -> Result<(), ormlite::Error> {
+    set_deleted_at::<M>(id, None, db).await
+}
+
+async fn set_deleted_at<'c, M: Model<DB>>(
+    id: impl for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB> + Send,
+    deleted_at: Option<DateTime<Utc>>,
+    db: impl Executor<'c, Database = DB>,
+) -> Result<(), ormlite::Error> {
+    let pk = M::primary_key().unwrap_or("id");
+    ormlite::query(&format!(
+        "UPDATE \"{}\" SET deleted_at = ? WHERE \"{pk}\" = ?",
+        M::table_name()
+    ))
+    .bind(deleted_at)
+    .bind(id)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Entities with a history of versions, registered using
+/// [`App::entity_versioned`](crate::App::entity_versioned).
+///
+/// Each version is a row with the columns `entity_id`, `version_id`, `created_at` and
+/// `published`. [`Update::update`] should insert a new version instead of changing the row, and
+/// [`Get`] and [`List`] should only return the published version, e.g. using
+/// [`published_query`]. The versions page at `/:name/:id/versions` lists the
+/// [versions](Versioned::versions) with a button to [publish](Versioned::publish) each of them,
+/// the edit form shows a version using `/:name/:id?version=:version_id`.
+///
+/// ```rust,ignore
+/// #[derive(Debug, Deserialize, Serialize, Entity, Model, TS)]
+/// struct Page {
+///     #[cms(id, skip_input)]
+///     #[serde(default = "Uuid::new_v4")]
+///     entity_id: Uuid,
+///     #[cms(skip_input, skip_column)]
+///     #[ormlite(primary_key)]
+///     #[serde(default = "Uuid::new_v4")]
+///     version_id: Uuid,
+///     #[cms(skip_input, skip_column)]
+///     #[serde(default = "Utc::now")]
+///     created_at: DateTime<Utc>,
+///     #[cms(skip_input, skip_column)]
+///     #[serde(default)]
+///     published: bool,
+///     title: Text,
+/// }
+///
+/// impl Update<Ctx> for Page {
+///     // ...
+///     async fn update(id: &Uuid, data: Self, ext: Self::RequestExt) -> Result<Self, Self::Error> {
+///         let draft = Page { entity_id: *id, version_id: Uuid::new_v4(), created_at: Utc::now(), published: false, ..data };
+///         Ok(draft.insert(ext.ext()).await?)
+///     }
+/// }
+///
+/// impl Versioned<Ctx> for Page {
+///     type VersionId = Uuid;
+///     type RequestExt = State<Ctx>;
+///     type Error = MyError;
+///
+///     async fn versions(id: &Uuid, ext: Self::RequestExt) -> Result<Vec<Version<Uuid>>, Self::Error> {
+///         Ok(entity::list_versions::<Self, _>(*id, ext.ext()).await?)
+///     }
+///     async fn get_version(id: &Uuid, version: &Uuid, ext: Self::RequestExt) -> Result<Option<Self>, Self::Error> {
+///         Ok(Self::select().where_("entity_id = ?").bind(id).where_("version_id = ?").bind(version)
+///             .fetch_optional(ext.ext()).await?)
+///     }
+///     async fn publish(id: &Uuid, version: &Uuid, ext: Self::RequestExt) -> Result<(), Self::Error> {
+///         Ok(entity::publish_version::<Self>(*id, *version, ext.ext()).await?)
+///     }
+/// }
+/// ```
+pub trait Versioned<S: ContextTrait>: EntityBase<S> {
+    type VersionId: for<'de> Deserialize<'de> + Clone + Display + Serialize + Send + Sync;
+    type RequestExt: FromRequestParts<S> + Send + Sync + Clone;
+    type Error: Into<AppError> + Serialize + Send;
+
+    /// all versions of the entity `id`, newest first
+    fn versions(
+        id: &<Self as EntityBase<S>>::Id,
+        ext: Self::RequestExt,
+    ) -> impl Future<Output = Result<Vec<Version<Self::VersionId>>, Self::Error>> + Send;
+
+    /// the entity `id` at `version`, `None` if it does not exist
+    fn get_version(
+        id: &<Self as EntityBase<S>>::Id,
+        version: &Self::VersionId,
+        ext: Self::RequestExt,
+    ) -> impl Future<Output = Result<Option<Self>, Self::Error>> + Send;
+
+    /// make `version` the published version of the entity `id`
+    fn publish(
+        id: &<Self as EntityBase<S>>::Id,
+        version: &Self::VersionId,
+        ext: Self::RequestExt,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+/// A version of a [`Versioned`] entity.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Version<V> {
+    pub id: V,
+    pub created_at: DateTime<Utc>,
+    /// whether this is the version returned by [`Get`] and [`List`]
+    pub published: bool,
+}
+
+/// Select the published [versions](Versioned) of `M`, i.e. where `published` is true.
+pub fn published_query<'a, M>() -> SelectQueryBuilder<'a, DB, M>
+where
+    M: Model<DB> + for<'r> FromRow<'r, <DB as Database>::Row> + Send + Sync + Unpin + 'static,
+{
+    M::select().where_("published")
+}
+
+/// The [versions](Versioned) of the entity `entity_id` of `M`, newest first.
+pub async fn list_versions<'c, M: Model<DB>, V>(
+    entity_id: impl for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB> + Send,
+    db: impl Executor<'c, Database = DB>,
+) -> Result<Vec<Version<V>>, ormlite::Error>
+where
+    V: for<'r> sqlx::Decode<'r, DB> + sqlx::Type<DB> + Send + Unpin,
+{
+    let rows: Vec<(V, DateTime<Utc>, bool)> = ormlite::query_as(&format!(
+        "SELECT version_id, created_at, published FROM \"{}\" WHERE entity_id = ? ORDER BY created_at DESC",
+        M::table_name()
+    ))
+    .bind(entity_id)
+    .fetch_all(db)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(id, created_at, published)| Version {
+            id,
+            created_at,
+            published,
+        })
+        .collect())
+}
+
+/// Publish the version `version_id` of the entity `entity_id` of `M` and unpublish all other versions.
+pub async fn publish_version<'c, M: Model<DB>>(
+    entity_id: impl for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB> + Send,
+    version_id: impl for<'q> sqlx::Encode<'q, DB> + sqlx::Type<DB> + Send,
+    db: impl Executor<'c, Database = DB>,
+) -> Result<(), ormlite::Error> {
+    ormlite::query(&format!(
+        "UPDATE \"{}\" SET published = (version_id = ?) WHERE entity_id = ?",
+        M::table_name()
+    ))
+    .bind(version_id)
+    .bind(entity_id)
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::{to_bytes, Body},
+        http::Request,
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn get_id(uri: &str) -> (StatusCode, String) {
+        let app = Router::new().route(
+            "/page/:id",
+            get(|IdPath(id): IdPath<String>| async move { id }),
+        );
+        let req = Request::get(uri).body(Body::empty()).unwrap();
+        let res = app.oneshot(req).await.unwrap();
+        let status = res.status();
+        let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn id_path_round_trips_slug_with_spaces() {
+        let path = edit_path("Page", "hello big world");
+        assert_eq!(path, "page/hello%20big%20world");
+        let (status, id) = get_id(&format!("/{path}")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(id, "hello big world");
+    }
+
+    #[tokio::test]
+    async fn id_path_rejects_slash() {
+        let (status, body) = get_id(&format!("/{}", edit_path("Page", "a/b"))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, IdPathRejection::Slash("a/b".to_string()).to_string());
+    }
+
+    fn page(limit: Option<u64>, offset: Option<u64>) -> (Vec<u32>, u64) {
+        let params = ListParams {
+            limit,
+            offset,
+            ..Default::default()
+        };
+        params.apply(1..=5)
+    }
+
+    #[test]
+    fn list_params_select_a_page_and_count_all_items() {
+        assert_eq!(page(None, None), (vec![1, 2, 3, 4, 5], 5));
+        assert_eq!(page(Some(2), None), (vec![1, 2], 5));
+        assert_eq!(page(Some(2), Some(2)), (vec![3, 4], 5));
+        assert_eq!(page(Some(2), Some(4)), (vec![5], 5));
+        assert_eq!(page(None, Some(3)), (vec![4, 5], 5));
+        assert_eq!(page(Some(0), None), (vec![], 5));
+        assert_eq!(page(Some(2), Some(5)), (vec![], 5));
+        assert_eq!(page(Some(u64::MAX), Some(u64::MAX)), (vec![], 5));
+    }
+}
@@ -0,0 +1,389 @@
+//! Filters of `GET /api/v1/:name-plural` by the fields marked with `#[cms(filter)]`, e.g.
+//! `?draft=false&date__gte=2024-01-01T00:00:00Z`.
+//!
+//! Keys are the serde names of fields, optionally followed by an operator: `__ne`, `__lt`,
+//! `__lte`, `__gt` or `__gte`. Values are parsed according to the type of the field, see
+//! [`Filterable`]. Unknown keys and invalid values are rejected with HTTP 400.
+//!
+//! The default implementation of [`List::list_paginated`](crate::entity::List::list_paginated)
+//! filters the result of [`List::list`](crate::entity::List::list). Use [`filter_query`] to filter
+//! in the database instead:
+//!
+//! ```rust,ignore
+//! async fn list_paginated(params: ListParams, ext: Self::RequestExt) -> Result<(Vec<Self>, u64), Self::Error> {
+//!     let select = filter::filter_query(Self::select(), &params.filter);
+//!     Ok(params.apply(select.fetch_all(ext.ext()).await?))
+//! }
+//! ```
+//!
+//! The search box of the list page is a [`Filter`] as well, see [`Filter::search`], so the
+//! entities it finds are the ones [`List::list_paginated`](crate::entity::List::list_paginated)
+//! returns, e.g. without soft-deleted entities.
+
+use std::cmp::Ordering;
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use ormlite::{query_builder::SelectQueryBuilder, Database, FromRow, Model};
+use serde::Serialize;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    context::ContextTrait,
+    entity::EntityBase,
+    property::{Email, Markdown, Text, Url},
+    DB,
+};
+
+/// Comparison of a [`Condition`], the suffix of the key in the query string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterOp {
+    /// no suffix
+    Eq,
+    /// `__ne`
+    Ne,
+    /// `__lt`
+    Lt,
+    /// `__lte`
+    Lte,
+    /// `__gt`
+    Gt,
+    /// `__gte`
+    Gte,
+}
+
+impl FilterOp {
+    const SUFFIXES: [(&'static str, Self); 5] = [
+        ("__ne", Self::Ne),
+        ("__lte", Self::Lte),
+        ("__lt", Self::Lt),
+        ("__gte", Self::Gte),
+        ("__gt", Self::Gt),
+    ];
+
+    /// split `key` into the name of the field and the operator
+    fn parse(key: &str) -> (&str, Self) {
+        Self::SUFFIXES
+            .iter()
+            .find_map(|(suffix, op)| Some((key.strip_suffix(suffix)?, *op)))
+            .unwrap_or((key, Self::Eq))
+    }
+
+    /// the SQL operator
+    pub fn sql(self) -> &'static str {
+        match self {
+            Self::Eq => "=",
+            Self::Ne => "<>",
+            Self::Lt => "<",
+            Self::Lte => "<=",
+            Self::Gt => ">",
+            Self::Gte => ">=",
+        }
+    }
+
+    /// whether a value that compares to the value of the filter as `ordering` matches,
+    /// `false` if they are not comparable
+    pub fn matches(self, ordering: Option<Ordering>) -> bool {
+        let Some(ordering) = ordering else {
+            return false;
+        };
+        match self {
+            Self::Eq => ordering.is_eq(),
+            Self::Ne => ordering.is_ne(),
+            Self::Lt => ordering.is_lt(),
+            Self::Lte => ordering.is_le(),
+            Self::Gt => ordering.is_gt(),
+            Self::Gte => ordering.is_ge(),
+        }
+    }
+}
+
+/// The value of a [`Condition`], parsed according to the type of the field.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterValue {
+    Text(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    DateTime(DateTime<Utc>),
+    Uuid(Uuid),
+}
+
+/// Types of fields that can be marked with `#[cms(filter)]`.
+pub trait Filterable {
+    /// parse the value of a filter in the query string, `None` if it is invalid
+    fn parse_filter(value: &str) -> Option<FilterValue>;
+    /// compare `self` to a value returned by [`Filterable::parse_filter`]
+    fn compare(&self, value: &FilterValue) -> Option<Ordering>;
+}
+
+macro_rules! filterable_text {
+    ($($t:ty),*) => {
+        $(
+            impl Filterable for $t {
+                fn parse_filter(value: &str) -> Option<FilterValue> {
+                    Some(FilterValue::Text(value.to_string()))
+                }
+                fn compare(&self, value: &FilterValue) -> Option<Ordering> {
+                    match value {
+                        FilterValue::Text(v) => Some(str::cmp(self, v)),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+filterable_text!(String, Text, Markdown, Email, Url);
+
+macro_rules! filterable_int {
+    ($($t:ty),*) => {
+        $(
+            impl Filterable for $t {
+                fn parse_filter(value: &str) -> Option<FilterValue> {
+                    let value = value.parse::<$t>().ok()?;
+                    Some(FilterValue::Integer(i64::try_from(value).ok()?))
+                }
+                fn compare(&self, value: &FilterValue) -> Option<Ordering> {
+                    match value {
+                        FilterValue::Integer(v) => Some(i128::from(*self).cmp(&i128::from(*v))),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+filterable_int!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+macro_rules! filterable_float {
+    ($($t:ty),*) => {
+        $(
+            impl Filterable for $t {
+                fn parse_filter(value: &str) -> Option<FilterValue> {
+                    let value = value.parse::<$t>().ok().filter(|v| v.is_finite())?;
+                    Some(FilterValue::Float(value.into()))
+                }
+                fn compare(&self, value: &FilterValue) -> Option<Ordering> {
+                    match value {
+                        FilterValue::Float(v) => f64::from(*self).partial_cmp(v),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+filterable_float!(f32, f64);
+
+impl Filterable for bool {
+    fn parse_filter(value: &str) -> Option<FilterValue> {
+        Some(FilterValue::Bool(value.parse().ok()?))
+    }
+    fn compare(&self, value: &FilterValue) -> Option<Ordering> {
+        match value {
+            FilterValue::Bool(v) => Some(self.cmp(v)),
+            _ => None,
+        }
+    }
+}
+
+impl Filterable for DateTime<Utc> {
+    /// RFC 3339, e.g. `2024-01-01T00:00:00Z`
+    fn parse_filter(value: &str) -> Option<FilterValue> {
+        let value = DateTime::parse_from_rfc3339(value).ok()?;
+        Some(FilterValue::DateTime(value.to_utc()))
+    }
+    fn compare(&self, value: &FilterValue) -> Option<Ordering> {
+        match value {
+            FilterValue::DateTime(v) => Some(self.cmp(v)),
+            _ => None,
+        }
+    }
+}
+
+impl Filterable for Uuid {
+    fn parse_filter(value: &str) -> Option<FilterValue> {
+        Some(FilterValue::Uuid(value.parse().ok()?))
+    }
+    fn compare(&self, value: &FilterValue) -> Option<Ordering> {
+        match value {
+            FilterValue::Uuid(v) => Some(self.cmp(v)),
+            _ => None,
+        }
+    }
+}
+
+/// `None` matches no condition
+impl<T: Filterable> Filterable for Option<T> {
+    fn parse_filter(value: &str) -> Option<FilterValue> {
+        T::parse_filter(value)
+    }
+    fn compare(&self, value: &FilterValue) -> Option<Ordering> {
+        self.as_ref()?.compare(value)
+    }
+}
+
+/// A field marked with `#[cms(filter)]`, see [`EntityBase::filter_field`].
+#[derive(Clone, Copy, Debug)]
+pub struct FilterField {
+    /// name of the database column
+    pub column: &'static str,
+    pub parse: fn(&str) -> Option<FilterValue>,
+}
+
+/// A condition on a field, e.g. `date__gte=2024-01-01T00:00:00Z`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    /// serde name of the field
+    pub field: String,
+    /// name of the database column
+    pub column: &'static str,
+    pub op: FilterOp,
+    pub value: FilterValue,
+}
+
+impl Condition {
+    /// whether `value`, the value of the field of an entity, matches this condition
+    pub fn matches(&self, value: &impl Filterable) -> bool {
+        self.op.matches(value.compare(&self.value))
+    }
+}
+
+/// The query entered in the search box of the list page.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Search {
+    /// lowercase query
+    pub query: String,
+    /// names of the database columns marked with `#[cms(searchable)]`
+    pub columns: &'static [&'static str],
+}
+
+/// All conditions of a request, which have to match.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    pub conditions: Vec<Condition>,
+    /// one of the searchable fields has to contain the query
+    pub search: Option<Search>,
+}
+
+#[derive(Debug, Error, Serialize)]
+#[serde(tag = "error", rename_all = "snake_case")]
+pub enum FilterError {
+    #[error("unknown filter {key:?}")]
+    UnknownFilter { key: String },
+    #[error("invalid value {value:?} of filter {key:?}")]
+    InvalidValue { key: String, value: String },
+}
+
+impl IntoResponse for FilterError {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, Json(self)).into_response()
+    }
+}
+
+impl Filter {
+    /// Parse the `key=value` pairs of a query string into conditions on the fields of `E`.
+    pub fn parse<E: EntityBase<S>, S: ContextTrait>(
+        pairs: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, FilterError> {
+        let conditions = pairs
+            .into_iter()
+            .map(|(key, value)| {
+                let (field, op) = FilterOp::parse(&key);
+                let Some(filter_field) = E::filter_field(field) else {
+                    return Err(FilterError::UnknownFilter { key });
+                };
+                let Some(parsed) = (filter_field.parse)(&value) else {
+                    return Err(FilterError::InvalidValue { key, value });
+                };
+                Ok(Condition {
+                    field: field.to_string(),
+                    column: filter_field.column,
+                    op,
+                    value: parsed,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            conditions,
+            search: None,
+        })
+    }
+
+    /// The entities of `E` where one of the fields marked with `#[cms(searchable)]` contains
+    /// `query`, ignoring case.
+    pub fn search<E: EntityBase<S>, S: ContextTrait>(query: &str) -> Self {
+        Self {
+            conditions: Vec::new(),
+            search: Some(Search {
+                query: query.to_lowercase(),
+                columns: E::searchable_columns(),
+            }),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty() && self.search.is_none()
+    }
+
+    /// whether `entity` matches all conditions and the search
+    pub fn matches<E: EntityBase<S>, S: ContextTrait>(&self, entity: &E) -> bool {
+        let search = match &self.search {
+            Some(search) => entity.matches_search(&search.query),
+            None => true,
+        };
+        search && self.conditions.iter().all(|c| entity.matches_filter(c))
+    }
+}
+
+/// Add the conditions and the search of `filter` to `select`, binding the values with their
+/// types. The search compares the lowercase columns using `LIKE`.
+pub fn filter_query<'a, E>(
+    mut select: SelectQueryBuilder<'a, DB, E>,
+    filter: &Filter,
+) -> SelectQueryBuilder<'a, DB, E>
+where
+    E: Model<DB> + for<'r> FromRow<'r, <DB as Database>::Row> + Send + Sync + Unpin + 'static,
+{
+    for c in &filter.conditions {
+        select = select.dangerous_where(&format!(r#""{}" {} ?"#, c.column, c.op.sql()));
+        select = match c.value.clone() {
+            FilterValue::Text(v) => select.bind(v),
+            FilterValue::Integer(v) => select.bind(v),
+            FilterValue::Float(v) => select.bind(v),
+            FilterValue::Bool(v) => select.bind(v),
+            FilterValue::DateTime(v) => select.bind(v),
+            FilterValue::Uuid(v) => select.bind(v),
+        };
+    }
+    if let Some(search) = filter.search.as_ref().filter(|s| !s.columns.is_empty()) {
+        let clause = search
+            .columns
+            .iter()
+            .map(|c| format!(r#"LOWER("{c}") LIKE ? ESCAPE '\'"#))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let pattern = format!(
+            "%{}%",
+            search
+                .query
+                .replace('\\', r"\\")
+                .replace('%', r"\%")
+                .replace('_', r"\_")
+        );
+        select = select.dangerous_where(&format!("({clause})"));
+        for _ in search.columns {
+            select = select.bind(pattern.clone());
+        }
+    }
+    select
+}
@@ -0,0 +1,169 @@
+//! Forms that create several related entities at once, see [`FormGroup`].
+
+use std::future::Future;
+
+use axum::extract::FromRequestParts;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    app::AppError,
+    auth::{Action, User},
+    context::ContextTrait,
+    entity::EntityScope,
+    input::Inputs,
+};
+
+/// A form spanning several entities, e.g. a new `Post` together with its initial `Tag`s, that are
+/// created at once. Registered using [`App::form_group`](crate::App::form_group), which serves
+/// the form at `/forms/:name` and links it in the sidebar.
+///
+/// The fields of the form are the fields of `Self`, which are parsed like the form of an entity,
+/// including uploads. The form is only served to users who may perform all
+/// [`FormGroup::actions`], see [`App::entity_with_perms`](crate::App::entity_with_perms).
+/// [`FormGroup::create`] receives the complete payload and should create all
+/// entities in a single transaction, so either all or none of them are created.
+///
+/// ```rust,ignore
+/// #[derive(Debug, Deserialize, Input)]
+/// struct PostWithTags {
+///     title: Text,
+///     tags: Vec<Text>,
+/// }
+///
+/// impl FormGroup<Ctx> for PostWithTags {
+///     type RequestExt = State<Ctx>;
+///     type Error = MyError;
+///
+///     fn name() -> &'static str {
+///         "Post with tags"
+///     }
+///
+///     fn actions() -> &'static [(&'static str, Action)] {
+///         &[("Post", Action::Create), ("Tag", Action::Create)]
+///     }
+///
+///     async fn create(self, ext: Self::RequestExt) -> Result<String, Self::Error> {
+///         let mut tx = ext.ext().begin().await?;
+///         let post = Post { id: Uuid::new_v4(), title: self.title }.insert(&mut *tx).await?;
+///         for name in self.tags {
+///             Tag { id: Uuid::new_v4(), post: post.id, name }.insert(&mut *tx).await?;
+///         }
+///         tx.commit().await?;
+///         Ok(format!("post/{}", post.id))
+///     }
+/// }
+///
+/// App::new().form_group::<PostWithTags>()
+/// ```
+pub trait FormGroup<S: ContextTrait>: Inputs<S> + DeserializeOwned + Send + 'static {
+    type RequestExt: FromRequestParts<S> + Send;
+    type Error: Into<AppError> + Send;
+
+    /// title of the form, converted to kebab-case for its URL
+    fn name() -> &'static str;
+
+    /// the [`Action`]s performed by [`FormGroup::create`] by the [name](crate::EntityBase::name)
+    /// of the entity. Requests are rejected with HTTP 403 unless the [`User`] may perform each of
+    /// them in every [scope](EntityScope) the entity is registered in.
+    fn actions() -> &'static [(&'static str, Action)];
+
+    /// Create all entities of the submitted form, e.g. in a transaction that is rolled back if
+    /// any of them fails. Returns the path to redirect to, relative to the
+    /// [base path](ContextTrait::base_path), e.g. the edit page of the main entity.
+    ///
+    /// The [API cache](crate::cache) is cleared afterwards, as the created entities are unknown.
+    fn create(
+        self,
+        ext: Self::RequestExt,
+    ) -> impl Future<Output = Result<String, Self::Error>> + Send;
+}
+
+/// Whether `user` may perform all [`FormGroup::actions`] of `G` on the registered `entities`,
+/// see [`EntityScope::allows_user`].
+pub(crate) fn allows<G: FormGroup<S>, S: ContextTrait>(
+    entities: &[EntityScope],
+    user: Option<&User>,
+) -> bool {
+    G::actions().iter().all(|(name, action)| {
+        entities
+            .iter()
+            .filter(|scope| scope.name() == *name)
+            .all(|scope| scope.allows_user(user, *action))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::{context::Context, input::InputInfo, property::Text};
+
+    type Ctx = Context<()>;
+
+    #[derive(Debug, Deserialize)]
+    struct PostWithTags {
+        title: Text,
+    }
+
+    impl<S: ContextTrait> Inputs<S> for PostWithTags {
+        fn inputs(value: Option<&Self>) -> impl IntoIterator<Item = InputInfo<'_, S>> {
+            [InputInfo::new(
+                "title",
+                "Title",
+                Box::new(value.map(|v| &v.title)),
+            )]
+        }
+    }
+
+    impl FormGroup<Ctx> for PostWithTags {
+        type RequestExt = ();
+        type Error = AppError;
+
+        fn name() -> &'static str {
+            "Post with tags"
+        }
+
+        fn actions() -> &'static [(&'static str, Action)] {
+            &[("Post", Action::Create), ("Tag", Action::Create)]
+        }
+
+        async fn create(self, _ext: ()) -> Result<String, AppError> {
+            Ok(format!("post/{}", self.title.0))
+        }
+    }
+
+    fn editors_create_tags(user: &User, action: Action) -> bool {
+        action != Action::Create || user.has_role("editor")
+    }
+
+    fn allows(entities: &[EntityScope], user: Option<&User>) -> bool {
+        super::allows::<PostWithTags, Ctx>(entities, user)
+    }
+
+    #[test]
+    fn requires_all_actions_in_every_scope() {
+        let editor = User::new("1", "Editor").with_role("editor");
+        let author = User::new("2", "Author");
+        let entities = [
+            EntityScope::new("Post", "Posts"),
+            EntityScope::new("Tag", "Tags").with_permissions(editors_create_tags),
+            EntityScope::new("Comment", "Comments").with_permissions(|_, _| false),
+        ];
+        assert!(allows(&entities, Some(&editor)));
+        assert!(!allows(&entities, Some(&author)));
+        assert!(!allows(&entities, None));
+
+        // the same entity in a second scope with stricter permissions
+        let tenant = EntityScope::new("Post", "Posts")
+            .with_prefix("tenant-a")
+            .with_permissions(|_, action| action == Action::List);
+        assert!(!allows(&[entities[0].clone(), tenant], Some(&editor)));
+    }
+
+    #[test]
+    fn allows_entities_without_permissions() {
+        assert!(allows(&[EntityScope::new("Post", "Posts")], None));
+        assert!(allows(&[], None));
+    }
+}
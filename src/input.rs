@@ -3,8 +3,9 @@ use std::fmt::Debug;
 pub use derived_cms_derive::Input;
 use i18n_embed::fluent::FluentLanguageLoader;
 use maud::Markup;
+use thiserror::Error;
 
-use crate::{context::ContextTrait, render::FormRenderContext};
+use crate::{context::ContextTrait, render::FormRenderContext, validate::ValidationErrors};
 
 /// A property of an entity or nested within another property that can be input in a HTML form
 pub trait Input<S: ContextTrait>: Debug {
@@ -16,6 +17,75 @@ pub trait Input<S: ContextTrait>: Debug {
         ctx: &FormRenderContext<'_, S>,
         i18n: &FluentLanguageLoader,
     ) -> Markup;
+
+    /// Render the input of an [`Option<Self>`], where `value` is `None` if the option is `None`.
+    /// Defaults to [`Input::render_input`] of `Self` that is not required.
+    fn render_input_optional(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        ctx: &FormRenderContext<'_, S>,
+        i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        Self::render_input(value, name, name_human, false, ctx, i18n)
+    }
+
+    /// whether the label of a [`required`](InputInfo::required) field is marked as required.
+    /// Should be `false` if the input always has a valid value, e.g. a checkbox.
+    /// Default: `true`
+    fn mark_required() -> bool {
+        true
+    }
+
+    /// `Some(accept)` if this is a file input, e.g. [`File`](crate::property::File). Lists of it
+    /// can then be extended by selecting multiple files at once, with `accept` as the `accept`
+    /// attribute of the file picker, e.g. `"image/*"`.
+    /// Default: `None`
+    fn multiple_files() -> Option<&'static str> {
+        None
+    }
+
+    /// `Some(key)` if the input of an unset [`Option<Self>`] submits an empty value named
+    /// `{name}[{key}]`, e.g. the tag of an enum. It is then omitted, such that the option is
+    /// deserialized as `None`.
+    /// Default: `None`
+    fn optional_key() -> Option<&'static str> {
+        None
+    }
+
+    /// whether the submitted value is a secret, e.g. a [`Password`](crate::property::Password).
+    /// Secrets are not sent back to the browser to restore a form that could not be saved.
+    /// Default: `false`
+    fn secret() -> bool {
+        false
+    }
+
+    /// Check the submitted value of the input named `name` against its `constraints`, e.g. the
+    /// [`max_length`](InputConstraints::max_length) of a text. Inputs containing other inputs,
+    /// e.g. lists or structs, check each of them against the constraints of its own input.
+    /// Default: `Ok(())`
+    fn check_constraints(
+        &self,
+        _name: &str,
+        _constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        Ok(())
+    }
+}
+
+/// A struct whose fields are rendered as the inputs of a form, e.g. an [`Entity`](crate::Entity)
+/// or its [`Create`](crate::EntityBase::Create)/[`Update`](crate::EntityBase::Update) type.
+///
+/// Implemented by `#[derive(Entity)]` and `#[derive(Input)]` for `struct`s.
+pub trait Inputs<S: ContextTrait> {
+    fn inputs(value: Option<&Self>) -> impl IntoIterator<Item = InputInfo<'_, S>>;
+
+    /// Check the submitted data before it is created or updated, see [`validate`](crate::validate).
+    /// Generated from the `#[cms(validate = "...")]` attributes of the fields when deriving.
+    /// Default: `Ok(())`
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        Ok(())
+    }
 }
 
 /// object safe trait that is automatically implemented for [`Option<T>`] where `T` implements [`Input`]
@@ -28,6 +98,22 @@ pub trait DynInput<S: ContextTrait>: Debug {
         ctx: &FormRenderContext<'_, S>,
         i18n: &FluentLanguageLoader,
     ) -> Markup;
+
+    /// see [`Input::mark_required`]
+    fn mark_required(&self) -> bool;
+
+    /// see [`Input::optional_key`]
+    fn optional_key(&self) -> Option<&'static str>;
+
+    /// see [`Input::secret`]
+    fn secret(&self) -> bool;
+
+    /// see [`Input::check_constraints`]
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError>;
 }
 
 impl<T: Input<S>, S: ContextTrait> DynInput<S> for Option<&T> {
@@ -41,6 +127,29 @@ impl<T: Input<S>, S: ContextTrait> DynInput<S> for Option<&T> {
     ) -> Markup {
         Input::render_input(self.as_deref(), name, name_human, required, ctx, i18n)
     }
+
+    fn mark_required(&self) -> bool {
+        T::mark_required()
+    }
+
+    fn optional_key(&self) -> Option<&'static str> {
+        T::optional_key()
+    }
+
+    fn secret(&self) -> bool {
+        T::secret()
+    }
+
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        match self {
+            Some(value) => Input::<S>::check_constraints(*value, name, constraints),
+            None => Ok(()),
+        }
+    }
 }
 
 /// a dynamic reference to an [`Input`] and it's name
@@ -48,5 +157,180 @@ impl<T: Input<S>, S: ContextTrait> DynInput<S> for Option<&T> {
 pub struct InputInfo<'a, S: ContextTrait> {
     pub name: &'a str,
     pub name_human: &'a str,
+    /// `false` if the field may be omitted, i.e. it is an [`Option<T>`] or has a `#[serde(default)]`
+    pub required: bool,
+    pub constraints: InputConstraints,
+    /// tab the input is rendered in if it is a top level input of a form, set using `#[cms(tab = "...")]`
+    pub tab: Option<&'a str>,
+    /// whether the input is rendered for the current request, see [`Visibility`](crate::context::Visibility)
+    pub visible: fn(&S) -> bool,
+    /// description rendered beneath the input
+    pub help: Option<Help>,
     pub value: Box<dyn DynInput<S> + 'a>,
 }
+
+impl<'a, S: ContextTrait> InputInfo<'a, S> {
+    /// A required input without constraints, tab or help that is always visible.
+    /// Fields may be added to [`InputInfo`], so set others using struct update syntax, e.g.
+    /// `InputInfo { tab: Some("SEO"), ..InputInfo::new(name, name_human, value) }`.
+    pub fn new(name: &'a str, name_human: &'a str, value: Box<dyn DynInput<S> + 'a>) -> Self {
+        Self {
+            name,
+            name_human,
+            required: true,
+            constraints: InputConstraints::default(),
+            tab: None,
+            visible: |_| true,
+            help: None,
+            value,
+        }
+    }
+}
+
+/// Check the values of `inputs` against their [constraints](InputInfo::constraints),
+/// see [`Input::check_constraints`].
+pub fn check_inputs<'a, S: ContextTrait>(
+    inputs: impl IntoIterator<Item = InputInfo<'a, S>>,
+) -> Result<(), ConstraintError> {
+    inputs
+        .into_iter()
+        .try_for_each(|i| i.value.check_constraints(i.name, &i.constraints))
+}
+
+/// Description of an input rendered beneath it, set using `#[cms(help = "...")]` or
+/// `#[cms(help_key = "...")]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Help {
+    /// rendered as is
+    Text(&'static str),
+    /// id of a message in the localizations, which can be added using
+    /// [`App::include_localizations`](crate::App::include_localizations)
+    Key(&'static str),
+}
+
+impl Help {
+    /// the text in the language of `i18n`, the id of the message if it does not exist
+    pub fn text(&self, i18n: &FluentLanguageLoader) -> String {
+        match *self {
+            Self::Text(text) => text.to_string(),
+            Self::Key(key) if i18n.has(key) => i18n.get(key),
+            Self::Key(key) => key.to_string(),
+        }
+    }
+}
+
+/// Constraints on the value of an input, e.g. from `#[cms(max_length = 280)]`.
+///
+/// They are rendered as HTML attributes and checked again when a form is submitted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InputConstraints {
+    /// minimum number of characters
+    pub min_length: Option<usize>,
+    /// maximum number of characters
+    pub max_length: Option<usize>,
+    /// accepted types of uploaded files in the format of the `accept` attribute, i.e. MIME types
+    /// like `image/png`, wildcards like `image/*` and extensions like `.pdf`, separated by commas.
+    /// Set using `#[cms(accept = "image/*")]`.
+    pub accept: Option<&'static str>,
+    /// maximum size of each uploaded file in bytes, set using `#[cms(max_size = "5MB")]`
+    pub max_size: Option<u64>,
+}
+
+impl InputConstraints {
+    pub fn check(&self, value: &str) -> Result<(), ConstraintViolation> {
+        let len = value.chars().count();
+        if let Some(min) = self.min_length.filter(|min| len < *min) {
+            return Err(ConstraintViolation::TooShort { min, len });
+        }
+        if let Some(max) = self.max_length.filter(|max| len > *max) {
+            return Err(ConstraintViolation::TooLong { max, len });
+        }
+        Ok(())
+    }
+
+    /// [`check`](Self::check) the value of the input named `name`
+    pub fn check_input(&self, name: &str, value: &str) -> Result<(), ConstraintError> {
+        self.check(value).map_err(|violation| ConstraintError {
+            field: name.to_string(),
+            violation,
+        })
+    }
+
+    /// check the type of an uploaded file against [`accept`](InputConstraints::accept)
+    pub fn check_file(
+        &self,
+        file_name: &str,
+        content_type: Option<&str>,
+    ) -> Result<(), ConstraintViolation> {
+        let Some(accept) = self.accept else {
+            return Ok(());
+        };
+        let content_type = content_type
+            .and_then(|t| t.split(';').next())
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        let file_name = file_name.to_ascii_lowercase();
+        let accepted = accept
+            .split(',')
+            .map(|t| t.trim().to_ascii_lowercase())
+            .any(|t| {
+                if t.starts_with('.') {
+                    file_name.ends_with(&t)
+                } else if let Some(prefix) = t.strip_suffix("/*") {
+                    prefix == "*"
+                        || content_type
+                            .split_once('/')
+                            .is_some_and(|(p, _)| p == prefix)
+                } else {
+                    content_type == t
+                }
+            });
+        match accepted {
+            true => Ok(()),
+            false => Err(ConstraintViolation::FileType {
+                content_type,
+                accept,
+            }),
+        }
+    }
+}
+
+/// A submitted value that violates the constraints of its input, see [`Input::check_constraints`]
+#[derive(Clone, Debug, Error)]
+#[error("invalid value for field '{field}': {violation}")]
+pub struct ConstraintError {
+    /// name of the input, e.g. `links[0][title]`
+    pub field: String,
+    pub violation: ConstraintViolation,
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum ConstraintViolation {
+    #[error("must be at least {min} characters long, got {len}")]
+    TooShort { min: usize, len: usize },
+    #[error("must be at most {max} characters long, got {len}")]
+    TooLong { max: usize, len: usize },
+    #[error("files of type '{content_type}' are not accepted, expected {accept}")]
+    FileType {
+        content_type: String,
+        accept: &'static str,
+    },
+    #[error("files must be at most {} large", format_size(*.max))]
+    FileTooLarge { max: u64 },
+}
+
+/// `bytes` in the largest unit it is at least one of, e.g. `1.5 MB`
+fn format_size(bytes: u64) -> String {
+    let units = ["KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = "bytes";
+    for u in units {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = u;
+    }
+    format!("{} {unit}", (size * 10.0).round() / 10.0)
+}
@@ -16,11 +16,13 @@
 //!
 //! #[derive(Debug, Deserialize, Serialize, Entity, Model, TS)]
 //! #[ts(export)]
+//! #[cms(label_case = "Title")]
 //! struct Post {
 //!     #[cms(id, skip_input)]
 //!     #[ormlite(primary_key)]
 //!     #[serde(default = "Uuid::new_v4")]
 //!     id: Uuid,
+//!     #[cms(max_length = 100, searchable)]
 //!     title: Text,
 //!     date: DateTime<Utc>,
 //!     #[cms(skip_column)]
@@ -138,10 +140,94 @@
 //!     let db = sqlx::Pool::<Sqlite>::connect("sqlite://.tmp/db.sqlite")
 //!         .await
 //!         .unwrap();
-//!     let app = App::new().entity::<Post>().with_state(db).build("uploads");
 //!     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-//!     axum::serve(listener, app).await.unwrap();
+//!     App::new()
+//!         .entity::<Post>()
+//!         .with_state(db)
+//!         .serve(listener, "uploads")
+//!         .await
+//!         .unwrap();
+//! }
+//! ```
+//!
+//! ## Enums using strum
+//!
+//! Enums deriving `strum::EnumIter` and `Display` can be rendered as a select using
+//! `#[cms(strum)]` instead of the `tag` and `content` required for other enums.
+//! Their serde representation has to match `Display`. Requires the `strum` feature.
+//!
+//! Enums with only unit variants are rendered as a select as well and only require `tag`.
+//!
+//! ```rust,ignore
+//! #[derive(Debug, Deserialize, Serialize, Display, EnumIter, Column, Input)]
+//! #[cms(strum)]
+//! enum Status {
+//!     Draft,
+//!     Published,
+//! }
+//! ```
+//!
+//! ## Renaming fields
+//!
+//! The names of inputs in forms always match serde, i.e. `#[serde(rename = "...")]` and
+//! `#[serde(rename_all = "...")]`, such that submitted forms can be deserialized.
+//! Labels of inputs and columns are taken from, in order of precedence:
+//!
+//! 1. `#[cms(rename = "...")]` of the field, used as is
+//! 2. the name in serde, converted to `#[cms(label_case = "...")]` if set
+//!
+//! ```rust
+//! use derived_cms::{context::Context, input::Inputs, property::Text, Input};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Deserialize, Serialize, Input)]
+//! #[serde(rename_all = "camelCase")]
+//! #[cms(label_case = "Title")]
+//! struct Author {
+//!     #[serde(rename = "name")]
+//!     #[cms(rename = "Full name")]
+//!     full_name: Text,
+//!     #[serde(rename = "mail")]
+//!     email_address: Text,
+//!     #[cms(rename = "Website")]
+//!     home_page: Text,
+//!     short_bio: Text,
 //! }
+//!
+//! type Ctx = Context<ormlite::Pool<sqlx::Sqlite>>;
+//!
+//! let inputs = <Author as Inputs<Ctx>>::inputs(None)
+//!     .into_iter()
+//!     .map(|i| (i.name, i.name_human))
+//!     .collect::<Vec<_>>();
+//! assert_eq!(
+//!     inputs,
+//!     [
+//!         ("name", "Full name"),
+//!         ("mail", "Mail"),
+//!         ("homePage", "Website"),
+//!         ("shortBio", "Short Bio"),
+//!     ]
+//! );
+//! ```
+//!
+//! ## Embedding in an existing application
+//!
+//! The [`Router`](axum::Router) returned by [`App::build`] has no state left and can be nested
+//! into an existing application using [`Router::nest`](axum::Router::nest). Set the same path
+//! using [`App::with_base_path`] such that links, redirects and uploaded files resolve correctly.
+//! Middleware of the outer application (e.g. authentication or tracing) wraps all routes of the CMS.
+//!
+//! ```rust,ignore
+//! let cms = App::new()
+//!     .entity::<Post>()
+//!     .with_base_path("/cms")
+//!     .with_state(db);
+//! let tasks = cms.background_tasks();
+//! let app = axum::Router::new()
+//!     .nest("/cms", cms.build("uploads"))
+//!     .layer(tower_http::trace::TraceLayer::new_for_http());
+//! derived_cms::app::serve(listener, app, tasks).await?;
 //! ```
 //!
 //! ## REST API
@@ -152,22 +238,57 @@
 //! converted to [kebab-case](convert_case::Case::Kebab):
 //!
 //! - `GET /api/v1/:name-plural`:
-//!   - allows filtering by exact value in the query string, e. g. `?slug=asdf`. This currently
-//!     only works for fields whose SQL representation is a string.
-//!   - returns an array of [entities](Entity), serialized using [serde_json].
+//!   - allows filtering by the fields marked with `#[cms(filter)]` in the query string, e. g.
+//!     `?draft=false&date__gte=2024-01-01T00:00:00Z`, see [`filter`].
+//!   - `?limit=` and `?offset=` select a page, see [`entity::List::list_paginated`].
+//!   - `?after=:id&limit=` selects the page after the entity `:id` instead, or the first page if
+//!     `:id` is empty, see [`entity::List::list_after`]. The next page is linked in the `Link`
+//!     header with `rel="next"`, its `after` is in the `X-Next-Cursor` header.
+//!   - returns an array of [entities](Entity), serialized using [serde_json], and the number of
+//!     entities of all pages in the `X-Total-Count` header.
+//! - `GET /api/v1/:name-plural.csv`
+//!   - the same as `GET /api/v1/:name-plural`, but returns the columns of the list page as CSV,
+//!     with a header row of the names of the columns, see [`Column::render_csv`].
 //! - `GET /api/v1/:name/:id`
 //!   - get an [Entity] by it's [id](ormlite::TableMeta::primary_key).
-//!   - returns the requested of [Entity], serialized using [serde_json].
+//!   - returns the requested of [Entity], serialized using [serde_json], and its
+//!     [version token](EntityBase::version_token) in the `ETag` header.
 //! - `POST /api/v1/:name-plural`
 //!   - create a new [Entity] from the request body JSON.
 //!   - returns the newly created [Entity] as JSON.
 //! - `POST /api/v1/:name/:id`
 //!   - replaces the [Entity] with the specified [id](ormlite::TableMeta::primary_key) with the
 //!     request body JSON.
+//!   - by default, a missing key and an explicit `null` are both deserialized as `None`.
+//!     Use `#[cms(update_struct = "...")]` or [`double_option`] in a custom
+//!     [`Update`](EntityBase::Update) type to distinguish them.
+//!   - returns `412` if the `If-Match` header does not match the current
+//!     [version token](EntityBase::version_token), see [`Update::update_if_version`](entity::Update::update_if_version).
 //!   - returns the updated [Entity] as JSON.
+//! - `PATCH /api/v1/:name/:id`
+//!   - replaces only the top-level fields present in the request body JSON object, other fields
+//!     keep their values. [`Json`](property::Json) fields are replaced as a whole, see [`entity::Patch`].
+//!   - returns `412` if the `If-Match` header does not match the current version token.
+//!   - returns the updated [Entity] as JSON, or `404` if it does not exist.
 //! - `DELETE /api/v1/:name/:id`
 //!   - deletes the [Entity] with the specified [id](ormlite::TableMeta::primary_key)
 //!   - returns the deleted Entity as JSON.
+//!
+//! Entities registered using [`App::entity_scoped`] with a prefix are served under
+//! `/api/v1/:prefix/:name-plural` and `/api/v1/:prefix/:name/:id`.
+//!
+//! Override [`EntityBase::api_serialize`] to redact or add fields in all responses of the API.
+//!
+//! Data rejected by [`Inputs::validate`](input::Inputs::validate), e.g. using
+//! `#[cms(validate = "path::to::fn")]`, is answered with HTTP 422 and the messages of the
+//! invalid fields, see [`validate`].
+//!
+//! To guard against accidental deletes by scripts, deletes can require a second request with a one-time token,
+//! see [`delete_token`].
+//!
+//! [`App::with_json_schema`] additionally serves a JSON Schema of the payloads of an entity, see [`schema`].
+
+#![forbid(unsafe_code)]
 
 pub use app::App;
 pub use column::Column;
@@ -175,14 +296,37 @@ pub use entity::{Entity, EntityBase};
 pub use input::Input;
 
 pub mod app;
+pub mod auth;
+pub mod bindings;
+pub mod branding;
+pub mod cache;
 pub mod column;
 pub mod context;
+pub mod csp;
+pub mod csrf;
+pub mod delete_token;
+pub mod double_option;
 pub mod easymde;
+pub mod encryption;
 mod endpoints;
 pub mod entity;
+pub mod filter;
+pub mod form_group;
 pub mod input;
+pub mod locale;
+pub mod maintenance;
+pub mod openapi;
+pub mod pagination;
 pub mod property;
+pub mod relation;
 pub mod render;
+pub mod request_id;
+pub mod schema;
+pub mod task;
+pub mod theme;
+mod time_zones;
+pub mod uploads;
+pub mod validate;
 
 #[doc(hidden)]
 pub mod derive {
@@ -190,6 +334,8 @@ pub mod derive {
     pub use i18n_embed;
     pub use maud;
     pub use ormlite;
+    pub use serde;
+    pub use uuid;
 }
 
 #[cfg(feature = "sqlite")]
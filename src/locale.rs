@@ -0,0 +1,132 @@
+//! Formatting and parsing of numbers in the format of the language of the editor,
+//! see [`Number`](crate::property::Number).
+
+use std::future::Future;
+
+use thiserror::Error;
+use unic_langid::LanguageIdentifier;
+
+tokio::task_local! {
+    /// format of numbers submitted in the form that is currently parsed
+    static NUMBER_FORMAT: NumberFormat;
+}
+
+/// Decimal and grouping separator of numbers, e.g. `1.234,56` in German.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub decimal: char,
+    /// separator of groups of three digits of the integer part
+    pub group: char,
+}
+
+impl Default for NumberFormat {
+    /// `1,234.56`
+    fn default() -> Self {
+        Self {
+            decimal: '.',
+            group: ',',
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("{0:?} is not a valid number")]
+pub struct InvalidNumber(pub String);
+
+impl NumberFormat {
+    /// the format of numbers in `lang`, `1,234.56` if it is unknown
+    pub fn of(lang: &LanguageIdentifier) -> Self {
+        let region = lang.region.as_ref().map(|r| r.as_str());
+        let (decimal, group) = match (lang.language.as_str(), region) {
+            ("de" | "it" | "fr", Some("CH" | "LI")) => ('.', '\''),
+            ("de" | "es" | "it" | "nl" | "id" | "tr" | "ro" | "da" | "el" | "hr" | "sl", _) => {
+                (',', '.')
+            }
+            ("pt", Some("PT")) => (',', '\u{a0}'),
+            ("pt", _) => (',', '.'),
+            ("fr", _) => (',', '\u{202f}'),
+            (
+                "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "nn" | "no" | "uk" | "hu" | "bg"
+                | "lt" | "lv" | "et",
+                _,
+            ) => (',', '\u{a0}'),
+            _ => ('.', ','),
+        };
+        Self { decimal, group }
+    }
+
+    /// The format of the form that is currently parsed, [`NumberFormat::default`] outside of forms,
+    /// e.g. in the JSON API.
+    pub fn current() -> Self {
+        NUMBER_FORMAT.try_with(|f| *f).unwrap_or_default()
+    }
+
+    /// Run `f` with `self` as the [current](NumberFormat::current) format.
+    pub async fn scope<F: Future>(self, f: F) -> F::Output {
+        NUMBER_FORMAT.scope(self, f).await
+    }
+
+    /// Format a number in its canonical representation, e.g. `-1234.5`, with the separators of `self`.
+    pub fn format(&self, number: &str) -> String {
+        let (sign, number) = match number.strip_prefix('-') {
+            Some(number) => ("-", number),
+            None => ("", number),
+        };
+        let (int, fraction) = match number.split_once('.') {
+            Some((int, fraction)) => (int, Some(fraction)),
+            None => (number, None),
+        };
+        let mut formatted = sign.to_string();
+        for (i, c) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0 {
+                formatted.push(self.group);
+            }
+            formatted.push(c);
+        }
+        if let Some(fraction) = fraction {
+            formatted.push(self.decimal);
+            formatted.push_str(fraction);
+        }
+        formatted
+    }
+
+    /// Normalize a number entered in this format into its canonical representation, e.g.
+    /// `1.234,5` into `1234.5` in German. Group separators are optional, but have to separate
+    /// groups of three digits. Spaces and `'` are accepted as group separators in all formats.
+    pub fn parse(&self, input: &str) -> Result<String, InvalidNumber> {
+        let invalid = || InvalidNumber(input.to_string());
+        let input = input.trim();
+        let (negative, number) = match input
+            .strip_prefix('-')
+            .or_else(|| input.strip_prefix('\u{2212}'))
+        {
+            Some(number) => (true, number),
+            None => (false, input.strip_prefix('+').unwrap_or(input)),
+        };
+        let (int, fraction) = match number.split_once(self.decimal) {
+            Some((int, fraction)) => (int, Some(fraction)),
+            None => (number, None),
+        };
+        let is_group = |c: char| c == self.group || c == '\'' || c.is_whitespace();
+        let groups = int.split(is_group).collect::<Vec<_>>();
+        let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+        let grouped =
+            groups.len() == 1 || (groups[0].len() <= 3 && groups[1..].iter().all(|g| g.len() == 3));
+        if !grouped || !groups.iter().all(|g| digits(g)) {
+            return Err(invalid());
+        }
+        let mut normalized = String::with_capacity(number.len() + 1);
+        if negative {
+            normalized.push('-');
+        }
+        normalized.extend(groups);
+        if let Some(fraction) = fraction {
+            if !digits(fraction) {
+                return Err(invalid());
+            }
+            normalized.push('.');
+            normalized.push_str(fraction);
+        }
+        Ok(normalized)
+    }
+}
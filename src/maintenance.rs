@@ -0,0 +1,202 @@
+//! Actions run over all entities of a type from the admin interface, e.g. rebuilding an external
+//! search index, see [`EntityBase::maintenance_actions`](crate::EntityBase::maintenance_actions).
+//!
+//! Actions are started using a button on the list page of the entity and run as background tasks
+//! of the [`App`](crate::App). Their progress, reported using [`Progress`], is shown on the list page.
+
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use convert_case::{Case, Casing};
+use derive_more::Debug;
+use futures_util::FutureExt;
+use tracing::{error, info};
+
+use crate::{
+    app::AppError,
+    context::ContextTrait,
+    entity::EntityScope,
+    task::{BackgroundTasks, CancellationToken},
+};
+
+type ActionFn<S> = Arc<
+    dyn Fn(S, Progress) -> Pin<Box<dyn Future<Output = Result<(), AppError>> + Send>> + Send + Sync,
+>;
+
+/// A button on the list page of an entity that runs user code in the background.
+#[derive(Debug)]
+pub struct MaintenanceAction<S> {
+    pub(crate) name: String,
+    pub(crate) label: String,
+    pub(crate) confirm: Option<String>,
+    #[debug(skip)]
+    pub(crate) run: ActionFn<S>,
+}
+
+impl<S: ContextTrait> MaintenanceAction<S> {
+    /// `name` identifies the action in the URL and is converted to [kebab-case](Case::Kebab),
+    /// `label` is the text of the button.
+    pub fn new<F, Fut>(name: impl AsRef<str>, label: impl Into<String>, run: F) -> Self
+    where
+        F: Fn(S, Progress) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), AppError>> + Send + 'static,
+    {
+        Self {
+            name: name.as_ref().to_case(Case::Kebab),
+            label: label.into(),
+            confirm: None,
+            run: Arc::new(move |ctx, progress| Box::pin(run(ctx, progress))),
+        }
+    }
+
+    /// Ask for confirmation with `message` before starting the action.
+    pub fn confirm(mut self, message: impl Into<String>) -> Self {
+        self.confirm = Some(message.into());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The state of the last run of a [`MaintenanceAction`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RunStatus {
+    Running { done: u64, total: Option<u64> },
+    Finished,
+    Failed(String),
+}
+
+/// Handle passed to a running [`MaintenanceAction`].
+#[derive(Clone, Debug)]
+pub struct Progress {
+    key: String,
+    runs: MaintenanceRuns,
+    token: CancellationToken,
+}
+
+impl Progress {
+    /// Report that `done` of `total` entities have been processed.
+    pub fn set(&self, done: u64, total: u64) {
+        self.runs.runs().insert(
+            self.key.clone(),
+            RunStatus::Running {
+                done,
+                total: Some(total),
+            },
+        );
+    }
+
+    /// token that is cancelled when the [`BackgroundTasks`] are shut down
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+/// The [`RunStatus`] of all [`MaintenanceAction`]s, available using [`ContextTrait::maintenance_runs`].
+#[derive(Clone, Debug, Default)]
+pub struct MaintenanceRuns {
+    tasks: BackgroundTasks,
+    runs: Arc<Mutex<HashMap<String, RunStatus>>>,
+}
+
+impl MaintenanceRuns {
+    pub(crate) fn new(tasks: BackgroundTasks) -> Self {
+        Self {
+            tasks,
+            runs: Default::default(),
+        }
+    }
+
+    fn runs(&self) -> MutexGuard<'_, HashMap<String, RunStatus>> {
+        self.runs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn key(scope: &EntityScope, action: &str) -> String {
+        format!("{}/{action}", scope.path_plural())
+    }
+
+    /// status of the last run of the action `name` of the entities served under `scope`
+    pub fn status(&self, scope: &EntityScope, name: &str) -> Option<RunStatus> {
+        self.runs().get(&Self::key(scope, name)).cloned()
+    }
+
+    /// Start `action` in the background. Returns `false` if it is already running.
+    pub(crate) fn start<S: ContextTrait>(
+        &self,
+        ctx: S,
+        scope: &EntityScope,
+        action: &MaintenanceAction<S>,
+    ) -> bool {
+        let key = Self::key(scope, &action.name);
+        {
+            let mut runs = self.runs();
+            if let Some(RunStatus::Running { .. }) = runs.get(&key) {
+                return false;
+            }
+            runs.insert(
+                key.clone(),
+                RunStatus::Running {
+                    done: 0,
+                    total: None,
+                },
+            );
+        }
+        let run = action.run.clone();
+        let runs = self.clone();
+        self.tasks.spawn(
+            ctx,
+            Box::new(move |ctx, token| {
+                Box::pin(async move {
+                    info!("starting maintenance action {key}");
+                    let progress = Progress {
+                        key: key.clone(),
+                        runs: runs.clone(),
+                        token,
+                    };
+                    let result = AssertUnwindSafe(run(ctx.clone(), progress))
+                        .catch_unwind()
+                        .await;
+                    let status = match result {
+                        Ok(Ok(())) => RunStatus::Finished,
+                        Ok(Err(e)) => {
+                            error!(
+                                "maintenance action {key} failed: {}: {}",
+                                e.title, e.description
+                            );
+                            RunStatus::Failed(format!("{}: {}", e.title, e.description))
+                        }
+                        Err(panic) => {
+                            let message = panic_message(&*panic);
+                            error!("maintenance action {key} panicked: {message}");
+                            RunStatus::Failed(format!("panicked: {message}"))
+                        }
+                    };
+                    // the action may have modified any entity, even if it failed
+                    if let Some(cache) = ctx.api_cache() {
+                        cache.clear();
+                    }
+                    runs.runs().insert(key, status);
+                })
+            }),
+        );
+        true
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    match panic.downcast_ref::<&'static str>() {
+        Some(message) => message,
+        None => panic
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .unwrap_or("unknown panic"),
+    }
+}
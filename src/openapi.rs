@@ -0,0 +1,247 @@
+//! [OpenAPI](https://spec.openapis.org/oas/v3.1.0) document of the JSON API, describing the
+//! endpoints of all registered entities, see [`App::openapi`](crate::App::openapi).
+//!
+//! The request and response bodies of entities registered using
+//! [`App::with_json_schema`](crate::App::with_json_schema) are described by their
+//! [JSON Schema](crate::schema) in `components/schemas`, the bodies of other entities are
+//! described as any object.
+//!
+//! ```rust,ignore
+//! let app = App::new()
+//!     .entity::<Post>()
+//!     .with_json_schema::<Post>()
+//!     .with_openapi();
+//! std::fs::write("openapi.json", app.openapi().to_string())?;
+//! ```
+
+use std::{
+    any::TypeId,
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use convert_case::{Case, Casing};
+use serde_json::{json, Map, Value};
+use ts_rs::{ExportError, TS};
+
+use crate::{bindings, context::ContextTrait, entity::EntityScope, schema, EntityBase};
+
+/// adds the schema of a type to `components/schemas` and returns a reference to it
+type Component = fn(&mut Map<String, Value>) -> Value;
+
+/// exports the TypeScript definition of a type, see [`bindings::ts_type`]
+type TsType = fn(&Path, &mut BTreeMap<String, String>) -> Result<String, ExportError>;
+
+/// JSON Schemas and TypeScript types of an entity and its [`Create`](EntityBase::Create) and
+/// [`Update`](EntityBase::Update) types
+#[derive(Clone, Copy)]
+pub(crate) struct Schemas {
+    entity: Component,
+    create: Component,
+    update: Component,
+    pub entity_ts: TsType,
+    pub create_ts: TsType,
+    pub update_ts: TsType,
+}
+
+/// The registered entities and their schemas, collected by the [`App`](crate::App).
+#[derive(Clone, Default)]
+pub(crate) struct OpenApi {
+    entities: Vec<(EntityScope, TypeId)>,
+    schemas: HashMap<TypeId, Schemas>,
+}
+
+impl OpenApi {
+    pub fn entity<E: 'static>(&mut self, scope: &EntityScope) {
+        self.entities.push((scope.clone(), TypeId::of::<E>()));
+    }
+
+    pub fn schemas<E, S>(&mut self)
+    where
+        E: EntityBase<S> + TS,
+        E::Create: TS,
+        E::Update: TS,
+        S: ContextTrait,
+    {
+        self.schemas.insert(
+            TypeId::of::<E>(),
+            Schemas {
+                entity: schema::component::<E>,
+                create: schema::component::<E::Create>,
+                update: schema::component::<E::Update>,
+                entity_ts: bindings::ts_type::<E>,
+                create_ts: bindings::ts_type::<E::Create>,
+                update_ts: bindings::ts_type::<E::Update>,
+            },
+        );
+    }
+
+    /// all registered entities with their schemas, if registered using
+    /// [`App::with_json_schema`](crate::App::with_json_schema)
+    pub fn entities(&self) -> impl Iterator<Item = (&EntityScope, Option<&Schemas>)> {
+        self.entities
+            .iter()
+            .map(|(scope, type_id)| (scope, self.schemas.get(type_id)))
+    }
+
+    /// the OpenAPI document of all entities
+    pub fn document(&self) -> Value {
+        let mut components = Map::new();
+        let mut paths = Map::new();
+        for (scope, schemas) in self.entities() {
+            let [entity, create, update] = match schemas {
+                Some(schemas) => [schemas.entity, schemas.create, schemas.update]
+                    .map(|component| component(&mut components)),
+                None => [(); 3].map(|_| json!({ "type": "object" })),
+            };
+            let (list, single) = entity_paths(scope, entity, create, update);
+            paths.insert(format!("/api/v1/{}", scope.path_plural()), list);
+            paths.insert(format!("/api/v1/{}/{{id}}", scope.path()), single);
+        }
+        json!({
+            "openapi": "3.1.0",
+            "info": {
+                "title": "derived-cms",
+                "version": "1",
+            },
+            "paths": paths,
+            "components": {
+                "schemas": components,
+            },
+        })
+    }
+}
+
+/// the path items of `/api/v1/:name-plural` and `/api/v1/:name/:id`
+fn entity_paths(
+    scope: &EntityScope,
+    entity: Value,
+    create: Value,
+    update: Value,
+) -> (Value, Value) {
+    let tag = scope.path_plural();
+    let operation_id = |action: &str, name: &str| {
+        format!("{action}_{}", name.replace('/', "_").to_case(Case::Snake))
+    };
+    let name = scope.path();
+    let name_plural = scope.path_plural();
+    let json = |schema: Value| json!({ "application/json": { "schema": schema } });
+    let ok = |schema: Value| json!({ "description": "OK", "content": json(schema) });
+    let id = json!({
+        "name": "id",
+        "in": "path",
+        "required": true,
+        "schema": { "type": "string" },
+    });
+    let if_match = json!({
+        "name": "If-Match",
+        "in": "header",
+        "description": "reject the update with 412 unless this is the current `ETag`",
+        "schema": { "type": "string" },
+    });
+    let invalid = json!({ "description": "the data is invalid" });
+    let precondition_failed =
+        json!({ "description": "the entity was modified since `If-Match` was loaded" });
+    let not_found = json!({ "description": "the entity does not exist" });
+    let list = json!({
+        "get": {
+            "tags": [tag],
+            "operationId": operation_id("list", &name_plural),
+            "parameters": [
+                { "name": "limit", "in": "query", "schema": { "type": "integer", "minimum": 0 } },
+                { "name": "offset", "in": "query", "schema": { "type": "integer", "minimum": 0 } },
+                {
+                    "name": "after",
+                    "in": "query",
+                    "description": "return the page after this id, ordered by id, instead of using `offset`",
+                    "schema": { "type": "string" },
+                },
+            ],
+            "responses": {
+                "200": {
+                    "description": "OK",
+                    "headers": {
+                        "X-Total-Count": {
+                            "description": "number of entities of all pages",
+                            "schema": { "type": "integer" },
+                        },
+                        "X-Next-Cursor": {
+                            "description": "`after` of the next page, if there may be more entities",
+                            "schema": { "type": "string" },
+                        },
+                        "Link": {
+                            "description": "URL of the next page with `after` as `rel=\"next\"`",
+                            "schema": { "type": "string" },
+                        },
+                    },
+                    "content": json(json!({ "type": "array", "items": entity })),
+                },
+                "400": { "description": "invalid filter" },
+            },
+        },
+        "post": {
+            "tags": [tag],
+            "operationId": operation_id("create", &name),
+            "requestBody": { "required": true, "content": json(create) },
+            "responses": {
+                "200": ok(entity.clone()),
+                "422": invalid,
+            },
+        },
+    });
+    let single = json!({
+        "parameters": [id],
+        "get": {
+            "tags": [tag],
+            "operationId": operation_id("get", &name),
+            "responses": {
+                "200": {
+                    "description": "OK",
+                    "headers": {
+                        "ETag": {
+                            "description": "version token, see `If-Match`",
+                            "schema": { "type": "string" },
+                        },
+                    },
+                    "content": json(entity.clone()),
+                },
+                "404": not_found,
+            },
+        },
+        "post": {
+            "tags": [tag],
+            "operationId": operation_id("update", &name),
+            "parameters": [if_match],
+            "requestBody": { "required": true, "content": json(update) },
+            "responses": {
+                "200": ok(entity.clone()),
+                "412": precondition_failed,
+                "422": invalid,
+            },
+        },
+        "patch": {
+            "tags": [tag],
+            "operationId": operation_id("patch", &name),
+            "parameters": [if_match],
+            "requestBody": { "required": true, "content": json(json!({ "type": "object" })) },
+            "responses": {
+                "200": ok(entity),
+                "404": not_found,
+                "412": precondition_failed,
+                "422": invalid,
+            },
+        },
+        "delete": {
+            "tags": [tag],
+            "operationId": operation_id("delete", &name),
+            "parameters": [
+                { "name": "confirm", "in": "query", "schema": { "type": "string" } },
+            ],
+            "responses": {
+                "200": { "description": "deleted" },
+                "409": { "description": "the delete has to be confirmed with the returned token" },
+            },
+        },
+    });
+    (list, single)
+}
@@ -0,0 +1,159 @@
+//! Controls to navigate between the pages of the list page, see [`App::with_paginator`](crate::App::with_paginator).
+//!
+//! ```rust,ignore
+//! #[derive(Debug)]
+//! struct PrevNext;
+//!
+//! impl Paginator for PrevNext {
+//!     fn render(&self, page: &Pagination, i18n: &FluentLanguageLoader) -> Markup {
+//!         html! {
+//!             @if let Some(prev) = page.previous() {
+//!                 a href=(page.url(prev)) { "←" }
+//!             }
+//!             @if let Some(next) = page.next() {
+//!                 a href=(page.url(next)) { "→" }
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! App::new().with_paginator(PrevNext)
+//! ```
+
+use std::fmt::Debug;
+
+use i18n_embed::fluent::FluentLanguageLoader;
+use i18n_embed_fl::fl;
+use maud::{html, Markup};
+
+/// Renders the pager of the list page.
+pub trait Paginator: Debug + Send + Sync + 'static {
+    fn render(&self, page: &Pagination, i18n: &FluentLanguageLoader) -> Markup;
+}
+
+/// The current page of a list, `limit` entities starting at `offset`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pagination {
+    pub offset: u64,
+    pub limit: u64,
+    /// number of entities of all pages
+    pub total: u64,
+    /// path of the list page relative to the base path, e.g. `posts`
+    path: String,
+    /// query string of the list page without `offset` and `limit`, e.g. the search query
+    query: String,
+}
+
+impl Pagination {
+    /// `path` is the path of the list page relative to the base path, e.g. `posts`, and `query`
+    /// its query string without the leading `?`. `offset` and `limit` in it are replaced by
+    /// [`Pagination::url`].
+    pub fn new(path: &str, offset: u64, limit: u64, total: u64, query: &str) -> Self {
+        let query = query
+            .split('&')
+            .filter(|p| {
+                let key = p.split_once('=').map_or(*p, |(k, _)| k);
+                !p.is_empty() && key != "offset" && key != "limit"
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+        Self {
+            offset,
+            limit: limit.max(1),
+            total,
+            path: path.to_string(),
+            query,
+        }
+    }
+
+    /// index of the current page, starting at 0
+    pub fn page(&self) -> u64 {
+        self.offset / self.limit
+    }
+
+    /// number of pages, at least 1
+    pub fn pages(&self) -> u64 {
+        self.total.div_ceil(self.limit).max(1)
+    }
+
+    /// offset of the page `page`
+    pub fn offset_of(&self, page: u64) -> u64 {
+        page * self.limit
+    }
+
+    /// offset of the previous page, if this is not the first page
+    pub fn previous(&self) -> Option<u64> {
+        (self.offset > 0).then(|| self.offset.saturating_sub(self.limit))
+    }
+
+    /// offset of the next page, if this is not the last page
+    pub fn next(&self) -> Option<u64> {
+        let next = self.offset + self.limit;
+        (next < self.total).then_some(next)
+    }
+
+    /// URL of the list page starting at `offset` relative to the base path, keeping the rest of the
+    /// query string
+    pub fn url(&self, offset: u64) -> String {
+        let mut url = format!("{}?offset={offset}&limit={}", self.path, self.limit);
+        if !self.query.is_empty() {
+            url.push('&');
+            url.push_str(&self.query);
+        }
+        url
+    }
+}
+
+/// Links to the previous and next page and to the pages around the current, first and last page.
+#[derive(Clone, Copy, Debug)]
+pub struct DefaultPaginator {
+    /// number of pages linked on each side of the current page
+    pub around: u64,
+}
+
+impl Default for DefaultPaginator {
+    fn default() -> Self {
+        Self { around: 2 }
+    }
+}
+
+impl Paginator for DefaultPaginator {
+    fn render(&self, page: &Pagination, i18n: &FluentLanguageLoader) -> Markup {
+        let current = page.page();
+        let last = page.pages() - 1;
+        // pages that are linked, `None` for a gap
+        let mut pages = Vec::<Option<u64>>::new();
+        for p in 0..=last {
+            if p == 0 || p == last || p.abs_diff(current) <= self.around {
+                pages.push(Some(p));
+            } else if pages.last().is_some_and(Option::is_some) {
+                pages.push(None);
+            }
+        }
+        html! {
+            @if last > 0 {
+                nav class="cms-pagination" {
+                    @match page.previous() {
+                        Some(offset) => a href=(page.url(offset)) rel="prev" class="cms-pagination-previous" {
+                            (fl!(i18n, "pagination-previous"))
+                        },
+                        None => span class="cms-pagination-previous" { (fl!(i18n, "pagination-previous")) },
+                    }
+                    @for p in pages {
+                        @match p {
+                            Some(p) if p == current => span class="cms-pagination-page active" aria-current="page" { (p + 1) },
+                            Some(p) => a href=(page.url(page.offset_of(p))) class="cms-pagination-page" { (p + 1) },
+                            None => span class="cms-pagination-gap" { "…" },
+                        }
+                    }
+                    @match page.next() {
+                        Some(offset) => a href=(page.url(offset)) rel="next" class="cms-pagination-next" {
+                            (fl!(i18n, "pagination-next"))
+                        },
+                        None => span class="cms-pagination-next" { (fl!(i18n, "pagination-next")) },
+                    }
+                }
+            }
+        }
+    }
+}
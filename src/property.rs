@@ -1,20 +1,35 @@
 use std::{
+    collections::{BTreeSet, HashSet},
     fmt::Debug,
+    hash::Hash,
+    marker::PhantomData,
     path::{Path, PathBuf},
+    sync::LazyLock,
 };
 
-use chrono::{DateTime, TimeZone};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
 use derive_more::{Deref, DerefMut, Display, From, FromStr, Into};
-use i18n_embed::fluent::FluentLanguageLoader;
+use i18n_embed::{fluent::FluentLanguageLoader, LanguageLoader};
 use i18n_embed_fl::fl;
 use maud::{html, Markup, PreEscaped};
 use serde::{Deserialize, Serialize};
 use sqlx::error::BoxDynError;
+use thiserror::Error;
 use ts_rs::TS;
 use uuid::Uuid;
 
 use crate::{
-    self as derived_cms, context::ContextTrait, input::InputInfo, render::FormRenderContext,
+    self as derived_cms,
+    context::ContextTrait,
+    csrf,
+    easymde::EditorBackend,
+    encryption::{EncryptionError, EncryptionKey},
+    entity::EntityPath,
+    input::{ConstraintError, InputConstraints, InputInfo},
+    locale::NumberFormat,
+    render::{self, FormRenderContext},
+    time_zones,
+    uploads::{self, UploadSigner},
     Column, Input, DB,
 };
 
@@ -22,7 +37,10 @@ use crate::{
 pub struct EnumVariant<'a, S: ContextTrait> {
     pub name: &'a str,
     pub value: &'a str,
+    /// content of a tuple variant
     pub content: Option<InputInfo<'a, S>>,
+    /// fields of a struct variant
+    pub fields: Vec<InputInfo<'a, S>>,
 }
 
 /********
@@ -111,13 +129,156 @@ impl<S: ContextTrait> Input<S> for Text {
         name: &str,
         name_human: &str,
         required: bool,
-        _ctx: &FormRenderContext<'_, S>,
+        ctx: &FormRenderContext<'_, S>,
+        _i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        html! {
+            input type="text" name=(name) placeholder=(name_human) class="cms-text-input"
+                value=[value]
+                required[required]
+                minlength=[ctx.constraints().min_length]
+                maxlength=[ctx.constraints().max_length] {}
+        }
+    }
+
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        constraints.check_input(name, &self.0)
+    }
+}
+
+/************
+ * Textarea *
+ ************/
+
+/// Multi-line plain text, rendered as a `<textarea>` with `ROWS` rows.
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Deref,
+    DerefMut,
+    Display,
+    From,
+    FromStr,
+    Into,
+    PartialEq,
+    Eq,
+    Hash,
+    Deserialize,
+    Serialize,
+)]
+#[serde(transparent)]
+pub struct Textarea<const ROWS: u16 = 4>(pub String);
+
+impl<const ROWS: u16> TS for Textarea<ROWS> {
+    type WithoutGenerics = Self;
+
+    fn decl() -> String {
+        String::decl()
+    }
+
+    fn decl_concrete() -> String {
+        String::decl_concrete()
+    }
+
+    fn name() -> String {
+        String::name()
+    }
+
+    fn inline() -> String {
+        String::inline()
+    }
+
+    fn inline_flattened() -> String {
+        String::inline_flattened()
+    }
+}
+
+impl<'r, const ROWS: u16> sqlx::Decode<'r, DB> for Textarea<ROWS>
+where
+    String: sqlx::Decode<'r, DB>,
+{
+    fn decode(
+        value: <DB as sqlx::Database>::ValueRef<'r>,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        Ok(Self(<String as sqlx::Decode<DB>>::decode(value)?))
+    }
+}
+
+impl<const ROWS: u16> sqlx::Type<DB> for Textarea<ROWS>
+where
+    String: sqlx::Type<DB>,
+{
+    fn type_info() -> <DB as sqlx::Database>::TypeInfo {
+        <String as sqlx::Type<DB>>::type_info()
+    }
+}
+
+impl<'r, const ROWS: u16> sqlx::Encode<'r, DB> for Textarea<ROWS>
+where
+    String: sqlx::Encode<'r, DB>,
+{
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'r>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        sqlx::Encode::<'_, DB>::encode(&self.0, buf)
+    }
+}
+
+impl<S: ContextTrait, const ROWS: u16> Input<S> for Textarea<ROWS> {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        required: bool,
+        ctx: &FormRenderContext<'_, S>,
         _i18n: &FluentLanguageLoader,
     ) -> Markup {
         html! {
-            input type="text" name=(name) placeholder=(name_human) class="cms-text-input" value=[value] required[required] {}
+            textarea name=(name) placeholder=(name_human) class="cms-textarea-input"
+                rows=(ROWS)
+                required[required]
+                minlength=[ctx.constraints().min_length]
+                maxlength=[ctx.constraints().max_length] {
+                (value.map(|v| v.0.as_str()).unwrap_or_default())
+            }
+        }
+    }
+
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        constraints.check_input(name, &self.0)
+    }
+}
+
+impl<const ROWS: u16> Column for Textarea<ROWS> {
+    /// first line of the text, truncated to 80 characters
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        const MAX_LEN: usize = 80;
+        let line = self.0.lines().next().unwrap_or_default();
+        let truncated = line.chars().count() > MAX_LEN || self.0.lines().nth(1).is_some();
+        html! {
+            span class="cms-textarea-preview" title=(self.0) {
+                (line.chars().take(MAX_LEN).collect::<String>())
+                @if truncated {
+                    "…"
+                }
+            }
         }
     }
+
+    /// the whole text
+    fn render_csv(&self, _i18n: &FluentLanguageLoader) -> String {
+        self.0.clone()
+    }
 }
 
 /************
@@ -173,37 +334,62 @@ impl<S: ContextTrait> Input<S> for Markdown {
         value: Option<&Self>,
         name: &str,
         name_human: &str,
-        _required: bool,
+        required: bool,
         ctx: &FormRenderContext<'_, S>,
         _i18n: &FluentLanguageLoader,
     ) -> Markup {
         let id = Uuid::new_v4();
-        let editor_construction = ctx.ctx.editor().map(|config| {
-            format!(
-                "new EasyMDE({{ element: this, imageMaxSize: {max_size}, uploadImage: {upload}, \
-                 imageUploadEndpoint: '/upload', imagePathAbsolute: true, imageAccept: \
-                 '{file_types}' }})",
-                max_size = config.upload_max_size,
-                upload = config.enable_uploads,
-                file_types = config.allowed_file_types.join(", ")
-            )
+        let editor = ctx.ctx.editor().and_then(|config| match &config.backend {
+            EditorBackend::EasyMde => {
+                let (script, stylesheet) = config.easymde_assets();
+                ctx.require_stylesheet(stylesheet);
+                ctx.require_script(script);
+                ctx.require_script("js/markdown.js");
+                Some(("cmsMarkdownInit", Some(config)))
+            }
+            EditorBackend::Plain => None,
+            EditorBackend::Custom {
+                script_url,
+                init_fn,
+            } => {
+                ctx.require_script(script_url.as_ref());
+                Some((init_fn.as_ref(), None))
+            }
         });
+        let easymde = editor.and_then(|(_, config)| config);
         html! {
             div .cms-markdown-editor {
-                @if editor_construction.is_some() {
-                    link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/easymde/dist/easymde.min.css" {}
-                    script src="https://cdn.jsdelivr.net/npm/easymde/dist/easymde.min.js" {}
-                }
                 textarea
                     #(id)
                     name=(name)
                     placeholder=(name_human)
-                    onmount=(editor_construction.unwrap_or_default()) {
+                    // the editor hides the textarea, which can't be focused when the validation fails
+                    required[required && editor.is_none()]
+                    minlength=[ctx.constraints().min_length]
+                    maxlength=[ctx.constraints().max_length]
+                    data-cms-mount=[editor.map(|(init, _)| init)]
+                    data-max-size=[easymde.map(|config| config.upload_max_size)]
+                    data-upload=[easymde.map(|config| config.enable_uploads)]
+                    data-upload-endpoint=[easymde.map(|_| format!(
+                        "upload{}",
+                        csrf::token()
+                            .map(|t| format!("?_csrf={}", urlencoding::encode(&t)))
+                            .unwrap_or_default()
+                    ))]
+                    data-accept=[easymde.map(|config| config.allowed_file_types.join(", "))] {
                     (value.map(|v| v.0.as_ref()).unwrap_or(""))
                 }
             }
         }
     }
+
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        constraints.check_input(name, &self.0)
+    }
 }
 impl<'r> sqlx::Decode<'r, DB> for Markdown
 where
@@ -359,7 +545,7 @@ impl<S: ContextTrait> Input<S> for u8 {
         _i18n: &FluentLanguageLoader,
     ) -> Markup {
         html! {
-            input type="number" name=(name) placeholder=(name_human) class="cms-uint-input" value=[value] required[required] step="1" min="0" {}
+            input type="number" name=(name) placeholder=(name_human) class="cms-uint-input" value=[value] required[required] step="1" min="0" max=(Self::MAX) {}
         }
     }
 }
@@ -373,7 +559,7 @@ impl<S: ContextTrait> Input<S> for u16 {
         _i18n: &FluentLanguageLoader,
     ) -> Markup {
         html! {
-            input type="number" name=(name) placeholder=(name_human) class="cms-uint-input" value=[value] required[required] step="1" min="0" {}
+            input type="number" name=(name) placeholder=(name_human) class="cms-uint-input" value=[value] required[required] step="1" min="0" max=(Self::MAX) {}
         }
     }
 }
@@ -387,7 +573,7 @@ impl<S: ContextTrait> Input<S> for u32 {
         _i18n: &FluentLanguageLoader,
     ) -> Markup {
         html! {
-            input type="number" name=(name) placeholder=(name_human) class="cms-uint-input" value=[value] required[required] step="1" min="0" {}
+            input type="number" name=(name) placeholder=(name_human) class="cms-uint-input" value=[value] required[required] step="1" min="0" max=(Self::MAX) {}
         }
     }
 }
@@ -401,7 +587,7 @@ impl<S: ContextTrait> Input<S> for u64 {
         _i18n: &FluentLanguageLoader,
     ) -> Markup {
         html! {
-            input type="number" name=(name) placeholder=(name_human) class="cms-uint-input" value=[value] required[required] step="1" min="0" {}
+            input type="number" name=(name) placeholder=(name_human) class="cms-uint-input" value=[value] required[required] step="1" min="0" max=(Self::MAX) {}
         }
     }
 }
@@ -415,7 +601,7 @@ impl<S: ContextTrait> Input<S> for u128 {
         _i18n: &FluentLanguageLoader,
     ) -> Markup {
         html! {
-            input type="number" name=(name) placeholder=(name_human) class="cms-uint-input" value=[value] required[required] step="1" min="0" {}
+            input type="number" name=(name) placeholder=(name_human) class="cms-uint-input" value=[value] required[required] step="1" min="0" max=(Self::MAX) {}
         }
     }
 }
@@ -455,175 +641,2025 @@ impl Column for u128 {
     }
 }
 
-/************
- * DateTime *
- ************/
+/*********
+ * float *
+ *********/
 
-impl<Tz: TimeZone, S: ContextTrait> Input<S> for DateTime<Tz>
-where
-    for<'de> DateTime<Tz>: Deserialize<'de>,
-{
+impl<S: ContextTrait> Input<S> for f32 {
     fn render_input(
         value: Option<&Self>,
         name: &str,
-        _name_human: &str,
+        name_human: &str,
         required: bool,
-        ctx: &FormRenderContext<'_, S>,
+        _ctx: &FormRenderContext<'_, S>,
         _i18n: &FluentLanguageLoader,
     ) -> Markup {
-        let input_id = Uuid::new_v4();
-        let hidden_id = Uuid::new_v4();
-        html! {
-            input type="datetime-local" id=(input_id) class="cms-datetime-input" required[required] {}
-            input type="hidden" name=(name) id=(hidden_id) value=[value.map(|v|v.to_rfc3339())] {}
-            script type="module" {(PreEscaped(format!(r#"
-const input = document.getElementById("{input_id}");
-const hidden = document.getElementById("{hidden_id}");
-const d = new Date(hidden.value);
-input.value = `${{d.getFullYear()}}-${{(d.getMonth()+1).toString().padStart(2, '0')}}-${{d.getDate().toString().padStart(2, '0')}}T${{d.getHours().toString().padStart(2, '0')}}:${{d.getMinutes().toString().padStart(2, '0')}}`;
-document.getElementById("{}").addEventListener("submit", () => {{
-    hidden.value = new Date(input.value).toISOString();
-}});
-            "#, ctx.form_id).trim()))}
-            noscript {
-                "It appears that JavaScript is disabled. JavaScript is required to set dates in your current timezone. Please enter dates in UTC (Coordinated universal time) instead."
-            }
-        }
-    }
-}
-impl<Tz: TimeZone> Column for DateTime<Tz>
-where
-    Tz::Offset: std::fmt::Display,
-{
-    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
         html! {
-            time datetime=(self.to_rfc3339()) {
-                (self.to_string())
-            }
+            input type="number" name=(name) placeholder=(name_human) class="cms-float-input" value=[value] required[required] step="any" {}
         }
     }
 }
-
-/********
- * bool *
- ********/
-
-impl<S: ContextTrait> Input<S> for bool {
+impl<S: ContextTrait> Input<S> for f64 {
     fn render_input(
         value: Option<&Self>,
         name: &str,
-        _name_human: &str,
-        _required: bool,
+        name_human: &str,
+        required: bool,
         _ctx: &FormRenderContext<'_, S>,
         _i18n: &FluentLanguageLoader,
     ) -> Markup {
         html! {
-            input type="checkbox" name=(name) value="true" checked[*value.unwrap_or(&false)] {}
+            input type="number" name=(name) placeholder=(name_human) class="cms-float-input" value=[value] required[required] step="any" {}
         }
     }
 }
-impl Column for bool {
+impl Column for f32 {
     fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
         html! {
-            input type="checkbox" disabled checked[*self] {}
+            (self)
+        }
+    }
+}
+impl Column for f64 {
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            (self)
         }
     }
 }
 
-/**********
- * Vec<T> *
- **********/
+/***********
+ * Decimal *
+ ***********/
 
-impl<T: Input<S>, S: ContextTrait> Input<S> for Vec<T> {
-    fn render_input(
-        value: Option<&Self>,
-        name: &str,
-        name_human: &str,
-        required: bool,
-        ctx: &FormRenderContext<'_, S>,
-        i18n: &FluentLanguageLoader,
-    ) -> Markup {
-        let btn_id = Uuid::new_v4();
-        let list_id = Uuid::new_v4();
-        let template_id = Uuid::new_v4();
-        let name_regex = regex::escape(name);
-        html! {
-            div class="cms-list-input" id=(list_id) {
-                @if let Some(v) = value {
-                    @for (i, v) in v.iter().enumerate() {
-                        fieldset class="cms-list-element" {
-                            (Input::render_input(Some(v), &format!("{name}[{i}]"), name_human, required, ctx, i18n))
-                        }
-                    }
-                }
-                fieldset id=(template_id) class="cms-list-element" style="display: none" onmount="return true" {
-                    (Input::render_input(Option::<&T>::None, &format!("{name}[]"), name_human, required, ctx, i18n))
+#[cfg(feature = "decimal")]
+mod decimal {
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    /// A number input with a step of `0.01`, e.g. for prices. It is submitted in the canonical
+    /// format `-1234.50` and parsed by the [`Deserialize`] implementation of [`Decimal`], which
+    /// keeps trailing zeros.
+    ///
+    /// [`Decimal`] is encoded by sqlx for PostgreSQL and MySQL, not for SQLite. It has no [`TS`]
+    /// implementation, so use `#[ts(type = "string")]` on fields of entities exporting bindings.
+    impl<S: ContextTrait> Input<S> for Decimal {
+        fn render_input(
+            value: Option<&Self>,
+            name: &str,
+            name_human: &str,
+            required: bool,
+            _ctx: &FormRenderContext<'_, S>,
+            _i18n: &FluentLanguageLoader,
+        ) -> Markup {
+            html! {
+                input type="number" name=(name) placeholder=(name_human) class="cms-decimal-input" value=[value] required[required] step="0.01" {}
+            }
+        }
+    }
+
+    /// Right-aligned in the format of the language of the editor, see [`NumberFormat`].
+    impl Column for Decimal {
+        fn render(&self, i18n: &FluentLanguageLoader) -> Markup {
+            html! {
+                span class="cms-decimal" style="display: block; text-align: right; font-variant-numeric: tabular-nums;" {
+                    (NumberFormat::of(&i18n.current_language()).format(&self.to_string()))
                 }
-                button id=(btn_id) {"+"}
-                script type="module" {(PreEscaped(format!(r#"
-const btn = document.getElementById("{btn_id}");
-const list = document.getElementById("{list_id}");
-const template = document.getElementById("{template_id}");
-template.remove();
-template.removeAttribute("style");
-btn.addEventListener("click", (e) => {{
-    e.preventDefault();
-    let el = template.cloneNode(true);
-    el.removeAttribute("id");
-    setIndex(el, list.childElementCount - 2)
-    list.insertBefore(el, btn);
-    callOnMountRecursive(el);
-}});
-function setIndex(el, i) {{
-    for (const e of el.querySelectorAll("[name]")) {{
-        e.name = e.name.replace(/^{name_regex}\[[0-9]*\]/, "{name}["+i+"]")
-    }}
-    for (const e of el.querySelectorAll("[id]")) {{
-        e.id = e.id.replace(/^{name_regex}\[[0-9]*\]/, "{name}["+i+"]")
-    }}
-    for (const e of el.querySelectorAll("[for]")) {{
-        e.attributes.for.value = e.attributes.for.value.replace(/^{name_regex}\[[0-9]*\]/, "{name}["+i+"]")
-    }}
-}}
-                "#).trim()))}
             }
         }
+
+        fn render_csv(&self, _i18n: &FluentLanguageLoader) -> String {
+            self.to_string()
+        }
     }
 }
 
+mod private {
+    pub trait Sealed {}
+    pub trait SealedNumber {}
+}
+
 /**********
- * Option *
+ * Number *
  **********/
 
-impl<T: Input<S>, S: ContextTrait> Input<S> for Option<T> {
-    fn render_input(
-        value: Option<&Self>,
-        name: &str,
-        name_human: &str,
-        _required: bool,
-        ctx: &FormRenderContext<'_, S>,
-        i18n: &FluentLanguageLoader,
-    ) -> Markup {
-        let value = match value {
-            Some(v) => v.as_ref(),
-            None => None,
-        };
-        T::render_input(value, name, name_human, false, ctx, i18n)
-    }
+/// Value of a [`Number`]: any integer or float
+pub trait NumberValue:
+    Copy + Debug + std::fmt::Display + std::str::FromStr + Send + Sync + private::SealedNumber
+{
+    /// whether the number may have a fractional part, used to select the on-screen keyboard
+    const FRACTIONAL: bool;
 }
 
-impl<T: Column> Column for Option<T> {
-    fn render(&self, i18n: &FluentLanguageLoader) -> Markup {
-        match self {
-            Some(v) => v.render(i18n),
-            None => html!(),
-        }
-    }
+macro_rules! number_value {
+    ($fractional:literal: $($t:ty),*) => {
+        $(
+            impl private::SealedNumber for $t {}
+
+            impl NumberValue for $t {
+                const FRACTIONAL: bool = $fractional;
+            }
+        )*
+    };
 }
 
-/********
- * Json *
- ********/
+number_value!(false: i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
+number_value!(true: f32, f64);
+
+/// A number entered and displayed in the format of the language of the editor,
+/// e.g. `1.234,56` in German and `1,234.56` in English, see [`NumberFormat`].
+///
+/// In the JSON API it is a plain number. Strings are parsed in the format of the form if they are
+/// submitted in the admin interface, otherwise as `1234.56`. Invalid numbers are rejected.
+#[derive(Clone, Copy, Debug, Default, Display, PartialEq, PartialOrd, Serialize)]
+#[serde(transparent)]
+pub struct Number<T: NumberValue>(pub T);
+
+impl<T: NumberValue> Number<T> {
+    pub fn get(self) -> T {
+        self.0
+    }
+}
+
+impl<'de, T: NumberValue> Deserialize<'de> for Number<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NumberVisitor<T>(std::marker::PhantomData<T>);
+
+        impl<T: NumberValue> NumberVisitor<T> {
+            fn parse<E: serde::de::Error>(canonical: &str) -> Result<Number<T>, E> {
+                canonical
+                    .parse()
+                    .map(Number)
+                    .map_err(|_| E::custom(format!("{canonical} is out of range")))
+            }
+        }
+
+        impl<T: NumberValue> serde::de::Visitor<'_> for NumberVisitor<T> {
+            type Value = Number<T>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a number")
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Self::parse(&v.to_string())
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Self::parse(&v.to_string())
+            }
+
+            fn visit_i128<E: serde::de::Error>(self, v: i128) -> Result<Self::Value, E> {
+                Self::parse(&v.to_string())
+            }
+
+            fn visit_u128<E: serde::de::Error>(self, v: u128) -> Result<Self::Value, E> {
+                Self::parse(&v.to_string())
+            }
+
+            fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                Self::parse(&v.to_string())
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                let canonical = NumberFormat::current().parse(v).map_err(E::custom)?;
+                Self::parse(&canonical)
+            }
+        }
+
+        deserializer.deserialize_any(NumberVisitor(std::marker::PhantomData))
+    }
+}
+
+impl<T: NumberValue + TS> TS for Number<T> {
+    type WithoutGenerics = Self;
+
+    fn decl() -> String {
+        T::decl()
+    }
+
+    fn decl_concrete() -> String {
+        T::decl_concrete()
+    }
+
+    fn name() -> String {
+        T::name()
+    }
+
+    fn inline() -> String {
+        T::inline()
+    }
+
+    fn inline_flattened() -> String {
+        T::inline_flattened()
+    }
+}
+
+impl<T: NumberValue + sqlx::Type<DB>> sqlx::Type<DB> for Number<T> {
+    fn type_info() -> <DB as sqlx::Database>::TypeInfo {
+        T::type_info()
+    }
+
+    fn compatible(ty: &<DB as sqlx::Database>::TypeInfo) -> bool {
+        T::compatible(ty)
+    }
+}
+
+impl<'r, T: NumberValue + sqlx::Decode<'r, DB>> sqlx::Decode<'r, DB> for Number<T> {
+    fn decode(value: <DB as sqlx::Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        T::decode(value).map(Self)
+    }
+}
+
+impl<'q, T: NumberValue + sqlx::Encode<'q, DB>> sqlx::Encode<'q, DB> for Number<T> {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'q>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        self.0.encode_by_ref(buf)
+    }
+}
+
+impl<T: NumberValue, S: ContextTrait> Input<S> for Number<T> {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        required: bool,
+        _ctx: &FormRenderContext<'_, S>,
+        i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        let format = NumberFormat::of(&i18n.current_language());
+        html! {
+            input
+                type="text"
+                inputmode=(if T::FRACTIONAL { "decimal" } else { "numeric" })
+                name=(name)
+                placeholder=(name_human)
+                class="cms-number-input"
+                value=[value.map(|v| format.format(&v.0.to_string()))]
+                required[required] {}
+        }
+    }
+}
+
+impl<T: NumberValue> Column for Number<T> {
+    fn render(&self, i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            (NumberFormat::of(&i18n.current_language()).format(&self.0.to_string()))
+        }
+    }
+}
+
+/***********
+ * Percent *
+ ***********/
+
+/// Value of a [`Percent`]: an integer from 0 to 100 or a float from 0.0 to 1.0
+pub trait PercentValue:
+    Copy + Debug + std::fmt::Display + PartialOrd + Send + Sync + private::Sealed
+{
+    const MIN: Self;
+    /// the value of 100 %
+    const MAX: Self;
+    /// step of the slider
+    const STEP: &'static str;
+
+    /// `self` as a fraction of [`PercentValue::MAX`]
+    fn fraction(self) -> f64;
+}
+
+/// A percentage rendered as a slider, stored as a `u8` from 0 to 100 (default)
+/// or as a `f64` from 0.0 to 1.0. Values out of range are rejected.
+#[derive(Clone, Copy, Debug, Display, PartialEq, PartialOrd, Serialize)]
+#[serde(transparent)]
+pub struct Percent<T: PercentValue = u8>(T);
+
+impl<T: PercentValue> Percent<T> {
+    /// `None` if `value` is not between [`PercentValue::MIN`] and [`PercentValue::MAX`]
+    pub fn new(value: T) -> Option<Self> {
+        (T::MIN <= value && value <= T::MAX).then_some(Self(value))
+    }
+
+    pub fn get(self) -> T {
+        self.0
+    }
+}
+
+impl<'de, T: PercentValue + Deserialize<'de>> Deserialize<'de> for Percent<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = T::deserialize(deserializer)?;
+        Self::new(value).ok_or_else(|| {
+            serde::de::Error::custom(format!("{value} is not between {} and {}", T::MIN, T::MAX))
+        })
+    }
+}
+
+/// implement [`PercentValue`] for `$t`, stored as `$sql`
+macro_rules! percent_value {
+    ($t:ty, $sql:ty, $min:literal, $max:literal, $step:literal) => {
+        impl private::Sealed for $t {}
+
+        impl PercentValue for $t {
+            const MIN: Self = $min;
+            const MAX: Self = $max;
+            const STEP: &'static str = $step;
+
+            fn fraction(self) -> f64 {
+                f64::from(self) / f64::from(Self::MAX)
+            }
+        }
+
+        impl TS for Percent<$t> {
+            type WithoutGenerics = Percent<$t>;
+
+            fn decl() -> String {
+                <$t>::decl()
+            }
+
+            fn decl_concrete() -> String {
+                <$t>::decl_concrete()
+            }
+
+            fn name() -> String {
+                <$t>::name()
+            }
+
+            fn inline() -> String {
+                <$t>::inline()
+            }
+
+            fn inline_flattened() -> String {
+                <$t>::inline_flattened()
+            }
+        }
+
+        impl sqlx::Type<DB> for Percent<$t>
+        where
+            $sql: sqlx::Type<DB>,
+        {
+            fn type_info() -> <DB as sqlx::Database>::TypeInfo {
+                <$sql as sqlx::Type<DB>>::type_info()
+            }
+        }
+
+        impl<'r> sqlx::Decode<'r, DB> for Percent<$t>
+        where
+            $sql: sqlx::Decode<'r, DB>,
+        {
+            fn decode(value: <DB as sqlx::Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+                let value = <$sql as sqlx::Decode<DB>>::decode(value)?;
+                let value = <$t>::try_from(value)?;
+                Ok(Self::new(value).ok_or_else(|| format!("percent out of range: {value}"))?)
+            }
+        }
+
+        impl<'r> sqlx::Encode<'r, DB> for Percent<$t>
+        where
+            $sql: sqlx::Encode<'r, DB>,
+        {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'r>,
+            ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+                sqlx::Encode::<'_, DB>::encode(<$sql>::from(self.0), buf)
+            }
+        }
+    };
+}
+
+percent_value!(u8, i16, 0, 100, "1");
+percent_value!(f64, f64, 0.0, 1.0, "0.01");
+
+impl<T: PercentValue, S: ContextTrait> Input<S> for Percent<T> {
+    /// A slider and a number input, which are kept in sync.
+    /// Only the number input is submitted, so it may be empty if the value is optional.
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        required: bool,
+        ctx: &FormRenderContext<'_, S>,
+        _i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        ctx.require_script("js/inputs.js");
+        html! {
+            div class="cms-percent-input" {
+                input
+                    type="range"
+                    min=(T::MIN)
+                    max=(T::MAX)
+                    step=(T::STEP)
+                    value=(value.map(|v| v.0).unwrap_or(T::MIN))
+                    data-cms-input="cmsPercentRange" {}
+                input
+                    type="number"
+                    name=(name)
+                    placeholder=(name_human)
+                    class="cms-percent-number"
+                    min=(T::MIN)
+                    max=(T::MAX)
+                    step=(T::STEP)
+                    value=[value.map(|v| v.0)]
+                    required[required]
+                    data-cms-input="cmsPercentNumber" {}
+            }
+        }
+    }
+}
+
+impl<T: PercentValue> Column for Percent<T> {
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        let fraction = self.0.fraction();
+        html! {
+            progress class="cms-percent-bar" max="1" value=(fraction) {}
+            " " (format!("{:.0} %", fraction * 100.0))
+        }
+    }
+}
+
+/************
+ * DateTime *
+ ************/
+
+impl<Tz: chrono::TimeZone, S: ContextTrait> Input<S> for DateTime<Tz>
+where
+    for<'de> DateTime<Tz>: Deserialize<'de>,
+{
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        _name_human: &str,
+        required: bool,
+        ctx: &FormRenderContext<'_, S>,
+        _i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        let input_id = Uuid::new_v4();
+        let hidden_id = Uuid::new_v4();
+        let time_zone = ctx.ctx.time_zone();
+        ctx.require_script("js/datetime.js");
+        html! {
+            input type="datetime-local" id=(input_id) class="cms-datetime-input" required[required] {}
+            @if let Some(time_zone) = time_zone {
+                " " span class="cms-datetime-zone" {(time_zone)}
+            }
+            input type="hidden" name=(name) id=(hidden_id) value=[value.map(|v|v.to_rfc3339())] {}
+            script nonce=[&ctx.nonce] {(PreEscaped(format!(
+                r#"cmsDateTimeInit(document.getElementById("{input_id}"), document.getElementById("{hidden_id}"), document.getElementById("{}"), {});"#,
+                ctx.form_id,
+                serde_json::to_string(&time_zone).unwrap_or_else(|_| "null".to_string()),
+            )))}
+            noscript {
+                "It appears that JavaScript is disabled. JavaScript is required to set dates in your current timezone. Please enter dates in UTC (Coordinated universal time) instead."
+            }
+        }
+    }
+}
+impl<Tz: chrono::TimeZone> Column for DateTime<Tz>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            time datetime=(self.to_rfc3339()) {
+                (self.to_string())
+            }
+        }
+    }
+
+    fn render_csv(&self, _i18n: &FluentLanguageLoader) -> String {
+        self.to_rfc3339()
+    }
+}
+
+/***************************************
+ * NaiveDate, NaiveTime, NaiveDateTime *
+ **************************************/
+
+impl<S: ContextTrait> Input<S> for NaiveDate {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        _name_human: &str,
+        required: bool,
+        _ctx: &FormRenderContext<'_, S>,
+        _i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        html! {
+            input type="date" name=(name) value=[value] required[required] {}
+        }
+    }
+}
+impl Column for NaiveDate {
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            time datetime=(self) {(self)}
+        }
+    }
+}
+
+impl<S: ContextTrait> Input<S> for NaiveTime {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        _name_human: &str,
+        required: bool,
+        _ctx: &FormRenderContext<'_, S>,
+        _i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        html! {
+            input type="time" name=(name) step="1" value=[value] required[required] {}
+        }
+    }
+}
+impl Column for NaiveTime {
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            time datetime=(self) {(self)}
+        }
+    }
+}
+
+impl<S: ContextTrait> Input<S> for NaiveDateTime {
+    /// A `datetime-local` input in no particular time zone. The browser omits the seconds if they
+    /// are zero, which are required by [`NaiveDateTime`], so they are added to a hidden input
+    /// which is submitted instead.
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        _name_human: &str,
+        required: bool,
+        ctx: &FormRenderContext<'_, S>,
+        _i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        let value = value.map(|v| v.format("%Y-%m-%dT%H:%M:%S").to_string());
+        ctx.require_script("js/inputs.js");
+        html! {
+            input
+                type="datetime-local"
+                step="1"
+                value=[&value]
+                required[required]
+                data-cms-input="cmsNaiveDateTimeInput" {}
+            input type="hidden" name=(name) value=[&value] {}
+        }
+    }
+}
+impl Column for NaiveDateTime {
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            time datetime=(self.format("%Y-%m-%dT%H:%M:%S%.f")) {(self)}
+        }
+    }
+
+    fn render_csv(&self, _i18n: &FluentLanguageLoader) -> String {
+        self.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+    }
+}
+
+/************
+ * TimeZone *
+ ************/
+
+/// Name of a time zone of the IANA time zone database, e.g. `Europe/Berlin`.
+///
+/// The input suggests all current zones while typing. Deprecated names like `Asia/Calcutta` are
+/// accepted as well, unknown names are rejected when deserializing.
+/// The column shows the current UTC offset, which is computed by the browser in the language of
+/// the page.
+#[derive(
+    Clone, Debug, Deref, Display, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize,
+)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeZone(String);
+
+#[derive(Debug, Error)]
+#[error("unknown time zone: {0}")]
+pub struct InvalidTimeZone(pub String);
+
+impl TimeZone {
+    pub fn new(name: impl Into<String>) -> Result<Self, InvalidTimeZone> {
+        let name = name.into();
+        match time_zones::CANONICAL.binary_search(&name.as_str()).is_ok()
+            || time_zones::ALIASES.binary_search(&name.as_str()).is_ok()
+        {
+            true => Ok(Self(name)),
+            false => Err(InvalidTimeZone(name)),
+        }
+    }
+
+    pub fn utc() -> Self {
+        Self("UTC".to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// names of all current time zones, sorted
+    pub fn all() -> &'static [&'static str] {
+        time_zones::CANONICAL
+    }
+}
+
+impl Default for TimeZone {
+    fn default() -> Self {
+        Self::utc()
+    }
+}
+
+impl TryFrom<String> for TimeZone {
+    type Error = InvalidTimeZone;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl std::str::FromStr for TimeZone {
+    type Err = InvalidTimeZone;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl From<TimeZone> for String {
+    fn from(value: TimeZone) -> Self {
+        value.0
+    }
+}
+
+impl TS for TimeZone {
+    type WithoutGenerics = TimeZone;
+
+    fn decl() -> String {
+        String::decl()
+    }
+
+    fn decl_concrete() -> String {
+        String::decl_concrete()
+    }
+
+    fn name() -> String {
+        String::name()
+    }
+
+    fn inline() -> String {
+        String::inline()
+    }
+
+    fn inline_flattened() -> String {
+        String::inline_flattened()
+    }
+}
+
+/// not validated, so zones removed from the database can still be loaded
+impl<'r> sqlx::Decode<'r, DB> for TimeZone
+where
+    String: sqlx::Decode<'r, DB>,
+{
+    fn decode(value: <DB as sqlx::Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(Self(<String as sqlx::Decode<DB>>::decode(value)?))
+    }
+}
+
+impl sqlx::Type<DB> for TimeZone
+where
+    String: sqlx::Type<DB>,
+{
+    fn type_info() -> <DB as sqlx::Database>::TypeInfo {
+        <String as sqlx::Type<DB>>::type_info()
+    }
+}
+
+impl<'r> sqlx::Encode<'r, DB> for TimeZone
+where
+    String: sqlx::Encode<'r, DB>,
+{
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'r>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        sqlx::Encode::<'_, DB>::encode(&self.0, buf)
+    }
+}
+
+impl<S: ContextTrait> Input<S> for TimeZone {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        required: bool,
+        _ctx: &FormRenderContext<'_, S>,
+        _i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        let list_id = Uuid::new_v4();
+        html! {
+            input type="text" name=(name) placeholder=(name_human) class="cms-time-zone-input"
+                list=(list_id)
+                autocomplete="off"
+                value=[value]
+                required[required] {}
+            datalist id=(list_id) {
+                @for zone in TimeZone::all() {
+                    option value=(zone) {}
+                }
+            }
+        }
+    }
+}
+
+impl Column for TimeZone {
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            (self.0) " "
+            // see `js/timezone.js`
+            span class="cms-time-zone-offset" data-time-zone=(self.0) {}
+        }
+    }
+
+    fn render_csv(&self, _i18n: &FluentLanguageLoader) -> String {
+        self.0.clone()
+    }
+}
+
+/*********
+ * Color *
+ *********/
+
+/// A color in hex notation `#RRGGBB`, rendered as a color picker. Other values are rejected
+/// when deserializing.
+///
+/// The color picker always has a value, such that an [`Option<Color>`] can't be unset in the
+/// admin interface.
+#[derive(Clone, Debug, Deref, Display, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color(String);
+
+#[derive(Debug, Error)]
+#[error("invalid color {0:?}, expected #RRGGBB")]
+pub struct InvalidColor(pub String);
+
+impl Color {
+    pub fn new(hex: impl Into<String>) -> Result<Self, InvalidColor> {
+        let hex = hex.into();
+        match hex.strip_prefix('#') {
+            Some(digits) if digits.len() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+                Ok(Self(hex))
+            }
+            _ => Err(InvalidColor(hex)),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// black, the default of a color picker
+impl Default for Color {
+    fn default() -> Self {
+        Self("#000000".to_string())
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = InvalidColor;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl std::str::FromStr for Color {
+    type Err = InvalidColor;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl From<Color> for String {
+    fn from(value: Color) -> Self {
+        value.0
+    }
+}
+
+impl TS for Color {
+    type WithoutGenerics = Color;
+
+    fn decl() -> String {
+        String::decl()
+    }
+
+    fn decl_concrete() -> String {
+        String::decl_concrete()
+    }
+
+    fn name() -> String {
+        String::name()
+    }
+
+    fn inline() -> String {
+        String::inline()
+    }
+
+    fn inline_flattened() -> String {
+        String::inline_flattened()
+    }
+}
+
+impl<'r> sqlx::Decode<'r, DB> for Color
+where
+    String: sqlx::Decode<'r, DB>,
+{
+    fn decode(value: <DB as sqlx::Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(Self::new(<String as sqlx::Decode<DB>>::decode(value)?)?)
+    }
+}
+
+impl sqlx::Type<DB> for Color
+where
+    String: sqlx::Type<DB>,
+{
+    fn type_info() -> <DB as sqlx::Database>::TypeInfo {
+        <String as sqlx::Type<DB>>::type_info()
+    }
+}
+
+impl<'r> sqlx::Encode<'r, DB> for Color
+where
+    String: sqlx::Encode<'r, DB>,
+{
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'r>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        sqlx::Encode::<'_, DB>::encode(&self.0, buf)
+    }
+}
+
+impl<S: ContextTrait> Input<S> for Color {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        _name_human: &str,
+        required: bool,
+        _ctx: &FormRenderContext<'_, S>,
+        _i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        html! {
+            input type="color" name=(name) class="cms-color-input"
+                value=[value]
+                required[required] {}
+        }
+    }
+
+    /// a color picker always has a value
+    fn mark_required() -> bool {
+        false
+    }
+}
+
+impl Column for Color {
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            span class="cms-color-swatch" style=(format!("background-color: {}", self.0)) {}
+            " " (self.0)
+        }
+    }
+}
+
+/***************
+ * Email / Url *
+ ***************/
+
+/// implement the conversions, [`TS`] and `sqlx` traits of a validated string `$t` with the
+/// constructor `$t::new` returning `Result<$t, $err>`, stored like [`Text`]
+macro_rules! validated_string {
+    ($t:ident, $err:ident) => {
+        impl $t {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl TryFrom<String> for $t {
+            type Error = $err;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                Self::new(value)
+            }
+        }
+
+        impl std::str::FromStr for $t {
+            type Err = $err;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::new(s)
+            }
+        }
+
+        impl From<$t> for String {
+            fn from(value: $t) -> Self {
+                value.0
+            }
+        }
+
+        impl TS for $t {
+            type WithoutGenerics = $t;
+
+            fn decl() -> String {
+                String::decl()
+            }
+
+            fn decl_concrete() -> String {
+                String::decl_concrete()
+            }
+
+            fn name() -> String {
+                String::name()
+            }
+
+            fn inline() -> String {
+                String::inline()
+            }
+
+            fn inline_flattened() -> String {
+                String::inline_flattened()
+            }
+        }
+
+        /// not validated, so rows written before the field was changed to this type can still be loaded
+        impl<'r> sqlx::Decode<'r, DB> for $t
+        where
+            String: sqlx::Decode<'r, DB>,
+        {
+            fn decode(value: <DB as sqlx::Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+                Ok(Self(<String as sqlx::Decode<DB>>::decode(value)?))
+            }
+        }
+
+        impl sqlx::Type<DB> for $t
+        where
+            String: sqlx::Type<DB>,
+        {
+            fn type_info() -> <DB as sqlx::Database>::TypeInfo {
+                <String as sqlx::Type<DB>>::type_info()
+            }
+        }
+
+        impl<'r> sqlx::Encode<'r, DB> for $t
+        where
+            String: sqlx::Encode<'r, DB>,
+        {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'r>,
+            ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+                sqlx::Encode::<'_, DB>::encode(&self.0, buf)
+            }
+        }
+    };
+}
+
+/// An email address, rendered as `<input type="email">` and as a `mailto:` link in columns.
+///
+/// Addresses are validated like the browser does for `<input type="email">`, invalid addresses
+/// are rejected when deserializing.
+#[derive(Clone, Debug, Deref, Display, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Email(String);
+
+#[derive(Debug, Error)]
+#[error("invalid email address {0:?}")]
+pub struct InvalidEmail(pub String);
+
+/// the "valid email address" of the HTML standard
+static EMAIL_REGEX: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(
+        r"^[a-zA-Z0-9.!#$%&'*+/=?^_`{|}~-]+@[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?(?:\.[a-zA-Z0-9](?:[a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?)*$",
+    )
+    .unwrap()
+});
+
+impl Email {
+    pub fn new(email: impl Into<String>) -> Result<Self, InvalidEmail> {
+        let email = email.into();
+        match EMAIL_REGEX.is_match(&email) {
+            true => Ok(Self(email)),
+            false => Err(InvalidEmail(email)),
+        }
+    }
+}
+
+validated_string!(Email, InvalidEmail);
+
+impl<S: ContextTrait> Input<S> for Email {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        required: bool,
+        ctx: &FormRenderContext<'_, S>,
+        _i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        html! {
+            input type="email" name=(name) placeholder=(name_human) class="cms-email-input"
+                value=[value]
+                required[required]
+                minlength=[ctx.constraints().min_length]
+                maxlength=[ctx.constraints().max_length] {}
+        }
+    }
+
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        constraints.check_input(name, &self.0)
+    }
+}
+
+impl Column for Email {
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            a href=(format!("mailto:{}", self.0)) {(self.0)}
+        }
+    }
+}
+
+/// An absolute `http` or `https` URL, rendered as `<input type="url">` and as a link in columns.
+///
+/// Other schemes like `javascript:` are rejected when deserializing, such that the link can be
+/// opened safely.
+#[derive(Clone, Debug, Deref, Display, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Url(String);
+
+#[derive(Debug, Error)]
+#[error("invalid URL {0:?}, expected an absolute http or https URL")]
+pub struct InvalidUrl(pub String);
+
+impl Url {
+    /// The URL is kept as entered, it is only parsed to validate it.
+    pub fn new(url: impl Into<String>) -> Result<Self, InvalidUrl> {
+        let url = url.into();
+        match url::Url::parse(&url) {
+            Ok(u) if matches!(u.scheme(), "http" | "https") && u.has_host() => Ok(Self(url)),
+            _ => Err(InvalidUrl(url)),
+        }
+    }
+}
+
+validated_string!(Url, InvalidUrl);
+
+impl<S: ContextTrait> Input<S> for Url {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        required: bool,
+        ctx: &FormRenderContext<'_, S>,
+        _i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        html! {
+            input type="url" name=(name) placeholder=(name_human) class="cms-url-input"
+                value=[value]
+                required[required]
+                minlength=[ctx.constraints().min_length]
+                maxlength=[ctx.constraints().max_length] {}
+        }
+    }
+
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        constraints.check_input(name, &self.0)
+    }
+}
+
+impl Column for Url {
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            a href=(self.0) target="_blank" rel="noopener noreferrer" {(self.0)}
+        }
+    }
+}
+
+/************
+ * Password *
+ ************/
+
+/// A password or another secret, e.g. the hash of a password, rendered as
+/// `<input type="password">`. The current value is never rendered, neither in the form nor in
+/// columns, and is redacted in [`Debug`].
+///
+/// The input is always empty, so an unchanged form submits an empty password. To keep the current
+/// value in this case, use a custom [`Update`](crate::EntityBase::Update) type with an
+/// `Option<Password>` field: empty optional inputs are not submitted, such that the field is
+/// `None` and [`Update::update`](crate::entity::Update::update) can keep the stored value.
+/// Otherwise an empty `Password` is submitted and has to be handled in
+/// [`Update::update`](crate::entity::Update::update), e.g. using [`Password::is_empty`].
+///
+/// The value is still serialized in the JSON API, override
+/// [`EntityBase::api_serialize`](crate::EntityBase::api_serialize) to redact it.
+#[derive(
+    Clone, Default, Deref, DerefMut, From, Into, PartialEq, Eq, Hash, Deserialize, Serialize,
+)]
+#[serde(transparent)]
+pub struct Password(pub String);
+
+impl Debug for Password {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Password(***)")
+    }
+}
+
+impl TS for Password {
+    type WithoutGenerics = Password;
+
+    fn decl() -> String {
+        String::decl()
+    }
+
+    fn decl_concrete() -> String {
+        String::decl_concrete()
+    }
+
+    fn name() -> String {
+        String::name()
+    }
+
+    fn inline() -> String {
+        String::inline()
+    }
+
+    fn inline_flattened() -> String {
+        String::inline_flattened()
+    }
+}
+
+impl<'r> sqlx::Decode<'r, DB> for Password
+where
+    String: sqlx::Decode<'r, DB>,
+{
+    fn decode(value: <DB as sqlx::Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(Self(<String as sqlx::Decode<DB>>::decode(value)?))
+    }
+}
+
+impl sqlx::Type<DB> for Password
+where
+    String: sqlx::Type<DB>,
+{
+    fn type_info() -> <DB as sqlx::Database>::TypeInfo {
+        <String as sqlx::Type<DB>>::type_info()
+    }
+}
+
+impl<'r> sqlx::Encode<'r, DB> for Password
+where
+    String: sqlx::Encode<'r, DB>,
+{
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'r>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        sqlx::Encode::<'_, DB>::encode(&self.0, buf)
+    }
+}
+
+impl<S: ContextTrait> Input<S> for Password {
+    /// `value` is ignored, the input is always empty
+    fn render_input(
+        _value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        required: bool,
+        ctx: &FormRenderContext<'_, S>,
+        _i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        html! {
+            input type="password" name=(name) placeholder=(name_human) class="cms-password-input"
+                autocomplete="new-password"
+                required[required]
+                minlength=[ctx.constraints().min_length]
+                maxlength=[ctx.constraints().max_length] {}
+        }
+    }
+
+    fn secret() -> bool {
+        true
+    }
+
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        constraints.check_input(name, &self.0)
+    }
+}
+
+impl Column for Password {
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html! { "••••••" }
+    }
+}
+
+/*******************
+ * Weekday / Month *
+ *******************/
+
+/// Small closed set of integers with localized names,
+/// stored as the integer and rendered as a `<select>`.
+macro_rules! choice {
+    (
+        $(#[$attr:meta])*
+        $name:ident, $ftl:literal, [$($n:literal => $const:ident, $label:literal),* $(,)?]
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+        #[serde(try_from = "u8", into = "u8")]
+        pub struct $name(u8);
+
+        impl $name {
+            $(pub const $const: Self = Self($n);)*
+
+            /// all values in ascending order
+            pub const ALL: &'static [Self] = &[$(Self::$const),*];
+
+            pub fn number(self) -> u8 {
+                self.0
+            }
+
+            /// localized name
+            pub fn label(self, i18n: &FluentLanguageLoader) -> String {
+                match self.0 {
+                    $($n => fl!(i18n, $ftl, $label),)*
+                    _ => unreachable!("checked by TryFrom<u8>"),
+                }
+            }
+        }
+
+        impl TryFrom<u8> for $name {
+            type Error = String;
+
+            fn try_from(value: u8) -> Result<Self, Self::Error> {
+                match Self::ALL.iter().any(|v| v.0 == value) {
+                    true => Ok(Self(value)),
+                    false => Err(format!("invalid {}: {value}", $ftl)),
+                }
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl TS for $name {
+            type WithoutGenerics = $name;
+
+            fn decl() -> String {
+                u8::decl()
+            }
+
+            fn decl_concrete() -> String {
+                u8::decl_concrete()
+            }
+
+            fn name() -> String {
+                u8::name()
+            }
+
+            fn inline() -> String {
+                u8::inline()
+            }
+
+            fn inline_flattened() -> String {
+                u8::inline_flattened()
+            }
+        }
+
+        impl<'r> sqlx::Decode<'r, DB> for $name
+        where
+            i16: sqlx::Decode<'r, DB>,
+        {
+            fn decode(value: <DB as sqlx::Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+                let value = <i16 as sqlx::Decode<DB>>::decode(value)?;
+                Ok(Self::try_from(u8::try_from(value)?)?)
+            }
+        }
+
+        impl sqlx::Type<DB> for $name
+        where
+            i16: sqlx::Type<DB>,
+        {
+            fn type_info() -> <DB as sqlx::Database>::TypeInfo {
+                <i16 as sqlx::Type<DB>>::type_info()
+            }
+        }
+
+        impl<'r> sqlx::Encode<'r, DB> for $name
+        where
+            i16: sqlx::Encode<'r, DB>,
+        {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'r>,
+            ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+                sqlx::Encode::<'_, DB>::encode(i16::from(self.0), buf)
+            }
+        }
+
+        impl<S: ContextTrait> Input<S> for $name {
+            fn render_input(
+                value: Option<&Self>,
+                name: &str,
+                _name_human: &str,
+                required: bool,
+                _ctx: &FormRenderContext<'_, S>,
+                i18n: &FluentLanguageLoader,
+            ) -> Markup {
+                let selected = value.map(|v| v.0.to_string());
+                render::select_input(
+                    name,
+                    required,
+                    Self::ALL.iter().map(|v| (v.0.to_string(), v.label(i18n))),
+                    selected.as_deref(),
+                )
+            }
+        }
+
+        impl Column for $name {
+            fn render(&self, i18n: &FluentLanguageLoader) -> Markup {
+                html! {
+                    (self.label(i18n))
+                }
+            }
+        }
+    };
+}
+
+choice!(
+    /// Day of the week, numbered from 1 (Monday) to 7 (Sunday) as in ISO 8601.
+    Weekday,
+    "weekday",
+    [
+        1 => MONDAY, "monday",
+        2 => TUESDAY, "tuesday",
+        3 => WEDNESDAY, "wednesday",
+        4 => THURSDAY, "thursday",
+        5 => FRIDAY, "friday",
+        6 => SATURDAY, "saturday",
+        7 => SUNDAY, "sunday",
+    ]
+);
+
+impl From<chrono::Weekday> for Weekday {
+    fn from(value: chrono::Weekday) -> Self {
+        Self(value.number_from_monday() as u8)
+    }
+}
+
+impl From<Weekday> for chrono::Weekday {
+    fn from(value: Weekday) -> Self {
+        chrono::Weekday::try_from(value.0 - 1).expect("weekday is in range")
+    }
+}
+
+choice!(
+    /// Month of the year, numbered from 1 (January) to 12 (December).
+    Month,
+    "month",
+    [
+        1 => JANUARY, "january",
+        2 => FEBRUARY, "february",
+        3 => MARCH, "march",
+        4 => APRIL, "april",
+        5 => MAY, "may",
+        6 => JUNE, "june",
+        7 => JULY, "july",
+        8 => AUGUST, "august",
+        9 => SEPTEMBER, "september",
+        10 => OCTOBER, "october",
+        11 => NOVEMBER, "november",
+        12 => DECEMBER, "december",
+    ]
+);
+
+impl From<chrono::Month> for Month {
+    fn from(value: chrono::Month) -> Self {
+        Self(value.number_from_month() as u8)
+    }
+}
+
+impl From<Month> for chrono::Month {
+    fn from(value: Month) -> Self {
+        chrono::Month::try_from(value.0).expect("month is in range")
+    }
+}
+
+/********
+ * bool *
+ ********/
+
+impl<S: ContextTrait> Input<S> for bool {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        _name_human: &str,
+        _required: bool,
+        _ctx: &FormRenderContext<'_, S>,
+        _i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        html! {
+            input type="checkbox" name=(name) value="true" checked[*value.unwrap_or(&false)] {}
+        }
+    }
+
+    /// an unchecked checkbox is `false`
+    fn mark_required() -> bool {
+        false
+    }
+
+    /// A checkbox can't distinguish `None` from `Some(false)`,
+    /// so [`Option<bool>`] is rendered as radio buttons with an additional unset option.
+    fn render_input_optional(
+        value: Option<&Self>,
+        name: &str,
+        _name_human: &str,
+        _ctx: &FormRenderContext<'_, S>,
+        i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        let options = [
+            (Some(true), fl!(i18n, "bool-input", "yes")),
+            (Some(false), fl!(i18n, "bool-input", "no")),
+            (None, fl!(i18n, "bool-input", "unset")),
+        ];
+        html! {
+            div class="cms-bool-tristate" {
+                @for (option, label) in options {
+                    @let option_value = option.map(|v| v.to_string()).unwrap_or_default();
+                    @let id = format!("{name}_radio-button_{option_value}");
+                    input
+                        type="radio"
+                        name=(name)
+                        value=(option_value)
+                        id=(id)
+                        checked[value.copied() == option] {}
+                    label for=(id) {(label)}
+                }
+            }
+        }
+    }
+}
+impl Column for bool {
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            input type="checkbox" disabled checked[*self] {}
+        }
+    }
+
+    fn render_csv(&self, _i18n: &FluentLanguageLoader) -> String {
+        self.to_string()
+    }
+}
+
+/**********
+ * Vec<T> *
+ **********/
+
+impl<T: Input<S>, S: ContextTrait> Input<S> for Vec<T> {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        required: bool,
+        ctx: &FormRenderContext<'_, S>,
+        i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        list_input(
+            value.map(|v| v.iter().collect()),
+            name,
+            name_human,
+            required,
+            ctx,
+            i18n,
+        )
+    }
+
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        self.iter().enumerate().try_for_each(|(i, v)| {
+            Input::<S>::check_constraints(v, &format!("{name}[{i}]"), constraints)
+        })
+    }
+}
+
+/// Suffix of the name of the file input of a list whose files are appended to the list,
+/// see [`Input::multiple_files`]. E.g. files submitted as `images[*]` are appended to `images`.
+pub(crate) const MULTIPLE_FILES: &str = "[*]";
+
+/// input with add/remove buttons for the elements of a list or set
+fn list_input<T: Input<S>, S: ContextTrait>(
+    value: Option<Vec<&T>>,
+    name: &str,
+    name_human: &str,
+    required: bool,
+    ctx: &FormRenderContext<'_, S>,
+    i18n: &FluentLanguageLoader,
+) -> Markup {
+    let btn_id = Uuid::new_v4();
+    let list_id = Uuid::new_v4();
+    let template_id = Uuid::new_v4();
+    let name_regex = regex::escape(name);
+    let len = value.as_ref().map_or(0, Vec::len);
+    if let Some(target) = ctx.list_elements().filter(|target| target.name == name) {
+        let range = target.range.start.min(len)..target.range.end.min(len);
+        let rendered = html! {
+            @for (i, v) in value.iter().flatten().enumerate().skip(range.start).take(range.len()) {
+                fieldset class="cms-list-element-content" data-index=(i) {
+                    (T::render_input(Some(*v), &format!("{name}[{i}]"), name_human, required, ctx, i18n))
+                }
+            }
+        };
+        *target.rendered.borrow_mut() = Some(rendered);
+        return html! {};
+    }
+    // the elements of a list within an element of this list are loaded
+    let contains_target = ctx
+        .list_elements()
+        .is_some_and(|target| target.name.starts_with(&format!("{name}[")));
+    let collapse_from = ctx
+        .ctx
+        .list_collapse_threshold()
+        .filter(|threshold| !contains_target && len > *threshold);
+    // the inputs of collapsed elements are loaded when they are expanded, or rendered in templates
+    let src = collapse_from.and_then(|_| ctx.list_elements_src(name));
+    if collapse_from.is_some() {
+        ctx.require_script("js/list.js");
+    }
+    html! {
+        div class="cms-list-input" id=(list_id) data-cms-list-src=[src.as_ref()] {
+            @if let Some(v) = value {
+                @for (i, v) in v.iter().enumerate() {
+                    @if collapse_from.is_some_and(|threshold| i >= threshold) {
+                        details class="cms-list-element cms-list-element-collapsed" data-cms-toggle="cmsListElementExpand" data-index=(i) {
+                            summary {(name_human) " " (i + 1)}
+                            @if src.is_none() {
+                                template {
+                                    (T::render_input(Some(*v), &format!("{name}[{i}]"), name_human, required, ctx, i18n))
+                                }
+                            }
+                        }
+                    } @else {
+                        fieldset class="cms-list-element" {
+                            (T::render_input(Some(*v), &format!("{name}[{i}]"), name_human, required, ctx, i18n))
+                        }
+                    }
+                }
+            }
+            fieldset id=(template_id) class="cms-list-element" style="display: none" data-cms-template {
+                (Input::render_input(Option::<&T>::None, &format!("{name}[]"), name_human, required, ctx, i18n))
+            }
+            button id=(btn_id) {"+"}
+            @if let Some(accept) = T::multiple_files().map(|accept| ctx.constraints().accept.unwrap_or(accept)) {
+                label class="cms-list-add-files" {
+                    (fl!(i18n, "list-input-add-files"))
+                    " "
+                    input type="file" multiple accept=(accept) name=(format!("{name}{MULTIPLE_FILES}")) {}
+                }
+            }
+            script type="module" nonce=[&ctx.nonce] {(PreEscaped(format!(r#"
+const btn = document.getElementById("{btn_id}");
+const list = document.getElementById("{list_id}");
+const template = document.getElementById("{template_id}");
+template.remove();
+template.removeAttribute("style");
+template.removeAttribute("data-cms-template");
+btn.addEventListener("click", (e) => {{
+    e.preventDefault();
+    let el = template.cloneNode(true);
+    el.removeAttribute("id");
+    setIndex(el, list.querySelectorAll(":scope > .cms-list-element").length);
+    list.insertBefore(el, btn);
+    callOnMountRecursive(el);
+}});
+function setIndex(el, i) {{
+    for (const e of el.querySelectorAll("[name]")) {{
+        e.name = e.name.replace(/^{name_regex}\[[0-9]*\]/, "{name}["+i+"]")
+    }}
+    for (const e of el.querySelectorAll("[id]")) {{
+        e.id = e.id.replace(/^{name_regex}\[[0-9]*\]/, "{name}["+i+"]")
+    }}
+    for (const e of el.querySelectorAll("[for]")) {{
+        e.attributes.for.value = e.attributes.for.value.replace(/^{name_regex}\[[0-9]*\]/, "{name}["+i+"]")
+    }}
+}}
+                "#).trim()))}
+        }
+    }
+}
+
+/**********
+ * Unique *
+ **********/
+
+/// A [`HashSet`] or [`BTreeSet`] that rejects duplicate elements when it is deserialized, e.g. from
+/// a submitted form or the JSON API, instead of silently collapsing them.
+/// Rendered as a list input like `Vec<T>` and as a column of the sorted members.
+#[derive(Clone, Debug, Default, Deref, DerefMut, From, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct Unique<C>(pub C);
+
+/// A set that [`Unique`] can be deserialized into.
+pub trait UniqueElements: Default {
+    type Element;
+
+    /// Insert `element`, returning `false` if it was already present.
+    fn insert_unique(&mut self, element: Self::Element) -> bool;
+}
+
+impl<T: Eq + Hash> UniqueElements for HashSet<T> {
+    type Element = T;
+
+    fn insert_unique(&mut self, element: T) -> bool {
+        self.insert(element)
+    }
+}
+
+impl<T: Ord> UniqueElements for BTreeSet<T> {
+    type Element = T;
+
+    fn insert_unique(&mut self, element: T) -> bool {
+        self.insert(element)
+    }
+}
+
+impl<'de, C> Deserialize<'de> for Unique<C>
+where
+    C: UniqueElements,
+    C::Element: Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor<C>(PhantomData<C>);
+
+        impl<'de, C> serde::de::Visitor<'de> for Visitor<C>
+        where
+            C: UniqueElements,
+            C::Element: Deserialize<'de>,
+        {
+            type Value = Unique<C>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a sequence of unique elements")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut set = C::default();
+                let mut i = 0;
+                while let Some(element) = seq.next_element()? {
+                    if !set.insert_unique(element) {
+                        return Err(serde::de::Error::custom(format!(
+                            "element {i} is a duplicate"
+                        )));
+                    }
+                    i += 1;
+                }
+                Ok(Unique(set))
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor(PhantomData))
+    }
+}
+
+impl<C: TS> TS for Unique<C> {
+    type WithoutGenerics = C::WithoutGenerics;
+
+    fn decl() -> String {
+        C::decl()
+    }
+
+    fn decl_concrete() -> String {
+        C::decl_concrete()
+    }
+
+    fn name() -> String {
+        C::name()
+    }
+
+    fn inline() -> String {
+        C::inline()
+    }
+
+    fn inline_flattened() -> String {
+        C::inline_flattened()
+    }
+
+    fn visit_dependencies(visitor: &mut impl ts_rs::TypeVisitor)
+    where
+        Self: 'static,
+    {
+        C::visit_dependencies(visitor)
+    }
+
+    fn visit_generics(visitor: &mut impl ts_rs::TypeVisitor)
+    where
+        Self: 'static,
+    {
+        C::visit_generics(visitor)
+    }
+
+    fn output_path() -> Option<&'static Path> {
+        C::output_path()
+    }
+}
+
+impl<T: Input<S> + Eq + Hash, S: ContextTrait> Input<S> for Unique<HashSet<T>> {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        required: bool,
+        ctx: &FormRenderContext<'_, S>,
+        i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        list_input(
+            value.map(|v| v.iter().collect()),
+            name,
+            name_human,
+            required,
+            ctx,
+            i18n,
+        )
+    }
+
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        self.iter().enumerate().try_for_each(|(i, v)| {
+            Input::<S>::check_constraints(v, &format!("{name}[{i}]"), constraints)
+        })
+    }
+}
+
+impl<T: Input<S> + Ord, S: ContextTrait> Input<S> for Unique<BTreeSet<T>> {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        required: bool,
+        ctx: &FormRenderContext<'_, S>,
+        i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        list_input(
+            value.map(|v| v.iter().collect()),
+            name,
+            name_human,
+            required,
+            ctx,
+            i18n,
+        )
+    }
+
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        self.iter().enumerate().try_for_each(|(i, v)| {
+            Input::<S>::check_constraints(v, &format!("{name}[{i}]"), constraints)
+        })
+    }
+}
+
+impl<C: Column> Column for Unique<C> {
+    fn render(&self, i18n: &FluentLanguageLoader) -> Markup {
+        self.0.render(i18n)
+    }
+
+    fn render_csv(&self, i18n: &FluentLanguageLoader) -> String {
+        self.0.render_csv(i18n)
+    }
+}
+
+impl<T: Column + Eq + Hash> Column for HashSet<T> {
+    /// members sorted by their rendered value
+    fn render(&self, i18n: &FluentLanguageLoader) -> Markup {
+        let mut members = self
+            .iter()
+            .map(|v| v.render(i18n).into_string())
+            .collect::<Vec<_>>();
+        members.sort();
+        html! {
+            (PreEscaped(members.join(", ")))
+        }
+    }
+}
+
+impl<T: Column + Ord> Column for BTreeSet<T> {
+    fn render(&self, i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            @for (i, v) in self.iter().enumerate() {
+                @if i > 0 {
+                    ", "
+                }
+                (v.render(i18n))
+            }
+        }
+    }
+}
+
+/********
+ * Tags *
+ ********/
+
+/// Freeform tags, rendered as a single text input that turns the entered text into a tag when
+/// pressing enter or comma, instead of one input per element like `Vec<Text>`.
+/// Serialized as an array of strings, e.g. stored as `Json<Tags>`.
+///
+/// No tags are submitted if there are none, so the field needs a `#[serde(default)]`.
+#[derive(
+    Clone, Debug, Default, Deref, DerefMut, From, Into, PartialEq, Eq, Hash, Deserialize, Serialize,
+)]
+#[serde(transparent)]
+pub struct Tags(pub Vec<Text>);
+
+impl TS for Tags {
+    type WithoutGenerics = Tags;
+
+    fn decl() -> String {
+        Vec::<String>::decl()
+    }
+
+    fn decl_concrete() -> String {
+        Vec::<String>::decl_concrete()
+    }
+
+    fn name() -> String {
+        Vec::<String>::name()
+    }
+
+    fn inline() -> String {
+        Vec::<String>::inline()
+    }
+
+    fn inline_flattened() -> String {
+        Vec::<String>::inline_flattened()
+    }
+}
+
+impl<S: ContextTrait> Input<S> for Tags {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        required: bool,
+        ctx: &FormRenderContext<'_, S>,
+        i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        ctx.require_script("js/tags.js");
+        let remove = fl!(i18n, "tags-input-remove");
+        let tags = value.map(|v| v.as_slice()).unwrap_or_default();
+        html! {
+            div class="cms-tags-input" data-remove-label=(remove) data-cms-mount="cmsTagsInit" {
+                @for tag in tags {
+                    span class="cms-tag" {
+                        (tag)
+                        button type="button" class="cms-tag-remove" aria-label=(remove) title=(remove) {"×"}
+                        input type="hidden" name=(format!("{name}[]")) value=(tag) {}
+                    }
+                }
+                // template of the hidden input of new tags, not submitted
+                input type="hidden" class="cms-tags-name" name=(format!("{name}[]")) disabled {}
+                input type="text" class="cms-tags-entry" placeholder=(name_human)
+                    required[required && tags.is_empty()]
+                    data-required[required]
+                    minlength=[ctx.constraints().min_length]
+                    maxlength=[ctx.constraints().max_length] {}
+            }
+        }
+    }
+
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        self.0
+            .iter()
+            .try_for_each(|tag| constraints.check_input(name, &tag.0))
+    }
+}
+
+impl Column for Tags {
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            span class="cms-tags" {
+                @for tag in &self.0 {
+                    span class="cms-tag" {(tag)}
+                }
+            }
+        }
+    }
+
+    fn render_csv(&self, _i18n: &FluentLanguageLoader) -> String {
+        self.0
+            .iter()
+            .map(|t| t.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/**********
+ * Option *
+ **********/
+
+impl<T: Input<S>, S: ContextTrait> Input<S> for Option<T> {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        _required: bool,
+        ctx: &FormRenderContext<'_, S>,
+        i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        let value = match value {
+            Some(v) => v.as_ref(),
+            None => None,
+        };
+        T::render_input_optional(value, name, name_human, ctx, i18n)
+    }
+
+    fn mark_required() -> bool {
+        T::mark_required()
+    }
+
+    fn optional_key() -> Option<&'static str> {
+        T::optional_key()
+    }
+
+    fn secret() -> bool {
+        T::secret()
+    }
+
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        match self {
+            Some(v) => Input::<S>::check_constraints(v, name, constraints),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: Column> Column for Option<T> {
+    fn render(&self, i18n: &FluentLanguageLoader) -> Markup {
+        match self {
+            Some(v) => v.render(i18n),
+            None => html!(),
+        }
+    }
+
+    fn render_thumbnail(&self, i18n: &FluentLanguageLoader) -> Markup {
+        match self {
+            Some(v) => v.render_thumbnail(i18n),
+            None => html!(),
+        }
+    }
+
+    fn render_csv(&self, i18n: &FluentLanguageLoader) -> String {
+        match self {
+            Some(v) => v.render_csv(i18n),
+            None => String::new(),
+        }
+    }
+}
+
+/********
+ * Json *
+ ********/
 
 #[cfg(feature = "json")]
 pub use json::Json;
@@ -632,128 +2668,482 @@ pub use json::Json;
 mod json {
     use super::*;
 
-    #[derive(
-        Copy,
-        Clone,
-        Debug,
-        Deref,
-        DerefMut,
-        PartialEq,
-        Eq,
-        PartialOrd,
-        Ord,
-        Hash,
-        Default,
-        Serialize,
-        Deserialize,
-    )]
-    #[serde(transparent)]
-    pub struct Json<T: ?Sized>(pub T);
+    #[derive(
+        Copy,
+        Clone,
+        Debug,
+        Deref,
+        DerefMut,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Hash,
+        Default,
+        Serialize,
+        Deserialize,
+    )]
+    #[serde(transparent)]
+    pub struct Json<T: ?Sized>(pub T);
+
+    impl<T: TS + ?Sized> TS for Json<T> {
+        type WithoutGenerics = T::WithoutGenerics;
+
+        fn decl() -> String {
+            T::decl()
+        }
+
+        fn decl_concrete() -> String {
+            T::decl_concrete()
+        }
+
+        fn name() -> String {
+            T::name()
+        }
+
+        fn inline() -> String {
+            T::inline()
+        }
+
+        fn inline_flattened() -> String {
+            T::inline_flattened()
+        }
+
+        fn visit_dependencies(visitor: &mut impl ts_rs::TypeVisitor)
+        where
+            Self: 'static,
+        {
+            T::visit_dependencies(visitor)
+        }
+
+        fn visit_generics(visitor: &mut impl ts_rs::TypeVisitor)
+        where
+            Self: 'static,
+        {
+            T::visit_generics(visitor)
+        }
+
+        fn output_path() -> Option<&'static Path> {
+            T::output_path()
+        }
+    }
+
+    impl<'r, T> sqlx::Decode<'r, DB> for Json<T>
+    where
+        sqlx::types::Json<T>: sqlx::Decode<'r, DB>,
+    {
+        fn decode(
+            value: <DB as sqlx::Database>::ValueRef<'r>,
+        ) -> Result<Self, sqlx::error::BoxDynError> {
+            Ok(Self(
+                <sqlx::types::Json<T> as sqlx::Decode<DB>>::decode(value)?.0,
+            ))
+        }
+    }
+
+    impl<T> sqlx::Type<DB> for Json<T>
+    where
+        sqlx::types::Json<T>: sqlx::Type<DB>,
+    {
+        fn type_info() -> <DB as sqlx::Database>::TypeInfo {
+            <sqlx::types::Json<T> as sqlx::Type<DB>>::type_info()
+        }
+    }
+
+    impl<'q, T> sqlx::Encode<'q, DB> for Json<T>
+    where
+        for<'a> sqlx::types::Json<&'a T>: sqlx::Encode<'q, DB>,
+    {
+        fn encode_by_ref(
+            &self,
+            buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'q>,
+        ) -> Result<sqlx_core::encode::IsNull, BoxDynError> {
+            <sqlx::types::Json<&T> as sqlx::Encode<'q, DB>>::encode(sqlx::types::Json(&self.0), buf)
+        }
+    }
+
+    #[cfg(feature = "json")]
+    impl<T: Input<S>, S: ContextTrait> Input<S> for Json<T> {
+        fn render_input(
+            value: Option<&Self>,
+            name: &str,
+            name_human: &str,
+            required: bool,
+            ctx: &FormRenderContext<'_, S>,
+            i18n: &FluentLanguageLoader,
+        ) -> Markup {
+            T::render_input(value.map(|v| &v.0), name, name_human, required, ctx, i18n)
+        }
+
+        fn render_input_optional(
+            value: Option<&Self>,
+            name: &str,
+            name_human: &str,
+            ctx: &FormRenderContext<'_, S>,
+            i18n: &FluentLanguageLoader,
+        ) -> Markup {
+            T::render_input_optional(value.map(|v| &v.0), name, name_human, ctx, i18n)
+        }
+
+        fn mark_required() -> bool {
+            T::mark_required()
+        }
+
+        fn multiple_files() -> Option<&'static str> {
+            T::multiple_files()
+        }
+
+        fn optional_key() -> Option<&'static str> {
+            T::optional_key()
+        }
+
+        fn secret() -> bool {
+            T::secret()
+        }
+
+        fn check_constraints(
+            &self,
+            name: &str,
+            constraints: &InputConstraints,
+        ) -> Result<(), ConstraintError> {
+            Input::<S>::check_constraints(&self.0, name, constraints)
+        }
+    }
+    #[cfg(feature = "json")]
+    impl<T: Column> Column for Json<T> {
+        fn render(&self, i18n: &FluentLanguageLoader) -> Markup {
+            self.0.render(i18n)
+        }
+
+        fn render_thumbnail(&self, i18n: &FluentLanguageLoader) -> Markup {
+            self.0.render_thumbnail(i18n)
+        }
+
+        fn render_csv(&self, i18n: &FluentLanguageLoader) -> String {
+            self.0.render_csv(i18n)
+        }
+    }
+}
+
+/*************
+ * Encrypted *
+ *************/
+
+/// A value that is stored encrypted in the database, see [`encryption`](crate::encryption).
+/// Inputs, columns and the JSON API show the plaintext.
+///
+/// The column has to be a blob (`BYTEA` in Postgres). Encrypted values cannot be filtered or sorted
+/// in SQL. Encoding and decoding fail if no key has been set using
+/// [`App::with_encryption_key`](crate::App::with_encryption_key).
+#[derive(Clone, Debug, Deref, DerefMut, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Encrypted<T>(pub T);
+
+impl<T: TS> TS for Encrypted<T> {
+    type WithoutGenerics = T::WithoutGenerics;
+
+    fn decl() -> String {
+        T::decl()
+    }
+
+    fn decl_concrete() -> String {
+        T::decl_concrete()
+    }
+
+    fn name() -> String {
+        T::name()
+    }
+
+    fn inline() -> String {
+        T::inline()
+    }
+
+    fn inline_flattened() -> String {
+        T::inline_flattened()
+    }
+
+    fn visit_dependencies(visitor: &mut impl ts_rs::TypeVisitor)
+    where
+        Self: 'static,
+    {
+        T::visit_dependencies(visitor)
+    }
+
+    fn visit_generics(visitor: &mut impl ts_rs::TypeVisitor)
+    where
+        Self: 'static,
+    {
+        T::visit_generics(visitor)
+    }
+
+    fn output_path() -> Option<&'static Path> {
+        T::output_path()
+    }
+}
+
+impl<T> sqlx::Type<DB> for Encrypted<T> {
+    fn type_info() -> <DB as sqlx::Database>::TypeInfo {
+        <Vec<u8> as sqlx::Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &<DB as sqlx::Database>::TypeInfo) -> bool {
+        <Vec<u8> as sqlx::Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'r, T: for<'de> Deserialize<'de>> sqlx::Decode<'r, DB> for Encrypted<T> {
+    fn decode(value: <DB as sqlx::Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        let encrypted = <&[u8] as sqlx::Decode<DB>>::decode(value)?;
+        let key = EncryptionKey::current().ok_or(EncryptionError::NoKey)?;
+        Ok(Self(serde_json::from_slice(&key.decrypt(encrypted)?)?))
+    }
+}
+
+impl<'q, T: Serialize> sqlx::Encode<'q, DB> for Encrypted<T> {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'q>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        let key = EncryptionKey::current().ok_or(EncryptionError::NoKey)?;
+        let encrypted = key.encrypt(&serde_json::to_vec(&self.0)?)?;
+        <Vec<u8> as sqlx::Encode<DB>>::encode(encrypted, buf)
+    }
+}
+
+impl<T: Input<S>, S: ContextTrait> Input<S> for Encrypted<T> {
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        required: bool,
+        ctx: &FormRenderContext<'_, S>,
+        i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        T::render_input(value.map(|v| &v.0), name, name_human, required, ctx, i18n)
+    }
+
+    fn render_input_optional(
+        value: Option<&Self>,
+        name: &str,
+        name_human: &str,
+        ctx: &FormRenderContext<'_, S>,
+        i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        T::render_input_optional(value.map(|v| &v.0), name, name_human, ctx, i18n)
+    }
+
+    fn mark_required() -> bool {
+        T::mark_required()
+    }
+
+    fn multiple_files() -> Option<&'static str> {
+        T::multiple_files()
+    }
+
+    fn optional_key() -> Option<&'static str> {
+        T::optional_key()
+    }
+
+    fn secret() -> bool {
+        T::secret()
+    }
+
+    fn check_constraints(
+        &self,
+        name: &str,
+        constraints: &InputConstraints,
+    ) -> Result<(), ConstraintError> {
+        Input::<S>::check_constraints(&self.0, name, constraints)
+    }
+}
+
+impl<T: Column> Column for Encrypted<T> {
+    fn render(&self, i18n: &FluentLanguageLoader) -> Markup {
+        self.0.render(i18n)
+    }
+
+    fn render_thumbnail(&self, i18n: &FluentLanguageLoader) -> Markup {
+        self.0.render_thumbnail(i18n)
+    }
+
+    fn render_csv(&self, i18n: &FluentLanguageLoader) -> String {
+        self.0.render_csv(i18n)
+    }
+}
+
+/********
+ * Uuid *
+ ********/
+
+impl Column for Uuid {
+    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html!((self))
+    }
+}
+
+/************
+ * Relation *
+ ************/
+
+/// A reference to an entity `E` by its id, e.g. the `post_id` of a `Comment`. Stored and
+/// serialized as the id.
+///
+/// The input is a select of all entities `E` labeled with their
+/// [`display_label`](crate::EntityBase::display_label), which are loaded from `/{name-plural}/options`.
+/// `E` has to be registered using [`App::entity`](crate::App::entity).
+/// The column links to the edit page of the referenced entity.
+pub struct Relation<E, Id = Uuid>(pub Id, PhantomData<fn() -> E>);
+
+impl<E, Id> Relation<E, Id> {
+    pub fn new(id: Id) -> Self {
+        Self(id, PhantomData)
+    }
+
+    pub fn id(&self) -> &Id {
+        &self.0
+    }
+}
+
+impl<E, Id> From<Id> for Relation<E, Id> {
+    fn from(id: Id) -> Self {
+        Self::new(id)
+    }
+}
+
+impl<E, Id: Clone> Clone for Relation<E, Id> {
+    fn clone(&self) -> Self {
+        Self::new(self.0.clone())
+    }
+}
+
+impl<E, Id: Copy> Copy for Relation<E, Id> {}
+
+impl<E, Id: Debug> Debug for Relation<E, Id> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Relation").field(&self.0).finish()
+    }
+}
+
+impl<E, Id: PartialEq> PartialEq for Relation<E, Id> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
 
-    impl<T: TS + ?Sized> TS for Json<T> {
-        type WithoutGenerics = T::WithoutGenerics;
+impl<E, Id: Eq> Eq for Relation<E, Id> {}
 
-        fn decl() -> String {
-            T::decl()
-        }
+impl<E, Id: Hash> Hash for Relation<E, Id> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
 
-        fn decl_concrete() -> String {
-            T::decl_concrete()
-        }
+impl<E, Id: Serialize> Serialize for Relation<E, Id> {
+    fn serialize<Se: serde::Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+        self.0.serialize(serializer)
+    }
+}
 
-        fn name() -> String {
-            T::name()
-        }
+impl<'de, E, Id: Deserialize<'de>> Deserialize<'de> for Relation<E, Id> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Id::deserialize(deserializer).map(Self::new)
+    }
+}
 
-        fn inline() -> String {
-            T::inline()
-        }
+impl<E: 'static, Id: TS> TS for Relation<E, Id> {
+    type WithoutGenerics = Id::WithoutGenerics;
 
-        fn inline_flattened() -> String {
-            T::inline_flattened()
-        }
+    fn decl() -> String {
+        Id::decl()
+    }
 
-        fn visit_dependencies(visitor: &mut impl ts_rs::TypeVisitor)
-        where
-            Self: 'static,
-        {
-            T::visit_dependencies(visitor)
-        }
+    fn decl_concrete() -> String {
+        Id::decl_concrete()
+    }
 
-        fn visit_generics(visitor: &mut impl ts_rs::TypeVisitor)
-        where
-            Self: 'static,
-        {
-            T::visit_generics(visitor)
-        }
+    fn name() -> String {
+        Id::name()
+    }
 
-        fn output_path() -> Option<&'static Path> {
-            T::output_path()
-        }
+    fn inline() -> String {
+        Id::inline()
     }
 
-    impl<'r, T> sqlx::Decode<'r, DB> for Json<T>
-    where
-        sqlx::types::Json<T>: sqlx::Decode<'r, DB>,
-    {
-        fn decode(
-            value: <DB as sqlx::Database>::ValueRef<'r>,
-        ) -> Result<Self, sqlx::error::BoxDynError> {
-            Ok(Self(
-                <sqlx::types::Json<T> as sqlx::Decode<DB>>::decode(value)?.0,
-            ))
-        }
+    fn inline_flattened() -> String {
+        Id::inline_flattened()
     }
 
-    impl<T> sqlx::Type<DB> for Json<T>
+    fn visit_dependencies(visitor: &mut impl ts_rs::TypeVisitor)
     where
-        sqlx::types::Json<T>: sqlx::Type<DB>,
+        Self: 'static,
     {
-        fn type_info() -> <DB as sqlx::Database>::TypeInfo {
-            <sqlx::types::Json<T> as sqlx::Type<DB>>::type_info()
-        }
+        Id::visit_dependencies(visitor)
     }
 
-    impl<'q, T> sqlx::Encode<'q, DB> for Json<T>
-    where
-        for<'a> sqlx::types::Json<&'a T>: sqlx::Encode<'q, DB>,
-    {
-        fn encode_by_ref(
-            &self,
-            buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'q>,
-        ) -> Result<sqlx_core::encode::IsNull, BoxDynError> {
-            <sqlx::types::Json<&T> as sqlx::Encode<'q, DB>>::encode(sqlx::types::Json(&self.0), buf)
-        }
+    fn output_path() -> Option<&'static Path> {
+        Id::output_path()
     }
+}
 
-    #[cfg(feature = "json")]
-    impl<T: Input<S>, S: ContextTrait> Input<S> for Json<T> {
-        fn render_input(
-            value: Option<&Self>,
-            name: &str,
-            name_human: &str,
-            required: bool,
-            ctx: &FormRenderContext<'_, S>,
-            i18n: &FluentLanguageLoader,
-        ) -> Markup {
-            T::render_input(value.map(|v| &v.0), name, name_human, required, ctx, i18n)
-        }
+impl<'r, E, Id: sqlx::Decode<'r, DB>> sqlx::Decode<'r, DB> for Relation<E, Id> {
+    fn decode(
+        value: <DB as sqlx::Database>::ValueRef<'r>,
+    ) -> Result<Self, sqlx::error::BoxDynError> {
+        Ok(Self::new(Id::decode(value)?))
     }
-    #[cfg(feature = "json")]
-    impl<T: Column> Column for Json<T> {
-        fn render(&self, i18n: &FluentLanguageLoader) -> Markup {
-            self.0.render(i18n)
-        }
+}
+
+impl<E, Id: sqlx::Type<DB>> sqlx::Type<DB> for Relation<E, Id> {
+    fn type_info() -> <DB as sqlx::Database>::TypeInfo {
+        Id::type_info()
+    }
+
+    fn compatible(ty: &<DB as sqlx::Database>::TypeInfo) -> bool {
+        Id::compatible(ty)
     }
 }
 
-/********
- * Uuid *
- ********/
+impl<'r, E, Id: sqlx::Encode<'r, DB>> sqlx::Encode<'r, DB> for Relation<E, Id> {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as sqlx::Database>::ArgumentBuffer<'r>,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        self.0.encode_by_ref(buf)
+    }
+}
 
-impl Column for Uuid {
+impl<S: ContextTrait, E: EntityPath<Id>, Id: Debug + std::fmt::Display> Input<S>
+    for Relation<E, Id>
+{
+    fn render_input(
+        value: Option<&Self>,
+        name: &str,
+        _name_human: &str,
+        required: bool,
+        ctx: &FormRenderContext<'_, S>,
+        _i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        ctx.require_script("js/relation.js");
+        let options = format!("{}/options", E::list_path());
+        html! {
+            select name=(name) class="cms-relation-input" required[required] data-options=(options) data-cms-mount="cmsRelationInit" {
+                @if !required {
+                    option value="" {}
+                }
+                // labeled once the options are loaded
+                @if let Some(value) = value {
+                    option value=(value.0) selected {(value.0)}
+                }
+            }
+        }
+    }
+}
+
+impl<E: EntityPath<Id>, Id: Debug + std::fmt::Display> Column for Relation<E, Id> {
     fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
-        html!((self))
+        html! {
+            a href=(E::edit_path(&self.0)) {(self.0)}
+        }
     }
 }
 
@@ -767,6 +3157,12 @@ pub struct File {
     pub(crate) id: Uuid,
     /// original filename
     pub(crate) name: String,
+    /// directory relative to `files_dir`, e.g. `post/image`,
+    /// see [`App::namespace_uploads`](crate::App::namespace_uploads)
+    pub(crate) dir: Option<String>,
+    /// name of the scaled down copy of the image stored next to the file,
+    /// see [`App::with_thumbnails`](crate::App::with_thumbnails)
+    pub(crate) thumbnail: Option<String>,
 }
 
 impl File {
@@ -774,17 +3170,92 @@ impl File {
         Self::new_with_id(Uuid::new_v4(), file_name)
     }
     pub fn new_with_id(id: Uuid, name: String) -> Self {
-        Self { id, name }
+        Self {
+            id,
+            name,
+            dir: None,
+            thumbnail: None,
+        }
+    }
+
+    /// Store the file in `dir` relative to the uploads directory.
+    /// Components of `dir` may only contain ASCII letters, digits, `-` and `_`.
+    pub fn in_dir(mut self, dir: impl Into<String>) -> Result<Self, InvalidUploadDir> {
+        let dir = dir.into();
+        if !is_valid_upload_dir(&dir) {
+            return Err(InvalidUploadDir(dir));
+        }
+        self.dir = Some(dir);
+        Ok(self)
     }
+
+    pub fn dir(&self) -> Option<&str> {
+        self.dir.as_deref()
+    }
+
+    /// The scaled down copy of the image, if it has been generated on upload,
+    /// see [`App::with_thumbnails`](crate::App::with_thumbnails).
+    pub fn thumbnail(&self) -> Option<Self> {
+        self.thumbnail.as_ref().map(|name| Self {
+            id: self.id,
+            name: name.clone(),
+            dir: self.dir.clone(),
+            thumbnail: None,
+        })
+    }
+
+    /// path of the file relative to the uploads directory, e.g. `post/image/<id>/<name>`
+    pub fn relative_path(&self) -> String {
+        match &self.dir {
+            Some(dir) => format!("{dir}/{}/{}", self.id, self.name),
+            None => format!("{}/{}", self.id, self.name),
+        }
+    }
+
+    /// absolute URL of the file, e.g. `/uploads/<id>/<name>`, for an admin interface that is
+    /// served at the root.
+    /// Inside the admin interface, use [`admin_url`](Self::admin_url) instead.
     pub fn url(&self) -> String {
-        format!("/uploads/{}/{}", self.id, self.name)
+        format!("/{}", self.admin_url())
+    }
+
+    /// URL of the file relative to the [base path](crate::context::ContextTrait::base_path)
+    /// of the admin interface.
+    /// If [private uploads](crate::App::with_private_uploads) are enabled, the URL is signed
+    /// and expires.
+    pub fn admin_url(&self) -> String {
+        match UploadSigner::current() {
+            Some(signer) => signer.sign(self),
+            None => format!("uploads/{}", self.relative_path()),
+        }
     }
 
     pub fn path(&self, uploads_dir: &Path) -> PathBuf {
-        uploads_dir.join(self.id.to_string()).join(&self.name)
+        let dir = match &self.dir {
+            Some(dir) => uploads_dir.join(dir),
+            None => uploads_dir.to_path_buf(),
+        };
+        dir.join(self.id.to_string()).join(&self.name)
     }
 }
 
+#[derive(Debug, Error)]
+#[error("invalid upload directory: {0}")]
+pub struct InvalidUploadDir(String);
+
+/// whether `name` is the name of a file in the directory of an upload
+fn is_valid_file_name(name: &str) -> bool {
+    !name.contains(['/', '\\']) && name != ".."
+}
+
+fn is_valid_upload_dir(dir: &str) -> bool {
+    dir.split('/').all(|c| {
+        !c.is_empty()
+            && c.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    })
+}
+
 impl<'de> Deserialize<'de> for File {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -796,19 +3267,49 @@ impl<'de> Deserialize<'de> for File {
             id_old: Option<Uuid>,
             name: Option<String>,
             name_old: Option<String>,
+            dir: Option<String>,
+            dir_old: Option<String>,
+            thumbnail: Option<String>,
+            thumbnail_old: Option<String>,
         }
         let f = File::deserialize(deserializer)?;
+        // the directory and thumbnail of a new upload replace the ones of the old file
+        let (dir, thumbnail) = match f.id {
+            Some(_) => (f.dir, f.thumbnail),
+            None => (f.dir_old, f.thumbnail_old),
+        };
+        if let Some(dir) = dir.as_ref().filter(|dir| !is_valid_upload_dir(dir)) {
+            return Err(serde::de::Error::custom(InvalidUploadDir(dir.clone())));
+        }
         let id =
             f.id.or(f.id_old)
-                .map(Into::into)
                 .ok_or(serde::de::Error::missing_field("id"))?;
         let name = f
             .name
             .or(f.name_old)
-            .map(Into::into)
             .ok_or(serde::de::Error::missing_field("name"))?;
-        // TODO: check if file exists
-        Ok(Self { id, name })
+        if let Some(name) = Some(&name)
+            .into_iter()
+            .chain(&thumbnail)
+            .find(|name| !is_valid_file_name(name))
+        {
+            return Err(serde::de::Error::custom(format!(
+                "invalid file name: {name}"
+            )));
+        }
+        let file = Self {
+            id,
+            name,
+            dir,
+            thumbnail,
+        };
+        if uploads::is_missing(&file) {
+            return Err(serde::de::Error::custom(format!(
+                "file does not exist: {}",
+                file.relative_path()
+            )));
+        }
+        Ok(file)
     }
 }
 
@@ -818,29 +3319,142 @@ impl<S: ContextTrait> Input<S> for File {
         name: &str,
         _name_human: &str,
         required: bool,
-        _ctx: &FormRenderContext<'_, S>,
+        ctx: &FormRenderContext<'_, S>,
         _i18n: &FluentLanguageLoader,
     ) -> Markup {
         html! {
-            @if let Some(v) = value {
-                input type="hidden" name=(format!("{name}[id_old]")) value=(v.id) {}
-                input type="hidden" name=(format!("{name}[name_old]")) value=(v.name) {}
+            div class="cms-file-input" {
+                @if let Some(v) = value {
+                    (v.render_old_inputs(name))
+                    a class="cms-file-name" href=(v.admin_url()) data-current=(v.name) target="_blank" {
+                        (v.name)
+                    }
+                } @else {
+                    span class="cms-file-name" {}
+                }
+                (file_input(ctx, name, ctx.constraints().accept, required && value.is_none()))
+            }
+        }
+    }
+
+    fn multiple_files() -> Option<&'static str> {
+        Some("")
+    }
+}
+
+impl File {
+    /// hidden inputs with the current value, which is kept if no new file is selected
+    fn render_old_inputs(&self, name: &str) -> Markup {
+        html! {
+            input type="hidden" name=(format!("{name}[id_old]")) value=(self.id) {}
+            input type="hidden" name=(format!("{name}[name_old]")) value=(self.name) {}
+            @if let Some(dir) = &self.dir {
+                input type="hidden" name=(format!("{name}[dir_old]")) value=(dir) {}
+            }
+            @if let Some(thumbnail) = &self.thumbnail {
+                input type="hidden" name=(format!("{name}[thumbnail_old]")) value=(thumbnail) {}
             }
-            input type="file" name=(name) required[required && value.is_none()] {}
         }
     }
 }
 
+/// `input type="file"` that updates the preview of its `.cms-file-input` and shows the upload
+/// progress when the form is submitted
+fn file_input<S: ContextTrait>(
+    ctx: &FormRenderContext<'_, S>,
+    name: &str,
+    accept: Option<&str>,
+    required: bool,
+) -> Markup {
+    ctx.require_script("js/file.js");
+    html! {
+        input
+            type="file"
+            name=(name)
+            accept=[accept]
+            required[required]
+            data-cms-change="cmsFileInputChange"
+            data-cms-mount="cmsFileInputMount" {}
+        progress class="cms-upload-progress" max="1" value="0" hidden {}
+    }
+}
+
 impl Column for File {
     fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
         html! {
-            a href=(self.url()) {
+            a href=(self.admin_url()) {
                 (self.name)
             }
         }
     }
 }
 
+/// Uploads referenced by a value, used by [`gc_uploads`](crate::uploads::gc_uploads) to find
+/// uploads that can be removed, see [`EntityBase::referenced_files`](crate::EntityBase::referenced_files).
+pub trait ReferencedFiles {
+    /// add the ids of all referenced [`File`]s to `ids`
+    fn referenced_files(&self, ids: &mut Vec<Uuid>);
+}
+
+impl ReferencedFiles for File {
+    fn referenced_files(&self, ids: &mut Vec<Uuid>) {
+        ids.push(self.id);
+    }
+}
+
+impl ReferencedFiles for Image {
+    fn referenced_files(&self, ids: &mut Vec<Uuid>) {
+        ids.push(self.file.id);
+    }
+}
+
+/// files uploaded using the [editor](crate::easymde) and linked as `uploads/<id>/<name>`
+impl ReferencedFiles for Markdown {
+    fn referenced_files(&self, ids: &mut Vec<Uuid>) {
+        ids.extend(
+            self.0
+                .match_indices("uploads/")
+                .filter_map(|(i, m)| self.0.get(i + m.len()..i + m.len() + 36))
+                .filter_map(|id| Uuid::try_parse(id).ok()),
+        );
+    }
+}
+
+impl<T: ReferencedFiles> ReferencedFiles for Option<T> {
+    fn referenced_files(&self, ids: &mut Vec<Uuid>) {
+        if let Some(v) = self {
+            v.referenced_files(ids);
+        }
+    }
+}
+
+impl<T: ReferencedFiles> ReferencedFiles for Vec<T> {
+    fn referenced_files(&self, ids: &mut Vec<Uuid>) {
+        for v in self {
+            v.referenced_files(ids);
+        }
+    }
+}
+
+impl<T: ReferencedFiles + ?Sized> ReferencedFiles for Box<T> {
+    fn referenced_files(&self, ids: &mut Vec<Uuid>) {
+        (**self).referenced_files(ids);
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T: ReferencedFiles + ?Sized> ReferencedFiles for Json<T> {
+    fn referenced_files(&self, ids: &mut Vec<Uuid>) {
+        self.0.referenced_files(ids);
+    }
+}
+
+impl<T: ReferencedFiles> ReferencedFiles for Encrypted<T> {
+    fn referenced_files(&self, ids: &mut Vec<Uuid>) {
+        self.0.referenced_files(ids);
+    }
+}
+
 /*********
  * Image *
  *********/
@@ -852,22 +3466,42 @@ pub struct Image {
     pub alt_text: Option<String>,
 }
 
+impl Image {
+    /// URL of the [thumbnail](File::thumbnail) of the image, or of the image itself if it has
+    /// no thumbnail
+    pub fn thumbnail_url(&self) -> String {
+        match self.file.thumbnail() {
+            Some(thumbnail) => thumbnail.admin_url(),
+            None => self.file.admin_url(),
+        }
+    }
+}
+
 impl<S: ContextTrait> Input<S> for Image {
     fn render_input(
         value: Option<&Self>,
         name: &str,
         _name_human: &str,
         required: bool,
-        _ctx: &FormRenderContext<'_, S>,
+        ctx: &FormRenderContext<'_, S>,
         i18n: &FluentLanguageLoader,
     ) -> Markup {
+        let accept = ctx.constraints().accept.unwrap_or("image/*");
         html! {
-            fieldset class="cms-image cms-prop-group" {
+            fieldset class="cms-image cms-prop-group cms-file-input" {
                 @if let Some(v) = value {
-                    input type="hidden" name=(format!("{name}[id_old]")) value=(v.file.id) {}
-                    input type="hidden" name=(format!("{name}[name_old]")) value=(v.file.name) {}
+                    (v.file.render_old_inputs(name))
                 }
-                input type="file" accept="image/*" name=(name) required[required && value.is_none()] {}
+                @let current = value.map(|v| v.file.admin_url());
+                img
+                    class="cms-image-preview"
+                    src=[&current]
+                    data-current=[&current]
+                    alt=""
+                    hidden[current.is_none()]
+                    style="max-height: 12em; max-width: 100%; object-fit: contain;"
+                {}
+                (file_input(ctx, name, Some(accept), required && value.is_none()))
                 input
                     type="text"
                     name=(format!("{name}[alt_text]"))
@@ -877,12 +3511,21 @@ impl<S: ContextTrait> Input<S> for Image {
             }
         }
     }
+
+    fn multiple_files() -> Option<&'static str> {
+        Some("image/*")
+    }
 }
 
 impl Column for Image {
-    fn render(&self, _i18n: &FluentLanguageLoader) -> Markup {
+    /// the [thumbnail](File::thumbnail) linking to the image if there is one, otherwise a link
+    /// with the name of the file
+    fn render(&self, i18n: &FluentLanguageLoader) -> Markup {
+        if self.file.thumbnail.is_some() {
+            return self.render_thumbnail(i18n);
+        }
         html! {
-            a href=(self.file.url()) {
+            a href=(self.file.admin_url()) {
                 (self.file.name)
             }
             @if let Some(alt_text) = &self.alt_text {
@@ -890,4 +3533,73 @@ impl Column for Image {
             }
         }
     }
+
+    /// the image scaled down to the height of a table row, linking to the original
+    fn render_thumbnail(&self, _i18n: &FluentLanguageLoader) -> Markup {
+        html! {
+            a href=(self.file.admin_url()) {
+                img
+                    class="cms-column-thumbnail"
+                    src=(self.thumbnail_url())
+                    alt=(self.alt_text.as_deref().unwrap_or(&self.file.name))
+                    loading="lazy"
+                    style="max-height: 3em; max-width: 6em; object-fit: contain; vertical-align: middle;"
+                {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_rejects_duplicates() {
+        let set: Unique<HashSet<Text>> = serde_json::from_str(r#"["a", "b"]"#).unwrap();
+        assert_eq!(set.len(), 2);
+        let err = serde_json::from_str::<Unique<BTreeSet<String>>>(r#"["a", "b", "a"]"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("element 2 is a duplicate"), "{err}");
+    }
+
+    #[test]
+    fn unique_rejects_duplicates_in_nested_forms() {
+        #[derive(Debug, Deserialize)]
+        struct Domain {
+            hosts: Unique<HashSet<Text>>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Form {
+            domains: Vec<Domain>,
+        }
+
+        let config = serde_qs::Config::new(5, false);
+        let form: Form = config
+            .deserialize_str("domains[0][hosts][0]=a&domains[0][hosts][1]=b")
+            .unwrap();
+        assert_eq!(form.domains[0].hosts.len(), 2);
+        let err = config
+            .deserialize_str::<Form>("domains[0][hosts][0]=a&domains[0][hosts][1]=a")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("element 1 is a duplicate"), "{err}");
+    }
+
+    #[test]
+    fn time_zone_accepts_current_and_deprecated_names() {
+        for name in [
+            "UTC",
+            "Europe/Berlin",
+            "America/Argentina/Buenos_Aires",
+            "Asia/Calcutta",
+        ] {
+            assert_eq!(TimeZone::new(name).unwrap().as_str(), name);
+        }
+        for name in ["", "localtime", "europe/berlin", "Europe/Atlantis"] {
+            assert!(TimeZone::new(name).is_err(), "{name}");
+        }
+    }
 }
@@ -0,0 +1,430 @@
+//! One-to-many relations whose children are edited inline on the edit page of the parent,
+//! see [`InlineRelation`].
+
+use std::{
+    any::{Any, TypeId},
+    collections::{HashMap, HashSet},
+    fmt::{self, Debug},
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::Arc,
+};
+
+use axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+};
+use convert_case::{Case, Casing};
+use i18n_embed::fluent::FluentLanguageLoader;
+use i18n_embed_fl::fl;
+use serde::{
+    de::{DeserializeSeed, IgnoredAny, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use crate::{
+    app::AppError,
+    auth::Action,
+    cache::invalidate,
+    context::ContextTrait,
+    entity,
+    render::{self, FormRenderContext, FormSection},
+    Entity, EntityBase,
+};
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A one-to-many relation whose children `C` are edited inline on the edit page of `Self`,
+/// e.g. the `Comment`s of a `Post`. Registered using [`App::inline_relation`](crate::App::inline_relation).
+///
+/// Saving the parent creates, updates and deletes the children using the
+/// [`Create`](entity::Create), [`Update`](entity::Update) and [`Delete`](entity::Delete) implementations of `C`.
+/// The reference to the parent should be excluded from the inputs of `C` with `#[cms(skip_input)]`
+/// and is set using [`InlineRelation::attach`].
+pub trait InlineRelation<S: ContextTrait, C: Entity<S>>: Entity<S> {
+    /// heading of the children on the edit page of the parent.
+    /// Default: [`name_plural`](crate::EntityBase::name_plural) of `C`
+    fn relation_name() -> &'static str {
+        C::name_plural()
+    }
+
+    /// the children of `self`
+    fn children(
+        &self,
+        ext: <C as entity::List<S>>::RequestExt,
+    ) -> impl Future<
+        Output = Result<impl IntoIterator<Item = C> + Send, <C as entity::List<S>>::Error>,
+    > + Send;
+
+    /// set the reference to `self` in a new child before it is created
+    fn attach(&self, child: &mut C::Create);
+}
+
+/// object safe trait that is automatically implemented for the [`InlineRelation`]s registered
+/// using [`App::inline_relation`](crate::App::inline_relation)
+pub trait DynInlineRelation<S: ContextTrait, P>: Send + Sync {
+    /// load the children of `parent` and return a function to render them within the form of the parent
+    fn load<'a>(
+        &'a self,
+        parent: &'a P,
+        parts: Parts,
+        ctx: &'a S,
+        i18n: &'a FluentLanguageLoader,
+    ) -> BoxFuture<'a, Result<FormSection<'static, S>, AppError>>;
+
+    /// Check that the children submitted in the form of the parent can be saved, i.e. the
+    /// updated and deleted entities are children of `parent`. Called before the parent or any of
+    /// its children is changed.
+    fn check<'a>(
+        &'a self,
+        parent: &'a P,
+        parts: Parts,
+        ctx: &'a S,
+        i18n: &'a FluentLanguageLoader,
+        query_string: &'a str,
+    ) -> BoxFuture<'a, Result<(), AppError>>;
+
+    /// create, update and delete the children of `parent` as submitted in the form of the parent,
+    /// after it was [checked](DynInlineRelation::check)
+    fn save<'a>(
+        &'a self,
+        parent: &'a P,
+        parts: Parts,
+        ctx: &'a S,
+        i18n: &'a FluentLanguageLoader,
+        query_string: &'a str,
+    ) -> BoxFuture<'a, Result<(), AppError>>;
+}
+
+pub(crate) struct Relation<C>(PhantomData<fn() -> C>);
+
+impl<C> Relation<C> {
+    pub(crate) fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// name of the form fields of the children
+pub(crate) fn field_name(relation_name: &str) -> String {
+    format!("__inline_{}", relation_name.to_case(Case::Snake))
+}
+
+async fn extract<T: FromRequestParts<S>, S: ContextTrait>(
+    parts: &mut Parts,
+    ctx: &S,
+    i18n: &FluentLanguageLoader,
+    name: &str,
+) -> Result<T, AppError> {
+    T::from_request_parts(parts, ctx).await.map_err(|_| {
+        AppError::new(
+            fl!(i18n, "error-inline-relation", "title", name = name),
+            fl!(
+                i18n,
+                "error-inline-relation",
+                "extract",
+                error = std::any::type_name::<T>()
+            ),
+        )
+    })
+}
+
+/// Whether the [`User`](crate::auth::User) of the current request may perform `action` on the
+/// children `C` in every [scope](crate::entity::EntityScope) they are served in.
+pub(crate) fn allows<C: EntityBase<S>, S: ContextTrait>(ctx: &S, action: Action) -> bool {
+    ctx.entities()
+        .iter()
+        .filter(|scope| scope.name() == C::name())
+        .all(|scope| scope.allows(action))
+}
+
+/// the form fields of the children of the relation named `relation_name`
+fn parse_form<C: Entity<S>, S: ContextTrait>(
+    relation_name: &str,
+    i18n: &FluentLanguageLoader,
+    query_string: &str,
+) -> Result<ChildrenForm<C, S>, AppError> {
+    let field = field_name(relation_name);
+    let config = serde_qs::Config::new(8, false);
+    let form = serde_qs::Deserializer::with_config(&config, query_string.as_bytes())
+        .and_then(|d| FieldSeed::<ChildrenForm<C, S>>::new(&field).deserialize(d))
+        .map_err(|e| {
+            AppError::new(
+                fl!(
+                    i18n,
+                    "error-inline-relation",
+                    "title",
+                    name = relation_name.to_case(Case::Title)
+                ),
+                fl!(
+                    i18n,
+                    "error-inline-relation",
+                    "parse-form",
+                    error = format!("{e:#}")
+                ),
+            )
+        })?;
+    Ok(form.unwrap_or_default())
+}
+
+impl<S, P, C> DynInlineRelation<S, P> for Relation<C>
+where
+    S: ContextTrait,
+    P: InlineRelation<S, C>,
+    C: Entity<S>,
+{
+    fn load<'a>(
+        &'a self,
+        parent: &'a P,
+        mut parts: Parts,
+        ctx: &'a S,
+        i18n: &'a FluentLanguageLoader,
+    ) -> BoxFuture<'a, Result<FormSection<'static, S>, AppError>> {
+        Box::pin(async move {
+            let name = P::relation_name().to_case(Case::Title);
+            let ext =
+                extract::<<C as entity::List<S>>::RequestExt, S>(&mut parts, ctx, i18n, &name)
+                    .await?;
+            let children = parent
+                .children(ext)
+                .await
+                .map_err(Into::into)?
+                .into_iter()
+                .collect::<Vec<_>>();
+            let section: FormSection<'static, S> = Box::new(
+                move |form_ctx: &FormRenderContext<'_, S>, i18n: &FluentLanguageLoader| {
+                    render::inline_relation::<C, S>(form_ctx, i18n, P::relation_name(), &children)
+                },
+            );
+            Ok(section)
+        })
+    }
+
+    fn check<'a>(
+        &'a self,
+        parent: &'a P,
+        mut parts: Parts,
+        ctx: &'a S,
+        i18n: &'a FluentLanguageLoader,
+        query_string: &'a str,
+    ) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move {
+            let name = P::relation_name().to_case(Case::Title);
+            let form = parse_form::<C, S>(P::relation_name(), i18n, query_string)?;
+            let denied = [
+                (Action::Delete, !form.delete.is_empty()),
+                (Action::Update, !form.update.is_empty()),
+                (Action::Create, !form.create.is_empty()),
+            ]
+            .into_iter()
+            .find(|(action, submitted)| *submitted && !allows::<C, S>(ctx, *action));
+            if let Some((action, _)) = denied {
+                return Err(AppError::new(
+                    fl!(i18n, "error-inline-relation", "title", name = name.as_str()),
+                    fl!(
+                        i18n,
+                        "error-inline-relation",
+                        "forbidden",
+                        action = format!("{action:?}").to_lowercase()
+                    ),
+                )
+                .with_status(StatusCode::FORBIDDEN));
+            }
+            // a form must not update or delete entities that are not children of `parent`
+            if !form.delete.is_empty() || !form.id.is_empty() {
+                let ext =
+                    extract::<<C as entity::List<S>>::RequestExt, S>(&mut parts, ctx, i18n, &name)
+                        .await?;
+                let children = parent
+                    .children(ext)
+                    .await
+                    .map_err(Into::into)?
+                    .into_iter()
+                    .map(|child| child.id().to_string())
+                    .collect::<HashSet<_>>();
+                if let Some(id) = form
+                    .delete
+                    .iter()
+                    .chain(&form.id)
+                    .map(ToString::to_string)
+                    .find(|id| !children.contains(id))
+                {
+                    return Err(AppError::new(
+                        fl!(i18n, "error-inline-relation", "title", name = name.as_str()),
+                        fl!(i18n, "error-inline-relation", "not-a-child", id = id),
+                    )
+                    .with_status(StatusCode::BAD_REQUEST));
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn save<'a>(
+        &'a self,
+        parent: &'a P,
+        mut parts: Parts,
+        ctx: &'a S,
+        i18n: &'a FluentLanguageLoader,
+        query_string: &'a str,
+    ) -> BoxFuture<'a, Result<(), AppError>> {
+        Box::pin(async move {
+            let name = P::relation_name().to_case(Case::Title);
+            let form = parse_form::<C, S>(P::relation_name(), i18n, query_string)?;
+            let deleted = form
+                .delete
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            if !form.delete.is_empty() {
+                let ext = extract::<<C as entity::Delete<S>>::RequestExt, S>(
+                    &mut parts, ctx, i18n, &name,
+                )
+                .await?;
+                for id in form.delete {
+                    let res = C::delete(&id, ext.clone()).await;
+                    invalidate::<C, S>(ctx, Some(&id.to_string()));
+                    res.map_err(Into::into)?;
+                }
+            }
+            if !form.update.is_empty() {
+                let ext = extract::<<C as entity::Update<S>>::RequestExt, S>(
+                    &mut parts, ctx, i18n, &name,
+                )
+                .await?;
+                for (id, data) in form.id.into_iter().zip(form.update) {
+                    if deleted.contains(&id.to_string()) {
+                        continue;
+                    }
+                    let res = C::update(&id, data, ext.clone()).await;
+                    invalidate::<C, S>(ctx, Some(&id.to_string()));
+                    res.map_err(Into::into)?;
+                }
+            }
+            if !form.create.is_empty() {
+                let ext = extract::<<C as entity::Create<S>>::RequestExt, S>(
+                    &mut parts, ctx, i18n, &name,
+                )
+                .await?;
+                for mut data in form.create {
+                    parent.attach(&mut data);
+                    let res = C::create(data, ext.clone()).await;
+                    invalidate::<C, S>(ctx, None);
+                    res.map_err(Into::into)?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// the form fields of the children of one relation, see [`render::inline_relation`]
+#[derive(Debug, Deserialize)]
+#[serde(bound(deserialize = "Cr: Deserialize<'de>, U: Deserialize<'de>, I: Deserialize<'de>"))]
+struct InlineForm<Cr, U, I> {
+    #[serde(default = "Vec::new")]
+    create: Vec<Cr>,
+    #[serde(default = "Vec::new")]
+    update: Vec<U>,
+    /// ids of the children in `update`
+    #[serde(default = "Vec::new")]
+    id: Vec<I>,
+    #[serde(default = "Vec::new")]
+    delete: Vec<I>,
+}
+
+/// the [`InlineForm`] of the children `C`
+type ChildrenForm<C, S> = InlineForm<
+    <C as EntityBase<S>>::Create,
+    <C as EntityBase<S>>::Update,
+    <C as EntityBase<S>>::Id,
+>;
+
+impl<Cr, U, I> Default for InlineForm<Cr, U, I> {
+    fn default() -> Self {
+        Self {
+            create: Vec::new(),
+            update: Vec::new(),
+            id: Vec::new(),
+            delete: Vec::new(),
+        }
+    }
+}
+
+/// deserialize only the field `name` of a map and ignore all other fields
+struct FieldSeed<'a, T> {
+    name: &'a str,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> FieldSeed<'a, T> {
+    fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for FieldSeed<'_, T> {
+    type Value = Option<T>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for FieldSeed<'_, T> {
+    type Value = Option<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a map")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut value = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key == self.name {
+                true => value = Some(map.next_value()?),
+                false => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(value)
+    }
+}
+
+/// [`DynInlineRelation`]s by the [`TypeId`] of the parent
+#[derive(Default)]
+pub(crate) struct InlineRelations(HashMap<TypeId, Box<dyn Any + Send + Sync>>);
+
+impl Debug for InlineRelations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("InlineRelations")
+            .field(&self.0.len())
+            .finish()
+    }
+}
+
+impl InlineRelations {
+    pub(crate) fn insert<S: ContextTrait, P: 'static>(
+        &mut self,
+        relation: Arc<dyn DynInlineRelation<S, P>>,
+    ) {
+        self.0
+            .entry(TypeId::of::<P>())
+            .or_insert_with(|| Box::new(Vec::<Arc<dyn DynInlineRelation<S, P>>>::new()))
+            .downcast_mut::<Vec<Arc<dyn DynInlineRelation<S, P>>>>()
+            .expect("inline relations are stored by the type of their parent")
+            .push(relation);
+    }
+
+    pub(crate) fn get<S: ContextTrait, P: 'static>(&self) -> &[Arc<dyn DynInlineRelation<S, P>>] {
+        self.0
+            .get(&TypeId::of::<P>())
+            .and_then(|v| v.downcast_ref::<Vec<Arc<dyn DynInlineRelation<S, P>>>>())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
@@ -1,14 +1,38 @@
-use std::{borrow::Borrow, cmp::Ordering, fmt::Display};
+use std::{
+    borrow::Borrow,
+    cell::{Cell, RefCell},
+    cmp::Ordering,
+    fmt::Display,
+    ops::Range,
+    sync::Arc,
+};
 
 use axum::extract::State;
 use convert_case::{Case, Casing};
-use i18n_embed::fluent::FluentLanguageLoader;
+use i18n_embed::{fluent::FluentLanguageLoader, LanguageLoader};
 use i18n_embed_fl::fl;
 use maud::{html, Markup, PreEscaped, DOCTYPE};
+use unic_langid::LanguageIdentifier;
 use uuid::Uuid;
 
 use crate::{
-    context::ContextTrait, entity::EntityBase, input::InputInfo, property::EnumVariant, Entity,
+    app::{AppError, LANGUAGE_COOKIE},
+    auth::{Action, User},
+    branding::BrandingConfig,
+    column::Column,
+    context::ContextTrait,
+    csp, csrf,
+    entity::{DeleteConfirm, EntityScope, ListRow, Version, Versioned},
+    form_group::FormGroup,
+    input::{InputConstraints, InputInfo, Inputs},
+    maintenance::{MaintenanceAction, RunStatus},
+    pagination::Pagination,
+    property::EnumVariant,
+    relation,
+    request_id::RequestId,
+    theme::{Theme, THEME_COOKIE},
+    validate::ValidationErrors,
+    Entity,
 };
 
 #[non_exhaustive]
@@ -16,64 +40,479 @@ pub struct FormRenderContext<'a, S: ContextTrait> {
     /// unique id of the HTML form element
     pub form_id: &'a str,
     pub ctx: S,
+    /// the previous submission of the form if it could not be saved
+    pub submission: &'a Submission,
+    /// nonce of the `Content-Security-Policy` that has to be set on inline `<script>` elements,
+    /// see [`csp`](crate::csp)
+    pub nonce: Option<String>,
+    /// scripts and stylesheets required by the inputs of the form, see [`FormRenderContext::require_script`]
+    assets: RefCell<Vec<Asset>>,
+    /// constraints of the input that is rendered, see [`FormRenderContext::constraints`]
+    constraints: Cell<InputConstraints>,
+    /// see [`Submission::list_elements`]
+    list_elements_url: Option<&'a str>,
+    /// the list whose elements are rendered instead of the form, see [`list_elements`]
+    list_elements: Option<&'a ListElements<'a>>,
+}
+
+/// The elements of a list input that are loaded by a collapsed list, see [`list_elements`].
+pub(crate) struct ListElements<'a> {
+    /// name of the list input
+    pub(crate) name: &'a str,
+    pub(crate) range: Range<usize>,
+    pub(crate) rendered: RefCell<Option<Markup>>,
+}
+
+impl<'a> ListElements<'a> {
+    pub(crate) fn new(name: &'a str, range: Range<usize>) -> Self {
+        Self {
+            name,
+            range,
+            rendered: RefCell::new(None),
+        }
+    }
+}
+
+/// A submission of a form that could not be saved, rendered again with the entered values and errors.
+#[derive(Clone, Debug, Default)]
+pub struct Submission {
+    /// rendered above the inputs
+    pub error: Option<AppError>,
+    /// messages of invalid fields, rendered next to their inputs
+    pub errors: ValidationErrors,
+    /// submitted values by the name of the input, restored in the browser if the form could not be
+    /// parsed into a value to render it with. The elements of lists and
+    /// [secrets](crate::input::Input::secret) are not restored.
+    pub values: Vec<(String, String)>,
+    /// [version token](crate::EntityBase::version_token) of the entity the form was loaded with,
+    /// submitted in the hidden input [`VERSION_FIELD`]
+    pub version: Option<String>,
+    /// URL of the stored entity the elements of [collapsed](crate::App::collapse_lists_longer_than)
+    /// lists are loaded from when they are expanded, instead of rendering them with the form,
+    /// e.g. `post/1/list-elements`. Set by the edit page, but not for a rejected submission.
+    pub list_elements: Option<String>,
+}
+
+/// name of the hidden input with the [version token](crate::EntityBase::version_token) in the edit form
+pub const VERSION_FIELD: &str = "_version";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Asset {
+    Script(String),
+    Stylesheet(String),
 }
 
-pub fn document(body: Markup) -> Markup {
+impl<S: ContextTrait> FormRenderContext<'_, S> {
+    /// Load the script at `url` once before the form, no matter how many inputs require it,
+    /// so its functions can be called from inline scripts of the inputs.
+    /// Relative URLs are relative to the [base path](ContextTrait::base_path), e.g. `js/list.js`.
+    pub fn require_script(&self, url: impl Into<String>) {
+        self.require(Asset::Script(url.into()));
+    }
+
+    /// Load the stylesheet at `url` once before the form, see [`FormRenderContext::require_script`].
+    pub fn require_stylesheet(&self, url: impl Into<String>) {
+        self.require(Asset::Stylesheet(url.into()));
+    }
+
+    /// [Constraints](InputInfo::constraints) of the input that is rendered, e.g. to set the
+    /// `maxlength` attribute. The elements of lists have the constraints of their list.
+    pub fn constraints(&self) -> InputConstraints {
+        self.constraints.get()
+    }
+
+    /// Render the input of `info` with its constraints, labelled `name_human`.
+    pub(crate) fn render_input(
+        &self,
+        info: &InputInfo<'_, S>,
+        name_human: &str,
+        required: bool,
+        i18n: &FluentLanguageLoader,
+    ) -> Markup {
+        let outer = self.constraints.replace(info.constraints);
+        let input = info
+            .value
+            .render_input(info.name, name_human, required, self, i18n);
+        self.constraints.set(outer);
+        input
+    }
+
+    /// URL to load the collapsed elements of the list input `name` from, if they are not rendered,
+    /// see [`Submission::list_elements`]
+    pub(crate) fn list_elements_src(&self, name: &str) -> Option<String> {
+        self.list_elements_url.map(|url| {
+            format!(
+                "{url}?form={}&field={}",
+                urlencoding::encode(self.form_id),
+                urlencoding::encode(name)
+            )
+        })
+    }
+
+    /// the list whose elements are rendered instead of the form, see [`list_elements`]
+    pub(crate) fn list_elements(&self) -> Option<&ListElements<'_>> {
+        self.list_elements
+    }
+
+    fn require(&self, asset: Asset) {
+        let mut assets = self.assets.borrow_mut();
+        if !assets.contains(&asset) {
+            assets.push(asset);
+        }
+    }
+
+    /// the required assets, in order of their first requirement
+    fn render_assets(&self) -> Markup {
+        html! {
+            @for asset in self.assets.borrow().iter() {
+                @match asset {
+                    Asset::Script(url) => script src=(url) nonce=[&self.nonce] {},
+                    Asset::Stylesheet(url) => link rel="stylesheet" href=(url) {},
+                }
+            }
+        }
+    }
+}
+
+/// Additional content of an entity form that is rendered after its inputs,
+/// e.g. the children of an [`InlineRelation`](crate::relation::InlineRelation).
+pub type FormSection<'a, S> =
+    Box<dyn FnOnce(&FormRenderContext<'_, S>, &FluentLanguageLoader) -> Markup + Send + 'a>;
+
+/// Wrap `body` in a complete HTML document.
+/// All URLs within the admin interface are relative to the `<base>` set to [`ContextTrait::base_path`].
+/// A page of the admin interface with the [banner](crate::App::with_banner) and
+/// [footer](crate::App::with_footer) around `body`.
+/// `title` is the heading of the page, followed by the name of the application in `<title>`,
+/// see [`App::with_branding`](crate::App::with_branding).
+pub fn document<S: ContextTrait>(
+    ctx: &S,
+    i18n: &FluentLanguageLoader,
+    title: &str,
+    body: Markup,
+) -> Markup {
+    let theme = Theme::current();
+    let branding = ctx.branding();
     html! {
         (DOCTYPE)
-        html {
+        html lang=(i18n.current_language()) data-theme=[theme.filter(|t| *t != Theme::System).map(Theme::as_str)] {
             head {
-                link rel="stylesheet" href="" {}
+                base href=(format!("{}/", ctx.base_path()));
                 meta charset="utf-8" {}
-                link rel="icon" href="/favicon.png" {}
-                link rel="stylesheet" type="text/css" href="/css/main.css" {}
+                title {(branding.page_title(title))}
+                link rel="icon" href=(branding.favicon.as_deref().unwrap_or("favicon.png")) {}
+                link rel="stylesheet" type="text/css" href="css/main.css" {}
+                @if theme.is_some() {
+                    meta name="color-scheme" content="light dark" {}
+                    link rel="stylesheet" type="text/css" href="css/theme.css" {}
+                }
+                @for href in ctx.stylesheets() {
+                    link rel="stylesheet" type="text/css" href=(href) {}
+                }
                 meta name="viewport" content="width=device-width, initial-scale=1" {}
+                @if let Some(token) = csrf::token() {
+                    meta name="csrf-token" content=(token) {}
+                }
+                script src="js/csrf.js" nonce=[csp::nonce()] {}
+                script src="js/events.js" nonce=[csp::nonce()] {}
+                script src="js/timezone.js" nonce=[csp::nonce()] defer {}
             }
             body {
+                @if let Some(banner) = ctx.banner(i18n) {
+                    div class="cms-banner" role="status" {(banner)}
+                }
                 (body)
+                @if let Some(footer) = ctx.footer(i18n) {
+                    footer class="cms-footer" {(footer)}
+                }
             }
         }
     }
 }
 
-pub fn sidebar(
-    _i18n: &FluentLanguageLoader,
-    names: impl IntoIterator<Item = impl AsRef<str>>,
-    active: &str,
-) -> Markup {
+/// Content rendered on every page of the admin interface for the current request,
+/// see [`App::with_banner`](crate::App::with_banner).
+pub type Slot<S> = Arc<dyn Fn(&S, &FluentLanguageLoader) -> Markup + Send + Sync>;
+
+/// `active` is the [`EntityScope::path_plural`] of the current page
+pub fn sidebar<S: ContextTrait>(ctx: &S, i18n: &FluentLanguageLoader, active: &str) -> Markup {
     html! {
         nav class="cms-sidebar" {
-            @for name in names {
-                @let name = name.as_ref();
-                a href=(&format!("/{}", name.to_case(Case::Kebab))) class=[(name == active).then_some("active")] {
-                    (name.to_case(Case::Title))
+            (sidebar_brand(ctx.branding()))
+            @if ctx.entities().iter().all(|scope| scope.group().is_none()) {
+                @for scope in ctx.entities() {
+                    (sidebar_link(scope, active))
+                }
+            } @else {
+                @for (group, scopes) in sidebar_groups(ctx.entities()) {
+                    @let open = scopes.iter().any(|scope| scope.path_plural() == active);
+                    section class="cms-sidebar-group" {
+                        details open[open] {
+                            summary class="cms-sidebar-group-heading" {
+                                @match group {
+                                    Some(group) => (group),
+                                    None => (fl!(i18n, "sidebar-group-general")),
+                                }
+                            }
+                            @for scope in scopes {
+                                (sidebar_link(scope, active))
+                            }
+                        }
+                    }
+                }
+            }
+            @for name in ctx.form_groups() {
+                @let path = form_group_path(name);
+                a href=(path) class=[(path == active).then_some("active")] {
+                    (name)
+                }
+            }
+            @if ctx.uploads_export() {
+                a href="export/uploads.zip" class="cms-sidebar-download" download {
+                    (fl!(i18n, "sidebar-download-uploads"))
+                }
+            }
+            (language_switcher(i18n, ctx.languages()))
+            (theme_switcher(i18n))
+            (user_menu(i18n))
+        }
+    }
+}
+
+/// logo and name of the application linking to the dashboard, see [`BrandingConfig`]
+fn sidebar_brand(branding: &BrandingConfig) -> Markup {
+    html! {
+        @if branding.logo.is_some() || branding.title.is_some() {
+            a href="" class="cms-sidebar-brand" {
+                @if let Some(logo) = &branding.logo {
+                    img src=(logo) alt="" {}
+                }
+                @if let Some(title) = &branding.title {
+                    span {(title)}
+                }
+            }
+        }
+    }
+}
+
+/// name of the [`User`] of the current request and a logout button, see [`auth`](crate::auth)
+fn user_menu(i18n: &FluentLanguageLoader) -> Markup {
+    html! {
+        @if let Some(user) = User::current() {
+            div class="cms-sidebar-user" {
+                span {(user.name)}
+                form method="post" action="logout" {
+                    button type="submit" {(fl!(i18n, "sidebar-logout"))}
+                }
+            }
+        }
+    }
+}
+
+fn sidebar_link(scope: &EntityScope, active: &str) -> Markup {
+    let path = scope.path_plural();
+    html! {
+        a href=(path) class=[(path == active).then_some("active")] {
+            @if let Some(prefix) = scope.prefix() {
+                (prefix.to_case(Case::Title)) " / "
+            }
+            (scope.name_plural().to_case(Case::Title))
+        }
+    }
+}
+
+/// `scopes` by [`EntityScope::group`], in the order of the first entity of each group
+fn sidebar_groups(scopes: &[EntityScope]) -> Vec<(Option<&str>, Vec<&EntityScope>)> {
+    let mut groups: Vec<(Option<&str>, Vec<&EntityScope>)> = Vec::new();
+    for scope in scopes {
+        match groups.iter_mut().find(|(group, _)| *group == scope.group()) {
+            Some((_, scopes)) => scopes.push(scope),
+            None => groups.push((scope.group(), vec![scope])),
+        }
+    }
+    groups
+}
+
+/// a dropdown that sets the [`LANGUAGE_COOKIE`] and reloads the page
+pub fn language_switcher(i18n: &FluentLanguageLoader, languages: &[LanguageIdentifier]) -> Markup {
+    let current = i18n.current_language();
+    html! {
+        @if languages.len() > 1 {
+            label class="cms-language-switcher" {
+                (fl!(i18n, "language-switcher-label"))
+                select data-cookie=(LANGUAGE_COOKIE) data-cms-change="cmsLanguageSelect" {
+                    @for lang in languages {
+                        option value=(lang) selected[*lang == current] {(lang)}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A dropdown that sets the [`THEME_COOKIE`] and applies the theme,
+/// if the theme switcher is enabled using [`App::with_theme_switcher`](crate::App::with_theme_switcher).
+pub fn theme_switcher(i18n: &FluentLanguageLoader) -> Markup {
+    html! {
+        @if let Some(current) = Theme::current().filter(|_| Theme::selectable()) {
+            label class="cms-theme-switcher" {
+                (fl!(i18n, "theme-switcher-label"))
+                select data-cookie=(THEME_COOKIE) data-cms-change="cmsThemeSelect" {
+                    @for theme in Theme::ALL {
+                        option value=(theme.as_str()) selected[theme == current] {
+                            @match theme {
+                                Theme::System => (fl!(i18n, "theme-switcher", "system")),
+                                Theme::Light => (fl!(i18n, "theme-switcher", "light")),
+                                Theme::Dark => (fl!(i18n, "theme-switcher", "dark")),
+                            }
+                        }
+                    }
                 }
             }
         }
     }
 }
 
-pub fn entity_inputs<E: Entity<S>, S: ContextTrait>(
+/// form with the [`Inputs`] of `T`, e.g. [`EntityBase::Create`] or [`EntityBase::Update`].
+/// If `value` is `None`, the [values](Submission::values) of `submission` are restored in the browser.
+pub fn entity_inputs<T: Inputs<S>, S: ContextTrait>(
     ctx: S,
     i18n: &FluentLanguageLoader,
-    value: Option<&E>,
+    value: Option<&T>,
+    submission: &Submission,
+    sections: Vec<FormSection<'_, S>>,
 ) -> Markup {
     let form_id = &Uuid::new_v4().to_string();
-    let ctx = FormRenderContext { form_id, ctx };
+    let ctx = FormRenderContext {
+        form_id,
+        ctx,
+        submission,
+        nonce: csp::nonce(),
+        assets: Default::default(),
+        constraints: Default::default(),
+        list_elements_url: submission.list_elements.as_deref(),
+        list_elements: None,
+    };
+    // rendered before the assets, which are only known afterwards
+    let inputs = html! {
+        (tabbed_inputs(&ctx, i18n, T::inputs(value)))
+        @for section in sections {
+            (section(&ctx, i18n))
+        }
+    };
+    // JS array of the submitted values, `</` must not end the script element
+    let restore = (value.is_none() && !submission.values.is_empty()).then(|| {
+        serde_json::to_string(&submission.values)
+            .unwrap_or_default()
+            .replace("</", r"<\/")
+    });
     html! {
         form id=(form_id) class="cms-entity-form cms-add-form" method="post" enctype="multipart/form-data" {
-            (inputs(&ctx, i18n, EntityBase::inputs(value)))
+            @if let Some(error) = &submission.error {
+                div class="cms-form-error" role="alert" {
+                    strong {(error.title)}
+                    p {(error.description)}
+                }
+            }
+            (ctx.render_assets())
+            @if let Some(version) = &submission.version {
+                input type="hidden" name=(VERSION_FIELD) value=(version);
+            }
+            (inputs)
             button class="cms-button" type="submit" {
                 (fl!(i18n, "entity-inputs-submit"))
             }
-            script src="/js/callOnMountRecursive.js" {}
-            script {
+            @if let Some(values) = restore {
+                script src="js/restore.js" nonce=[&ctx.nonce] {}
+                script nonce=[&ctx.nonce] {
+                    (PreEscaped(format!(r#"cmsRestoreForm(document.getElementById("{form_id}"), {values});"#)))
+                }
+            }
+            script src="js/callOnMountRecursive.js" nonce=[&ctx.nonce] {}
+            script nonce=[&ctx.nonce] {
                 (PreEscaped(format!(r#"callOnMountRecursive(document.getElementById("{form_id}"));"#)))
             }
         }
     }
 }
 
+/// The inputs of the elements of `target` in the form `form_id` of `value`, loaded by a collapsed
+/// list when it is expanded, with the scripts they require.
+/// `None` if the form has no visible list input named like `target`, see [`Submission::list_elements`].
+pub(crate) fn list_elements<T: Inputs<S>, S: ContextTrait>(
+    ctx: S,
+    i18n: &FluentLanguageLoader,
+    value: &T,
+    url: &str,
+    form_id: &str,
+    nonce: Option<String>,
+    target: ListElements<'_>,
+) -> Option<Markup> {
+    let submission = Submission::default();
+    let ctx = FormRenderContext {
+        form_id,
+        ctx,
+        submission: &submission,
+        nonce,
+        assets: Default::default(),
+        constraints: Default::default(),
+        list_elements_url: Some(url),
+        list_elements: Some(&target),
+    };
+    // only the elements of the target are kept
+    let _ = tabbed_inputs(&ctx, i18n, T::inputs(Some(value)));
+    let rendered = target.rendered.take()?;
+    Some(html! {
+        (ctx.render_assets())
+        (rendered)
+    })
+}
+
+/// Render the [`tab`](InputInfo::tab)s of `inputs` as a tab bar with one panel per tab, in order of
+/// first occurrence. Inputs without a tab are rendered above the tab bar.
+pub fn tabbed_inputs<'a, S: ContextTrait>(
+    ctx: &FormRenderContext<'_, S>,
+    i18n: &FluentLanguageLoader,
+    fields: impl IntoIterator<Item = InputInfo<'a, S>>,
+) -> Markup {
+    let mut untabbed = Vec::new();
+    let mut tabs: Vec<(&str, Vec<InputInfo<'a, S>>)> = Vec::new();
+    for f in fields.into_iter().filter(|f| (f.visible)(&ctx.ctx)) {
+        match f.tab {
+            None => untabbed.push(f),
+            Some(tab) => match tabs.iter_mut().find(|(name, _)| *name == tab) {
+                Some((_, fields)) => fields.push(f),
+                None => tabs.push((tab, vec![f])),
+            },
+        }
+    }
+    if !tabs.is_empty() {
+        ctx.require_script("js/tabs.js");
+    }
+    html! {
+        (inputs(ctx, i18n, untabbed))
+        @if !tabs.is_empty() {
+            div class="cms-tabs" {
+                div class="cms-tab-bar" role="tablist" {
+                    @for (i, (name, _)) in tabs.iter().enumerate() {
+                        button type="button" class="cms-tab" role="tab" aria-selected=(i == 0) data-cms-click="cmsTabSelect" {
+                            (name)
+                        }
+                    }
+                }
+                @for (i, (_, fields)) in tabs.into_iter().enumerate() {
+                    div class="cms-tab-panel" role="tabpanel" hidden[i != 0] {
+                        (inputs(ctx, i18n, fields))
+                    }
+                }
+                script nonce=[&ctx.nonce] {
+                    (PreEscaped(format!(r#"cmsTabsInit(document.getElementById("{}"));"#, ctx.form_id)))
+                }
+            }
+        }
+    }
+}
+
 pub fn struct_input<'a, S: ContextTrait>(
     ctx: &FormRenderContext<'_, S>,
     i18n: &FluentLanguageLoader,
@@ -86,154 +525,672 @@ pub fn struct_input<'a, S: ContextTrait>(
     }
 }
 
+/// The label and input of each [`visible`](InputInfo::visible) field. Labels of
+/// [`required`](InputInfo::required) fields are marked with an asterisk, followed by the
+/// [errors](Submission::errors) of the field.
 pub fn inputs<'a, S: ContextTrait>(
     ctx: &FormRenderContext<'_, S>,
     i18n: &FluentLanguageLoader,
-    inputs: impl IntoIterator<Item = InputInfo<'a, S>>,
+    inputs: impl IntoIterator<Item = impl Borrow<InputInfo<'a, S>>>,
 ) -> Markup {
     html! {
-        @for f in inputs {
-            div class="cms-prop-container" {
-                label class="cms-prop-label" {(f.name_human)}
-                (f.value.render_input(f.name, f.name_human, true, &ctx, i18n))
+        @for f in inputs.into_iter().filter(|f| (f.borrow().visible)(&ctx.ctx)) {
+            @let f = f.borrow();
+            @let required = f.required && f.value.mark_required();
+            @let errors = ctx.submission.errors.get(f.name);
+            div.cms-prop-container.cms-invalid[!errors.is_empty()] {
+                label.cms-prop-label.cms-required[required] {
+                    (f.name_human)
+                    @if required {
+                        " "
+                        abbr class="cms-required-marker" title=(fl!(i18n, "input-required")) {"*"}
+                    }
+                }
+                (ctx.render_input(f, f.name_human, f.required, i18n))
+                @if let Some(help) = f.help {
+                    small class="cms-prop-help" {(help.text(i18n))}
+                }
+                @for message in errors {
+                    p class="cms-prop-error" role="alert" {(message)}
+                }
             }
         }
     }
 }
 
+/// the dashboard at `/` with a card per entity and the number of its entities if it could be
+/// counted, see [`List::count`](crate::entity::List::count)
+pub fn dashboard_page<S: ContextTrait>(
+    ctx: &S,
+    i18n: &FluentLanguageLoader,
+    entities: &[(&EntityScope, Option<u64>)],
+) -> Markup {
+    let title = fl!(i18n, "dashboard", "title");
+    document(
+        ctx,
+        i18n,
+        &title,
+        html! {
+            (sidebar(ctx, i18n, ""))
+            main {
+                h1 {(title)}
+                div class="cms-dashboard" {
+                    @for (scope, count) in entities {
+                        section class="cms-dashboard-card" {
+                            h2 {
+                                @if let Some(prefix) = scope.prefix() {
+                                    (prefix.to_case(Case::Title)) " / "
+                                }
+                                (scope.name_plural().to_case(Case::Title))
+                            }
+                            @if let Some(count) = count {
+                                p class="cms-dashboard-count" {
+                                    (fl!(i18n, "dashboard", "count", count = count))
+                                }
+                            }
+                            a href=(scope.path_plural()) class="cms-button" {
+                                (fl!(i18n, "dashboard", "view"))
+                            }
+                            @if scope.allows(Action::Create) {
+                                a href=(format!("{}/add", scope.path_plural())) class="cms-button" {
+                                    (fl!(i18n, "enitity-list-add"))
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
 pub fn entity_list_page<E: Entity<S>, S: ContextTrait>(
     ctx: State<S>,
     i18n: &FluentLanguageLoader,
-    entities: impl IntoIterator<Item = impl Borrow<E>>,
+    scope: &EntityScope,
+    search: Option<&str>,
+    pagination: &Pagination,
+    rows: impl IntoIterator<Item = impl ListRow<E, S>>,
 ) -> Markup {
-    document(html! {
-        (sidebar(&i18n, ctx.names_plural(), E::name_plural()))
-        main {
-            header class="cms-header" {
-                h1 {(E::name_plural().to_case(Case::Title))}
-                a href=(format!("/{}/add", (E::name_plural().to_case(Case::Kebab)))) class="cms-button" {
-                    (fl!(i18n, "enitity-list-add"))
-                }
-            }
-            @for (i, c) in E::columns().iter().enumerate() {
-                @let i = i + 1;
-                @let id = format!("cms-list-column-filter-input-{i}");
-                input id=(id) class=("cms-list-column-filter-input") type="checkbox" checked[!c.hidden] {}
-                label for=(id) {
-                    (c.name)
-                }
-                style {(PreEscaped(format!(r#"
+    let title = scope.name_plural().to_case(Case::Title);
+    document(
+        &*ctx,
+        i18n,
+        &title,
+        html! {
+            (sidebar(&*ctx, i18n, &scope.path_plural()))
+            main {
+                header class="cms-header" {
+                    h1 {(title)}
+                    @if scope.allows(Action::Create) {
+                        a href=(format!("{}/add", scope.path_plural())) class="cms-button" {
+                            (fl!(i18n, "enitity-list-add"))
+                        }
+                    }
+                    @if scope.trash() {
+                        a href=(format!("{}/trash", scope.path_plural())) class="cms-button" {
+                            (fl!(i18n, "entity-list-trash"))
+                        }
+                    }
+                }
+                (maintenance_actions(&*ctx, i18n, scope, &E::maintenance_actions()))
+                @if !E::searchable_columns().is_empty() {
+                    form class="cms-list-search" method="get" action=(scope.path_plural()) {
+                        input type="search" name="q" value=[search] placeholder=(fl!(i18n, "entity-list-search", "placeholder")) {}
+                        button type="submit" class="cms-button" {(fl!(i18n, "entity-list-search", "submit"))}
+                    }
+                }
+                @let visible = E::visible_columns(&ctx);
+                @let delete = scope.allows(Action::Delete);
+                @let columns = E::columns().into_iter().zip(visible.iter()).filter(|(_, v)| **v).map(|(c, _)| c).collect::<Vec<_>>();
+                @for (i, c) in columns.iter().enumerate() {
+                    @let i = i + 1;
+                    @let id = format!("cms-list-column-filter-input-{i}");
+                    input id=(id) class=("cms-list-column-filter-input") type="checkbox" checked[!c.hidden] {}
+                    label for=(id) {
+                        (c.name)
+                    }
+                    style {(PreEscaped(format!(r#"
 #{id}:not(:checked) ~ .cms-entity-list .cms-list-column:nth-child({i}) {{
     display: none;
 }}
                 "#).trim()))}
-            }
-            table class="cms-entity-list" {
-                tr {
-                    @for c in E::columns() {
-                        th class="cms-list-column" {(c.name)}
-                    }
-                    th {}
-                }
-                @for e in entities {
-                    @let e = e.borrow();
-                    @let name = E::name().to_case(Case::Kebab);
-                    @let id = e.id().to_string();
-                    @let id = urlencoding::encode(&id);
-                    @let row_id = Uuid::new_v4();
-                    @let dialog_id = Uuid::new_v4();
-                    tr id=(row_id) {
-                        @for c in e.column_values() {
-                            td class="cms-list-column" onclick=(format!(
-                                "window.location = \"/{name}/{id}\"",
-                            )) {
-                                (c.render(i18n))
+                }
+                table class="cms-entity-list" {
+                    tr {
+                        @for c in &columns {
+                            th class="cms-list-column" {(c.name)}
+                        }
+                        @if delete {
+                            th {}
+                        }
+                    }
+                    @for e in rows {
+                        @let name = scope.path();
+                        @let id = E::url_id(e.id());
+                        @let id = urlencoding::encode(&id);
+                        @let row_id = Uuid::new_v4();
+                        @let dialog_id = Uuid::new_v4();
+                        tr id=(row_id) {
+                            @for ((info, c), _) in E::columns().iter().zip(e.column_values()).zip(visible.iter()).filter(|(_, v)| **v) {
+                                td class="cms-list-column" data-cms-click="cmsNavigate" data-href=(format!("{name}/{id}")) {
+                                    @if info.thumbnail {
+                                        (c.render_thumbnail(i18n))
+                                    } @else {
+                                        (c.render(i18n))
+                                    }
+                                }
+                            }
+                            @if delete {
+                                td
+                                    class="cms-list-column cms-list-delete-button"
+                                    data-cms-click="cmsShowModal"
+                                    data-dialog=(dialog_id)
+                                {
+                                    "X"
+                                }
+                                (confirm_delete_modal(
+                                    i18n,
+                                    dialog_id,
+                                    &scope.name().to_case(Case::Title),
+                                    &e.delete_confirmation(),
+                                    scope.trash(),
+                                    &format!("api/v1/{name}/{id}"),
+                                    row_id,
+                                ))
                             }
                         }
-                        td
-                            class="cms-list-column cms-list-delete-button"
-                            onclick=(format!(r#"document.getElementById("{dialog_id}").showModal()"#))
+                    }
+                }
+                (ctx.paginator().render(pagination, i18n))
+            }
+        },
+    )
+}
+
+/// a button per [`MaintenanceAction`] with the status of its last run.
+/// The page is reloaded periodically while an action is running.
+pub fn maintenance_actions<S: ContextTrait>(
+    ctx: &S,
+    i18n: &FluentLanguageLoader,
+    scope: &EntityScope,
+    actions: &[MaintenanceAction<S>],
+) -> Markup {
+    let statuses = actions
+        .iter()
+        .map(|a| ctx.maintenance_runs().status(scope, a.name()))
+        .collect::<Vec<_>>();
+    let running = statuses
+        .iter()
+        .any(|s| matches!(s, Some(RunStatus::Running { .. })));
+    html! {
+        @if !actions.is_empty() {
+            div class="cms-maintenance-actions" {
+                @for (action, status) in actions.iter().zip(statuses) {
+                    form
+                        method="post"
+                        action=(format!("{}/actions/{}", scope.path_plural(), action.name()))
+                        data-cms-submit=[action.confirm.as_ref().map(|_| "cmsConfirmSubmit")]
+                        data-confirm=[&action.confirm]
+                    {
+                        button
+                            type="submit"
+                            class="cms-button"
+                            disabled[matches!(status, Some(RunStatus::Running { .. }))]
                         {
-                            "X"
+                            (action.label)
+                        }
+                        @if let Some(status) = status {
+                            span class="cms-maintenance-status" {
+                                @match status {
+                                    RunStatus::Running { done, total: Some(total) } => {
+                                        (fl!(i18n, "maintenance-action", "progress", done = done, total = total))
+                                    }
+                                    RunStatus::Running { total: None, .. } => {
+                                        (fl!(i18n, "maintenance-action", "running"))
+                                    }
+                                    RunStatus::Finished => (fl!(i18n, "maintenance-action", "finished")),
+                                    RunStatus::Failed(error) => {
+                                        (fl!(i18n, "maintenance-action", "failed", error = error))
+                                    }
+                                }
+                            }
                         }
-                        (confirm_delete_modal(
-                            i18n,
-                            dialog_id,
-                            &E::name().to_case(Case::Title),
-                            format!(r#"
-fetch("/api/v1/{name}/{id}", {{ method: "DELETE" }})
-    .then((r) => {{
-        if (!r.ok) return;
-        document.getElementById("{row_id}").remove();
-        document.getElementById("{dialog_id}").remove();
-    }})
-                            "#).trim()
-                        ))
                     }
                 }
+                @if running {
+                    script nonce=[csp::nonce()] {"setTimeout(() => location.reload(), 2000);"}
+                }
             }
         }
-    })
+    }
 }
 
+/// A dialog to confirm deleting the entity at the API URL `url`, e.g. `api/v1/post/1`.
+/// The element with the id `remove` and the dialog are removed once it is deleted.
 pub fn confirm_delete_modal(
     i18n: &FluentLanguageLoader,
     dialog_id: impl Display,
     name: &str,
-    on_submit: impl Display,
+    confirm: &DeleteConfirm,
+    trash: bool,
+    url: &str,
+    remove: impl Display,
 ) -> Markup {
+    let input_id = Uuid::new_v4();
+    let button_id = Uuid::new_v4();
     html! {
         dialog id=(dialog_id) class="cms-confirm-delete-modal" {
-            p {(fl!(i18n, "confirm-delete-modal", "title", name = name))}
+            p {
+                @match &confirm.message {
+                    Some(message) => (message),
+                    None if trash => (fl!(i18n, "confirm-delete-modal", "trash-title", name = name)),
+                    None => (fl!(i18n, "confirm-delete-modal", "title", name = name)),
+                }
+            }
             form method="dialog" {
+                @if let Some(text) = &confirm.type_to_confirm {
+                    label for=(input_id) {
+                        (fl!(i18n, "confirm-delete-modal", "type-to-confirm", text = text.as_str()))
+                    }
+                    input
+                        id=(input_id)
+                        type="text"
+                        autocomplete="off"
+                        data-cms-input="cmsTypeToConfirm"
+                        data-button=(button_id)
+                        data-expected=(text) {}
+                }
                 button {
                     (fl!(i18n, "confirm-delete-modal", "cancel"))
                 }
-                button onclick=(on_submit) {
-                    (fl!(i18n, "confirm-delete-modal", "confirm"))
+                button
+                    id=(button_id)
+                    disabled[confirm.type_to_confirm.is_some()]
+                    data-cms-click="cmsDeleteConfirmed"
+                    data-url=(url)
+                    data-remove=(remove)
+                    data-expected=[&confirm.type_to_confirm]
+                    data-input=(input_id)
+                {
+                    @if trash {
+                        (fl!(i18n, "confirm-delete-modal", "trash-confirm"))
+                    } @else {
+                        (fl!(i18n, "confirm-delete-modal", "confirm"))
+                    }
                 }
             }
         }
     }
 }
 
+/// The soft-deleted `entities` with a button to restore each of them, see
+/// [`SoftDelete`](crate::entity::SoftDelete).
+pub fn trash_page<E: Entity<S>, S: ContextTrait>(
+    State(ctx): State<S>,
+    i18n: &FluentLanguageLoader,
+    scope: &EntityScope,
+    entities: impl IntoIterator<Item = E>,
+) -> Markup {
+    let visible = E::visible_columns(&ctx);
+    let mut entities = entities.into_iter().peekable();
+    let title = fl!(
+        i18n,
+        "entity-trash",
+        "title",
+        name = scope.name_plural().to_case(Case::Title)
+    );
+    document(
+        &ctx,
+        i18n,
+        &title,
+        html! {
+            (sidebar(&ctx, i18n, &scope.path_plural()))
+            main {
+                header class="cms-header" {
+                    h1 {(title)}
+                    a href=(scope.path_plural()) class="cms-button" {
+                        (fl!(i18n, "entity-trash", "back"))
+                    }
+                }
+                @if entities.peek().is_none() {
+                    p {(fl!(i18n, "entity-trash", "empty"))}
+                } @else {
+                    table class="cms-entity-list" {
+                        tr {
+                            @for (c, _) in E::columns().iter().zip(visible.iter()).filter(|(_, v)| **v) {
+                                th class="cms-list-column" {(c.name)}
+                            }
+                            th {}
+                        }
+                        @for e in entities {
+                            @let id = E::url_id(e.id());
+                            tr {
+                                @for ((info, c), _) in E::columns().iter().zip(e.column_values()).zip(visible.iter()).filter(|(_, v)| **v) {
+                                    td class="cms-list-column" {
+                                        @if info.thumbnail {
+                                            (c.render_thumbnail(i18n))
+                                        } @else {
+                                            (c.render(i18n))
+                                        }
+                                    }
+                                }
+                                td {
+                                    form method="post" action=(format!("{}/{}/restore", scope.path(), urlencoding::encode(&id))) {
+                                        button type="submit" class="cms-button" {(fl!(i18n, "entity-trash", "restore"))}
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// The `versions` of the entity `id` with a link to view and a button to publish each of them,
+/// see [`Versioned`](crate::entity::Versioned).
+pub fn versions_page<E: Entity<S> + Versioned<S>, S: ContextTrait>(
+    State(ctx): State<S>,
+    i18n: &FluentLanguageLoader,
+    scope: &EntityScope,
+    id: &E::Id,
+    versions: Vec<Version<E::VersionId>>,
+) -> Markup {
+    let path = format!("{}/{}", scope.path(), urlencoding::encode(&E::url_id(id)));
+    let title = fl!(
+        i18n,
+        "entity-versions",
+        "title",
+        name = scope.name().to_case(Case::Title)
+    );
+    document(
+        &ctx,
+        i18n,
+        &title,
+        html! {
+            (sidebar(&ctx, i18n, &scope.path_plural()))
+            main {
+                header class="cms-header" {
+                    h1 {(title)}
+                    a href=(path) class="cms-button" {
+                        (fl!(i18n, "entity-versions", "back"))
+                    }
+                }
+                @if versions.is_empty() {
+                    p {(fl!(i18n, "entity-versions", "empty"))}
+                } @else {
+                    table class="cms-entity-list" {
+                        tr {
+                            th {(fl!(i18n, "entity-versions", "created"))}
+                            th {(fl!(i18n, "entity-versions", "version"))}
+                            th {}
+                        }
+                        @for version in versions {
+                            @let version_id = urlencoding::encode(&version.id.to_string()).into_owned();
+                            tr {
+                                td {(version.created_at.render(i18n))}
+                                td {
+                                    a href=(format!("{path}?version={version_id}")) {
+                                        code {(version.id)}
+                                    }
+                                }
+                                td {
+                                    @if version.published {
+                                        strong {(fl!(i18n, "entity-versions", "published"))}
+                                    } @else {
+                                        form method="post" action=(format!("{path}/versions/{version_id}/publish")) {
+                                            button type="submit" class="cms-button" {(fl!(i18n, "entity-versions", "publish"))}
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
 pub fn entity_page<E: Entity<S>, S: ContextTrait>(
     State(ctx): State<S>,
     i18n: &FluentLanguageLoader,
-    entity: Option<&E>,
+    scope: &EntityScope,
+    id: &E::Id,
+    update: Option<&E::Update>,
+    submission: &Submission,
+    sections: Vec<FormSection<'_, S>>,
 ) -> Markup {
-    document(html! {
-        (sidebar(i18n, ctx.names_plural(), E::name_plural()))
-        main {
-            h1 {(fl!(i18n, "edit-entity-title", name = E::name().to_case(Case::Title)))}
-            (entity_inputs::<E, S>(ctx, i18n, entity))
-        }
-    })
+    let title = fl!(
+        i18n,
+        "edit-entity-title",
+        name = scope.name().to_case(Case::Title)
+    );
+    document(
+        &ctx,
+        i18n,
+        &title,
+        html! {
+            (sidebar(&ctx, i18n, &scope.path_plural()))
+            main {
+                header class="cms-header" {
+                    h1 {(title)}
+                    @if scope.versions() {
+                        a href=(format!("{}/{}/versions", scope.path(), urlencoding::encode(&E::url_id(id)))) class="cms-button" {
+                            (fl!(i18n, "edit-entity-versions"))
+                        }
+                    }
+                }
+                (entity_inputs::<E::Update, S>(ctx.clone(), i18n, update, submission, sections))
+            }
+        },
+    )
+}
+
+/// Read-only page of `entity` with the [`Column::render`](crate::Column::render) of each visible column
+/// in a definition list, served at `/:name/:id/view`.
+pub fn entity_detail_page<E: Entity<S>, S: ContextTrait>(
+    State(ctx): State<S>,
+    i18n: &FluentLanguageLoader,
+    scope: &EntityScope,
+    entity: &E,
+) -> Markup {
+    let id = E::url_id(entity.id());
+    let visible = E::visible_columns(&ctx);
+    let title = scope.name().to_case(Case::Title);
+    document(
+        &ctx,
+        i18n,
+        &title,
+        html! {
+            (sidebar(&ctx, i18n, &scope.path_plural()))
+            main {
+                header class="cms-header" {
+                    h1 {(title)}
+                    a href=(format!("{}/{}", scope.path(), urlencoding::encode(&id))) class="cms-button" {
+                        (fl!(i18n, "entity-detail-edit"))
+                    }
+                }
+                dl class="cms-entity-detail" {
+                    @for ((info, c), _) in E::columns().iter().zip(entity.column_values()).zip(visible.iter()).filter(|(_, v)| **v) {
+                        dt {(info.name)}
+                        dd {(c.render(i18n))}
+                    }
+                }
+            }
+        },
+    )
 }
 
 pub fn add_entity_page<E: Entity<S>, S: ContextTrait>(
     State(ctx): State<S>,
     i18n: &FluentLanguageLoader,
-    entity: Option<&E>,
+    scope: &EntityScope,
+    entity: Option<&E::Create>,
+    submission: &Submission,
+) -> Markup {
+    let title = fl!(
+        i18n,
+        "create-entity-title",
+        name = scope.name().to_case(Case::Title)
+    );
+    document(
+        &ctx,
+        i18n,
+        &title,
+        html! {
+            (sidebar(&ctx, i18n, &scope.path_plural()))
+            main {
+                h1 {(title)}
+                (entity_inputs::<E::Create, S>(ctx.clone(), i18n, entity, submission, Vec::new()))
+            }
+        },
+    )
+}
+
+/// path of the form of a [`FormGroup`] named `name`, relative to the base path
+pub fn form_group_path(name: &str) -> String {
+    format!("forms/{}", name.to_case(Case::Kebab))
+}
+
+pub fn form_group_page<G: FormGroup<S>, S: ContextTrait>(
+    State(ctx): State<S>,
+    i18n: &FluentLanguageLoader,
+    value: Option<&G>,
+    submission: &Submission,
+) -> Markup {
+    document(
+        &ctx,
+        i18n,
+        G::name(),
+        html! {
+            (sidebar(&ctx, i18n, &form_group_path(G::name())))
+            main {
+                h1 {(G::name())}
+                (entity_inputs::<G, S>(ctx.clone(), i18n, value, submission, Vec::new()))
+            }
+        },
+    )
+}
+
+/// the children of an [`InlineRelation`](crate::relation::InlineRelation) within the form of the parent.
+/// Existing children can be edited and marked for deletion, new children can be added, each only
+/// if the user [is allowed to](EntityScope::allows). Nothing is rendered if none of it is allowed.
+pub fn inline_relation<C: Entity<S>, S: ContextTrait>(
+    ctx: &FormRenderContext<'_, S>,
+    i18n: &FluentLanguageLoader,
+    name: &str,
+    children: &[C],
+) -> Markup {
+    let create = relation::allows::<C, S>(&ctx.ctx, Action::Create);
+    let update = relation::allows::<C, S>(&ctx.ctx, Action::Update);
+    let delete = relation::allows::<C, S>(&ctx.ctx, Action::Delete);
+    if !create && !update && !delete {
+        return html!();
+    }
+    let field = relation::field_name(name);
+    ctx.require_script("js/inline.js");
+    html! {
+        fieldset class="cms-inline-relation" {
+            legend {(name.to_case(Case::Title))}
+            @for (i, child) in children.iter().enumerate() {
+                fieldset class="cms-list-element" {
+                    @if update {
+                        @let update = child.to_update();
+                        @let prefix = format!("{field}[update][{i}]");
+                        input type="hidden" name=(format!("{field}[id][{i}]")) value=(child.id()) {}
+                        (prefixed_inputs(ctx, i18n, &prefix, C::Update::inputs(Some(update.borrow()))))
+                    } @else {
+                        p {(child.display_label())}
+                    }
+                    @if delete {
+                        label class="cms-inline-relation-delete" {
+                            input type="checkbox" name=(format!("{field}[delete][{i}]")) value=(child.id()) {}
+                            (fl!(i18n, "inline-relation-delete"))
+                        }
+                    }
+                }
+            }
+            @if create {
+                template {
+                    fieldset class="cms-list-element" {
+                        (prefixed_inputs(ctx, i18n, &format!("{field}[create][]"), C::Create::inputs(None)))
+                    }
+                }
+                button type="button" data-cms-click="cmsInlineRelationAdd" data-name=(format!("{field}[create]")) {"+"}
+            }
+        }
+    }
+}
+
+/// [`inputs`] with names nested within `prefix`
+fn prefixed_inputs<'a, S: ContextTrait>(
+    ctx: &FormRenderContext<'_, S>,
+    i18n: &FluentLanguageLoader,
+    prefix: &str,
+    fields: impl IntoIterator<Item = InputInfo<'a, S>>,
 ) -> Markup {
-    document(html! {
-        (sidebar(i18n, ctx.names_plural(), E::name_plural()))
-        main {
-            h1 {(fl!(i18n, "create-entity-title", name = E::name().to_case(Case::Title)))}
-            (entity_inputs::<E, S>(ctx, i18n, entity))
+    let fields = fields.into_iter().collect::<Vec<_>>();
+    let names = fields
+        .iter()
+        .map(|f| format!("{prefix}[{}]", f.name))
+        .collect::<Vec<_>>();
+    inputs(
+        ctx,
+        i18n,
+        fields
+            .into_iter()
+            .zip(&names)
+            .map(|(f, name)| InputInfo { name, ..f }),
+    )
+}
+
+/// A `<select>` of `options` (value, label). An empty option is added if the input is not `required`.
+pub fn select_input(
+    name: &str,
+    required: bool,
+    options: impl IntoIterator<Item = (String, String)>,
+    selected: Option<&str>,
+) -> Markup {
+    html! {
+        select name=(name) class="cms-select-input" required[required] {
+            @if !required {
+                option value="" selected[selected.is_none()] {}
+            }
+            @for (value, label) in options {
+                option selected[selected == Some(value.as_str())] value=(value) {(label)}
+            }
         }
-    })
+    }
 }
 
+/// `shared` are the inputs of fields that are part of every variant (`#[serde(flatten)]`).
+/// They are rendered once, independent of the selected variant.
+///
+/// If the input is `optional`, i.e. of an [`Option`] of the enum, an additional "none" option
+/// submits an empty tag, which is selected if `selected` is `None`. Otherwise the first variant is
+/// selected by default.
 pub fn input_enum<S: ContextTrait>(
     ctx: &FormRenderContext<'_, S>,
     i18n: &FluentLanguageLoader,
     variants: &[EnumVariant<'_, S>],
-    selected: usize,
+    shared: &[InputInfo<'_, S>],
+    selected: Option<usize>,
     required: bool,
+    optional: bool,
 ) -> Markup {
     let id_type = Uuid::new_v4();
     let id_data = Uuid::new_v4();
+    // the "none" option is rendered after the variants
+    let none = variants.first().filter(|_| optional).map(|v| v.name);
+    let selected = match (selected, none) {
+        (Some(i), _) => i,
+        (None, Some(_)) => variants.len(),
+        (None, None) => 0,
+    };
+    ctx.require_script("js/enum.js");
     html! {
         div class="cms-enum-type" id=(id_type) {
             @for (i, variant) in variants.iter().enumerate() {
@@ -244,9 +1201,20 @@ pub fn input_enum<S: ContextTrait>(
                     value=(variant.value)
                     id=(id)
                     checked[i == selected]
-                    onchange="cmsEnumInputOnchange(this)" {}
+                    data-cms-change="cmsEnumInputOnchange" {}
                 label for=(id) {(variant.value.to_case(Case::Title))}
             }
+            @if let Some(name) = none {
+                @let id = &format!("{name}_radio-button_");
+                input
+                    type="radio"
+                    name=(name)
+                    value=""
+                    id=(id)
+                    checked[selected == variants.len()]
+                    data-cms-change="cmsEnumInputOnchange" {}
+                label for=(id) {(fl!(i18n, "enum-input-none"))}
+            }
         }
         div class="cms-enum-data" id=(id_data) {
             @for (i, variant) in variants.iter().enumerate() {
@@ -257,26 +1225,218 @@ pub fn input_enum<S: ContextTrait>(
                 };
                 fieldset class=(class) disabled[i != selected] {
                     @if let Some(ref data) = variant.content {
-                        (data.value.render_input(data.name, &variant.value.to_case(Case::Title), required, ctx, i18n))
+                        (ctx.render_input(data, &variant.value.to_case(Case::Title), required, i18n))
                     }
+                    (inputs(ctx, i18n, &variant.fields))
                 }
             }
+            @if none.is_some() {
+                fieldset
+                    class=(if selected == variants.len() { "cms-enum-container" } else { "cms-enum-container cms-enum-hidden cms-enum-hidden-right" })
+                    disabled {}
+            }
+        }
+        @if !shared.is_empty() {
+            div class="cms-enum-shared" {
+                (inputs(ctx, i18n, shared))
+            }
         }
-        script src="/js/enum.js" {}
     }
 }
 
-pub fn error_page(title: &str, description: &str) -> Markup {
-    document(html! {
-        main {
-            h1 {(title)}
-            p {
-                @for line in description.split('\n') {
-                    (line)
-                    br;
+/// `failed` if the credentials of the previous attempt were invalid,
+/// `next` is the page to redirect to after logging in
+pub fn login_page<S: ContextTrait>(
+    ctx: &S,
+    i18n: &FluentLanguageLoader,
+    failed: bool,
+    next: Option<&str>,
+) -> Markup {
+    let title = fl!(i18n, "login-title");
+    document(
+        ctx,
+        i18n,
+        &title,
+        html! {
+            main class="cms-login" {
+                h1 {(title)}
+                @if failed {
+                    p class="cms-login-failed" role="alert" {(fl!(i18n, "login-failed"))}
+                }
+                form method="post" action="login" {
+                    @if let Some(next) = next {
+                        input type="hidden" name="next" value=(next);
+                    }
+                    label {
+                        (fl!(i18n, "login-username"))
+                        input type="text" name="username" autocomplete="username" required autofocus;
+                    }
+                    label {
+                        (fl!(i18n, "login-password"))
+                        input type="password" name="password" autocomplete="current-password" required;
+                    }
+                    button type="submit" {(fl!(i18n, "login-submit"))}
+                }
+            }
+        },
+    )
+}
+
+pub fn error_page<S: ContextTrait>(
+    ctx: &S,
+    i18n: &FluentLanguageLoader,
+    title: &str,
+    description: &str,
+) -> Markup {
+    document(
+        ctx,
+        i18n,
+        title,
+        html! {
+            (sidebar(ctx, i18n, ""))
+            main {
+                h1 {(title)}
+                p {
+                    @for line in description.split('\n') {
+                        (line)
+                        br;
+                    }
+                }
+                @if let Some(id) = RequestId::current() {
+                    p class="cms-request-id" {
+                        (fl!(i18n, "error-page-request-id")) " " code {(id)}
+                    }
                 }
+                a href="" data-cms-click="cmsHistoryBack" {(fl!(i18n, "error-page-back"))}
             }
-            a href="javascript:history.back()" {"Go Back"}
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use i18n_embed::fluent::fluent_language_loader;
+
+    use super::*;
+    use crate::{context::Context, property::Text, App};
+
+    type Ctx = Context<()>;
+
+    struct Post {
+        tags: Vec<Text>,
+    }
+
+    impl Inputs<Ctx> for Post {
+        fn inputs(value: Option<&Self>) -> impl IntoIterator<Item = InputInfo<'_, Ctx>> {
+            [InputInfo::new(
+                "tags",
+                "Tags",
+                Box::new(value.map(|v| &v.tags)),
+            )]
         }
-    })
+    }
+
+    fn post(len: usize) -> Post {
+        Post {
+            tags: (0..len).map(|i| Text(format!("tag-{i}"))).collect(),
+        }
+    }
+
+    fn ctx(threshold: usize) -> Ctx {
+        App::<(), ()>::new()
+            .collapse_lists_longer_than(threshold)
+            .build_parts(std::env::temp_dir())
+            .1
+    }
+
+    fn render(post: &Post, list_elements: Option<&str>) -> String {
+        let submission = Submission {
+            list_elements: list_elements.map(str::to_string),
+            ..Default::default()
+        };
+        entity_inputs(
+            ctx(2),
+            &fluent_language_loader!(),
+            Some(post),
+            &submission,
+            Vec::new(),
+        )
+        .into_string()
+    }
+
+    #[test]
+    fn collapsed_elements_are_loaded_from_the_stored_entity() {
+        let html = render(&post(5), Some("post/1/list-elements"));
+        for i in 0..2 {
+            assert!(html.contains(&format!(r#"value="tag-{i}""#)), "{i}: {html}");
+        }
+        for i in 2..5 {
+            assert!(!html.contains(&format!("tag-{i}")), "{i}: {html}");
+            assert!(
+                html.contains(&format!(r#"data-index="{i}""#)),
+                "{i}: {html}"
+            );
+        }
+        assert!(!html.contains("<template>"), "{html}");
+        assert!(
+            html.contains(r#"data-cms-list-src="post/1/list-elements?form="#),
+            "{html}"
+        );
+        assert!(html.contains("&amp;field=tags"), "{html}");
+    }
+
+    #[test]
+    fn collapsed_elements_are_rendered_in_templates_without_a_stored_entity() {
+        let html = render(&post(5), None);
+        for i in 0..5 {
+            assert!(html.contains(&format!(r#"value="tag-{i}""#)), "{i}: {html}");
+        }
+        assert_eq!(html.matches("<template>").count(), 3, "{html}");
+        assert!(!html.contains("data-cms-list-src"), "{html}");
+    }
+
+    #[test]
+    fn short_lists_are_not_collapsed() {
+        let html = render(&post(2), Some("post/1/list-elements"));
+        assert!(!html.contains("cms-list-element-collapsed"), "{html}");
+        assert!(!html.contains("data-cms-list-src"), "{html}");
+    }
+
+    #[test]
+    fn list_elements_renders_only_the_requested_range() {
+        let render = |name, range| {
+            list_elements(
+                ctx(2),
+                &fluent_language_loader!(),
+                &post(5),
+                "post/1/list-elements",
+                "form",
+                None,
+                ListElements::new(name, range),
+            )
+            .map(Markup::into_string)
+        };
+        let html = render("tags", 3..10).unwrap();
+        assert_eq!(
+            html.matches("cms-list-element-content").count(),
+            2,
+            "{html}"
+        );
+        for i in 0..3 {
+            assert!(!html.contains(&format!("tag-{i}")), "{i}: {html}");
+        }
+        for i in 3..5 {
+            assert!(
+                html.contains(&format!(r#"data-index="{i}""#)),
+                "{i}: {html}"
+            );
+            assert!(
+                html.contains(&format!(r#"name="tags[{i}]""#)),
+                "{i}: {html}"
+            );
+            assert!(html.contains(&format!(r#"value="tag-{i}""#)), "{i}: {html}");
+        }
+        assert_eq!(render("tags", 7..9).as_deref(), Some(""));
+        assert_eq!(render("categories", 0..1), None);
+    }
 }
@@ -0,0 +1,72 @@
+//! Request ids shown on error pages and in the logs, see [`App::with_request_id`](crate::App::with_request_id).
+
+use std::fmt::Display;
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{info_span, Instrument};
+use uuid::Uuid;
+
+/// `X-Request-Id`
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+tokio::task_local! {
+    /// id of the request that is currently handled
+    static REQUEST_ID: RequestId;
+}
+
+/// Identifies a request in error pages, API errors and log lines.
+///
+/// Taken from the `X-Request-Id` header of the request, e.g. set by a reverse proxy,
+/// or a random UUID if it is missing.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// The id of the current request, if request ids are enabled.
+    pub fn current() -> Option<Self> {
+        REQUEST_ID.try_with(Clone::clone).ok()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// use the id from `header` if it is short and printable, otherwise generate a new one
+    fn from_header(header: Option<&HeaderValue>) -> Self {
+        match header.and_then(|v| v.to_str().ok()) {
+            Some(id)
+                if !id.is_empty()
+                    && id.len() <= 128
+                    && id.bytes().all(|b| b.is_ascii_graphic()) =>
+            {
+                Self(id.to_string())
+            }
+            _ => Self(Uuid::new_v4().to_string()),
+        }
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Handle the request in a span with the [`RequestId`] and return it in the `X-Request-Id` header.
+pub(crate) async fn request_id(mut req: Request, next: Next) -> Response {
+    let id = RequestId::from_header(req.headers().get(&X_REQUEST_ID));
+    req.extensions_mut().insert(id.clone());
+    let span = info_span!("request", request_id = %id, method = %req.method(), uri = %req.uri());
+    let mut res = REQUEST_ID
+        .scope(id.clone(), next.run(req).instrument(span))
+        .await;
+    if let Ok(value) = HeaderValue::from_str(id.as_str()) {
+        res.headers_mut().insert(X_REQUEST_ID.clone(), value);
+    }
+    res
+}
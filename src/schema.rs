@@ -0,0 +1,425 @@
+//! [JSON Schema](https://json-schema.org) of the payloads of the JSON API, generated from the
+//! TypeScript definitions of [`TS`].
+//!
+//! ```rust,ignore
+//! let schema = derived_cms::schema::json_schema::<<Post as EntityBase<Ctx>>::Create>();
+//! ```
+//!
+//! Use [`App::with_json_schema`](crate::App::with_json_schema) to serve the schemas of an entity
+//! at `/api/v1/:name/schema`, `/api/v1/:name/schema/create` and `/api/v1/:name/schema/update`.
+//!
+//! The schemas are as precise as the TypeScript definitions, e.g. all numbers are `number` and
+//! fields with `#[serde(default)]` are only optional if they are marked with `#[ts(optional)]`.
+
+use std::{
+    any::TypeId,
+    collections::{BTreeMap, HashSet},
+};
+
+use serde_json::{json, Map, Value};
+use tracing::error;
+use ts_rs::{TypeVisitor, TS};
+
+/// JSON Schema of `T`, with all types it depends on in `$defs`.
+pub fn json_schema<T: TS + 'static + ?Sized>() -> Value {
+    let mut defs = Defs {
+        root: T::name(),
+        ..Default::default()
+    };
+    defs.visited.insert(TypeId::of::<T>());
+    T::visit_dependencies(&mut defs);
+    T::visit_generics(&mut defs);
+    let root = match T::output_path() {
+        Some(_) => T::inline(),
+        None => T::name(),
+    };
+    let mut schema = match defs.translate(&T::name(), &root) {
+        Value::Object(schema) => schema,
+        other => Map::from_iter([("allOf".to_string(), json!([other]))]),
+    };
+    schema.insert(
+        "$schema".to_string(),
+        json!("https://json-schema.org/draft/2020-12/schema"),
+    );
+    schema.insert("title".to_string(), json!(T::name()));
+    let types = std::mem::take(&mut defs.types);
+    if !types.is_empty() {
+        let defs = types
+            .into_iter()
+            .map(|(name, ts)| (def_key(&name), defs.translate(&name, &ts)))
+            .collect::<Map<_, _>>();
+        schema.insert("$defs".to_string(), Value::Object(defs));
+    }
+    Value::Object(schema)
+}
+
+/// Add the JSON Schema of `T` and all types it depends on to `schemas`, the `components/schemas`
+/// of an [OpenAPI](crate::openapi) document, and return a reference to it.
+pub(crate) fn component<T: TS + 'static + ?Sized>(schemas: &mut Map<String, Value>) -> Value {
+    let key = def_key(&T::name());
+    let prefix = "#/components/schemas/";
+    let mut schema = json_schema::<T>();
+    if let Value::Object(map) = &mut schema {
+        map.remove("$schema");
+        if let Some(Value::Object(defs)) = map.remove("$defs") {
+            for (name, mut def) in defs {
+                rewrite_refs(&mut def, &key, prefix);
+                schemas.insert(name, def);
+            }
+        }
+    }
+    rewrite_refs(&mut schema, &key, prefix);
+    schemas.insert(key.clone(), schema);
+    json!({ "$ref": format!("{prefix}{key}") })
+}
+
+/// replace the references to `$defs` and the root `#` of a schema of `root` by references to `prefix`
+fn rewrite_refs(schema: &mut Value, root: &str, prefix: &str) {
+    match schema {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(r)) => {
+                        *r = match r.strip_prefix("#/$defs/") {
+                            Some(name) => format!("{prefix}{name}"),
+                            None => format!("{prefix}{root}"),
+                        }
+                    }
+                    (_, value) => rewrite_refs(value, root, prefix),
+                }
+            }
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|v| rewrite_refs(v, root, prefix)),
+        _ => {}
+    }
+}
+
+/// the TypeScript definitions of all declared types, by name
+#[derive(Default)]
+struct Defs {
+    types: BTreeMap<String, String>,
+    /// all visited types, including undeclared ones like `Vec<T>`, to stop at recursive types
+    visited: HashSet<TypeId>,
+    /// name of the type of the schema, referenced as `#` by recursive types
+    root: String,
+}
+
+impl TypeVisitor for Defs {
+    fn visit<T: TS + 'static + ?Sized>(&mut self) {
+        if !self.visited.insert(TypeId::of::<T>()) {
+            return;
+        }
+        if T::output_path().is_some() {
+            self.types.insert(T::name(), T::inline());
+        }
+        T::visit_dependencies(self);
+        T::visit_generics(self);
+    }
+}
+
+impl Defs {
+    fn translate(&self, name: &str, ts: &str) -> Value {
+        let tokens = tokenize(ts);
+        let mut parser = Parser {
+            src: ts,
+            tokens: &tokens,
+            pos: 0,
+            defs: self,
+        };
+        match parser.ty() {
+            Ok(schema) if parser.pos == tokens.len() => schema,
+            Ok(_) | Err(_) => {
+                error!(
+                    "failed to translate the TypeScript definition of {name} to JSON Schema: {ts}"
+                );
+                json!({})
+            }
+        }
+    }
+}
+
+/// name of `$defs` entry of the type `name`, e.g. `Json_Post_` for `Json<Post>`
+fn def_key(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '.' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Punct(char),
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Doc(String),
+}
+
+/// tokens of `src` with their byte range
+fn tokenize(src: &str) -> Vec<(Token, usize, usize)> {
+    let mut tokens = Vec::new();
+    let mut chars = src.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '/' if src[start..].starts_with("/*") => {
+                let end = src[start + 2..]
+                    .find("*/")
+                    .map_or(src.len(), |i| start + 2 + i + 2);
+                while chars.next_if(|(i, _)| *i < end).is_some() {}
+                let doc = src[start..end]
+                    .trim_start_matches("/**")
+                    .trim_end_matches("*/")
+                    .lines()
+                    .map(|l| l.trim().trim_start_matches('*').trim())
+                    .filter(|l| !l.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                tokens.push((Token::Doc(doc), start, end));
+                continue;
+            }
+            '"' => {
+                let mut s = String::new();
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => s.extend(chars.next().map(|(_, c)| c)),
+                        c => s.push(c),
+                    }
+                }
+                Token::Str(s)
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) =
+                    chars.next_if(|(_, c)| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E'))
+                {
+                    end = i + c.len_utf8();
+                }
+                Token::Num(src[start..end].parse().unwrap_or_default())
+            }
+            c if c.is_alphabetic() || c == '_' || c == '$' => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) =
+                    chars.next_if(|(_, c)| c.is_alphanumeric() || matches!(c, '_' | '$' | '.'))
+                {
+                    end = i + c.len_utf8();
+                }
+                Token::Ident(src[start..end].to_string())
+            }
+            c => Token::Punct(c),
+        };
+        let end = chars.peek().map_or(src.len(), |(i, _)| *i);
+        tokens.push((token, start, end));
+    }
+    tokens
+}
+
+/// recursive descent parser of the TypeScript types generated by [`TS`]
+struct Parser<'a> {
+    src: &'a str,
+    tokens: &'a [(Token, usize, usize)],
+    pos: usize,
+    defs: &'a Defs,
+}
+
+type ParseResult = Result<Value, ()>;
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _, _)| t)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let t = self.tokens.get(self.pos).map(|(t, _, _)| t);
+        self.pos += 1;
+        t
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(&Token::Punct(c)) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ()> {
+        self.eat(c).then_some(()).ok_or(())
+    }
+
+    fn doc(&mut self) -> Option<String> {
+        let mut doc = None;
+        while let Some(Token::Doc(d)) = self.peek() {
+            doc = Some(d.clone());
+            self.pos += 1;
+        }
+        doc
+    }
+
+    /// `A | B`
+    fn ty(&mut self) -> ParseResult {
+        self.doc();
+        self.eat('|');
+        let mut variants = vec![self.intersection()?];
+        while self.eat('|') {
+            variants.push(self.intersection()?);
+        }
+        if variants.len() == 1 {
+            return Ok(variants.remove(0));
+        }
+        let consts = variants
+            .iter()
+            .map(|v| v.as_object().filter(|v| v.len() == 1)?.get("const"))
+            .collect::<Option<Vec<_>>>();
+        Ok(match consts {
+            Some(consts) => json!({ "enum": consts }),
+            None => json!({ "anyOf": variants }),
+        })
+    }
+
+    /// `A & B`
+    fn intersection(&mut self) -> ParseResult {
+        let mut parts = vec![self.postfix()?];
+        while self.eat('&') {
+            parts.push(self.postfix()?);
+        }
+        Ok(match parts.len() {
+            1 => parts.remove(0),
+            _ => json!({ "allOf": parts }),
+        })
+    }
+
+    /// `A[]`
+    fn postfix(&mut self) -> ParseResult {
+        let mut ty = self.primary()?;
+        while self.peek() == Some(&Token::Punct('['))
+            && self.tokens.get(self.pos + 1).map(|(t, _, _)| t) == Some(&Token::Punct(']'))
+        {
+            self.pos += 2;
+            ty = json!({ "type": "array", "items": ty });
+        }
+        Ok(ty)
+    }
+
+    fn primary(&mut self) -> ParseResult {
+        let start = self.pos;
+        match self.next().cloned().ok_or(())? {
+            Token::Punct('(') => {
+                let ty = self.ty()?;
+                self.expect(')')?;
+                Ok(ty)
+            }
+            Token::Punct('{') => self.object(),
+            Token::Punct('[') => {
+                let mut items = Vec::new();
+                while !self.eat(']') {
+                    items.push(self.ty()?);
+                    self.eat(',');
+                }
+                let len = items.len();
+                Ok(json!({
+                    "type": "array",
+                    "prefixItems": items,
+                    "minItems": len,
+                    "maxItems": len,
+                }))
+            }
+            Token::Str(s) => Ok(json!({ "const": s })),
+            Token::Num(n) => Ok(json!({ "const": n })),
+            Token::Ident(ident) => self.named(start, &ident),
+            _ => Err(()),
+        }
+    }
+
+    /// a primitive or declared type, e.g. `string`, `Array<T>` or `Post`
+    fn named(&mut self, start: usize, ident: &str) -> ParseResult {
+        let mut args = Vec::new();
+        if self.eat('<') {
+            while !self.eat('>') {
+                args.push(self.ty()?);
+                self.eat(',');
+            }
+        }
+        let name = &self.src[self.tokens[start].1..self.tokens[self.pos - 1].2];
+        Ok(match (ident, args.as_slice()) {
+            ("string", []) => json!({ "type": "string" }),
+            ("number", []) => json!({ "type": "number" }),
+            ("bigint", []) => json!({ "type": "integer" }),
+            ("boolean", []) => json!({ "type": "boolean" }),
+            ("null", []) => json!({ "type": "null" }),
+            ("true", []) => json!({ "const": true }),
+            ("false", []) => json!({ "const": false }),
+            ("never", []) => json!({ "not": {} }),
+            ("Array", [items]) => json!({ "type": "array", "items": items }),
+            ("Record", [_, values]) => json!({ "type": "object", "additionalProperties": values }),
+            _ if name == self.defs.root => json!({ "$ref": "#" }),
+            _ if self.defs.types.contains_key(name) => {
+                json!({ "$ref": format!("#/$defs/{}", def_key(name)) })
+            }
+            // `unknown`, `any` and generic parameters
+            _ => json!({}),
+        })
+    }
+
+    /// `{ a: A, "b"?: B, [key in K]?: V }`
+    fn object(&mut self) -> ParseResult {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        let mut additional = None;
+        loop {
+            let doc = self.doc();
+            if self.eat('}') {
+                break;
+            }
+            if self.eat('[') {
+                // index signature of a map
+                let Some(Token::Ident(_)) = self.next() else {
+                    return Err(());
+                };
+                if self.next() != Some(&Token::Ident("in".to_string())) {
+                    return Err(());
+                }
+                self.ty()?;
+                self.expect(']')?;
+                self.eat('?');
+                self.expect(':')?;
+                additional = Some(self.ty()?);
+            } else {
+                let key = match self.next().cloned() {
+                    Some(Token::Ident(key) | Token::Str(key)) => key,
+                    Some(Token::Num(n)) => n.to_string(),
+                    _ => return Err(()),
+                };
+                let optional = self.eat('?');
+                self.expect(':')?;
+                let mut ty = self.ty()?;
+                if let (Some(doc), Value::Object(ty)) = (doc, &mut ty) {
+                    ty.insert("description".to_string(), json!(doc));
+                }
+                if !optional {
+                    required.push(json!(key));
+                }
+                properties.insert(key, ty);
+            }
+            if !self.eat(',') {
+                self.eat(';');
+            }
+        }
+        let mut schema = Map::from_iter([("type".to_string(), json!("object"))]);
+        if !properties.is_empty() {
+            schema.insert("properties".to_string(), Value::Object(properties));
+        }
+        if !required.is_empty() {
+            schema.insert("required".to_string(), Value::Array(required));
+        }
+        if let Some(additional) = additional {
+            schema.insert("additionalProperties".to_string(), additional);
+        }
+        Ok(Value::Object(schema))
+    }
+}
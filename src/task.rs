@@ -0,0 +1,61 @@
+//! Background tasks tied to the lifecycle of the [`App`](crate::App), e.g. periodic cleanup or cache warming.
+//!
+//! Tasks are registered using [`App::spawn_task`](crate::App::spawn_task) and started by
+//! [`App::build`](crate::App::build). Each task receives a clone of the [`Context`](crate::context::Context)
+//! and a [`CancellationToken`] that is cancelled when [`BackgroundTasks::shutdown`] is called.
+
+use std::{future::Future, pin::Pin};
+
+pub use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::error;
+
+use crate::{app::AppError, encryption::EncryptionKey};
+
+pub(crate) type Task<S> =
+    Box<dyn FnOnce(S, CancellationToken) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+pub(crate) type Seed<S> =
+    Box<dyn FnOnce(S) -> Pin<Box<dyn Future<Output = Result<(), AppError>> + Send>> + Send>;
+
+/// Run the seeds registered using [`App::seed`](crate::App::seed) one after another.
+/// Errors are logged and do not prevent the following seeds from running.
+pub(crate) async fn run_seeds<S: Clone>(ctx: S, seeds: Vec<Seed<S>>) {
+    for (i, seed) in seeds.into_iter().enumerate() {
+        if let Err(e) = seed(ctx.clone()).await {
+            error!("Seed {i} failed: {}: {}", e.title, e.description);
+        }
+    }
+}
+
+/// Handle to the background tasks of an [`App`](crate::App), obtained using
+/// [`App::background_tasks`](crate::App::background_tasks).
+#[derive(Clone, Debug, Default)]
+pub struct BackgroundTasks {
+    token: CancellationToken,
+    tracker: TaskTracker,
+    /// key of [`App::with_encryption_key`](crate::App::with_encryption_key), set as current key of all tasks
+    pub(crate) encryption_key: Option<EncryptionKey>,
+}
+
+impl BackgroundTasks {
+    pub(crate) fn spawn<S>(&self, ctx: S, task: Task<S>) {
+        let task = task(ctx, self.token.child_token());
+        match self.encryption_key.clone() {
+            Some(key) => self.tracker.spawn(key.scope(task)),
+            None => self.tracker.spawn(task),
+        };
+    }
+
+    /// token that is cancelled when [`BackgroundTasks::shutdown`] is called
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    /// Signal all tasks to stop and wait until they have finished.
+    pub async fn shutdown(&self) {
+        self.token.cancel();
+        self.tracker.close();
+        self.tracker.wait().await;
+    }
+}
@@ -0,0 +1,78 @@
+//! Light and dark theme of the admin interface, see [`App::with_theme`](crate::App::with_theme)
+//! and [`App::with_theme_switcher`](crate::App::with_theme_switcher).
+
+use std::str::FromStr;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::app::cookie;
+
+/// name of the cookie storing the [`Theme`] selected by the editor
+pub const THEME_COOKIE: &str = "theme";
+
+tokio::task_local! {
+    /// theme of the current request and whether the editor can select it
+    static THEME: (Theme, bool);
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Theme {
+    /// follow `prefers-color-scheme` of the browser
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Self; 3] = [Self::System, Self::Light, Self::Dark];
+
+    /// The theme of the current request, if a theme is set or the theme switcher is enabled.
+    pub fn current() -> Option<Self> {
+        THEME.try_with(|(t, _)| *t).ok()
+    }
+
+    /// Whether the editor can select the theme in the sidebar,
+    /// see [`App::with_theme_switcher`](crate::App::with_theme_switcher).
+    pub fn selectable() -> bool {
+        THEME.try_with(|(_, s)| *s).unwrap_or(false)
+    }
+
+    /// value of the [`THEME_COOKIE`] and of the `data-theme` attribute
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::Light => "light",
+            Self::Dark => "dark",
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|t| t.as_str() == s).ok_or(())
+    }
+}
+
+/// the theme of [`App::with_theme`](crate::App::with_theme) and whether the theme switcher is enabled
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ThemeConfig {
+    pub(crate) default: Theme,
+    pub(crate) switcher: bool,
+}
+
+/// Handle the request with the [`Theme`] of the [`THEME_COOKIE`] if the theme switcher is enabled,
+/// otherwise with the default theme.
+pub(crate) async fn theme(State(config): State<ThemeConfig>, req: Request, next: Next) -> Response {
+    let theme = cookie(req.headers(), THEME_COOKIE)
+        .filter(|_| config.switcher)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(config.default);
+    THEME.scope((theme, config.switcher), next.run(req)).await
+}
@@ -0,0 +1,227 @@
+//! Removal of uploads that are no longer referenced by any entity, see [`gc_uploads`].
+
+use std::{
+    collections::HashSet,
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    time::{Duration, SystemTime},
+};
+
+use ormlite::{Database, Executor, FromRow, Model};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    context::{Context, ContextExt, ContextTrait},
+    EntityBase, DB,
+};
+
+/// Uploads created more recently are never removed, as the entity referencing them may not
+/// have been saved yet.
+const MIN_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// loads the ids of the uploads referenced by all entities of one type,
+/// see [`App::track_uploads`](crate::App::track_uploads)
+pub(crate) type LoadReferencedFiles<S> =
+    for<'a> fn(
+        &'a S,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Uuid>, ormlite::Error>> + Send + 'a>>;
+
+/// [`LoadReferencedFiles`] of the entity `E`
+pub(crate) fn load_referenced_files<E, T>(
+    ctx: &Context<T>,
+) -> Pin<Box<dyn Future<Output = Result<Vec<Uuid>, ormlite::Error>> + Send + '_>>
+where
+    E: EntityBase<Context<T>> + Model<DB> + for<'r> FromRow<'r, <DB as Database>::Row>,
+    T: ContextExt<Context<T>> + 'static,
+    for<'c> &'c T: Executor<'c, Database = DB>,
+{
+    Box::pin(async move {
+        let entities = E::select().fetch_all(ctx.ext()).await?;
+        Ok(entities.iter().flat_map(E::referenced_files).collect())
+    })
+}
+
+/// Whether [`gc_uploads`] removes the uploads it found.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GcMode {
+    /// only list the uploads that would be removed
+    #[default]
+    DryRun,
+    Delete,
+}
+
+#[derive(Debug, Error)]
+pub enum GcError {
+    #[error("failed to load the referenced uploads: {0}")]
+    Database(#[from] ormlite::Error),
+    #[error("failed to read the uploads directory: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Find the uploads in [`uploads_dir`](ContextTrait::uploads_dir) that are not referenced by any
+/// entity registered using [`App::track_uploads`](crate::App::track_uploads), see
+/// [`EntityBase::referenced_files`]. Returns the directories `[dir/]<id>` of these uploads and
+/// removes them if `mode` is [`GcMode::Delete`].
+///
+/// Uploads referenced by entities which are not tracked are removed as well, so all entities
+/// with files have to be tracked. If no entity is tracked, no uploads are returned.
+/// Uploads created within the last hour are kept, as they may belong to a form that is just
+/// being submitted.
+///
+/// ```rust,ignore
+/// for dir in gc_uploads(&ctx, GcMode::DryRun).await? {
+///     println!("unreferenced: {}", dir.display());
+/// }
+/// ```
+pub async fn gc_uploads<T>(ctx: &Context<T>, mode: GcMode) -> Result<Vec<PathBuf>, GcError>
+where
+    T: ContextExt<Context<T>> + 'static,
+{
+    if ctx.referenced_files.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut referenced = HashSet::new();
+    for load in ctx.referenced_files.iter() {
+        referenced.extend(load(ctx).await?);
+    }
+    let unreferenced = unreferenced_dirs(ctx.uploads_dir(), &referenced, SystemTime::now()).await?;
+    if mode == GcMode::Delete {
+        for dir in &unreferenced {
+            tokio::fs::remove_dir_all(dir).await?;
+        }
+    }
+    Ok(unreferenced)
+}
+
+/// directories named by the id of an upload in `dir`, recursively, which are not `referenced`
+/// and were modified at least [`MIN_AGE`] before `now`, sorted by path
+async fn unreferenced_dirs(
+    dir: &Path,
+    referenced: &HashSet<Uuid>,
+    now: SystemTime,
+) -> io::Result<Vec<PathBuf>> {
+    let mut unreferenced = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let mut read_dir = match tokio::fs::read_dir(&dir).await {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        while let Some(entry) = read_dir.next_entry().await? {
+            if !entry.file_type().await?.is_dir() {
+                continue;
+            }
+            // other directories are namespaces, see `App::namespace_uploads`
+            let Some(id) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse().ok())
+            else {
+                dirs.push(entry.path());
+                continue;
+            };
+            let age = entry
+                .metadata()
+                .await?
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok());
+            if !referenced.contains(&id) && age.is_some_and(|age| age >= MIN_AGE) {
+                unreferenced.push(entry.path());
+            }
+        }
+    }
+    unreferenced.sort();
+    Ok(unreferenced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("derived-cms-gc-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// create the directory of the upload `id` with a file in `dir`
+    fn upload(dir: &Path, id: Uuid) -> PathBuf {
+        let upload = dir.join(id.to_string());
+        std::fs::create_dir_all(&upload).unwrap();
+        std::fs::write(upload.join("file.txt"), "content").unwrap();
+        upload
+    }
+
+    #[tokio::test]
+    async fn recent_uploads_are_kept() {
+        let dir = temp_dir();
+        let upload = upload(&dir, Uuid::new_v4());
+        let now = SystemTime::now();
+        let referenced = HashSet::new();
+        assert!(unreferenced_dirs(&dir, &referenced, now)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(unreferenced_dirs(&dir, &referenced, now + MIN_AGE / 2)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            unreferenced_dirs(&dir, &referenced, now + MIN_AGE * 2)
+                .await
+                .unwrap(),
+            [upload]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn referenced_uploads_are_kept() {
+        let dir = temp_dir();
+        let (kept, removed) = (Uuid::new_v4(), Uuid::new_v4());
+        upload(&dir, kept);
+        let removed = upload(&dir, removed);
+        let referenced = HashSet::from([kept]);
+        let later = SystemTime::now() + MIN_AGE * 2;
+        assert_eq!(
+            unreferenced_dirs(&dir, &referenced, later).await.unwrap(),
+            [removed]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn namespaces_are_searched_recursively() {
+        let dir = temp_dir();
+        let (kept, removed) = (Uuid::new_v4(), Uuid::new_v4());
+        let root = upload(&dir, Uuid::new_v4());
+        upload(&dir.join("articles"), kept);
+        let nested = upload(&dir.join("articles/images"), removed);
+        // files named like an upload are not uploads
+        std::fs::write(dir.join("articles").join(Uuid::new_v4().to_string()), "").unwrap();
+        let referenced = HashSet::from([kept]);
+        let later = SystemTime::now() + MIN_AGE * 2;
+        let mut expected = vec![root, nested];
+        expected.sort();
+        assert_eq!(
+            unreferenced_dirs(&dir, &referenced, later).await.unwrap(),
+            expected
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn missing_directory_is_empty() {
+        let dir = std::env::temp_dir().join(format!("derived-cms-gc-{}", Uuid::new_v4()));
+        let later = SystemTime::now() + MIN_AGE * 2;
+        assert!(unreferenced_dirs(&dir, &HashSet::new(), later)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,317 @@
+//! Access control for uploaded files, see [`App::with_private_uploads`](crate::App::with_private_uploads),
+//! export of all uploads, see [`App::with_uploads_export`](crate::App::with_uploads_export),
+//! thumbnails of images, see [`App::with_thumbnails`](crate::App::with_thumbnails),
+//! and removal of unreferenced uploads, see [`gc_uploads`].
+
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+        StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::error;
+
+use crate::{auth::User, context::ContextTrait, property::File};
+
+mod gc;
+mod thumbnail;
+mod zip;
+
+pub use gc::{gc_uploads, GcError, GcMode};
+pub(crate) use gc::{load_referenced_files, LoadReferencedFiles};
+pub(crate) use thumbnail::create_thumbnail;
+
+tokio::task_local! {
+    /// signer of the [`App`](crate::App) handling the current request
+    static SIGNER: UploadSigner;
+    /// uploads directory while a submitted form is deserialized, see [`verify_files_in`]
+    static VERIFY_DIR: PathBuf;
+}
+
+/// Deserialize a submitted form using `f`, rejecting [`File`]s that do not exist in `dir`, so a
+/// form can't reference uploads it doesn't know the name of.
+pub(crate) fn verify_files_in<R>(dir: &Path, f: impl FnOnce() -> R) -> R {
+    VERIFY_DIR.sync_scope(dir.to_path_buf(), f)
+}
+
+/// Like [`verify_files_in`], but for all deserialization while `f` runs, e.g. of the merged data
+/// of [`Patch::patch`](crate::entity::Patch::patch).
+pub(crate) async fn verify_files_during<F: Future>(dir: &Path, f: F) -> F::Output {
+    VERIFY_DIR.scope(dir.to_path_buf(), f).await
+}
+
+/// whether `file` is missing from the directory of [`verify_files_in`], if called within it
+pub(crate) fn is_missing(file: &File) -> bool {
+    VERIFY_DIR
+        .try_with(|dir| !file.path(dir).is_file())
+        .unwrap_or(false)
+}
+
+/// Creates and verifies signed, expiring URLs of uploaded [`File`]s.
+///
+/// The signature is a HMAC-SHA256 of the path of the file and the expiry timestamp.
+#[derive(Clone)]
+pub struct UploadSigner {
+    secret: Arc<[u8]>,
+    valid_for: Duration,
+}
+
+impl std::fmt::Debug for UploadSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UploadSigner")
+            .field("valid_for", &self.valid_for)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Signature {
+    expires: u64,
+    signature: String,
+}
+
+impl UploadSigner {
+    /// Signed URLs are valid for `valid_for` after they have been created.
+    pub fn new(secret: impl AsRef<[u8]>, valid_for: Duration) -> Self {
+        Self {
+            secret: secret.as_ref().into(),
+            valid_for,
+        }
+    }
+
+    /// The signer of the current request, if private uploads are enabled.
+    pub fn current() -> Option<Self> {
+        SIGNER.try_with(Clone::clone).ok()
+    }
+
+    /// URL of `file` that is valid until `expires` (seconds since the unix epoch),
+    /// relative to the [base path](crate::context::ContextTrait::base_path) of the admin interface.
+    pub fn sign_until(&self, file: &File, expires: u64) -> String {
+        let signature = self.signature(&file.relative_path(), expires);
+        let dir = file.dir().map(|dir| format!("{dir}/")).unwrap_or_default();
+        format!(
+            "uploads/{dir}{}/{}?expires={expires}&signature={}",
+            file.id,
+            urlencoding::encode(&file.name),
+            hex::encode(signature)
+        )
+    }
+
+    /// URL of `file` that is valid for the configured duration.
+    pub fn sign(&self, file: &File) -> String {
+        self.sign_until(file, now() + self.valid_for.as_secs())
+    }
+
+    fn mac(&self, path: &str, expires: u64) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts keys of any size");
+        mac.update(path.as_bytes());
+        mac.update(b"\n");
+        mac.update(expires.to_string().as_bytes());
+        mac
+    }
+
+    fn signature(&self, path: &str, expires: u64) -> Vec<u8> {
+        self.mac(path, expires).finalize().into_bytes().to_vec()
+    }
+
+    /// `path` is the path of the file relative to the uploads directory
+    fn verify(&self, path: &str, expires: u64, signature: &str) -> bool {
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        expires >= now() && self.mac(path, expires).verify_slice(&signature).is_ok()
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// make the signer available to [`File::url`] while handling the request
+pub(crate) async fn provide_signer(
+    State(signer): State<UploadSigner>,
+    req: Request,
+    next: Next,
+) -> Response {
+    SIGNER.scope(signer, next.run(req)).await
+}
+
+/// reject requests to `/uploads` without a valid signature with HTTP 403
+pub(crate) async fn verify_signature(
+    State(signer): State<UploadSigner>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let path = req.uri().path().trim_start_matches('/');
+    let path = path.strip_prefix("uploads/").unwrap_or(path);
+    let valid = match (
+        urlencoding::decode(path),
+        serde_qs::from_str::<Signature>(req.uri().query().unwrap_or_default()),
+    ) {
+        (Ok(path), Ok(Signature { expires, signature })) => {
+            signer.verify(&path, expires, &signature)
+        }
+        _ => false,
+    };
+    match valid {
+        true => next.run(req).await,
+        false => StatusCode::FORBIDDEN.into_response(),
+    }
+}
+
+/// whether a [`User`] may download the uploads export, see
+/// [`App::with_uploads_export`](crate::App::with_uploads_export)
+#[derive(Clone, Copy)]
+pub(crate) struct ExportPermission(pub fn(&User) -> bool);
+
+/// Stream a zip archive of the uploads directory while it is written.
+/// If writing fails, the response is aborted, so a truncated archive is not mistaken for a complete one.
+pub(crate) async fn export<S: ContextTrait>(
+    State(ctx): State<S>,
+    Extension(ExportPermission(allow)): Extension<ExportPermission>,
+    user: Option<User>,
+) -> Response {
+    if !user.or_else(User::current).is_some_and(|user| allow(&user)) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let (tx, rx) = mpsc::channel(16);
+    let dir = ctx.uploads_dir().to_path_buf();
+    tokio::spawn(async move {
+        match zip::write_dir(&dir, &tx).await {
+            Ok(()) => {}
+            // the client has disconnected
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {}
+            Err(e) => {
+                error!("Failed to export uploads: {e:#}");
+                let _ = tx.send(Err(e)).await;
+            }
+        }
+    });
+    (
+        [
+            (CONTENT_TYPE, "application/zip"),
+            (CONTENT_DISPOSITION, "attachment; filename=\"uploads.zip\""),
+        ],
+        Body::from_stream(ReceiverStream::new(rx)),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn signer() -> UploadSigner {
+        UploadSigner::new("secret", Duration::from_secs(60))
+    }
+
+    /// status of requesting `url`, relative to the base path
+    async fn status(url: &str) -> StatusCode {
+        let app = Router::new()
+            .route("/uploads/*path", get(|| async { "file" }))
+            .layer(middleware::from_fn_with_state(signer(), verify_signature));
+        let req = Request::get(format!("/{url}")).body(Body::empty()).unwrap();
+        app.oneshot(req).await.unwrap().status()
+    }
+
+    fn file(name: &str, dir: Option<&str>) -> File {
+        File {
+            dir: dir.map(str::to_string),
+            ..File::new(name.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn valid_signature() {
+        assert_eq!(
+            status(&signer().sign(&file("a.pdf", None))).await,
+            StatusCode::OK
+        );
+        let file = file("Übersicht 2024.pdf", Some("post/image"));
+        assert_eq!(status(&signer().sign(&file)).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn expired_signature() {
+        let url = signer().sign_until(&file("a.pdf", None), now() - 1);
+        assert_eq!(status(&url).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn tampered_signature() {
+        let expires = now() + 60;
+        let url = signer().sign_until(&file("a.pdf", None), expires);
+        let (url, signature) = url.split_once("&signature=").unwrap();
+        let flipped = match &signature[..1] {
+            "0" => format!("1{}", &signature[1..]),
+            _ => format!("0{}", &signature[1..]),
+        };
+        assert_eq!(
+            status(&format!("{url}&signature={flipped}")).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(&format!("{url}&signature={}", &signature[2..])).await,
+            StatusCode::FORBIDDEN
+        );
+        // a later expiry with the signature of the original one
+        let later = url.replace(&expires.to_string(), &(expires + 3600).to_string());
+        assert_eq!(
+            status(&format!("{later}&signature={signature}")).await,
+            StatusCode::FORBIDDEN
+        );
+        let other = UploadSigner::new("other", Duration::from_secs(60));
+        assert_eq!(
+            status(&other.sign(&file("a.pdf", None))).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn signature_of_another_path() {
+        let public = file("public.pdf", None);
+        let secret = File {
+            id: public.id,
+            ..file("secret.pdf", None)
+        };
+        let url = signer().sign(&public);
+        let query = url.split_once('?').unwrap().1;
+        assert_eq!(
+            status(&format!("uploads/{}?{query}", secret.relative_path())).await,
+            StatusCode::FORBIDDEN
+        );
+        let namespaced = file("public.pdf", Some("post"));
+        let namespaced = File {
+            id: public.id,
+            ..namespaced
+        };
+        assert_eq!(
+            status(&format!("uploads/{}?{query}", namespaced.relative_path())).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+}
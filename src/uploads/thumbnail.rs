@@ -0,0 +1,48 @@
+//! Scaled down copies of uploaded images, see [`App::with_thumbnails`](crate::App::with_thumbnails).
+
+use std::path::{Path, PathBuf};
+
+use image::{ImageError, ImageReader};
+use tracing::debug;
+
+/// Store a copy of the image at `path` scaled down to fit into `max_size`×`max_size` pixels
+/// next to it. Returns the name of the thumbnail if it was created, which is not the case for
+/// images that are already small enough and for files that can't be decoded, e.g. because they
+/// are not images.
+pub(crate) async fn create_thumbnail(path: PathBuf, max_size: u32) -> Option<String> {
+    let result = tokio::task::spawn_blocking(move || write_thumbnail(&path, max_size)).await;
+    match result {
+        Ok(Ok(name)) => name,
+        Ok(Err(e)) => {
+            debug!("Skipping thumbnail: {e:#}");
+            None
+        }
+        Err(e) => {
+            debug!("Failed to create thumbnail: {e:#}");
+            None
+        }
+    }
+}
+
+fn write_thumbnail(path: &Path, max_size: u32) -> Result<Option<String>, ImageError> {
+    let reader = ImageReader::open(path)?.with_guessed_format()?;
+    let (Some(format), Some(name)) = (reader.format(), path.file_name().and_then(|n| n.to_str()))
+    else {
+        return Ok(None);
+    };
+    // the directory of an upload only contains the original, so this doesn't collide
+    let name = format!("thumbnail-{name}");
+    let image = reader.decode()?;
+    if image.width() <= max_size && image.height() <= max_size {
+        return Ok(None);
+    }
+    let thumbnail_path = path.with_file_name(&name);
+    if let Err(e) = image
+        .thumbnail(max_size, max_size)
+        .save_with_format(&thumbnail_path, format)
+    {
+        let _ = std::fs::remove_file(&thumbnail_path);
+        return Err(e);
+    }
+    Ok(Some(name))
+}
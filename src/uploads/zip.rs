@@ -0,0 +1,476 @@
+//! Minimal streaming writer of zip archives with uncompressed entries, see
+//! [`App::with_uploads_export`](crate::App::with_uploads_export).
+//!
+//! Entries are written with a data descriptor after their content, so files are read only once
+//! and never buffered. Zip64 records are used for files and archives larger than 4 GiB.
+
+use std::path::{Path, PathBuf};
+
+use axum::body::Bytes;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use crc::{Crc, CRC_32_ISO_HDLC};
+use tokio::{
+    fs::File,
+    io::{self, AsyncReadExt},
+    sync::mpsc::Sender,
+};
+
+const CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+/// general purpose flags: sizes and crc follow in a data descriptor, file names are UTF-8
+const FLAGS: u16 = 1 << 3 | 1 << 11;
+/// 4.5, required for zip64
+const VERSION: u16 = 45;
+const ZIP64_EXTRA: u16 = 0x0001;
+
+/// an entry written to the archive, for the central directory
+struct Entry {
+    name: String,
+    crc: u32,
+    size: u64,
+    offset: u64,
+    time: u16,
+    date: u16,
+    zip64: bool,
+}
+
+/// Sends the archive in chunks and counts the bytes written, which are the offsets within the archive.
+struct Writer<'a> {
+    chunks: &'a Sender<io::Result<Bytes>>,
+    written: u64,
+}
+
+impl Writer<'_> {
+    async fn write(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.chunks
+            .send(Ok(Bytes::copy_from_slice(buf)))
+            .await
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        self.written += buf.len() as u64;
+        Ok(())
+    }
+}
+
+/// Send a zip archive of all files in `dir` and its subdirectories to `chunks`,
+/// with their paths relative to `dir`.
+/// Fails with [`io::ErrorKind::BrokenPipe`] if the receiver has been dropped.
+pub(crate) async fn write_dir(dir: &Path, chunks: &Sender<io::Result<Bytes>>) -> io::Result<()> {
+    let mut out = Writer { chunks, written: 0 };
+    let mut entries = Vec::new();
+    for path in files(dir).await? {
+        let name = path
+            .strip_prefix(dir)
+            .unwrap_or(&path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let mut file = match File::open(&path).await {
+            Ok(file) => file,
+            // deleted since the directory has been listed
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        let metadata = file.metadata().await?;
+        let (time, date) = dos_date_time(metadata.modified().map(DateTime::<Utc>::from).ok());
+        let mut entry = Entry {
+            name,
+            crc: 0,
+            size: 0,
+            offset: out.written,
+            time,
+            date,
+            zip64: metadata.len() >= u32::MAX as u64,
+        };
+        write_local_header(&mut out, &entry).await?;
+        let mut digest = CRC32.digest();
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let n = file.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            digest.update(&buf[..n]);
+            out.write(&buf[..n]).await?;
+            entry.size += n as u64;
+        }
+        entry.crc = digest.finalize();
+        if !entry.zip64 && entry.size >= u32::MAX as u64 {
+            return Err(io::Error::other(format!(
+                "{} has grown beyond 4 GiB while it was written",
+                entry.name
+            )));
+        }
+        write_data_descriptor(&mut out, &entry).await?;
+        entries.push(entry);
+    }
+    write_central_directory(&mut out, &entries).await
+}
+
+/// all files in `dir`, recursively, sorted by path
+async fn files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let mut read_dir = match tokio::fs::read_dir(&dir).await {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        while let Some(entry) = read_dir.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            if file_type.is_dir() {
+                dirs.push(entry.path());
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// MS-DOS time and date, which can represent 1980 to 2107 with a precision of 2 seconds
+fn dos_date_time(time: Option<DateTime<Utc>>) -> (u16, u16) {
+    match time.filter(|t| (1980..2108).contains(&t.year())) {
+        Some(t) => (
+            ((t.hour() << 11) | (t.minute() << 5) | (t.second() / 2)) as u16,
+            (((t.year() as u32 - 1980) << 9) | (t.month() << 5) | t.day()) as u16,
+        ),
+        // 1980-01-01 00:00
+        None => (0, (1 << 5) | 1),
+    }
+}
+
+async fn write_local_header(out: &mut Writer<'_>, entry: &Entry) -> io::Result<()> {
+    let mut header = Vec::with_capacity(30 + entry.name.len() + 20);
+    header.extend(0x04034b50u32.to_le_bytes());
+    header.extend(VERSION.to_le_bytes());
+    header.extend(FLAGS.to_le_bytes());
+    // stored
+    header.extend(0u16.to_le_bytes());
+    header.extend(entry.time.to_le_bytes());
+    header.extend(entry.date.to_le_bytes());
+    // crc and sizes follow in the data descriptor
+    header.extend(0u32.to_le_bytes());
+    let size = if entry.zip64 { u32::MAX } else { 0 };
+    header.extend(size.to_le_bytes());
+    header.extend(size.to_le_bytes());
+    header.extend((entry.name.len() as u16).to_le_bytes());
+    header.extend((if entry.zip64 { 20u16 } else { 0 }).to_le_bytes());
+    header.extend(entry.name.as_bytes());
+    if entry.zip64 {
+        header.extend(ZIP64_EXTRA.to_le_bytes());
+        header.extend(16u16.to_le_bytes());
+        header.extend(0u64.to_le_bytes());
+        header.extend(0u64.to_le_bytes());
+    }
+    out.write(&header).await
+}
+
+async fn write_data_descriptor(out: &mut Writer<'_>, entry: &Entry) -> io::Result<()> {
+    let mut descriptor = Vec::with_capacity(24);
+    descriptor.extend(0x08074b50u32.to_le_bytes());
+    descriptor.extend(entry.crc.to_le_bytes());
+    if entry.zip64 {
+        descriptor.extend(entry.size.to_le_bytes());
+        descriptor.extend(entry.size.to_le_bytes());
+    } else {
+        descriptor.extend((entry.size as u32).to_le_bytes());
+        descriptor.extend((entry.size as u32).to_le_bytes());
+    }
+    out.write(&descriptor).await
+}
+
+async fn write_central_directory(out: &mut Writer<'_>, entries: &[Entry]) -> io::Result<()> {
+    let start = out.written;
+    for entry in entries {
+        let large_size = entry.zip64 || entry.size >= u32::MAX as u64;
+        let large_offset = entry.offset >= u32::MAX as u64;
+        let mut extra = Vec::new();
+        if large_size {
+            extra.extend(entry.size.to_le_bytes());
+            extra.extend(entry.size.to_le_bytes());
+        }
+        if large_offset {
+            extra.extend(entry.offset.to_le_bytes());
+        }
+        let mut header = Vec::with_capacity(46 + entry.name.len() + 4 + extra.len());
+        header.extend(0x02014b50u32.to_le_bytes());
+        // made by: unix
+        header.extend((3u16 << 8 | VERSION).to_le_bytes());
+        header.extend(VERSION.to_le_bytes());
+        header.extend(FLAGS.to_le_bytes());
+        header.extend(0u16.to_le_bytes());
+        header.extend(entry.time.to_le_bytes());
+        header.extend(entry.date.to_le_bytes());
+        header.extend(entry.crc.to_le_bytes());
+        let size = if large_size {
+            u32::MAX
+        } else {
+            entry.size as u32
+        };
+        header.extend(size.to_le_bytes());
+        header.extend(size.to_le_bytes());
+        header.extend((entry.name.len() as u16).to_le_bytes());
+        let extra_len = if extra.is_empty() { 0 } else { 4 + extra.len() };
+        header.extend((extra_len as u16).to_le_bytes());
+        // comment length, disk number, internal attributes
+        header.extend([0; 6]);
+        // external attributes: regular file, rw-r--r--
+        header.extend((0o100644u32 << 16).to_le_bytes());
+        let offset = if large_offset {
+            u32::MAX
+        } else {
+            entry.offset as u32
+        };
+        header.extend(offset.to_le_bytes());
+        header.extend(entry.name.as_bytes());
+        if !extra.is_empty() {
+            header.extend(ZIP64_EXTRA.to_le_bytes());
+            header.extend((extra.len() as u16).to_le_bytes());
+            header.extend(extra);
+        }
+        out.write(&header).await?;
+    }
+    let end = out.written;
+    let size = end - start;
+    let count = entries.len() as u64;
+    let zip64 = count >= u16::MAX as u64 || size >= u32::MAX as u64 || start >= u32::MAX as u64;
+    let mut record = Vec::with_capacity(56 + 20 + 22);
+    if zip64 {
+        record.extend(0x06064b50u32.to_le_bytes());
+        // size of the remaining record
+        record.extend(44u64.to_le_bytes());
+        record.extend((3u16 << 8 | VERSION).to_le_bytes());
+        record.extend(VERSION.to_le_bytes());
+        // number of this disk and of the disk with the central directory
+        record.extend([0; 8]);
+        record.extend(count.to_le_bytes());
+        record.extend(count.to_le_bytes());
+        record.extend(size.to_le_bytes());
+        record.extend(start.to_le_bytes());
+        // locator
+        record.extend(0x07064b50u32.to_le_bytes());
+        record.extend(0u32.to_le_bytes());
+        record.extend(end.to_le_bytes());
+        record.extend(1u32.to_le_bytes());
+    }
+    record.extend(0x06054b50u32.to_le_bytes());
+    record.extend([0; 4]);
+    let count = count.min(u16::MAX as u64) as u16;
+    record.extend(count.to_le_bytes());
+    record.extend(count.to_le_bytes());
+    record.extend((size.min(u32::MAX as u64) as u32).to_le_bytes());
+    record.extend((start.min(u32::MAX as u64) as u32).to_le_bytes());
+    // comment length
+    record.extend(0u16.to_le_bytes());
+    out.write(&record).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    fn u16_at(b: &[u8], i: usize) -> u16 {
+        u16::from_le_bytes(b[i..i + 2].try_into().unwrap())
+    }
+    fn u32_at(b: &[u8], i: usize) -> u32 {
+        u32::from_le_bytes(b[i..i + 4].try_into().unwrap())
+    }
+    fn u64_at(b: &[u8], i: usize) -> u64 {
+        u64::from_le_bytes(b[i..i + 8].try_into().unwrap())
+    }
+
+    /// an entry of the central directory
+    #[derive(Debug, PartialEq)]
+    struct Listed {
+        name: String,
+        crc: u32,
+        size: u64,
+        offset: u64,
+    }
+
+    /// Read the central directory of `zip`, the end of an archive starting at `base`.
+    fn central_directory(zip: &[u8], base: u64) -> Vec<Listed> {
+        let end = zip.len() - 22;
+        assert_eq!(u32_at(zip, end), 0x06054b50);
+        let (mut count, mut start) = (u16_at(zip, end + 10) as u64, u32_at(zip, end + 16) as u64);
+        if count == u16::MAX as u64 || start == u32::MAX as u64 {
+            let locator = end - 20;
+            assert_eq!(u32_at(zip, locator), 0x07064b50);
+            let record = (u64_at(zip, locator + 8) - base) as usize;
+            assert_eq!(u32_at(zip, record), 0x06064b50);
+            count = u64_at(zip, record + 32);
+            start = u64_at(zip, record + 48);
+        }
+        let mut i = (start - base) as usize;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            assert_eq!(u32_at(zip, i), 0x02014b50);
+            assert_eq!(u16_at(zip, i + 8) & FLAGS, FLAGS);
+            let name_len = u16_at(zip, i + 28) as usize;
+            let extra_len = u16_at(zip, i + 30) as usize;
+            let mut size = u32_at(zip, i + 24) as u64;
+            let mut offset = u32_at(zip, i + 42) as u64;
+            let name = String::from_utf8(zip[i + 46..i + 46 + name_len].to_vec()).unwrap();
+            let mut extra = i + 46 + name_len;
+            if extra_len > 0 {
+                assert_eq!(u16_at(zip, extra), ZIP64_EXTRA);
+                extra += 4;
+                if size == u32::MAX as u64 {
+                    size = u64_at(zip, extra);
+                    assert_eq!(u64_at(zip, extra + 8), size);
+                    extra += 16;
+                }
+                if offset == u32::MAX as u64 {
+                    offset = u64_at(zip, extra);
+                }
+            }
+            entries.push(Listed {
+                name,
+                crc: u32_at(zip, i + 16),
+                size,
+                offset,
+            });
+            i += 46 + name_len + extra_len;
+        }
+        entries
+    }
+
+    /// names and contents of the files in `zip`, checking their local headers and crc
+    fn read(zip: &[u8]) -> Vec<(String, Vec<u8>)> {
+        central_directory(zip, 0)
+            .into_iter()
+            .map(|entry| {
+                let i = entry.offset as usize;
+                assert_eq!(u32_at(zip, i), 0x04034b50);
+                let name_len = u16_at(zip, i + 26) as usize;
+                let extra_len = u16_at(zip, i + 28) as usize;
+                assert_eq!(&zip[i + 30..i + 30 + name_len], entry.name.as_bytes());
+                let data = i + 30 + name_len + extra_len;
+                let content = zip[data..data + entry.size as usize].to_vec();
+                assert_eq!(CRC32.checksum(&content), entry.crc);
+                let descriptor = data + entry.size as usize;
+                assert_eq!(u32_at(zip, descriptor), 0x08074b50);
+                assert_eq!(u32_at(zip, descriptor + 4), entry.crc);
+                (entry.name, content)
+            })
+            .collect()
+    }
+
+    /// the archive written by `f`
+    async fn archive<F, Fut>(f: F) -> Vec<u8>
+    where
+        F: FnOnce(Sender<io::Result<Bytes>>) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let (tx, mut rx) = mpsc::channel::<io::Result<Bytes>>(16);
+        let read = async {
+            let mut zip = Vec::new();
+            while let Some(chunk) = rx.recv().await {
+                zip.extend(chunk.unwrap());
+            }
+            zip
+        };
+        tokio::join!(f(tx), read).1
+    }
+
+    async fn zip_dir(dir: &Path) -> Vec<u8> {
+        archive(|tx| async move { write_dir(dir, &tx).await.unwrap() }).await
+    }
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("derived-cms-zip-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn empty_directory() {
+        let dir = temp_dir();
+        let zip = zip_dir(&dir).await;
+        assert_eq!(zip.len(), 22);
+        assert!(read(&zip).is_empty());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn round_trip_with_non_ascii_names() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(dir.join("Bilder/Übersicht")).unwrap();
+        std::fs::write(dir.join("a.txt"), "Hello").unwrap();
+        std::fs::write(dir.join("Bilder/Übersicht/日本語 ✓.txt"), "こんにちは").unwrap();
+        std::fs::write(dir.join("empty"), "").unwrap();
+        assert_eq!(
+            read(&zip_dir(&dir).await),
+            [
+                ("Bilder/Übersicht/日本語 ✓.txt", "こんにちは".as_bytes()),
+                ("a.txt", b"Hello"),
+                ("empty", b""),
+            ]
+            .map(|(name, content)| (name.to_string(), content.to_vec()))
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn zip64_sizes_and_offsets() {
+        const GIB: u64 = 1 << 30;
+        let entry = |name: &str, size, offset, zip64| Entry {
+            name: name.to_string(),
+            crc: 42,
+            size,
+            offset,
+            time: 0,
+            date: 0,
+            zip64,
+        };
+        let entries = [
+            entry("small", 5, 0, false),
+            entry("large", 5 * GIB, 100, true),
+            entry("after", 5, 5 * GIB + 200, false),
+        ];
+        // the central directory written after 6 GiB of entries
+        let zip = archive(|tx| async move {
+            let mut out = Writer {
+                chunks: &tx,
+                written: 6 * GIB,
+            };
+            write_central_directory(&mut out, &entries).await.unwrap();
+        })
+        .await;
+        assert_eq!(
+            central_directory(&zip, 6 * GIB),
+            [
+                ("small", 5, 0),
+                ("large", 5 * GIB, 100),
+                ("after", 5, 5 * GIB + 200),
+            ]
+            .map(|(name, size, offset)| Listed {
+                name: name.to_string(),
+                crc: 42,
+                size,
+                offset,
+            })
+        );
+
+        let descriptor = archive(|tx| async move {
+            let mut out = Writer {
+                chunks: &tx,
+                written: 0,
+            };
+            write_data_descriptor(&mut out, &entry("large", 5 * GIB, 0, true))
+                .await
+                .unwrap();
+        })
+        .await;
+        assert_eq!(descriptor.len(), 24);
+        assert_eq!(u64_at(&descriptor, 8), 5 * GIB);
+        assert_eq!(u64_at(&descriptor, 16), 5 * GIB);
+    }
+}
@@ -0,0 +1,89 @@
+//! Validation of submitted data before it is created or updated, see [`Inputs::validate`].
+//!
+//! The admin interface renders the form again with the messages next to the inputs, the JSON API
+//! responds with HTTP 422 and `{"error": "validation", "fields": {"title": ["..."]}}`.
+//!
+//! Fields can be validated using `#[cms(validate = "path::to::fn")]` when deriving
+//! [`Entity`](crate::Entity) or [`Input`](crate::Input), where `fn(&T) -> Result<(), M>` receives
+//! the value of the field and `M` is the message, e.g. `String` or `&'static str`:
+//!
+//! ```rust,ignore
+//! #[derive(Debug, Deserialize, Serialize, Entity, Model, TS)]
+//! struct Post {
+//!     #[cms(validate = "not_blank")]
+//!     title: Text,
+//!     // ...
+//! }
+//!
+//! fn not_blank(title: &Text) -> Result<(), &'static str> {
+//!     match title.trim().is_empty() {
+//!         true => Err("must not be blank"),
+//!         false => Ok(()),
+//!     }
+//! }
+//! ```
+//!
+//! [`Inputs::validate`]: crate::input::Inputs::validate
+
+use std::collections::BTreeMap;
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::json;
+
+/// Messages of invalid fields, by the name of the field in the form.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct ValidationErrors(BTreeMap<String, Vec<String>>);
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.0.entry(field.into()).or_default().push(message.into());
+    }
+
+    /// add the error of a validator of the field `field`, if any
+    pub fn check<M: Into<String>>(&mut self, field: &str, result: Result<(), M>) {
+        if let Err(message) = result {
+            self.add(field, message);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// messages of the field `field`
+    pub fn get(&self, field: &str) -> &[String] {
+        self.0.get(field).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_slice()))
+    }
+
+    /// `Ok(())` if there are no errors
+    pub fn into_result(self) -> Result<(), Self> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => Err(self),
+        }
+    }
+}
+
+impl IntoResponse for ValidationErrors {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "error": "validation",
+            "fields": self,
+        });
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response()
+    }
+}
@@ -1,16 +1,17 @@
 /**
- * evaluate the string attribute `onmount` on all children. Stop recusion if `onmount` evaluates to `true`.
+ * Call the global function named by the `data-cms-mount` attribute of all children with the element,
+ * e.g. `data-cms-mount="cmsTagsInit"`. Templates of inputs marked with `data-cms-template` are skipped.
  * @param {HTMLElement} e
  */
 function callOnMountRecursive(e) {
   for (const c of e.children) {
+    if (c.hasAttribute("data-cms-template")) continue;
     try {
-      const a = c.getAttribute("onmount");
-      if (!a || !new Function(a).call(c)) {
-        callOnMountRecursive(c);
-      }
+      const name = c.getAttribute("data-cms-mount");
+      if (name) window[name](c);
     } catch (err) {
       console.error(err, e, c);
     }
+    callOnMountRecursive(c);
   }
 }
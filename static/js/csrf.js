@@ -0,0 +1,29 @@
+/**
+ * the CSRF token of the page, if CSRF protection is enabled
+ * @returns {string | undefined}
+ */
+function cmsCsrfToken() {
+  return document.querySelector('meta[name="csrf-token"]')?.content;
+}
+
+/**
+ * headers with the CSRF token for requests using `fetch`
+ * @returns {Record<string, string>}
+ */
+function cmsCsrfHeaders() {
+  const token = cmsCsrfToken();
+  return token ? { "X-CSRF-Token": token } : {};
+}
+
+// send the token with all forms as the `_csrf` query parameter, before other submit handlers run
+document.addEventListener(
+  "submit",
+  (e) => {
+    const token = cmsCsrfToken();
+    if (!token || e.target.method !== "post") return;
+    const url = new URL(e.target.action);
+    url.searchParams.set("_csrf", token);
+    e.target.action = url;
+  },
+  true,
+);
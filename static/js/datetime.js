@@ -0,0 +1,59 @@
+/**
+ * the wall clock time of `date` in `timeZone` as the value of an `input type="datetime-local"`
+ * @param {Date} date
+ * @param {string | null} timeZone IANA time zone, the time zone of the browser if `null`
+ */
+function cmsDateTimeToLocal(date, timeZone) {
+  const pad = (n) => n.toString().padStart(2, "0");
+  if (!timeZone) {
+    return `${date.getFullYear()}-${pad(date.getMonth() + 1)}-${pad(date.getDate())}T${pad(date.getHours())}:${pad(date.getMinutes())}`;
+  }
+  const parts = {};
+  for (const p of new Intl.DateTimeFormat("en-US", {
+    timeZone,
+    hourCycle: "h23",
+    year: "numeric",
+    month: "2-digit",
+    day: "2-digit",
+    hour: "2-digit",
+    minute: "2-digit",
+  }).formatToParts(date)) {
+    parts[p.type] = p.value;
+  }
+  return `${parts.year.padStart(4, "0")}-${parts.month}-${parts.day}T${parts.hour}:${parts.minute}`;
+}
+
+/**
+ * the instant of the wall clock time `value` of an `input type="datetime-local"` in `timeZone`
+ * @param {string} value
+ * @param {string | null} timeZone IANA time zone, the time zone of the browser if `null`
+ */
+function cmsDateTimeFromLocal(value, timeZone) {
+  if (!timeZone) return new Date(value);
+  // interpret `value` as UTC, then correct by the offset of `timeZone` at that instant
+  const guess = new Date(value + "Z");
+  const offset = (d) => new Date(cmsDateTimeToLocal(d, timeZone) + "Z") - d;
+  const first = new Date(guess - offset(guess));
+  // the offset may differ at the corrected instant around DST transitions
+  return new Date(guess - offset(first));
+}
+
+/**
+ * show the value of `hidden` in `input` and write it back as RFC 3339 when `form` is submitted
+ * @param {HTMLInputElement} input
+ * @param {HTMLInputElement} hidden
+ * @param {HTMLFormElement} form
+ * @param {string | null} timeZone IANA time zone, the time zone of the browser if `null`
+ */
+function cmsDateTimeInit(input, hidden, form, timeZone) {
+  try {
+    if (timeZone) new Intl.DateTimeFormat("en-US", { timeZone });
+  } catch (err) {
+    console.error(`invalid time zone ${timeZone}, using the time zone of the browser`, err);
+    timeZone = null;
+  }
+  if (hidden.value) input.value = cmsDateTimeToLocal(new Date(hidden.value), timeZone);
+  form.addEventListener("submit", () => {
+    hidden.value = input.value ? cmsDateTimeFromLocal(input.value, timeZone).toISOString() : "";
+  });
+}
@@ -0,0 +1,118 @@
+/**
+ * Event handlers of the admin interface, set using `data-cms-{type}` attributes with the name of a
+ * global function, e.g. `data-cms-click="cmsTabSelect"`, which is called with the element and the
+ * event. Returning `false` prevents the default action, like an inline event handler.
+ * Inline event handlers would require `script-src-attr 'unsafe-inline'` in the
+ * Content-Security-Policy.
+ */
+for (const type of ["click", "change", "input", "submit", "toggle"]) {
+  const attribute = `data-cms-${type}`;
+  // capturing, since some events, e.g. `toggle`, don't bubble
+  document.addEventListener(
+    type,
+    (e) => {
+      let el = e.target instanceof Element ? e.target.closest(`[${attribute}]`) : null;
+      if (!e.bubbles && el !== e.target) return;
+      while (el) {
+        const handler = window[el.getAttribute(attribute)];
+        if (typeof handler !== "function") {
+          console.error(`no function ${el.getAttribute(attribute)}`, el);
+        } else if (handler(el, e) === false) {
+          e.preventDefault();
+        }
+        el = e.bubbles ? el.parentElement?.closest(`[${attribute}]`) : null;
+      }
+    },
+    true,
+  );
+}
+
+/**
+ * open the dialog with the id `el.dataset.dialog`
+ * @param {HTMLElement} el
+ */
+function cmsShowModal(el) {
+  document.getElementById(el.dataset.dialog).showModal();
+}
+
+/**
+ * navigate to `el.dataset.href`
+ * @param {HTMLElement} el
+ */
+function cmsNavigate(el) {
+  location.assign(el.dataset.href);
+}
+
+function cmsHistoryBack() {
+  history.back();
+  return false;
+}
+
+/**
+ * ask the user to confirm submitting the form `form` with the message `form.dataset.confirm`
+ * @param {HTMLFormElement} form
+ */
+function cmsConfirmSubmit(form) {
+  return confirm(form.dataset.confirm);
+}
+
+/**
+ * set the cookie `el.dataset.cookie` to the selected value for a year
+ * @param {HTMLSelectElement} el
+ */
+function cmsSetCookie(el) {
+  document.cookie = `${el.dataset.cookie}=${el.value}; path=/; max-age=31536000; samesite=lax`;
+}
+
+/**
+ * @param {HTMLSelectElement} el
+ */
+function cmsLanguageSelect(el) {
+  cmsSetCookie(el);
+  location.reload();
+}
+
+/**
+ * @param {HTMLSelectElement} el
+ */
+function cmsThemeSelect(el) {
+  cmsSetCookie(el);
+  if (el.value === "system") delete document.documentElement.dataset.theme;
+  else document.documentElement.dataset.theme = el.value;
+}
+
+/**
+ * enable the button `input.dataset.button` once the text `input.dataset.expected` is typed
+ * @param {HTMLInputElement} input
+ */
+function cmsTypeToConfirm(input) {
+  document.getElementById(input.dataset.button).disabled = input.value !== input.dataset.expected;
+}
+
+/**
+ * Delete the entity at the API URL `button.dataset.url` and remove the element `button.dataset.remove`
+ * and the dialog of `button`. A required confirmation token is requested and sent right away, since the
+ * deletion was already confirmed in the dialog.
+ * @param {HTMLButtonElement} button
+ */
+function cmsDeleteConfirmed(button) {
+  const { url, expected, input, remove } = button.dataset;
+  if (expected !== undefined && document.getElementById(input).value !== expected) return false;
+  const dialog = button.closest("dialog");
+  fetch(url, { method: "DELETE", headers: cmsCsrfHeaders() })
+    .then((r) =>
+      r.status !== 409
+        ? r
+        : r.json().then(({ confirm }) =>
+            fetch(`${url}?confirm=${encodeURIComponent(confirm)}`, {
+              method: "DELETE",
+              headers: cmsCsrfHeaders(),
+            }),
+          ),
+    )
+    .then((r) => {
+      if (!r.ok) return;
+      document.getElementById(remove).remove();
+      dialog.remove();
+    });
+}
@@ -0,0 +1,65 @@
+/**
+ * show the name of the file selected in `input` and, for images, a preview
+ * @param {HTMLInputElement} input
+ */
+function cmsFileInputChange(input) {
+  const container = input.closest(".cms-file-input");
+  const file = input.files[0];
+  const name = container.querySelector(".cms-file-name");
+  if (name) name.textContent = file ? file.name : name.dataset.current || "";
+  const preview = container.querySelector("img.cms-image-preview");
+  if (!preview) return;
+  if (preview.dataset.objectUrl) {
+    URL.revokeObjectURL(preview.dataset.objectUrl);
+    delete preview.dataset.objectUrl;
+  }
+  if (file) {
+    preview.dataset.objectUrl = URL.createObjectURL(file);
+    preview.src = preview.dataset.objectUrl;
+  } else if (preview.dataset.current) {
+    preview.src = preview.dataset.current;
+  }
+  preview.hidden = !preview.getAttribute("src");
+}
+
+/**
+ * submit the form of `input` using `XMLHttpRequest` to show the upload progress in all
+ * `progress.cms-upload-progress` elements of the form
+ * @param {HTMLInputElement} input
+ */
+function cmsFileInputMount(input) {
+  const form = input.form;
+  if (!form || form.dataset.cmsUploadProgress) return;
+  form.dataset.cmsUploadProgress = "true";
+  form.addEventListener("submit", (ev) => {
+    const hasFiles = Array.prototype.some.call(
+      form.querySelectorAll("input[type=file]"),
+      (i) => !i.disabled && i.files.length > 0,
+    );
+    if (!hasFiles || ev.defaultPrevented) return;
+    ev.preventDefault();
+    const bars = form.querySelectorAll("progress.cms-upload-progress");
+    const xhr = new XMLHttpRequest();
+    xhr.upload.addEventListener("progress", (e) => {
+      for (const bar of bars) {
+        bar.hidden = false;
+        if (e.lengthComputable) bar.value = e.loaded / e.total;
+      }
+    });
+    xhr.addEventListener("load", () => {
+      if (xhr.responseURL && xhr.responseURL !== form.action) {
+        location.assign(xhr.responseURL);
+      } else {
+        document.open();
+        document.write(xhr.responseText);
+        document.close();
+      }
+    });
+    xhr.addEventListener("error", () => {
+      for (const bar of bars) bar.hidden = true;
+      form.submit();
+    });
+    xhr.open(form.method, form.action);
+    xhr.send(new FormData(form, ev.submitter));
+  });
+}
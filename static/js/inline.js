@@ -0,0 +1,19 @@
+/**
+ * add the inputs of a new child to the inline relation of `button`
+ * @param {HTMLButtonElement} button with the name prefix of the new children in `data-name`,
+ * e.g. `__inline_comments[create]`
+ */
+function cmsInlineRelationAdd(button) {
+  const root = button.parentElement;
+  const name = button.dataset.name;
+  const template = root.querySelector(":scope > template");
+  const el = template.content.firstElementChild.cloneNode(true);
+  const i = root.querySelectorAll(":scope > .cms-list-element-new").length;
+  el.classList.add("cms-list-element-new");
+  const prefix = name + "[]";
+  for (const e of el.querySelectorAll("[name]")) {
+    if (e.name.startsWith(prefix)) e.name = name + "[" + i + "]" + e.name.slice(prefix.length);
+  }
+  template.before(el);
+  callOnMountRecursive(el);
+}
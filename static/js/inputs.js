@@ -0,0 +1,24 @@
+/**
+ * show the value of the slider `range` in the number input of a percent input
+ * @param {HTMLInputElement} range
+ */
+function cmsPercentRange(range) {
+  range.nextElementSibling.value = range.value;
+}
+
+/**
+ * show the value of the number input `number` in the slider of a percent input
+ * @param {HTMLInputElement} number
+ */
+function cmsPercentNumber(number) {
+  if (number.value !== "") number.previousElementSibling.value = number.value;
+}
+
+/**
+ * copy the value of the `datetime-local` input `input` to the hidden input submitted instead,
+ * adding the seconds which are omitted by the browser if they are zero
+ * @param {HTMLInputElement} input
+ */
+function cmsNaiveDateTimeInput(input) {
+  input.nextElementSibling.value = input.value.length == 16 ? input.value + ":00" : input.value;
+}
@@ -0,0 +1,102 @@
+/**
+ * create the input of a collapsed list element when it is expanded for the first time, from its
+ * template or loaded from the `data-cms-list-src` of the list
+ * @param {HTMLDetailsElement} el
+ */
+async function cmsListElementExpand(el) {
+  const template = el.querySelector(":scope > template");
+  if (template) {
+    const content = document.createElement("fieldset");
+    content.classList.add("cms-list-element-content");
+    content.append(template.content.cloneNode(true));
+    template.replaceWith(content);
+    callOnMountRecursive(content);
+  } else if (el.parentElement.dataset.cmsListSrc && !cmsListElementLoaded(el)) {
+    const index = Number(el.dataset.index);
+    await cmsListLoad(el.parentElement, index, index + 1);
+  }
+}
+
+/**
+ * @param {HTMLDetailsElement} el
+ */
+function cmsListElementLoaded(el) {
+  return el.querySelector(":scope > .cms-list-element-content") !== null;
+}
+
+/**
+ * create the inputs of all collapsed list elements within `root` from their templates such that
+ * their values are submitted
+ * @param {HTMLElement} root
+ */
+function cmsListExpandAll(root) {
+  let template;
+  while ((template = root.querySelector("details.cms-list-element-collapsed > template"))) {
+    cmsListElementExpand(template.parentElement);
+  }
+}
+
+/**
+ * load the inputs of the collapsed elements `start..end` of `list` from its `data-cms-list-src`,
+ * with the scripts they require
+ * @param {HTMLElement} list
+ * @param {number} start
+ * @param {number} end
+ */
+async function cmsListLoad(list, start, end) {
+  const url = new URL(list.dataset.cmsListSrc, document.baseURI);
+  url.searchParams.set("start", start);
+  url.searchParams.set("end", end);
+  // inline scripts of the inputs need the nonce of this page
+  const nonce = document.querySelector("script[nonce]")?.nonce;
+  if (nonce) url.searchParams.set("nonce", nonce);
+  const res = await fetch(url);
+  if (!res.ok) throw new Error(`failed to load the elements of ${list.id}: ${res.status}`);
+  const fragment = document.createRange().createContextualFragment(await res.text());
+  for (const el of [...fragment.children]) {
+    if (el.matches("script[src]")) {
+      el.remove();
+      const src = el.getAttribute("src");
+      if (document.querySelector(`script[src="${CSS.escape(src)}"]`)) continue;
+      const script = document.createElement("script");
+      script.src = src;
+      if (nonce) script.nonce = nonce;
+      await new Promise((resolve) => {
+        script.onload = script.onerror = resolve;
+        document.head.append(script);
+      });
+    } else if (el.matches("link[rel=stylesheet]")) {
+      el.remove();
+      const href = el.getAttribute("href");
+      if (!document.querySelector(`link[href="${CSS.escape(href)}"]`)) document.head.append(el);
+    } else if (el.matches(".cms-list-element-content")) {
+      const details = list.querySelector(`:scope > details[data-index="${el.dataset.index}"]`);
+      if (!details || cmsListElementLoaded(details)) continue;
+      details.append(el);
+      callOnMountRecursive(el);
+    }
+  }
+}
+
+// the values of collapsed elements are submitted as well, so their inputs are created first
+document.addEventListener(
+  "submit",
+  (e) => {
+    const form = e.target;
+    cmsListExpandAll(form);
+    const missing = [...form.querySelectorAll("[data-cms-list-src]")].flatMap((list) => {
+      const indices = [...list.querySelectorAll(":scope > details.cms-list-element-collapsed")]
+        .filter((el) => !cmsListElementLoaded(el))
+        .map((el) => Number(el.dataset.index));
+      return indices.length ? [[list, Math.min(...indices), Math.max(...indices) + 1]] : [];
+    });
+    if (!missing.length) return;
+    e.preventDefault();
+    e.stopImmediatePropagation();
+    Promise.all(missing.map(([list, start, end]) => cmsListLoad(list, start, end))).then(
+      () => form.requestSubmit(e.submitter),
+      (err) => console.error(err),
+    );
+  },
+  true,
+);
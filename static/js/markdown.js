@@ -0,0 +1,15 @@
+/**
+ * replace the `textarea` with EasyMDE, configured by its data attributes
+ * @param {HTMLTextAreaElement} textarea
+ */
+function cmsMarkdownInit(textarea) {
+  const { maxSize, upload, uploadEndpoint, accept } = textarea.dataset;
+  new EasyMDE({
+    element: textarea,
+    imageMaxSize: Number(maxSize),
+    uploadImage: upload === "true",
+    imageUploadEndpoint: uploadEndpoint,
+    imagePathAbsolute: true,
+    imageAccept: accept,
+  });
+}
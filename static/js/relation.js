@@ -0,0 +1,24 @@
+/**
+ * load the options of the relation select `el` from `el.dataset.options`, keeping the selected id
+ * @param {HTMLSelectElement} el
+ */
+async function cmsRelationInit(el) {
+  const res = await fetch(el.dataset.options);
+  if (!res.ok) {
+    console.error(`failed to load the options of ${el.name}`, res);
+    return;
+  }
+  const selected = el.value;
+  for (const { id, label } of await res.json()) {
+    const existing = [...el.options].find((o) => o.value === id);
+    if (existing) {
+      existing.textContent = label;
+      continue;
+    }
+    const option = document.createElement("option");
+    option.value = id;
+    option.textContent = label;
+    el.append(option);
+  }
+  el.value = selected;
+}
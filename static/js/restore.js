@@ -0,0 +1,26 @@
+/**
+ * set the inputs of `form` to the values of a previous submission and notify them using a `change` event.
+ * Values without an input, e.g. of elements of lists that are not rendered, are skipped.
+ * Passwords are not restored.
+ * @param {HTMLFormElement} form
+ * @param {[string, string][]} values the name and value of each submitted field, in order
+ */
+function cmsRestoreForm(form, values) {
+  const submitted = new Map();
+  for (const [name, value] of values) {
+    if (!submitted.has(name)) submitted.set(name, []);
+    submitted.get(name).push(value);
+  }
+  for (const el of form.elements) {
+    if (!el.name || el.disabled || el.type === "file" || el.type === "password") continue;
+    const v = submitted.get(el.name);
+    if (el.type === "checkbox" || el.type === "radio") {
+      el.checked = !!v && v.includes(el.value);
+    } else if (v && v.length) {
+      el.value = v.shift();
+    } else {
+      continue;
+    }
+    el.dispatchEvent(new Event("change", { bubbles: true }));
+  }
+}
@@ -0,0 +1,46 @@
+/**
+ * @param {HTMLButtonElement} tab
+ */
+function cmsTabSelect(tab) {
+  const tabs = tab.closest(".cms-tabs");
+  const buttons = Array.from(tabs.querySelector(".cms-tab-bar").children);
+  const idx = buttons.indexOf(tab);
+  buttons.forEach((b, i) => b.setAttribute("aria-selected", i === idx));
+  Array.from(tabs.querySelectorAll(":scope > .cms-tab-panel")).forEach(
+    (p, i) => (p.hidden = i !== idx),
+  );
+}
+
+/**
+ * Switch to the tab containing the first invalid input when the form is submitted
+ * and flag all tabs containing invalid inputs.
+ * @param {HTMLFormElement} form
+ */
+function cmsTabsInit(form) {
+  let switched = false;
+  form.addEventListener(
+    "invalid",
+    (e) => {
+      const panel = e.target.closest(".cms-tab-panel");
+      if (!panel) return;
+      const tabs = panel.closest(".cms-tabs");
+      const panels = Array.from(tabs.querySelectorAll(":scope > .cms-tab-panel"));
+      const tab = tabs.querySelector(".cms-tab-bar").children[panels.indexOf(panel)];
+      tab.classList.add("cms-tab-error");
+      if (!switched) {
+        switched = true;
+        cmsTabSelect(tab);
+        // reset once all invalid events of this submission have been dispatched
+        setTimeout(() => (switched = false));
+      }
+    },
+    true,
+  );
+  form.addEventListener("input", (e) => {
+    const panel = e.target.closest(".cms-tab-panel");
+    if (!panel || panel.querySelector(":invalid")) return;
+    const tabs = panel.closest(".cms-tabs");
+    const panels = Array.from(tabs.querySelectorAll(":scope > .cms-tab-panel"));
+    tabs.querySelector(".cms-tab-bar").children[panels.indexOf(panel)].classList.remove("cms-tab-error");
+  });
+}
@@ -0,0 +1,50 @@
+/**
+ * add a tag for the text entered into the tags input `el` when pressing enter or comma,
+ * and remove tags using their button or backspace
+ * @param {HTMLElement} el
+ */
+function cmsTagsInit(el) {
+  const entry = el.querySelector(":scope > .cms-tags-entry");
+  const update = () => {
+    entry.required = entry.dataset.required !== undefined && !el.querySelector(":scope > .cms-tag");
+  };
+  const remove = (tag) => {
+    tag.remove();
+    update();
+  };
+  const add = () => {
+    const value = entry.value.trim();
+    entry.value = "";
+    if (!value) return;
+    for (const input of el.querySelectorAll(":scope > .cms-tag > input")) {
+      if (input.value === value) return;
+    }
+    const hidden = el.querySelector(":scope > .cms-tags-name").cloneNode();
+    hidden.removeAttribute("class");
+    hidden.disabled = false;
+    hidden.value = value;
+    const button = document.createElement("button");
+    button.type = "button";
+    button.className = "cms-tag-remove";
+    button.ariaLabel = button.title = el.dataset.removeLabel;
+    button.textContent = "×";
+    const tag = document.createElement("span");
+    tag.className = "cms-tag";
+    tag.append(value, button, hidden);
+    el.insertBefore(tag, el.querySelector(":scope > .cms-tags-name"));
+    update();
+  };
+  el.addEventListener("click", (e) => {
+    if (e.target.classList.contains("cms-tag-remove")) remove(e.target.parentElement);
+  });
+  entry.addEventListener("keydown", (e) => {
+    if (e.key === "Enter" || e.key === ",") {
+      e.preventDefault();
+      add();
+    } else if (e.key === "Backspace" && !entry.value) {
+      const tags = el.querySelectorAll(":scope > .cms-tag");
+      if (tags.length) remove(tags[tags.length - 1]);
+    }
+  });
+  entry.closest("form")?.addEventListener("submit", add);
+}
@@ -0,0 +1,23 @@
+/**
+ * show the current UTC offset of the time zones of `.cms-time-zone-offset[data-time-zone]` elements
+ * within `root`, formatted for the language of the page
+ * @param {ParentNode} root
+ */
+function cmsTimeZoneOffsets(root) {
+  const locale = document.documentElement.lang || undefined;
+  for (const el of root.querySelectorAll(".cms-time-zone-offset[data-time-zone]")) {
+    try {
+      const offset = new Intl.DateTimeFormat(locale, {
+        timeZone: el.dataset.timeZone,
+        timeZoneName: "shortOffset",
+      })
+        .formatToParts()
+        .find((p) => p.type === "timeZoneName").value;
+      el.textContent = `(${offset})`;
+    } catch {
+      // unknown to the browser
+    }
+  }
+}
+
+document.addEventListener("DOMContentLoaded", () => cmsTimeZoneOffsets(document));
@@ -0,0 +1,163 @@
+use std::borrow::Borrow;
+
+use derived_cms::{
+    context::Context, entity::ListRows, input::Inputs, property::Text, Entity, EntityBase, Input,
+};
+use ormlite::Model;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+type Ctx = Context<()>;
+
+#[derive(Debug, Deserialize, Serialize, Input)]
+#[serde(rename_all = "camelCase")]
+#[cms(label_case = "Title")]
+struct Author {
+    #[serde(rename = "name")]
+    #[cms(rename = "Full name")]
+    full_name: Text,
+    #[serde(rename = "mail")]
+    email_address: Text,
+    #[cms(rename = "Place of birth")]
+    birth_place: Text,
+    short_bio: Text,
+}
+
+/// `(name, name_human)` of the inputs of `T`
+fn names<T: Inputs<Ctx>>() -> Vec<(String, String)> {
+    T::inputs(None)
+        .into_iter()
+        .map(|i| (i.name.to_string(), i.name_human.to_string()))
+        .collect()
+}
+
+#[test]
+fn cms_rename_labels_and_serde_rename_names() {
+    assert_eq!(
+        names::<Author>(),
+        [
+            ("name", "Full name"),
+            ("mail", "Mail"),
+            ("birthPlace", "Place of birth"),
+            ("shortBio", "Short Bio"),
+        ]
+        .map(|(name, label)| (name.to_string(), label.to_string()))
+    );
+}
+
+#[test]
+fn form_names_round_trip() {
+    let query = names::<Author>()
+        .into_iter()
+        .map(|(name, _)| format!("{name}=value+of+{name}"))
+        .collect::<Vec<_>>()
+        .join("&");
+    let author: Author = serde_qs::from_str(&query).unwrap();
+    assert_eq!(author.full_name.0, "value of name");
+    assert_eq!(author.email_address.0, "value of mail");
+    assert_eq!(author.birth_place.0, "value of birthPlace");
+    assert_eq!(author.short_bio.0, "value of shortBio");
+}
+
+#[derive(Debug, Deserialize, Serialize, Entity, Model, TS)]
+#[cms(list_rows)]
+struct Article {
+    #[cms(id)]
+    #[ormlite(primary_key)]
+    id: i64,
+    #[cms(searchable)]
+    #[ormlite(column = "headline")]
+    title: Text,
+    #[cms(skip_column)]
+    body: Text,
+}
+
+#[test]
+fn list_rows_use_column_names() {
+    assert_eq!(
+        <Article as ListRows<Ctx>>::row_columns(),
+        ["id", "headline"]
+    );
+}
+
+#[test]
+fn searchable_columns_use_column_names() {
+    assert_eq!(
+        <Article as EntityBase<Ctx>>::searchable_columns(),
+        ["headline"]
+    );
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn list_rows_are_read_from_renamed_columns() {
+    let db = ormlite::sqlite::SqlitePool::connect("sqlite::memory:")
+        .await
+        .unwrap();
+    ormlite::query("CREATE TABLE article (id INTEGER PRIMARY KEY, headline TEXT, body TEXT)")
+        .execute(&db)
+        .await
+        .unwrap();
+    ormlite::query("INSERT INTO article VALUES (1, 'Hello', 'World')")
+        .execute(&db)
+        .await
+        .unwrap();
+    use derived_cms::entity::{fetch_list_rows, list_rows_query, ListRow};
+
+    let query = list_rows_query::<Article, Ctx>();
+    let rows = fetch_list_rows::<Article, Ctx>(query, &db).await.unwrap();
+    let [row] = &rows[..] else {
+        panic!("expected one row, got {rows:?}");
+    };
+    assert_eq!(*ListRow::<Article, Ctx>::id(row), 1);
+    assert_eq!(row.title.0, "Hello");
+}
+
+#[derive(Debug, Deserialize, Serialize, Entity, Model, TS)]
+#[serde(rename_all = "camelCase")]
+#[cms(update_struct = "ProfileUpdate")]
+struct Profile {
+    #[cms(id)]
+    #[ormlite(primary_key)]
+    id: i64,
+    display_name: Text,
+    short_bio: Option<Text>,
+    #[serde(rename = "site")]
+    website: Option<Text>,
+}
+
+#[test]
+fn update_struct_distinguishes_null_from_missing() {
+    let update: ProfileUpdate = serde_json::from_str(
+        r#"{"id": 1, "displayName": "Ada", "shortBio": null, "site": "https://example.com"}"#,
+    )
+    .unwrap();
+    assert_eq!(update.display_name.0, "Ada");
+    assert!(matches!(update.short_bio, Some(None)));
+    assert!(matches!(&update.website, Some(Some(site)) if site.0 == "https://example.com"));
+
+    let update: ProfileUpdate = serde_json::from_str(r#"{"id": 1, "displayName": "Ada"}"#).unwrap();
+    assert!(update.short_bio.is_none());
+    assert!(update.website.is_none());
+    // a missing key is skipped again, an explicit `null` is kept
+    let json = serde_json::to_value(&update).unwrap();
+    assert_eq!(json, serde_json::json!({"id": 1, "displayName": "Ada"}));
+}
+
+#[test]
+fn update_struct_has_the_values_and_inputs_of_the_entity() {
+    let profile = Profile {
+        id: 1,
+        display_name: Text("Ada".to_string()),
+        short_bio: None,
+        website: Some(Text("https://example.com".to_string())),
+    };
+    let update = <Profile as EntityBase<Ctx>>::to_update(&profile);
+    let update: &ProfileUpdate = update.borrow();
+    assert!(matches!(update.short_bio, Some(None)));
+    assert_eq!(
+        serde_json::to_value(update).unwrap(),
+        serde_json::to_value(&profile).unwrap()
+    );
+    assert_eq!(names::<ProfileUpdate>(), names::<Profile>());
+}
@@ -0,0 +1,223 @@
+#![cfg(feature = "sqlite")]
+
+use axum::{http::StatusCode, response::IntoResponse};
+use chrono::{DateTime, TimeZone, Utc};
+use derived_cms::{
+    context::Context,
+    filter::{self, Condition, Filter, FilterError, FilterOp, FilterValue},
+    property::Text,
+    Entity,
+};
+use ormlite::{sqlite::SqlitePool, Model};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use uuid::Uuid;
+
+type Ctx = Context<()>;
+
+#[derive(Debug, Deserialize, Serialize, Entity, Model, TS)]
+#[serde(rename_all = "camelCase")]
+struct Event {
+    #[cms(id)]
+    #[ormlite(primary_key)]
+    id: i64,
+    #[cms(filter)]
+    title: Text,
+    #[cms(filter)]
+    #[ormlite(column = "attendees")]
+    attendee_count: i32,
+    #[cms(filter)]
+    price: f64,
+    #[cms(filter)]
+    draft: bool,
+    #[cms(filter)]
+    starts: DateTime<Utc>,
+    #[cms(filter, skip_input)]
+    organizer: Option<Uuid>,
+    notes: Text,
+}
+
+const ORGANIZER: Uuid = Uuid::from_u128(1);
+
+fn events() -> Vec<Event> {
+    (1..=3)
+        .map(|id| Event {
+            id,
+            title: Text(format!("Event {id}")),
+            attendee_count: id as i32 * 10,
+            price: id as f64 * 1.5,
+            draft: id == 2,
+            starts: Utc.with_ymd_and_hms(2024, id as u32, 1, 0, 0, 0).unwrap(),
+            organizer: (id != 3).then_some(ORGANIZER),
+            notes: Text(String::new()),
+        })
+        .collect()
+}
+
+fn parse(query: &[(&str, &str)]) -> Result<Filter, FilterError> {
+    Filter::parse::<Event, Ctx>(query.iter().map(|(k, v)| (k.to_string(), v.to_string())))
+}
+
+#[test]
+fn unknown_keys_are_rejected() {
+    for key in [
+        "notes",
+        "nope",
+        "attendees",
+        "attendee_count",
+        "title__like",
+    ] {
+        let error = parse(&[(key, "1")]).unwrap_err();
+        assert!(
+            matches!(&error, FilterError::UnknownFilter { key: k } if k == key),
+            "{key}"
+        );
+        assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[test]
+fn invalid_values_are_rejected() {
+    for (key, value) in [
+        ("attendeeCount", "ten"),
+        ("attendeeCount", "1.5"),
+        ("attendeeCount__gt", "99999999999"),
+        ("price", "NaN"),
+        ("draft", "yes"),
+        ("starts", "2024-01-01"),
+        ("organizer", "1"),
+    ] {
+        let error = parse(&[(key, value)]).unwrap_err();
+        assert!(
+            matches!(&error, FilterError::InvalidValue { key: k, value: v } if k == key && v == value),
+            "{key}={value}"
+        );
+        assert_eq!(error.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[test]
+fn values_are_parsed_by_type() {
+    let filter = parse(&[
+        ("title", "Event 1"),
+        ("attendeeCount", "-10"),
+        ("price", "1.5"),
+        ("draft", "false"),
+        ("starts", "2024-01-01T01:00:00+01:00"),
+        ("organizer", &ORGANIZER.to_string()),
+    ])
+    .unwrap();
+    let values = filter
+        .conditions
+        .into_iter()
+        .map(|c| (c.column, c.value))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        values,
+        [
+            ("title", FilterValue::Text("Event 1".to_string())),
+            ("attendees", FilterValue::Integer(-10)),
+            ("price", FilterValue::Float(1.5)),
+            ("draft", FilterValue::Bool(false)),
+            (
+                "starts",
+                FilterValue::DateTime(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap())
+            ),
+            ("organizer", FilterValue::Uuid(ORGANIZER)),
+        ]
+    );
+}
+
+#[test]
+fn operators_are_parsed_from_the_suffix() {
+    for (suffix, op) in [
+        ("", FilterOp::Eq),
+        ("__ne", FilterOp::Ne),
+        ("__lt", FilterOp::Lt),
+        ("__lte", FilterOp::Lte),
+        ("__gt", FilterOp::Gt),
+        ("__gte", FilterOp::Gte),
+    ] {
+        let filter = parse(&[(&format!("attendeeCount{suffix}"), "20")]).unwrap();
+        assert_eq!(
+            filter.conditions,
+            [Condition {
+                field: "attendeeCount".to_string(),
+                column: "attendees",
+                op,
+                value: FilterValue::Integer(20),
+            }],
+        );
+    }
+}
+
+#[test]
+fn filter_query_quotes_columns_and_binds_values() {
+    let filter = parse(&[("attendeeCount__gte", "20"), ("draft__ne", "true")]).unwrap();
+    let (sql, _) = filter::filter_query(Event::select(), &filter)
+        .into_query_and_args()
+        .unwrap();
+    assert!(
+        sql.ends_with(r#"WHERE "attendees" >= ? AND "draft" <> ?"#),
+        "{sql}"
+    );
+}
+
+async fn db() -> SqlitePool {
+    let db = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    ormlite::query(
+        "CREATE TABLE event (id INTEGER PRIMARY KEY, title TEXT, attendees INTEGER, price REAL, \
+         draft BOOLEAN, starts TEXT, organizer BLOB, notes TEXT)",
+    )
+    .execute(&db)
+    .await
+    .unwrap();
+    for event in events() {
+        event.insert(&db).await.unwrap();
+    }
+    db
+}
+
+/// ids of the events matching `query`, in the database and in memory
+async fn matching(db: &SqlitePool, query: &[(&str, &str)]) -> (Vec<i64>, Vec<i64>) {
+    let filter = parse(query).unwrap();
+    let in_db = filter::filter_query(Event::select(), &filter)
+        .fetch_all(db)
+        .await
+        .unwrap();
+    let in_memory = events()
+        .into_iter()
+        .filter(|e| filter.matches::<Event, Ctx>(e));
+    (
+        in_db.iter().map(|e| e.id).collect(),
+        in_memory.map(|e| e.id).collect(),
+    )
+}
+
+#[tokio::test]
+async fn database_and_memory_agree() {
+    let db = db().await;
+    let organizer = ORGANIZER.to_string();
+    for (query, expected) in [
+        (vec![("title", "Event 2")], vec![2]),
+        (vec![("title__gt", "Event 1")], vec![2, 3]),
+        (vec![("attendeeCount", "20")], vec![2]),
+        (vec![("attendeeCount__ne", "20")], vec![1, 3]),
+        (vec![("attendeeCount__lt", "20")], vec![1]),
+        (vec![("attendeeCount__lte", "20")], vec![1, 2]),
+        (vec![("attendeeCount__gt", "20")], vec![3]),
+        (vec![("attendeeCount__gte", "20")], vec![2, 3]),
+        (vec![("price__gt", "2")], vec![2, 3]),
+        (vec![("draft", "true")], vec![2]),
+        (vec![("starts__gte", "2024-02-01T00:00:00Z")], vec![2, 3]),
+        (vec![("organizer", &organizer)], vec![1, 2]),
+        (
+            vec![("draft", "false"), ("attendeeCount__lt", "30")],
+            vec![1],
+        ),
+    ] {
+        let (in_db, in_memory) = matching(&db, &query).await;
+        assert_eq!(in_db, expected, "{query:?} in the database");
+        assert_eq!(in_memory, expected, "{query:?} in memory");
+    }
+}
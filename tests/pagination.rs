@@ -0,0 +1,87 @@
+#![cfg(feature = "sqlite")]
+
+use derived_cms::{
+    context::Context,
+    entity::{self, KeysetPage},
+    filter::{self, Filter},
+    property::Text,
+    Entity,
+};
+use ormlite::{sqlite::SqlitePool, Model};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+type Ctx = Context<()>;
+
+#[derive(Debug, Deserialize, Serialize, Entity, Model, TS)]
+#[serde(rename_all = "camelCase")]
+struct Note {
+    #[cms(id)]
+    #[ormlite(primary_key)]
+    id: i64,
+    title: Text,
+    #[cms(filter)]
+    pinned: bool,
+}
+
+async fn db() -> SqlitePool {
+    let db = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    ormlite::query("CREATE TABLE note (id INTEGER PRIMARY KEY, title TEXT, pinned BOOLEAN)")
+        .execute(&db)
+        .await
+        .unwrap();
+    // inserted out of order, pages are ordered by the primary key
+    for id in [4, 1, 5, 3, 2] {
+        Note {
+            id,
+            title: Text(format!("Note {id}")),
+            pinned: id % 2 == 1,
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+    }
+    db
+}
+
+async fn page(
+    db: &SqlitePool,
+    cursor: Option<i64>,
+    limit: u64,
+    filter: &Filter,
+) -> (Vec<i64>, Option<i64>) {
+    let select = filter::filter_query(entity::after_query::<Note>(cursor, limit), filter);
+    let (notes, next): KeysetPage<Note, Ctx> =
+        entity::keyset_page::<Note, Ctx>(select.fetch_all(db).await.unwrap(), limit);
+    (notes.iter().map(|n| n.id).collect(), next)
+}
+
+#[tokio::test]
+async fn pages_follow_the_cursor_until_the_end() {
+    let db = db().await;
+    let all = Filter::default();
+    assert_eq!(page(&db, None, 2, &all).await, (vec![1, 2], Some(2)));
+    assert_eq!(page(&db, Some(2), 2, &all).await, (vec![3, 4], Some(4)));
+    assert_eq!(page(&db, Some(4), 2, &all).await, (vec![5], None));
+    assert_eq!(page(&db, Some(5), 2, &all).await, (vec![], None));
+}
+
+#[tokio::test]
+async fn last_full_page_has_no_cursor() {
+    let db = db().await;
+    let all = Filter::default();
+    assert_eq!(page(&db, None, 5, &all).await, (vec![1, 2, 3, 4, 5], None));
+    assert_eq!(page(&db, Some(3), 2, &all).await, (vec![4, 5], None));
+    assert_eq!(
+        page(&db, None, u64::MAX, &all).await,
+        (vec![1, 2, 3, 4, 5], None)
+    );
+}
+
+#[tokio::test]
+async fn pages_are_filtered() {
+    let db = db().await;
+    let pinned = Filter::parse::<Note, Ctx>([("pinned".to_string(), "true".to_string())]).unwrap();
+    assert_eq!(page(&db, None, 2, &pinned).await, (vec![1, 3], Some(3)));
+    assert_eq!(page(&db, Some(3), 2, &pinned).await, (vec![5], None));
+}
@@ -0,0 +1,157 @@
+#![cfg(feature = "sqlite")]
+
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts, http::StatusCode};
+use chrono::{DateTime, Utc};
+use derived_cms::{
+    app::AppError,
+    context::Context,
+    entity::{self, List, ListParams},
+    filter::{self, Filter},
+    property::Text,
+    Entity,
+};
+use ormlite::{sqlite::SqlitePool, Model};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+type Ctx = Context<()>;
+
+/// the database of the tests, passed to `List` directly
+#[derive(Clone)]
+struct Db(SqlitePool);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Db {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(_parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Err(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct DbError(String);
+
+impl From<ormlite::Error> for DbError {
+    fn from(e: ormlite::Error) -> Self {
+        Self(format!("{e:#}"))
+    }
+}
+
+impl From<DbError> for AppError {
+    fn from(e: DbError) -> Self {
+        Self::new("Database error".to_string(), e.0)
+    }
+}
+
+/// searched in memory by the default `List::list_paginated`
+#[derive(Debug, Deserialize, Serialize, Entity, Model, TS)]
+#[ormlite(table = "note")]
+struct Note {
+    #[cms(id)]
+    #[ormlite(primary_key)]
+    id: i64,
+    #[cms(searchable)]
+    title: Text,
+    #[cms(skip_input, skip_column)]
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+impl List<Ctx> for Note {
+    type RequestExt = Db;
+    type Error = DbError;
+
+    async fn list(ext: Self::RequestExt) -> Result<impl IntoIterator<Item = Self>, Self::Error> {
+        Ok(entity::not_deleted_query::<Self>()
+            .fetch_all(&ext.0)
+            .await?)
+    }
+}
+
+/// searched in the database using `filter_query`
+#[derive(Debug, Deserialize, Serialize, Entity, Model, TS)]
+#[ormlite(table = "note")]
+struct Memo {
+    #[cms(id)]
+    #[ormlite(primary_key)]
+    id: i64,
+    #[cms(searchable)]
+    #[ormlite(column = "title")]
+    heading: Text,
+    #[cms(skip_input, skip_column)]
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+impl List<Ctx> for Memo {
+    type RequestExt = Db;
+    type Error = DbError;
+
+    async fn list(ext: Self::RequestExt) -> Result<impl IntoIterator<Item = Self>, Self::Error> {
+        Ok(entity::not_deleted_query::<Self>()
+            .fetch_all(&ext.0)
+            .await?)
+    }
+
+    async fn list_paginated(
+        params: ListParams,
+        ext: Self::RequestExt,
+    ) -> Result<(Vec<Self>, u64), Self::Error> {
+        let select = filter::filter_query(entity::not_deleted_query(), &params.filter);
+        Ok(params.apply(select.fetch_all(&ext.0).await?))
+    }
+}
+
+async fn db() -> Db {
+    let db = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    ormlite::query("CREATE TABLE note (id INTEGER PRIMARY KEY, title TEXT, deleted_at TEXT)")
+        .execute(&db)
+        .await
+        .unwrap();
+    for (id, title, deleted) in [
+        (1, "Hello World", false),
+        (2, "Deleted hello", true),
+        (3, "Other", false),
+        (4, "100% HELLO", false),
+    ] {
+        ormlite::query("INSERT INTO note VALUES (?, ?, ?)")
+            .bind(id)
+            .bind(title)
+            .bind(deleted.then(Utc::now))
+            .execute(&db)
+            .await
+            .unwrap();
+    }
+    Db(db)
+}
+
+async fn search<E: List<Ctx, RequestExt = Db>>(query: &str) -> (Vec<i64>, u64)
+where
+    E::Error: std::fmt::Debug,
+{
+    let params = ListParams {
+        filter: Filter::search::<E, Ctx>(query),
+        ..Default::default()
+    };
+    let (entities, total) = E::list_paginated(params, db().await).await.unwrap();
+    let ids = entities
+        .iter()
+        .map(|e| e.id().to_string().parse().unwrap())
+        .collect();
+    (ids, total)
+}
+
+#[tokio::test]
+async fn search_in_memory_skips_soft_deleted() {
+    assert_eq!(search::<Note>("hello").await, (vec![1, 4], 2));
+}
+
+#[tokio::test]
+async fn search_in_database_skips_soft_deleted() {
+    assert_eq!(search::<Memo>("hello").await, (vec![1, 4], 2));
+}
+
+#[tokio::test]
+async fn search_in_database_escapes_wildcards() {
+    assert_eq!(search::<Memo>("100%").await, (vec![4], 1));
+    assert_eq!(search::<Memo>("_").await, (vec![], 0));
+}
@@ -0,0 +1,185 @@
+#![cfg(feature = "sqlite")]
+
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts, http::StatusCode};
+use derived_cms::{
+    app::AppError,
+    context::Context,
+    entity::{self, Get, Patch, PatchError, Update},
+    property::Text,
+    Entity,
+};
+use ormlite::{sqlite::SqlitePool, Model};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use ts_rs::TS;
+
+type Ctx = Context<()>;
+
+/// the database of the tests, passed to `Get` and `Update` directly
+#[derive(Clone)]
+struct Db(SqlitePool);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Db {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(_parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Err(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct DbError(String);
+
+impl From<ormlite::Error> for DbError {
+    fn from(e: ormlite::Error) -> Self {
+        Self(format!("{e:#}"))
+    }
+}
+
+impl From<DbError> for AppError {
+    fn from(e: DbError) -> Self {
+        Self::new("Database error".to_string(), e.0)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Entity, Model, TS)]
+struct Doc {
+    #[cms(id)]
+    #[ormlite(primary_key)]
+    id: i64,
+    title: Text,
+    #[cms(version, skip_input)]
+    version: i64,
+}
+
+impl Get<Ctx> for Doc {
+    type RequestExt = Db;
+    type Error = DbError;
+
+    async fn get(id: &i64, ext: Self::RequestExt) -> Result<Option<Self>, Self::Error> {
+        Ok(Self::select()
+            .where_("id = ?")
+            .bind(*id)
+            .fetch_optional(&ext.0)
+            .await?)
+    }
+}
+
+impl Update<Ctx> for Doc {
+    type RequestExt = Db;
+    type Error = DbError;
+
+    async fn update(id: &i64, mut data: Self, ext: Self::RequestExt) -> Result<Self, Self::Error> {
+        data.id = *id;
+        data.version += 1;
+        Ok(data.update_all_fields(&ext.0).await?)
+    }
+
+    async fn update_if_version(
+        id: &i64,
+        mut data: Self,
+        versions: &[String],
+        ext: Self::RequestExt,
+    ) -> Result<Option<Self>, Self::Error> {
+        data.id = *id;
+        data.version += 1;
+        Ok(entity::update_if_version::<Self, Ctx>(*id, data, versions, &ext.0).await?)
+    }
+}
+
+async fn db() -> Db {
+    let db = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    ormlite::query("CREATE TABLE doc (id INTEGER PRIMARY KEY, title TEXT, version INTEGER)")
+        .execute(&db)
+        .await
+        .unwrap();
+    ormlite::query("INSERT INTO doc VALUES (1, 'First', 1)")
+        .execute(&db)
+        .await
+        .unwrap();
+    Db(db)
+}
+
+fn doc(title: &str, version: i64) -> Doc {
+    Doc {
+        id: 1,
+        title: Text(title.to_string()),
+        version,
+    }
+}
+
+fn versions(versions: &[&str]) -> Vec<String> {
+    versions.iter().map(|v| v.to_string()).collect()
+}
+
+async fn stored(db: &Db) -> Doc {
+    Doc::get(&1, db.clone()).await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn update_if_version_requires_the_stored_version() {
+    let db = db().await;
+    let updated = Doc::update_if_version(&1, doc("Second", 1), &versions(&["1"]), db.clone())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!((updated.title.0.as_str(), updated.version), ("Second", 2));
+
+    // based on the first version, which was changed in the meantime
+    let stale = Doc::update_if_version(&1, doc("Stale", 1), &versions(&["1"]), db.clone())
+        .await
+        .unwrap();
+    assert!(stale.is_none());
+    let doc = stored(&db).await;
+    assert_eq!((doc.title.0.as_str(), doc.version), ("Second", 2));
+
+    let updated = Doc::update_if_version(
+        &1,
+        self::doc("Third", 2),
+        &versions(&["1", "2"]),
+        db.clone(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(updated.version, 3);
+}
+
+#[tokio::test]
+async fn update_if_version_of_a_missing_entity() {
+    let db = db().await;
+    let missing = Doc::update_if_version(&2, doc("Missing", 1), &versions(&["1"]), db.clone())
+        .await
+        .unwrap();
+    assert!(missing.is_none());
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM doc")
+        .fetch_one(&db.0)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[tokio::test]
+async fn patch_checks_the_version() {
+    let db = db().await;
+    let patch = |title: &str, expected: Option<&[&str]>| {
+        Doc::patch(
+            &1,
+            json!({ "title": title }),
+            expected.map(versions),
+            db.clone(),
+            db.clone(),
+        )
+    };
+    let updated = patch("Second", Some(&["1"])).await.ok().flatten().unwrap();
+    assert_eq!((updated.title.0.as_str(), updated.version), ("Second", 2));
+
+    let stale = patch("Stale", Some(&["1"])).await;
+    assert!(matches!(stale, Err(PatchError::VersionChanged)));
+    assert_eq!(stored(&db).await.title.0, "Second");
+
+    // without `If-Match`
+    let updated = patch("Third", None).await.ok().flatten().unwrap();
+    assert_eq!((updated.title.0.as_str(), updated.version), ("Third", 3));
+}